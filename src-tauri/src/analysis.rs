@@ -0,0 +1,79 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{StreamProperty, StreamPropertyMap};
+
+/// `name` of the General stream as a number, 0 when it's missing.
+pub fn general_f64(properties: &[StreamPropertyMap], name: &str) -> f64 {
+  stream_f64(properties, MediaInfoStreamKind::General, 0, name)
+}
+
+/// The requests for `names` of every `stream` kind stream.
+pub fn properties(stream: MediaInfoStreamKind, names: &[&str]) -> Vec<StreamProperty> {
+  names
+    .iter()
+    .map(|name| StreamProperty {
+      stream,
+      property: name.to_string(),
+    })
+    .collect()
+}
+
+/// `name` of the `num`th `stream` kind stream as a number, 0 when it's missing.
+pub fn stream_f64(properties: &[StreamPropertyMap], stream: MediaInfoStreamKind, num: i32, name: &str) -> f64 {
+  properties
+    .iter()
+    .find(|map| map.stream == stream && map.num == num)
+    .and_then(|map| map.property_map.get(name))
+    .and_then(|value| value.parse::<f64>().ok())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_stream_f64() {
+    let map = |stream, num, pairs: &[(&str, &str)]| StreamPropertyMap {
+      stream,
+      num,
+      property_map: pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+      source: Default::default(),
+    };
+    let properties = [
+      map(
+        MediaInfoStreamKind::General,
+        0,
+        &[("Duration", "1500.5"), ("Title", "x")],
+      ),
+      map(MediaInfoStreamKind::Audio, 1, &[("SamplingRate", "48000")]),
+    ];
+    assert_eq!(general_f64(&properties, "Duration"), 1500.5);
+    assert_eq!(general_f64(&properties, "Title"), 0.0);
+    assert_eq!(general_f64(&properties, "AudioCount"), 0.0);
+    assert_eq!(
+      stream_f64(&properties, MediaInfoStreamKind::Audio, 1, "SamplingRate"),
+      48000.0
+    );
+    assert_eq!(
+      stream_f64(&properties, MediaInfoStreamKind::Audio, 0, "SamplingRate"),
+      0.0
+    );
+  }
+}
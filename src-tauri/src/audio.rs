@@ -0,0 +1,225 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+
+use crate::analysis;
+use crate::config::{AudioCodec, ConfigAudioPreset};
+use crate::loudness;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{AudioDownmix, AudioExportOptions, AudioExportResult, StreamProperty, StreamPropertyMap};
+
+const AUDIO_PROPERTIES: &[&str] = &["SamplingRate", "BitDepth"];
+const GENERAL_PROPERTIES: &[&str] = &["Duration", "AudioCount"];
+/// Loudness range `loudnorm` aims for, in LU; it's the filter's default.
+const LOUDNORM_LRA: f64 = 11.0;
+const MIN_LENGTH: f64 = 0.1;
+const OPUS_SAMPLING_RATE: u32 = 48000;
+
+/// Export the audio stream `options` ask for into `options.output`. The
+/// properties are the `properties()` of `file`. `run` executes the encode with
+/// the exported duration to report progress against (see `ffmpeg::run_analysis`).
+pub fn export(
+  file: &str,
+  options: &AudioExportOptions,
+  properties: &[StreamPropertyMap],
+  run: impl FnOnce(&[String], f64) -> Result<String>,
+) -> Result<AudioExportResult> {
+  if options.stream >= analysis::general_f64(properties, "AudioCount") as u32 {
+    return Err(anyhow::anyhow!("AUDIO_NO_STREAM:{}:{}", file, options.stream));
+  }
+  let duration = analysis::general_f64(properties, "Duration") / 1000.0;
+  let start = options.start.max(0.0);
+  let end = match (options.end > 0.0, duration > 0.0) {
+    (true, true) => options.end.min(duration),
+    (true, false) => options.end,
+    (false, _) => duration,
+  };
+  if end > 0.0 && end - start < MIN_LENGTH {
+    return Err(anyhow::anyhow!("AUDIO_EMPTY_RANGE:{}:{}", options.start, options.end));
+  }
+  let audio = |name: &str| analysis::stream_f64(properties, MediaInfoStreamKind::Audio, options.stream as i32, name);
+  let sampling_rate = audio("SamplingRate") as u32;
+  let bit_depth = audio("BitDepth") as u32;
+  let length = if end > 0.0 { end - start } else { 0.0 };
+  run(&args(file, options, length, sampling_rate, bit_depth), length)?;
+  Ok(AudioExportResult {
+    output: options.output.clone(),
+    size: std::fs::metadata(&options.output)?.len(),
+    duration: length,
+  })
+}
+
+/// Input seeking to the range, the chosen stream alone, the optional
+/// `loudnorm` pass and channel mix, then the preset's encoder. `length` is 0
+/// when the end of the file isn't known, which reads to the end.
+fn args(file: &str, options: &AudioExportOptions, length: f64, sampling_rate: u32, bit_depth: u32) -> Vec<String> {
+  let mut args = vec!["-y".to_string()];
+  if options.start > 0.0 {
+    args.extend(["-ss".to_string(), format!("{:.3}", options.start)]);
+  }
+  if length > 0.0 {
+    args.extend(["-t".to_string(), format!("{:.3}", length)]);
+  }
+  args.extend([
+    "-i".to_string(),
+    file.to_string(),
+    "-map".to_string(),
+    format!("0:a:{}", options.stream),
+    "-vn".to_string(),
+    "-sn".to_string(),
+    "-dn".to_string(),
+  ]);
+  if let Some(target) = options.normalize {
+    let (integrated, true_peak) = loudness::levels(target);
+    args.extend([
+      "-af".to_string(),
+      format!("loudnorm=I={}:TP={}:LRA={}", integrated, true_peak, LOUDNORM_LRA),
+    ]);
+    // loudnorm resamples to 192 kHz internally; bring it back to the source
+    // rate, or to 48 kHz for Opus, which takes only a few fixed rates.
+    let rate = match options.preset.codec {
+      AudioCodec::Opus => OPUS_SAMPLING_RATE,
+      _ => sampling_rate,
+    };
+    if rate > 0 {
+      args.extend(["-ar".to_string(), rate.to_string()]);
+    }
+  }
+  match options.downmix {
+    AudioDownmix::Keep => {}
+    AudioDownmix::Stereo => args.extend(["-ac".to_string(), "2".to_string()]),
+    AudioDownmix::Mono => args.extend(["-ac".to_string(), "1".to_string()]),
+  }
+  args.extend(codec_args(&options.preset, bit_depth));
+  args.push(options.output.clone());
+  args
+}
+
+/// Encoder arguments of `preset`. WAV keeps 24-bit samples when the source
+/// has more than 16 bits.
+fn codec_args(preset: &ConfigAudioPreset, bit_depth: u32) -> Vec<String> {
+  let bitrate = format!("{}k", preset.bitrate.max(8));
+  let args: Vec<&str> = match preset.codec {
+    AudioCodec::Flac => vec!["-c:a", "flac", "-compression_level", "8"],
+    AudioCodec::Opus => vec!["-c:a", "libopus", "-b:a", &bitrate],
+    AudioCodec::Aac => vec!["-c:a", "aac", "-b:a", &bitrate],
+    AudioCodec::Wav if bit_depth > 16 => vec!["-c:a", "pcm_s24le"],
+    AudioCodec::Wav => vec!["-c:a", "pcm_s16le"],
+  };
+  args.into_iter().map(|arg| arg.to_string()).collect()
+}
+
+/// The MediaInfo properties `export` needs.
+pub fn properties() -> Vec<StreamProperty> {
+  let mut properties = analysis::properties(MediaInfoStreamKind::General, GENERAL_PROPERTIES);
+  properties.extend(analysis::properties(MediaInfoStreamKind::Audio, AUDIO_PROPERTIES));
+  properties
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::protocol::LoudnessTarget;
+  use std::collections::HashMap;
+
+  fn options(codec: AudioCodec, bitrate: u32) -> AudioExportOptions {
+    AudioExportOptions {
+      output: "out.opus".to_string(),
+      stream: 1,
+      preset: ConfigAudioPreset {
+        name: "test".to_string(),
+        codec,
+        bitrate,
+      },
+      downmix: AudioDownmix::Keep,
+      normalize: None,
+      start: 0.0,
+      end: 0.0,
+    }
+  }
+
+  fn property_map(stream: MediaInfoStreamKind, num: i32, values: &[(&str, &str)]) -> StreamPropertyMap {
+    StreamPropertyMap {
+      stream,
+      num,
+      property_map: values
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>(),
+      source: Default::default(),
+    }
+  }
+
+  #[test]
+  fn args_map_the_stream_and_apply_the_options() {
+    let mut options = options(AudioCodec::Opus, 96);
+    options.start = 5.0;
+    options.downmix = AudioDownmix::Stereo;
+    options.normalize = Some(LoudnessTarget::Streaming14);
+    let args = args("in.mkv", &options, 10.0, 48000, 0).join(" ");
+    assert_eq!(
+      args,
+      "-y -ss 5.000 -t 10.000 -i in.mkv -map 0:a:1 -vn -sn -dn -af loudnorm=I=-14:TP=-1:LRA=11 \
+       -ar 48000 -ac 2 -c:a libopus -b:a 96k out.opus"
+    );
+  }
+
+  #[test]
+  fn args_resample_normalized_opus_to_48_khz() {
+    let mut options = options(AudioCodec::Opus, 128);
+    options.normalize = Some(LoudnessTarget::EbuR128);
+    let opus = args("in.mkv", &options, 0.0, 44100, 16).join(" ");
+    assert!(opus.contains("-ar 48000 -c:a libopus"));
+    options.preset.codec = AudioCodec::Flac;
+    let flac = args("in.mkv", &options, 0.0, 44100, 16).join(" ");
+    assert!(flac.contains("-ar 44100 -c:a flac"));
+  }
+
+  #[test]
+  fn codec_args_follow_the_preset() {
+    let flac = ConfigAudioPreset {
+      name: "FLAC".to_string(),
+      codec: AudioCodec::Flac,
+      bitrate: 0,
+    };
+    assert_eq!(codec_args(&flac, 24), ["-c:a", "flac", "-compression_level", "8"]);
+    let wav = ConfigAudioPreset {
+      codec: AudioCodec::Wav,
+      ..flac
+    };
+    assert_eq!(codec_args(&wav, 24), ["-c:a", "pcm_s24le"]);
+    assert_eq!(codec_args(&wav, 16), ["-c:a", "pcm_s16le"]);
+  }
+
+  #[test]
+  fn export_rejects_missing_streams_and_empty_ranges() {
+    let properties = vec![property_map(
+      MediaInfoStreamKind::General,
+      0,
+      &[("Duration", "60000"), ("AudioCount", "1")],
+    )];
+    let run = |_: &[String], _: f64| -> Result<String> { panic!("nothing to encode") };
+    let error = export("in.mkv", &options(AudioCodec::Aac, 256), &properties, run).unwrap_err();
+    assert!(error.to_string().starts_with("AUDIO_NO_STREAM:"));
+    let mut options = options(AudioCodec::Aac, 256);
+    options.stream = 0;
+    options.start = 70.0;
+    let error = export("in.mkv", &options, &properties, run).unwrap_err();
+    assert!(error.to_string().starts_with("AUDIO_EMPTY_RANGE:"));
+  }
+}
@@ -16,177 +16,47 @@
 */
 
 use anyhow::Result;
-use std::path::{Path, PathBuf};
 
-use crate::config;
+use crate::config::Config;
 use crate::protocol::BatchMkvExtractStatus;
+use crate::tools::{self, Tool};
 
-const TOOL_STEM: &str = "BatchMkvExtract";
+pub struct BatchMkvExtract;
 
-fn binary_path(dir: &Path) -> Option<PathBuf> {
-  let direct = dir.join(TOOL_STEM);
-  if direct.exists() && direct.is_file() {
-    return Some(direct);
+impl Tool for BatchMkvExtract {
+  fn name(&self) -> &'static str {
+    "BatchMkvExtract"
   }
-  #[cfg(target_os = "windows")]
-  {
-    let exe = dir.join(format!("{}.exe", TOOL_STEM));
-    if exe.exists() && exe.is_file() {
-      return Some(exe);
-    }
-  }
-  None
-}
 
-#[cfg(target_os = "macos")]
-fn find_macos_app_bundle(bin: &Path) -> Option<PathBuf> {
-  // The Mach-O binary lives at `<bundle>.app/Contents/MacOS/BatchMkvExtract`,
-  // so the `.app` ancestor is at most three levels up. Walk a bounded number
-  // of parents instead of looping unbounded.
-  let mut current = bin.parent()?;
-  for _ in 0..4 {
-    if current.extension().and_then(|s| s.to_str()) == Some("app") {
-      return Some(current.to_path_buf());
-    }
-    current = current.parent()?;
+  fn binaries(&self) -> &'static [&'static str] {
+    &["BatchMkvExtract"]
   }
-  None
-}
 
-fn find_running_process_dir() -> Option<PathBuf> {
-  let exe_name = process_name();
-  let sys = sysinfo::System::new_all();
-  for process in sys.processes().values() {
-    let name = process.name().to_string_lossy();
-    // Process names are case-sensitive on Linux/macOS but conventionally
-    // case-insensitive on Windows; eq_ignore_ascii_case is safe across all
-    // three because the canonical name we look for is unambiguous.
-    if !name.eq_ignore_ascii_case(exe_name) {
-      continue;
-    }
-    if let Some(exe) = process.exe() {
-      if let Some(parent) = exe.parent() {
-        return Some(parent.to_path_buf());
-      }
-    }
+  fn config_path(&self, config: &Config) -> String {
+    config.batch_mkv_extract.path.clone()
   }
-  None
-}
 
-pub async fn get_batchmkvextract_status(path: String, check_running: bool) -> Result<BatchMkvExtractStatus> {
-  if check_running {
-    if let Some(dir) = find_running_process_dir() {
-      // The directory we got back is the directory holding the running
-      // binary. On macOS that's `<App>.app/Contents/MacOS`, which is what
-      // we want to store. On Windows/Linux it's the install directory
-      // (e.g. `C:\Program Files\BatchMkvExtract`, `/usr/bin`).
-      if has_executable(&dir) {
-        persist_path(&dir)?;
-        return Ok(BatchMkvExtractStatus {
-          found: true,
-          path: dir.to_string_lossy().to_string(),
-        });
-      }
-    }
-  }
-  let trimmed = path.trim();
-  if trimmed.is_empty() {
-    return Ok(BatchMkvExtractStatus {
-      found: false,
-      path: String::new(),
-    });
+  fn set_config_path(&self, config: &mut Config, path: String) {
+    config.batch_mkv_extract.path = path;
   }
-  let (resolved, found) = resolve(trimmed);
-  if found {
-    persist_path(&resolved)?;
-  }
-  Ok(BatchMkvExtractStatus {
-    found,
-    path: resolved.to_string_lossy().to_string(),
-  })
-}
-
-fn has_executable(path: &Path) -> bool {
-  binary_path(path).is_some()
-}
 
-fn persist_path(path: &Path) -> Result<()> {
-  let new_path = path.to_string_lossy().to_string();
-  let mut cfg = config::get_config();
-  if cfg.batch_mkv_extract.path == new_path {
-    return Ok(());
+  fn process_name(&self) -> Option<&'static str> {
+    Some("BatchMkvExtract")
   }
-  cfg.batch_mkv_extract.path = new_path;
-  config::set_config(cfg)?;
-  Ok(())
-}
 
-fn process_name() -> &'static str {
-  // Windows binaries carry the .exe suffix in sysinfo's process name; Linux
-  // and macOS report the bare executable name. On macOS the running process
-  // is the bundle binary at `<App>.app/Contents/MacOS/BatchMkvExtract`, so
-  // the bare name still matches.
-  if cfg!(target_os = "windows") {
-    "BatchMkvExtract.exe"
-  } else {
-    TOOL_STEM
+  fn open_macos_bundle(&self) -> bool {
+    true
   }
 }
 
-fn resolve(path: &str) -> (PathBuf, bool) {
-  let trimmed = path.trim();
-  let configured = PathBuf::from(trimmed);
-  if has_executable(&configured) {
-    return (configured, true);
-  }
-  (configured, false)
+pub async fn get_batchmkvextract_status(path: String, check_running: bool) -> Result<BatchMkvExtractStatus> {
+  let status = tools::status(&BatchMkvExtract, &path, check_running)?;
+  Ok(BatchMkvExtractStatus {
+    found: status.found,
+    path: status.path,
+  })
 }
 
 pub fn spawn_batchmkvextract(file: &str) -> Result<()> {
-  let file_path = Path::new(file);
-  if !file_path.exists() {
-    return Err(anyhow::anyhow!("File {} does not exist.", file));
-  }
-  let cfg = config::get_config();
-  let dir = PathBuf::from(&cfg.batch_mkv_extract.path);
-  let bin = binary_path(&dir)
-    .ok_or_else(|| anyhow::anyhow!("BatchMkvExtract executable not found under {}.", dir.display()))?;
-
-  // On macOS, going through `open` lets Launch Services activate the bundle
-  // cleanly — spawning the bundle's Mach-O binary directly causes a brief
-  // window flash because the child never goes through proper app activation.
-  #[cfg(target_os = "macos")]
-  {
-    if let Some(app_bundle) = find_macos_app_bundle(&bin) {
-      let mut cmd = std::process::Command::new("/usr/bin/open");
-      cmd
-        .arg("-a")
-        .arg(&app_bundle)
-        .arg("--args")
-        .arg(file)
-        .stdin(std::process::Stdio::null())
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null());
-      cmd
-        .spawn()
-        .map_err(|e| anyhow::anyhow!("Failed to launch BatchMkvExtract via open: {}", e))?;
-      return Ok(());
-    }
-  }
-
-  let mut cmd = std::process::Command::new(&bin);
-  cmd
-    .arg(file)
-    .stdin(std::process::Stdio::null())
-    .stdout(std::process::Stdio::null())
-    .stderr(std::process::Stdio::null());
-  #[cfg(target_os = "windows")]
-  {
-    use std::os::windows::process::CommandExt;
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-  }
-  cmd
-    .spawn()
-    .map_err(|e| anyhow::anyhow!("Failed to launch BatchMkvExtract: {}", e))?;
-  Ok(())
+  tools::launch(&BatchMkvExtract, file)
 }
@@ -0,0 +1,253 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+//! An 8x8 bitmap font for printable ASCII (the public-domain font8x8 basic
+//! set), enough to label images rendered with the `image` crate without
+//! shipping a TrueType font and rasterizer.
+
+use image::{Rgb, RgbImage};
+
+/// Glyph width and height in pixels at scale 1.
+pub const GLYPH_SIZE: u32 = 8;
+
+/// Rows of U+0020..=U+007E, top to bottom; bit 0 is the leftmost pixel.
+const GLYPHS: [[u8; 8]; 95] = [
+  [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+  [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+  [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+  [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // '#'
+  [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // '$'
+  [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // '%'
+  [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // '&'
+  [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // '\''
+  [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // '('
+  [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // ')'
+  [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // '*'
+  [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // '+'
+  [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ','
+  [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // '-'
+  [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+  [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // '/'
+  [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // '0'
+  [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // '1'
+  [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // '2'
+  [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // '3'
+  [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // '4'
+  [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // '5'
+  [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // '6'
+  [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // '7'
+  [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // '8'
+  [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // '9'
+  [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // ':'
+  [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ';'
+  [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // '<'
+  [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // '='
+  [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // '>'
+  [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // '?'
+  [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // '@'
+  [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // 'A'
+  [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // 'B'
+  [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // 'C'
+  [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // 'D'
+  [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // 'E'
+  [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // 'F'
+  [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // 'G'
+  [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // 'H'
+  [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'I'
+  [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // 'J'
+  [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // 'K'
+  [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // 'L'
+  [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // 'M'
+  [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // 'N'
+  [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // 'O'
+  [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // 'P'
+  [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // 'Q'
+  [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // 'R'
+  [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // 'S'
+  [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'T'
+  [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // 'U'
+  [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'V'
+  [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // 'W'
+  [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // 'X'
+  [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // 'Y'
+  [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // 'Z'
+  [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // '['
+  [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // '\\'
+  [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ']'
+  [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // '^'
+  [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // '_'
+  [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+  [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // 'a'
+  [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // 'b'
+  [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // 'c'
+  [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // 'd'
+  [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // 'e'
+  [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // 'f'
+  [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'g'
+  [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // 'h'
+  [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'i'
+  [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // 'j'
+  [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // 'k'
+  [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'l'
+  [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // 'm'
+  [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // 'n'
+  [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // 'o'
+  [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // 'p'
+  [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // 'q'
+  [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // 'r'
+  [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // 's'
+  [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // 't'
+  [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // 'u'
+  [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'v'
+  [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // 'w'
+  [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // 'x'
+  [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'y'
+  [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // 'z'
+  [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // '{'
+  [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // '|'
+  [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // '}'
+  [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
+
+/// `text` in the characters the font has: Latin letters lose their diacritics
+/// and typographic quotes and dashes become plain ones. None when anything
+/// else is left, e.g. CJK, which the caller leaves out rather than print as `?`.
+pub fn ascii(text: &str) -> Option<String> {
+  let mut result = String::with_capacity(text.len());
+  for c in text.chars() {
+    let folded = match c {
+      ' '..='~' => {
+        result.push(c);
+        continue;
+      }
+      '\u{a0}' => " ",
+      'À'..='Å' => "A",
+      'Æ' => "AE",
+      'Ç' => "C",
+      'È'..='Ë' => "E",
+      'Ì'..='Ï' => "I",
+      'Ð' => "D",
+      'Ñ' => "N",
+      'Ò'..='Ö' | 'Ø' => "O",
+      'Ù'..='Ü' => "U",
+      'Ý' => "Y",
+      'Þ' => "Th",
+      'ß' => "ss",
+      'à'..='å' => "a",
+      'æ' => "ae",
+      'ç' => "c",
+      'è'..='ë' => "e",
+      'ì'..='ï' => "i",
+      'ð' => "d",
+      'ñ' => "n",
+      'ò'..='ö' | 'ø' => "o",
+      'ù'..='ü' => "u",
+      'ý' | 'ÿ' => "y",
+      'þ' => "th",
+      '‘' | '’' => "'",
+      '“' | '”' => "\"",
+      '–' | '—' => "-",
+      '…' => "...",
+      _ => return None,
+    };
+    result.push_str(folded);
+  }
+  Some(result)
+}
+
+/// Draw `text` with its top-left corner at (`x`, `y`), each font pixel a
+/// `scale` x `scale` block. Characters outside printable ASCII are drawn as
+/// `?`; pixels outside the image are clipped.
+pub fn draw_text(image: &mut RgbImage, x: i64, y: i64, text: &str, scale: u32, color: Rgb<u8>) {
+  let scale = scale.max(1) as i64;
+  let (width, height) = (image.width() as i64, image.height() as i64);
+  for (index, c) in text.chars().enumerate() {
+    let glyph = glyph(c);
+    let left = x + index as i64 * GLYPH_SIZE as i64 * scale;
+    for (row, bits) in glyph.iter().enumerate() {
+      for column in 0..GLYPH_SIZE as i64 {
+        if bits & (1 << column) == 0 {
+          continue;
+        }
+        for dy in 0..scale {
+          for dx in 0..scale {
+            let px = left + column * scale + dx;
+            let py = y + row as i64 * scale + dy;
+            if px >= 0 && py >= 0 && px < width && py < height {
+              image.put_pixel(px as u32, py as u32, color);
+            }
+          }
+        }
+      }
+    }
+  }
+}
+
+fn glyph(c: char) -> &'static [u8; 8] {
+  match c {
+    ' '..='~' => &GLYPHS[c as usize - ' ' as usize],
+    _ => &GLYPHS['?' as usize - ' ' as usize],
+  }
+}
+
+/// `text` cut to at most `max_width` pixels at `scale`, ending in `...` when cut.
+pub fn truncate(text: &str, max_width: u32, scale: u32) -> String {
+  let max_chars = (max_width / (GLYPH_SIZE * scale.max(1))) as usize;
+  if text.chars().count() <= max_chars {
+    text.to_owned()
+  } else if max_chars > 3 {
+    text.chars().take(max_chars - 3).chain("...".chars()).collect()
+  } else {
+    text.chars().take(max_chars).collect()
+  }
+}
+
+/// The width in pixels `draw_text` covers for `text` at `scale`.
+pub fn text_width(text: &str, scale: u32) -> u32 {
+  text.chars().count() as u32 * GLYPH_SIZE * scale.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn ascii_folds_latin_and_rejects_the_rest() {
+    assert_eq!(ascii("Am\u{e9}lie \u{2013} Part 1").as_deref(), Some("Amelie - Part 1"));
+    assert_eq!(ascii("Stra\u{df}e.mkv").as_deref(), Some("Strasse.mkv"));
+    assert_eq!(ascii("\u{6771}\u{4eac}.mp4"), None);
+  }
+
+  #[test]
+  fn draw_text_clips_and_substitutes() {
+    let mut image = RgbImage::new(12, 8);
+    let white = Rgb([255, 255, 255]);
+    draw_text(&mut image, -2, 0, "I\u{e9}", 1, white);
+    // 'I' row 0 is 0x1E: pixels 1..=4, shifted left by 2.
+    assert_eq!(image.get_pixel(0, 0), &white);
+    assert_eq!(image.get_pixel(3, 0), &Rgb([0, 0, 0]));
+    // The non-ASCII character renders as '?' (row 0 is 0x1E too).
+    assert_eq!(image.get_pixel(7, 0), &white);
+    assert_eq!(text_width("ab", 2), 32);
+  }
+
+  #[test]
+  fn truncate_adds_ellipsis() {
+    assert_eq!(truncate("contact sheet", 80, 1), "contact...");
+    assert_eq!(truncate("sheet", 80, 1), "sheet");
+  }
+}
@@ -0,0 +1,264 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use image::{Rgb, RgbImage};
+use std::path::Path;
+
+use crate::chart::{self, Panel};
+use crate::ffprobe::StreamPacket;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{BitrateGraph, BitrateSeries, StreamPropertyMap};
+
+const AUDIO_COLOR: Rgb<u8> = Rgb([0xF5, 0x9E, 0x0B]);
+/// Most windows a series gets; longer files get wider windows.
+const MAX_POINTS: f64 = 20_000.0;
+const MIN_WINDOW: f64 = 0.04;
+/// Margins around each panel: room for the y labels, the title and the x labels.
+const PANEL_LEFT: u32 = 64;
+const PANEL_RIGHT: u32 = 16;
+const PANEL_TOP: u32 = 20;
+const PANEL_BOTTOM: u32 = 24;
+const VIDEO_COLOR: Rgb<u8> = Rgb([0xF9, 0x73, 0x16]);
+
+/// Bin the `packets` of `file` into windows of about `window` seconds per
+/// video and audio stream. `streams` are the `ffprobe::probe` maps of `file`,
+/// which carry each stream's ffprobe index, codec and the duration.
+pub fn analyze(
+  file: &str,
+  window: f64,
+  streams: &[StreamPropertyMap],
+  packets: &[StreamPacket],
+) -> Result<BitrateGraph> {
+  let start = packets
+    .iter()
+    .filter_map(|packet| packet.time)
+    .fold(f64::INFINITY, f64::min);
+  let end = packets
+    .iter()
+    .filter_map(|packet| packet.time)
+    .fold(f64::NEG_INFINITY, f64::max);
+  let duration = streams
+    .iter()
+    .find(|map| map.stream == MediaInfoStreamKind::General)
+    .and_then(|map| map.property_map.get("Duration"))
+    .and_then(|value| value.parse::<f64>().ok())
+    .map(|ms| ms / 1000.0)
+    .unwrap_or(end - start);
+  if !start.is_finite() || duration <= 0.0 {
+    return Err(anyhow::anyhow!("BITRATE_NO_PACKETS:{}", file));
+  }
+  let window = window.max(MIN_WINDOW).max(duration / MAX_POINTS);
+  let bins = (duration / window).ceil().max(1.0) as usize;
+  let series = streams
+    .iter()
+    .filter(|map| matches!(map.stream, MediaInfoStreamKind::Video | MediaInfoStreamKind::Audio))
+    .filter_map(|map| {
+      let index = map.property_map.get("index")?.parse::<i64>().ok()?;
+      let mut bits = vec![0.0; bins];
+      let mut total = 0.0;
+      for packet in packets.iter().filter(|packet| packet.index == index) {
+        let Some(time) = packet.time else {
+          continue;
+        };
+        let bin = (((time - start) / window) as usize).min(bins - 1);
+        bits[bin] += packet.size as f64 * 8.0;
+        total += packet.size as f64 * 8.0;
+      }
+      let points: Vec<f64> = bits.iter().map(|bits| bits / window / 1000.0).collect();
+      let full = &points[..points.len().saturating_sub(1).max(1)];
+      Some(BitrateSeries {
+        stream: map.stream,
+        num: map.num,
+        codec: map.property_map.get("codec_name").cloned().unwrap_or_default(),
+        average: total / duration / 1000.0,
+        min: full.iter().copied().fold(f64::INFINITY, f64::min),
+        max: full.iter().copied().fold(0.0, f64::max),
+        points,
+      })
+    })
+    .collect::<Vec<_>>();
+  if series.is_empty() {
+    return Err(anyhow::anyhow!("BITRATE_NO_PACKETS:{}", file));
+  }
+  Ok(BitrateGraph {
+    window,
+    duration,
+    series,
+  })
+}
+
+/// `1.5M` / `800k` for a rate in kbit/s.
+fn format_rate(kbps: f64) -> String {
+  if kbps >= 1000.0 {
+    let mbps = kbps / 1000.0;
+    if mbps.fract().abs() < 1e-9 {
+      format!("{}M", mbps)
+    } else {
+      format!("{:.1}M", mbps)
+    }
+  } else {
+    format!("{}k", kbps.round())
+  }
+}
+
+/// Draw `graph` as one panel per series, stacked, `width` x `height` pixels
+/// in total, and write it to `output` as PNG. Each pixel column shows the
+/// highest window it covers, so peaks survive downscaling; the average is a
+/// horizontal line.
+pub fn render(graph: &BitrateGraph, output: &Path, width: u32, height: u32) -> Result<()> {
+  let rows = graph.series.len().max(1) as u32;
+  let panel_height = (height / rows).saturating_sub(PANEL_TOP + PANEL_BOTTOM);
+  let panel_width = width.saturating_sub(PANEL_LEFT + PANEL_RIGHT);
+  if panel_height < 16 || panel_width < 16 {
+    return Err(anyhow::anyhow!("BITRATE_GRAPH_TOO_SMALL:{}x{}", width, height));
+  }
+  let mut image = RgbImage::from_pixel(width, height, chart::BACKGROUND);
+  let x_ticks: Vec<(f64, String)> = chart::nice_ticks(graph.duration, 8)
+    .into_iter()
+    .map(|seconds| (seconds / graph.duration, chart::format_time(seconds)))
+    .collect();
+  for (row, series) in graph.series.iter().enumerate() {
+    let panel = Panel {
+      left: PANEL_LEFT,
+      top: row as u32 * (height / rows) + PANEL_TOP,
+      width: panel_width,
+      height: panel_height,
+    };
+    let ceiling = series.max.max(series.points.iter().copied().fold(0.0, f64::max)) * 1.05;
+    let y_ticks: Vec<(f64, String)> = chart::nice_ticks(ceiling, 4)
+      .into_iter()
+      .map(|kbps| (kbps / ceiling.max(1e-9), format_rate(kbps)))
+      .collect();
+    let title = format!(
+      "{} #{} ({})  avg {}b/s  min {}b/s  max {}b/s",
+      if series.stream == MediaInfoStreamKind::Video {
+        "Video"
+      } else {
+        "Audio"
+      },
+      series.num + 1,
+      series.codec,
+      format_rate(series.average),
+      format_rate(series.min),
+      format_rate(series.max),
+    );
+    chart::draw_axes(&mut image, &panel, &title, &x_ticks, &y_ticks);
+    let color = if series.stream == MediaInfoStreamKind::Video {
+      VIDEO_COLOR
+    } else {
+      AUDIO_COLOR
+    };
+    let points = series.points.len();
+    // The series may end before `duration` (a partial last window).
+    let span = (points as f64 * graph.window / graph.duration).min(1.0);
+    let columns = ((panel.width - 2) as f64 * span).round() as usize;
+    for column in 0..columns {
+      let first = column * points / columns.max(1);
+      let last = ((column + 1) * points / columns.max(1)).max(first + 1).min(points);
+      let value = series.points[first..last].iter().copied().fold(0.0, f64::max);
+      if ceiling > 0.0 && value > 0.0 {
+        let x = panel.left + 1 + column as u32;
+        chart::draw_vline(&mut image, x, panel.y(value / ceiling), panel.y(0.0) - 1, color);
+      }
+    }
+    if ceiling > 0.0 {
+      chart::draw_hline(
+        &mut image,
+        panel.left + 1,
+        panel.left + panel.width - 2,
+        panel.y(series.average / ceiling),
+        chart::FOREGROUND,
+      );
+    }
+  }
+  chart::write_png(&image, output)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::protocol::PropertySource;
+
+  fn map(stream: MediaInfoStreamKind, num: i32, pairs: &[(&str, &str)]) -> StreamPropertyMap {
+    StreamPropertyMap {
+      stream,
+      num,
+      property_map: pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+      source: PropertySource::Ffprobe,
+    }
+  }
+
+  #[test]
+  fn test_analyze() {
+    let streams = [
+      map(MediaInfoStreamKind::General, 0, &[("Duration", "3500")]),
+      map(MediaInfoStreamKind::Video, 0, &[("index", "0"), ("codec_name", "h264")]),
+      map(MediaInfoStreamKind::Audio, 0, &[("index", "1"), ("codec_name", "aac")]),
+      map(MediaInfoStreamKind::Text, 0, &[("index", "2")]),
+    ];
+    // Timestamps start at 10 s, as in many transport streams.
+    let packets: Vec<StreamPacket> = [
+      (0, 10.0, 125_000),
+      (1, 10.0, 1_000),
+      (0, 10.5, 125_000),
+      (0, 11.2, 50_000),
+      (1, 11.5, 1_000),
+      (0, 12.0, 250_000),
+      (0, 13.4, 10_000),
+      (2, 13.0, 100),
+    ]
+    .iter()
+    .map(|(index, time, size)| StreamPacket {
+      index: *index,
+      time: Some(*time),
+      size: *size,
+    })
+    .collect();
+    let graph = analyze("in.ts", 1.0, &streams, &packets).unwrap();
+    assert_eq!(graph.series.len(), 2);
+    let video = &graph.series[0];
+    assert_eq!(video.points, vec![2000.0, 400.0, 2000.0, 80.0]);
+    assert_eq!((video.min, video.max), (400.0, 2000.0));
+    assert_eq!(graph.series[1].codec, "aac");
+    assert_eq!(graph.series[1].points, vec![8.0, 8.0, 0.0, 0.0]);
+  }
+
+  #[test]
+  fn test_render() {
+    let graph = BitrateGraph {
+      window: 1.0,
+      duration: 4.0,
+      series: vec![BitrateSeries {
+        stream: MediaInfoStreamKind::Video,
+        num: 0,
+        codec: "h264".to_string(),
+        points: vec![2000.0, 400.0, 2000.0, 80.0],
+        average: 1120.0,
+        min: 400.0,
+        max: 2000.0,
+      }],
+    };
+    let output = std::env::temp_dir().join("bettermediainfo_bitrate_test.png");
+    render(&graph, &output, 400, 200).unwrap();
+    let image = image::open(&output).unwrap().to_rgb8();
+    std::fs::remove_file(&output).ok();
+    assert_eq!(image.dimensions(), (400, 200));
+    assert!(image.pixels().any(|pixel| *pixel == VIDEO_COLOR));
+    assert!(render(&graph, &output, 40, 20).is_err());
+  }
+}
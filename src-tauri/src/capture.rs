@@ -0,0 +1,250 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use crate::ffprobe::{Chapter, VideoPacket};
+use crate::protocol::CapturePoint;
+
+/// Characters no file name on Windows, macOS or Linux may hold.
+const FORBIDDEN: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const MAX_TITLE_CHARS: usize = 80;
+
+/// One point per chapter start, labelled with its number and title, or its
+/// time when it has no title.
+pub fn chapter_points(chapters: &[Chapter]) -> Vec<CapturePoint> {
+  let width = chapters.len().to_string().len().max(2);
+  chapters
+    .iter()
+    .enumerate()
+    .map(|(index, chapter)| {
+      let title = sanitize(&chapter.title);
+      CapturePoint {
+        seconds: chapter.start,
+        label: format!(
+          "{:0width$} {}",
+          index + 1,
+          if title.is_empty() {
+            timestamp_label(chapter.start)
+          } else {
+            title
+          },
+          width = width
+        ),
+      }
+    })
+    .collect()
+}
+
+/// One point per keyframe, in presentation order.
+pub fn keyframe_points(packets: &[VideoPacket]) -> Vec<CapturePoint> {
+  let mut times: Vec<f64> = packets
+    .iter()
+    .filter(|packet| packet.key)
+    .filter_map(|packet| packet.pts)
+    .collect();
+  times.sort_by(f64::total_cmp);
+  timestamp_points(&times)
+}
+
+/// The chapters of the Menu streams in MediaInfo's text report, where each
+/// chapter is a `00:01:23.456 : en:Title` line.
+pub fn menu_chapters(inform: &str) -> Vec<Chapter> {
+  let mut chapters = Vec::new();
+  let mut in_menu = false;
+  for line in inform.lines() {
+    let Some((key, value)) = line.split_once(" :") else {
+      let header = line.trim();
+      if !header.is_empty() {
+        in_menu = header == "Menu" || header.starts_with("Menu #");
+      }
+      continue;
+    };
+    if let Some(start) = parse_time(key.trim()).filter(|_| in_menu) {
+      chapters.push(Chapter {
+        start,
+        title: strip_language(value.trim()).to_owned(),
+      });
+    }
+  }
+  chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+  chapters.dedup_by(|a, b| a.start == b.start);
+  chapters
+}
+
+/// `HH:MM:SS.mmm`, or `HH:MM:SS:mmm` as older MediaInfo versions write it.
+fn parse_time(text: &str) -> Option<f64> {
+  let parts = text
+    .split(':')
+    .map(|part| part.parse::<f64>().ok())
+    .collect::<Option<Vec<f64>>>()?;
+  match parts[..] {
+    [hours, minutes, seconds] => Some(hours * 3600.0 + minutes * 60.0 + seconds),
+    [hours, minutes, seconds, millis] => Some(hours * 3600.0 + minutes * 60.0 + seconds + millis / 1000.0),
+    _ => None,
+  }
+}
+
+/// A chapter title usable in a file name: forbidden and control characters
+/// become `_`, runs of spaces collapse, and trailing dots go (Windows drops them).
+/// `%` stays: point captures write their single image with `-update 1`, so
+/// ffmpeg doesn't read it as a frame-number token.
+fn sanitize(title: &str) -> String {
+  let replaced: String = title
+    .chars()
+    .map(|c| {
+      if FORBIDDEN.contains(&c) || c.is_control() {
+        '_'
+      } else {
+        c
+      }
+    })
+    .collect();
+  let collapsed = replaced.split_whitespace().collect::<Vec<_>>().join(" ");
+  collapsed
+    .chars()
+    .take(MAX_TITLE_CHARS)
+    .collect::<String>()
+    .trim_end_matches(['.', ' '])
+    .to_owned()
+}
+
+/// MediaInfo prefixes chapter titles with their language (`en:Intro`), or a
+/// bare `:` when there is none.
+fn strip_language(value: &str) -> &str {
+  match value.split_once(':') {
+    Some((language, title)) if language.len() <= 5 && language.chars().all(|c| c.is_ascii_lowercase() || c == '-') => {
+      title.trim()
+    }
+    _ => value,
+  }
+}
+
+/// `HH-MM-SS.mmm`, a timestamp that sorts and is valid in file names.
+pub fn timestamp_label(seconds: f64) -> String {
+  let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+  format!(
+    "{:02}-{:02}-{:02}.{:03}",
+    millis / 3_600_000,
+    millis / 60_000 % 60,
+    millis / 1000 % 60,
+    millis % 1000
+  )
+}
+
+/// One point per distinct timestamp, in the order given.
+pub fn timestamp_points(timestamps: &[f64]) -> Vec<CapturePoint> {
+  let mut points: Vec<CapturePoint> = Vec::new();
+  for seconds in timestamps.iter().filter(|seconds| **seconds >= 0.0) {
+    let label = timestamp_label(*seconds);
+    if points.iter().all(|point| point.label != label) {
+      points.push(CapturePoint {
+        seconds: *seconds,
+        label,
+      });
+    }
+  }
+  points
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_chapter_points() {
+    let chapters = vec![
+      Chapter {
+        start: 0.0,
+        title: "Intro: Part 1/2".to_string(),
+      },
+      Chapter {
+        start: 312.5,
+        title: String::new(),
+      },
+      Chapter {
+        start: 600.0,
+        title: "  The  End... ".to_string(),
+      },
+      Chapter {
+        start: 900.0,
+        title: "100% Done".to_string(),
+      },
+    ];
+    let labels: Vec<String> = chapter_points(&chapters).into_iter().map(|point| point.label).collect();
+    assert_eq!(
+      labels,
+      vec!["01 Intro_ Part 1_2", "02 00-05-12.500", "03 The End", "04 100% Done"]
+    );
+  }
+
+  #[test]
+  fn test_menu_chapters() {
+    let inform = "General\n\
+      Complete name                            : movie.mkv\n\
+      \n\
+      Menu\n\
+      00:00:00.000                             : en:Opening\n\
+      00:05:12.500                             : :Part 2: Night\n\
+      00:10:00:250                             : Credits\n\
+      \n\
+      Text\n\
+      Duration                                 : 00:20:00.000\n";
+    assert_eq!(
+      menu_chapters(inform),
+      vec![
+        Chapter {
+          start: 0.0,
+          title: "Opening".to_string(),
+        },
+        Chapter {
+          start: 312.5,
+          title: "Part 2: Night".to_string(),
+        },
+        Chapter {
+          start: 600.25,
+          title: "Credits".to_string(),
+        },
+      ]
+    );
+    assert!(menu_chapters("General\nFormat : Matroska\n").is_empty());
+  }
+
+  #[test]
+  fn test_timestamp_points() {
+    let packets = vec![
+      VideoPacket {
+        pts: Some(4.004),
+        key: true,
+      },
+      VideoPacket {
+        pts: Some(0.0),
+        key: true,
+      },
+      VideoPacket {
+        pts: Some(1.0),
+        key: false,
+      },
+    ];
+    let points = keyframe_points(&packets);
+    assert_eq!(points[0].label, "00-00-00.000");
+    assert_eq!(points[1].label, "00-00-04.004");
+    let points = timestamp_points(&[3725.5, -1.0, 3725.5, 10.0]);
+    assert_eq!(
+      points.iter().map(|point| point.label.as_str()).collect::<Vec<_>>(),
+      vec!["01-02-05.500", "00-00-10.000"]
+    );
+  }
+}
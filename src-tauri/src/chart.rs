@@ -0,0 +1,185 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use image::{ImageFormat, Rgb, RgbImage};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::bitmap_font::{self, GLYPH_SIZE};
+
+pub const BACKGROUND: Rgb<u8> = Rgb([0x20, 0x20, 0x20]);
+pub const FOREGROUND: Rgb<u8> = Rgb([0xE0, 0xE0, 0xE0]);
+pub const GRID: Rgb<u8> = Rgb([0x40, 0x40, 0x40]);
+/// Length of a tick mark outside the plot area, in pixels.
+const TICK: u32 = 4;
+
+/// The plot area of a chart inside its image, in pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct Panel {
+  pub left: u32,
+  pub top: u32,
+  pub width: u32,
+  pub height: u32,
+}
+
+impl Panel {
+  /// The column at `fraction` (0-1) of the width, from the left.
+  pub fn x(&self, fraction: f64) -> u32 {
+    self.left + (fraction.clamp(0.0, 1.0) * (self.width - 1) as f64).round() as u32
+  }
+
+  /// The row at `fraction` (0-1) of the height, from the bottom.
+  pub fn y(&self, fraction: f64) -> u32 {
+    self.top + self.height - 1 - (fraction.clamp(0.0, 1.0) * (self.height - 1) as f64).round() as u32
+  }
+}
+
+/// Frame `panel`, draw a grid line and a label at every tick, and `title`
+/// above it. Ticks are (fraction, label) pairs: x ticks go below the panel,
+/// y ticks to its left.
+pub fn draw_axes(
+  image: &mut RgbImage,
+  panel: &Panel,
+  title: &str,
+  x_ticks: &[(f64, String)],
+  y_ticks: &[(f64, String)],
+) {
+  for (fraction, label) in x_ticks {
+    let x = panel.x(*fraction);
+    draw_vline(image, x, panel.top, panel.top + panel.height - 1, GRID);
+    draw_vline(
+      image,
+      x,
+      panel.top + panel.height,
+      panel.top + panel.height + TICK,
+      FOREGROUND,
+    );
+    let width = bitmap_font::text_width(label, 1) as i64;
+    let left = (x as i64 - width / 2).clamp(0, image.width() as i64 - width);
+    bitmap_font::draw_text(
+      image,
+      left,
+      (panel.top + panel.height + TICK + 2) as i64,
+      label,
+      1,
+      FOREGROUND,
+    );
+  }
+  for (fraction, label) in y_ticks {
+    let y = panel.y(*fraction);
+    draw_hline(image, panel.left, panel.left + panel.width - 1, y, GRID);
+    draw_hline(image, panel.left.saturating_sub(TICK), panel.left, y, FOREGROUND);
+    let width = bitmap_font::text_width(label, 1) as i64;
+    bitmap_font::draw_text(
+      image,
+      panel.left as i64 - TICK as i64 - 2 - width,
+      y as i64 - GLYPH_SIZE as i64 / 2,
+      label,
+      1,
+      FOREGROUND,
+    );
+  }
+  let (right, bottom) = (panel.left + panel.width - 1, panel.top + panel.height - 1);
+  draw_hline(image, panel.left, right, panel.top, FOREGROUND);
+  draw_hline(image, panel.left, right, bottom, FOREGROUND);
+  draw_vline(image, panel.left, panel.top, bottom, FOREGROUND);
+  draw_vline(image, right, panel.top, bottom, FOREGROUND);
+  let title = bitmap_font::truncate(title, panel.width, 1);
+  bitmap_font::draw_text(
+    image,
+    panel.left as i64,
+    panel.top as i64 - GLYPH_SIZE as i64 - 4,
+    &title,
+    1,
+    FOREGROUND,
+  );
+}
+
+/// A horizontal line from `x0` to `x1` (inclusive) on row `y`, clipped to the image.
+pub fn draw_hline(image: &mut RgbImage, x0: u32, x1: u32, y: u32, color: Rgb<u8>) {
+  if y >= image.height() {
+    return;
+  }
+  for x in x0.min(x1)..=x0.max(x1).min(image.width().saturating_sub(1)) {
+    image.put_pixel(x, y, color);
+  }
+}
+
+/// A vertical line from `y0` to `y1` (inclusive) on column `x`, clipped to the image.
+pub fn draw_vline(image: &mut RgbImage, x: u32, y0: u32, y1: u32, color: Rgb<u8>) {
+  if x >= image.width() {
+    return;
+  }
+  for y in y0.min(y1)..=y0.max(y1).min(image.height().saturating_sub(1)) {
+    image.put_pixel(x, y, color);
+  }
+}
+
+/// `H:MM:SS` (or `M:SS` under an hour) for a position in seconds.
+pub fn format_time(seconds: f64) -> String {
+  let total = seconds.max(0.0).round() as u64;
+  let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+  if hours > 0 {
+    format!("{}:{:02}:{:02}", hours, minutes, seconds)
+  } else {
+    format!("{}:{:02}", minutes, seconds)
+  }
+}
+
+/// Round tick values from 0 up to `max`, about `target` of them, spaced by
+/// 1, 2 or 5 times a power of ten.
+pub fn nice_ticks(max: f64, target: u32) -> Vec<f64> {
+  if max.is_nan() || max <= 0.0 || target == 0 {
+    return vec![0.0];
+  }
+  let raw = max / target as f64;
+  let magnitude = 10f64.powf(raw.log10().floor());
+  let step = [1.0, 2.0, 5.0, 10.0]
+    .iter()
+    .map(|factor| factor * magnitude)
+    .find(|step| *step >= raw)
+    .unwrap_or(10.0 * magnitude);
+  (0..)
+    .map(|index| index as f64 * step)
+    .take_while(|value| *value <= max * (1.0 + 1e-9))
+    .collect()
+}
+
+pub fn write_png(image: &RgbImage, output: &Path) -> Result<()> {
+  if let Some(parent) = output.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+    std::fs::create_dir_all(parent)?;
+  }
+  let mut writer = BufWriter::new(File::create(output)?);
+  image.write_to(&mut writer, ImageFormat::Png)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_nice_ticks() {
+    assert_eq!(nice_ticks(10.0, 5), vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+    assert_eq!(nice_ticks(7300.0, 4), vec![0.0, 2000.0, 4000.0, 6000.0]);
+    assert_eq!(nice_ticks(0.0, 4), vec![0.0]);
+    assert_eq!(format_time(3725.4), "1:02:05");
+    assert_eq!(format_time(65.0), "1:05");
+  }
+}
@@ -0,0 +1,369 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+
+use crate::analysis;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{ClipFormat, ClipOptions, ClipResult, ClipSegments, StreamProperty, StreamPropertyMap};
+
+/// Encodes tried to get under the target size before settling for the last one.
+const MAX_ATTEMPTS: u32 = 5;
+const MAX_SEGMENTS: u32 = 50;
+/// A clip is never slowed below this rate or shrunk below this width to meet
+/// its target size.
+const MIN_FPS: f64 = 5.0;
+const MIN_LENGTH: f64 = 0.1;
+const MIN_WIDTH: u32 = 64;
+const PROPERTIES: &[&str] = &[
+  "Width",
+  "Height",
+  "DisplayAspectRatio",
+  "Rotation",
+  "FrameRate",
+  "Duration",
+];
+/// Each retry aims this far under the target, since the size doesn't shrink
+/// exactly with the pixel count.
+const SIZE_MARGIN: f64 = 0.9;
+const WEBP_MIN_QUALITY: u32 = 40;
+const WEBP_QUALITY: u32 = 75;
+
+/// Frame size, rate and WebP quality of one encode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Encode {
+  width: u32,
+  height: u32,
+  fps: f64,
+  quality: u32,
+}
+
+impl Encode {
+  /// Settings for the next attempt after an encode came out `ratio` times too
+  /// large: the size goes first, then the rate, and WebP also loses quality.
+  /// None when nothing is left to give.
+  fn shrink(&self, format: ClipFormat, ratio: f64) -> Option<Self> {
+    let factor = ratio * SIZE_MARGIN;
+    let width = even((self.width as f64 * factor.sqrt().min(0.95)) as u32)
+      .max(MIN_WIDTH)
+      .min(self.width);
+    let area = (width as f64 / self.width as f64).powi(2);
+    let fps = ((self.fps * (factor / area).min(1.0) * 100.0).round() / 100.0)
+      .max(MIN_FPS)
+      .min(self.fps);
+    let quality = if format == ClipFormat::Webp {
+      self.quality.saturating_sub(10).max(WEBP_MIN_QUALITY).min(self.quality)
+    } else {
+      self.quality
+    };
+    let next = Self {
+      width,
+      height: even((self.height as f64 * width as f64 / self.width as f64).round() as u32),
+      fps,
+      quality,
+    };
+    (next != *self).then_some(next)
+  }
+}
+
+/// Encode the clip `options` ask for into `options.output`, re-encoding
+/// smaller until it fits `options.target_size`. The properties are the
+/// `properties()` of `file`. `run` executes one encode, numbered from 1, with
+/// the clip duration to report progress against (see `ffmpeg::run_analysis`).
+pub fn create(
+  file: &str,
+  options: &ClipOptions,
+  properties: &[StreamPropertyMap],
+  mut run: impl FnMut(u32, &[String], f64) -> Result<String>,
+) -> Result<ClipResult> {
+  let get = |name: &str| analysis::stream_f64(properties, MediaInfoStreamKind::Video, 0, name);
+  let (source_width, source_height) = (get("Width") as u32, get("Height") as u32);
+  if source_width == 0 || source_height == 0 {
+    return Err(anyhow::anyhow!("CLIP_NO_VIDEO:{}", file));
+  }
+  let (source_width, source_height) =
+    display_size(source_width, source_height, get("DisplayAspectRatio"), get("Rotation"));
+  let segments = segments(file, options, get("Duration") / 1000.0)?;
+  let duration: f64 = segments.iter().map(|(_, length)| length).sum();
+  let source_fps = get("FrameRate");
+  let (width, height) = fit(source_width, source_height, options.max_width, options.max_height);
+  let mut encode = Encode {
+    width,
+    height,
+    fps: if source_fps > 0.0 {
+      options.fps.clamp(1.0, source_fps)
+    } else {
+      options.fps.max(1.0)
+    },
+    quality: WEBP_QUALITY,
+  };
+  let mut attempts = 0;
+  loop {
+    attempts += 1;
+    run(attempts, &args(file, options, &segments, &encode), duration)?;
+    let size = std::fs::metadata(&options.output)?.len();
+    let target_met = options.target_size == 0 || size <= options.target_size;
+    let next = if target_met || attempts >= MAX_ATTEMPTS {
+      None
+    } else {
+      encode.shrink(options.format, options.target_size as f64 / size as f64)
+    };
+    let Some(next) = next else {
+      return Ok(ClipResult {
+        output: options.output.clone(),
+        size,
+        width: encode.width,
+        height: encode.height,
+        fps: encode.fps,
+        duration,
+        attempts,
+        target_met,
+      });
+    };
+    encode = next;
+  }
+}
+
+/// One input per segment, cut with input seeking, each brought to the clip's
+/// rate and size and joined. GIF and APNG are quantized to a palette built
+/// from the whole clip.
+fn args(file: &str, options: &ClipOptions, segments: &[(f64, f64)], encode: &Encode) -> Vec<String> {
+  let mut args = vec!["-y".to_string()];
+  for (start, length) in segments {
+    args.extend([
+      "-ss".to_string(),
+      format!("{:.3}", start),
+      "-t".to_string(),
+      format!("{:.3}", length),
+      "-i".to_string(),
+      file.to_string(),
+    ]);
+  }
+  let palette = options.format != ClipFormat::Webp;
+  let mut graph = String::new();
+  for index in 0..segments.len() {
+    graph.push_str(&format!(
+      "[{}:v:0]setpts=PTS-STARTPTS,fps={},scale={}:{}:flags=lanczos,setsar=1[s{}];",
+      index, encode.fps, encode.width, encode.height, index
+    ));
+  }
+  for index in 0..segments.len() {
+    graph.push_str(&format!("[s{}]", index));
+  }
+  graph.push_str(&format!(
+    "concat=n={}:v=1:a=0{}",
+    segments.len(),
+    if palette { "[v]" } else { "[out]" }
+  ));
+  if palette {
+    graph.push_str(
+      ";[v]split[a][b];[a]palettegen=stats_mode=diff[p];[b][p]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle[out]",
+    );
+  }
+  args.extend([
+    "-filter_complex".to_string(),
+    graph,
+    "-map".to_string(),
+    "[out]".to_string(),
+  ]);
+  let output: &[&str] = match options.format {
+    ClipFormat::Gif => &["-loop", "0", "-f", "gif"],
+    ClipFormat::Webp => &["-c:v", "libwebp", "-compression_level", "6", "-loop", "0", "-f", "webp"],
+    ClipFormat::Apng => &["-c:v", "apng", "-plays", "0", "-f", "apng"],
+  };
+  args.extend(output.iter().map(|arg| arg.to_string()));
+  if options.format == ClipFormat::Webp {
+    args.extend(["-quality".to_string(), encode.quality.to_string()]);
+  }
+  args.push(options.output.clone());
+  args
+}
+
+/// The size the frames are shown at: anamorphic pixels stretched to the
+/// display aspect ratio (0 when unknown), then turned for a rotation of 90 or
+/// 270 degrees, which ffmpeg applies before the filters.
+fn display_size(width: u32, height: u32, aspect_ratio: f64, rotation: f64) -> (u32, u32) {
+  let width = if aspect_ratio > 0.0 {
+    ((height as f64 * aspect_ratio).round() as u32).max(1)
+  } else {
+    width
+  };
+  if (rotation.round() as i64).rem_euclid(180) == 90 {
+    (height, width)
+  } else {
+    (width, height)
+  }
+}
+
+/// Round down to an even number, as the scaler and most encoders want.
+fn even(value: u32) -> u32 {
+  (value / 2 * 2).max(2)
+}
+
+/// `width`x`height` scaled down to fit `max_width`x`max_height` (0 for no
+/// limit), keeping the aspect ratio.
+fn fit(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+  let limit = |max: u32, size: u32| if max == 0 { 1.0 } else { max as f64 / size as f64 };
+  let scale = limit(max_width, width).min(limit(max_height, height)).min(1.0);
+  (
+    even((width as f64 * scale).round() as u32),
+    even((height as f64 * scale).round() as u32),
+  )
+}
+
+pub fn properties() -> Vec<StreamProperty> {
+  analysis::properties(MediaInfoStreamKind::Video, PROPERTIES)
+}
+
+/// Start and length in seconds of each part of the clip. Even segments are
+/// centred in equal slices of `duration`, shortened so they don't overlap.
+fn segments(file: &str, options: &ClipOptions, duration: f64) -> Result<Vec<(f64, f64)>> {
+  match options.segments {
+    ClipSegments::Range => {
+      let start = options.start.max(0.0);
+      let end = if duration > 0.0 {
+        options.end.min(duration)
+      } else {
+        options.end
+      };
+      if end - start < MIN_LENGTH {
+        return Err(anyhow::anyhow!("CLIP_EMPTY_RANGE:{}:{}", options.start, options.end));
+      }
+      Ok(vec![(start, end - start)])
+    }
+    ClipSegments::Even => {
+      if duration <= 0.0 {
+        return Err(anyhow::anyhow!("CLIP_NO_DURATION:{}", file));
+      }
+      let count = options.count.clamp(1, MAX_SEGMENTS);
+      let length = options.length.max(MIN_LENGTH).min(duration / count as f64);
+      Ok(
+        (0..count)
+          .map(|index| {
+            let middle = duration * (index as f64 + 0.5) / count as f64;
+            ((middle - length / 2.0).clamp(0.0, duration - length), length)
+          })
+          .collect(),
+      )
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn options(format: ClipFormat, segments: ClipSegments) -> ClipOptions {
+    ClipOptions {
+      output: "out.gif".to_string(),
+      format,
+      segments,
+      start: 10.0,
+      end: 14.0,
+      count: 4,
+      length: 1.0,
+      fps: 12.0,
+      max_width: 480,
+      max_height: 0,
+      target_size: 0,
+    }
+  }
+
+  #[test]
+  fn test_args() {
+    let encode = Encode {
+      width: 480,
+      height: 270,
+      fps: 12.0,
+      quality: WEBP_QUALITY,
+    };
+    let gif = options(ClipFormat::Gif, ClipSegments::Even);
+    let gif_args = args("in.mkv", &gif, &[(5.5, 1.0), (15.5, 1.0)], &encode);
+    assert_eq!(&gif_args[..7], &["-y", "-ss", "5.500", "-t", "1.000", "-i", "in.mkv"]);
+    assert_eq!(
+      gif_args[14],
+      "[0:v:0]setpts=PTS-STARTPTS,fps=12,scale=480:270:flags=lanczos,setsar=1[s0];\
+       [1:v:0]setpts=PTS-STARTPTS,fps=12,scale=480:270:flags=lanczos,setsar=1[s1];\
+       [s0][s1]concat=n=2:v=1:a=0[v];\
+       [v]split[a][b];[a]palettegen=stats_mode=diff[p];[b][p]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle[out]"
+    );
+    assert_eq!(
+      &gif_args[15..],
+      &["-map", "[out]", "-loop", "0", "-f", "gif", "out.gif"]
+    );
+    let webp = options(ClipFormat::Webp, ClipSegments::Range);
+    let webp_args = args("in.mkv", &webp, &[(10.0, 4.0)], &encode);
+    assert!(webp_args[8].ends_with("concat=n=1:v=1:a=0[out]"));
+    assert_eq!(&webp_args[webp_args.len() - 3..], &["-quality", "75", "out.gif"]);
+  }
+
+  #[test]
+  fn test_display_size() {
+    assert_eq!(display_size(1920, 1080, 0.0, 0.0), (1920, 1080));
+    assert_eq!(display_size(1920, 1080, 1.778, 90.0), (1080, 1920));
+    assert_eq!(display_size(1920, 1080, 1.778, -90.0), (1080, 1920));
+    assert_eq!(display_size(1920, 1080, 1.778, 180.0), (1920, 1080));
+    assert_eq!(display_size(720, 576, 16.0 / 9.0, 0.0), (1024, 576));
+    assert_eq!(display_size(720, 576, 16.0 / 9.0, 270.0), (576, 1024));
+    let (width, height) = display_size(1440, 1080, 16.0 / 9.0, 90.0);
+    assert_eq!(fit(width, height, 480, 0), (480, 852));
+  }
+
+  #[test]
+  fn test_fit_and_shrink() {
+    assert_eq!(fit(1920, 1080, 480, 0), (480, 270));
+    assert_eq!(fit(1920, 1080, 0, 200), (356, 200));
+    assert_eq!(fit(640, 360, 1280, 720), (640, 360));
+    let encode = Encode {
+      width: 480,
+      height: 270,
+      fps: 15.0,
+      quality: WEBP_QUALITY,
+    };
+    let next = encode.shrink(ClipFormat::Gif, 0.5).unwrap();
+    assert_eq!(
+      (next.width, next.height, next.fps, next.quality),
+      (320, 180, 15.0, WEBP_QUALITY)
+    );
+    let webp = encode.shrink(ClipFormat::Webp, 0.9).unwrap();
+    assert_eq!((webp.width, webp.quality), (432, 65));
+    let small = Encode {
+      width: MIN_WIDTH,
+      height: 36,
+      fps: 10.0,
+      quality: WEBP_QUALITY,
+    };
+    assert_eq!(small.shrink(ClipFormat::Gif, 0.25).unwrap().fps, 5.0);
+    let floor = Encode { fps: MIN_FPS, ..small };
+    assert_eq!(floor.shrink(ClipFormat::Gif, 0.25), None);
+  }
+
+  #[test]
+  fn test_segments() {
+    let range = options(ClipFormat::Gif, ClipSegments::Range);
+    assert_eq!(segments("a", &range, 60.0).unwrap(), vec![(10.0, 4.0)]);
+    assert_eq!(segments("a", &range, 12.0).unwrap(), vec![(10.0, 2.0)]);
+    assert!(segments("a", &range, 8.0).is_err());
+    let even = options(ClipFormat::Gif, ClipSegments::Even);
+    assert_eq!(
+      segments("a", &even, 40.0).unwrap(),
+      vec![(4.5, 1.0), (14.5, 1.0), (24.5, 1.0), (34.5, 1.0)]
+    );
+    assert_eq!(segments("a", &even, 2.0).unwrap()[3], (1.5, 0.5));
+    assert!(segments("a", &even, 0.0).is_err());
+  }
+}
@@ -0,0 +1,266 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use image::RgbaImage;
+use std::path::Path;
+
+use crate::capture;
+use crate::ffmpeg;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{
+  FrameAlignment, FrameCompareOptions, FrameComparison, FramePair, StreamProperty, StreamPropertyMap,
+};
+use crate::quality;
+
+const PROPERTIES: &[&str] = &["Width", "Height", "FrameRate"];
+
+/// The first video stream of a file as far as a comparison needs it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Video {
+  width: u32,
+  height: u32,
+  frame_rate: f64,
+}
+
+impl Video {
+  fn new(file: &str, properties: &[StreamPropertyMap]) -> Result<Self> {
+    let map = properties
+      .iter()
+      .find(|map| map.stream == MediaInfoStreamKind::Video && map.num == 0)
+      .map(|map| &map.property_map)
+      .ok_or_else(|| anyhow::anyhow!("COMPARE_NO_VIDEO:{}", file))?;
+    let get = |name: &str| -> f64 {
+      map
+        .get(name)
+        .and_then(|value| value.parse::<f64>().ok())
+        .unwrap_or_default()
+    };
+    let video = Self {
+      width: get("Width") as u32,
+      height: get("Height") as u32,
+      frame_rate: get("FrameRate"),
+    };
+    if video.width == 0 || video.height == 0 {
+      return Err(anyhow::anyhow!("COMPARE_NO_VIDEO:{}", file));
+    }
+    Ok(video)
+  }
+
+  /// Where ffmpeg seeks to for `position` and the time that position stands
+  /// for. A frame number seeks half a frame early: ffmpeg keeps the first frame
+  /// at or after the seek point, and the millisecond rounding of the seek would
+  /// otherwise skip past the frame asked for.
+  fn seek(&self, alignment: FrameAlignment, position: f64) -> Result<(f64, f64)> {
+    match alignment {
+      FrameAlignment::Timestamp => Ok((position, position)),
+      FrameAlignment::Frame if self.frame_rate > 0.0 => {
+        Ok(((position - 0.5).max(0.0) / self.frame_rate, position / self.frame_rate))
+      }
+      FrameAlignment::Frame => Err(anyhow::anyhow!("COMPARE_NO_FRAME_RATE")),
+    }
+  }
+}
+
+/// Capture the frame at each position of `options` from both files and save,
+/// per position, the two frames at the reference's size (a pair a slider can
+/// flip between), the two side by side, and their difference amplified by
+/// `options.gain`, with the PSNR of the pair. The properties are the
+/// `properties()` of each file. `on_pair` is told before each position is
+/// captured and returns false to cancel.
+pub fn compare(
+  reference: &str,
+  distorted: &str,
+  options: &FrameCompareOptions,
+  reference_properties: &[StreamPropertyMap],
+  distorted_properties: &[StreamPropertyMap],
+  mut on_pair: impl FnMut(u32, u32) -> bool,
+) -> Result<FrameComparison> {
+  let reference_video = Video::new(reference, reference_properties)?;
+  let distorted_video = Video::new(distorted, distorted_properties)?;
+  let positions: Vec<f64> = options
+    .positions
+    .iter()
+    .copied()
+    .filter(|position| position.is_finite() && *position >= 0.0)
+    .collect();
+  if positions.is_empty() {
+    return Err(anyhow::anyhow!("COMPARE_NO_POSITIONS"));
+  }
+  let dir = Path::new(&options.output_dir);
+  std::fs::create_dir_all(dir)?;
+  let stem = Path::new(distorted)
+    .file_stem()
+    .map(|stem| stem.to_string_lossy().into_owned())
+    .unwrap_or_default();
+  let scaled = (distorted_video.width, distorted_video.height) != (reference_video.width, reference_video.height);
+  let total = positions.len() as u32;
+  let mut pairs = Vec::with_capacity(positions.len());
+  for (index, position) in positions.into_iter().enumerate() {
+    if !on_pair(index as u32, total) {
+      return Err(anyhow::anyhow!("FFMPEG_ANALYSIS_CANCELLED"));
+    }
+    let (reference_seek, reference_time) = reference_video.seek(options.alignment, position)?;
+    let (distorted_seek, distorted_time) = distorted_video.seek(options.alignment, position)?;
+    let reference_image = decode(ffmpeg::capture_frame(
+      reference.to_owned(),
+      reference_seek,
+      reference_video.width,
+    )?)?;
+    let mut distorted_image = decode(ffmpeg::capture_frame(
+      distorted.to_owned(),
+      distorted_seek,
+      distorted_video.width,
+    )?)?;
+    if distorted_image.dimensions() != reference_image.dimensions() {
+      distorted_image = image::imageops::resize(
+        &distorted_image,
+        reference_image.width(),
+        reference_image.height(),
+        image::imageops::FilterType::Lanczos3,
+      );
+    }
+    let label = match options.alignment {
+      FrameAlignment::Timestamp => capture::timestamp_label(position),
+      FrameAlignment::Frame => format!("f{}", position.round() as u64),
+    };
+    let save = |role: &str, image: &RgbaImage| -> Result<String> {
+      let path = dir.join(format!("{}_{}_{}.png", stem, label, role));
+      image.save_with_format(&path, image::ImageFormat::Png)?;
+      Ok(path.to_string_lossy().into_owned())
+    };
+    pairs.push(FramePair {
+      position,
+      reference_time,
+      distorted_time,
+      psnr: psnr(&reference_image, &distorted_image),
+      reference: save("reference", &reference_image)?,
+      distorted: save("distorted", &distorted_image)?,
+      side_by_side: save("side", &side_by_side(&reference_image, &distorted_image))?,
+      difference: save("diff", &difference(&reference_image, &distorted_image, options.gain))?,
+    });
+  }
+  Ok(FrameComparison {
+    width: reference_video.width,
+    height: reference_video.height,
+    scaled,
+    pairs,
+  })
+}
+
+fn decode(bytes: Vec<u8>) -> Result<RgbaImage> {
+  Ok(image::load_from_memory(&bytes)?.to_rgba8())
+}
+
+/// Per channel absolute difference of two images of the same size, multiplied
+/// by `gain` so that small coding errors become visible.
+fn difference(a: &RgbaImage, b: &RgbaImage, gain: f64) -> RgbaImage {
+  let gain = if gain.is_finite() { gain.max(1.0) } else { 1.0 };
+  RgbaImage::from_fn(a.width(), a.height(), |x, y| {
+    let (p, q) = (a.get_pixel(x, y).0, b.get_pixel(x, y).0);
+    let channel = |c: usize| ((p[c].abs_diff(q[c]) as f64 * gain).min(255.0)) as u8;
+    image::Rgba([channel(0), channel(1), channel(2), 255])
+  })
+}
+
+pub fn properties() -> Vec<StreamProperty> {
+  PROPERTIES
+    .iter()
+    .map(|property| StreamProperty {
+      stream: MediaInfoStreamKind::Video,
+      property: property.to_string(),
+    })
+    .collect()
+}
+
+/// PSNR in dB of the RGB channels of two images of the same size; identical
+/// images score `quality::MAX_PSNR`, like the quality comparison.
+fn psnr(a: &RgbaImage, b: &RgbaImage) -> f64 {
+  let (sum, count) = a
+    .pixels()
+    .zip(b.pixels())
+    .flat_map(|(p, q)| (0..3).map(move |c| p.0[c] as f64 - q.0[c] as f64))
+    .fold((0.0, 0u64), |(sum, count), error| (sum + error * error, count + 1));
+  if count == 0 || sum == 0.0 {
+    return quality::MAX_PSNR;
+  }
+  let mse = sum / count as f64;
+  (10.0 * (255.0 * 255.0 / mse).log10()).min(quality::MAX_PSNR)
+}
+
+/// The two images next to each other, the reference on the left.
+fn side_by_side(a: &RgbaImage, b: &RgbaImage) -> RgbaImage {
+  let mut image = RgbaImage::new(a.width() + b.width(), a.height().max(b.height()));
+  image::imageops::replace(&mut image, a, 0, 0);
+  image::imageops::replace(&mut image, b, a.width() as i64, 0);
+  image
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashMap;
+
+  fn video_map(width: &str, height: &str, frame_rate: &str) -> Vec<StreamPropertyMap> {
+    let property_map: HashMap<String, String> = [("Width", width), ("Height", height), ("FrameRate", frame_rate)]
+      .iter()
+      .map(|(key, value)| (key.to_string(), value.to_string()))
+      .collect();
+    vec![StreamPropertyMap {
+      stream: MediaInfoStreamKind::Video,
+      num: 0,
+      property_map,
+      source: Default::default(),
+    }]
+  }
+
+  #[test]
+  fn test_difference_and_psnr() {
+    let a = RgbaImage::from_pixel(4, 2, image::Rgba([100, 100, 100, 255]));
+    let mut b = a.clone();
+    assert_eq!(psnr(&a, &b), quality::MAX_PSNR);
+    b.put_pixel(0, 0, image::Rgba([104, 100, 98, 255]));
+    // 20 squared error over 24 samples.
+    let expected = 10.0 * (255.0f64 * 255.0 / (20.0 / 24.0)).log10();
+    assert!((psnr(&a, &b) - expected).abs() < 1e-9);
+    let diff = difference(&a, &b, 10.0);
+    assert_eq!(diff.get_pixel(0, 0).0, [40, 0, 20, 255]);
+    assert_eq!(diff.get_pixel(1, 0).0, [0, 0, 0, 255]);
+    assert_eq!(difference(&a, &b, 100.0).get_pixel(0, 0).0, [255, 0, 200, 255]);
+  }
+
+  #[test]
+  fn test_side_by_side() {
+    let a = RgbaImage::from_pixel(3, 2, image::Rgba([255, 0, 0, 255]));
+    let b = RgbaImage::from_pixel(3, 2, image::Rgba([0, 0, 255, 255]));
+    let image = side_by_side(&a, &b);
+    assert_eq!(image.dimensions(), (6, 2));
+    assert_eq!(image.get_pixel(2, 1).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(3, 0).0, [0, 0, 255, 255]);
+  }
+
+  #[test]
+  fn test_video_seek() {
+    let video = Video::new("a.mkv", &video_map("1920", "1080", "25.000")).unwrap();
+    assert_eq!(video.seek(FrameAlignment::Timestamp, 12.5).unwrap(), (12.5, 12.5));
+    assert_eq!(video.seek(FrameAlignment::Frame, 0.0).unwrap(), (0.0, 0.0));
+    assert_eq!(video.seek(FrameAlignment::Frame, 50.0).unwrap(), (1.98, 2.0));
+    let still = Video::new("b.png", &video_map("640", "480", "")).unwrap();
+    assert!(still.seek(FrameAlignment::Frame, 1.0).is_err());
+    assert!(Video::new("c.mka", &[]).is_err());
+  }
+}
@@ -23,6 +23,7 @@ use std::path::PathBuf;
 use std::sync::{OnceLock, RwLock};
 
 use crate::constants::APP_NAME;
+use crate::media_info::MediaInfoStreamKind;
 
 static CONFIG: OnceLock<RwLock<Config>> = OnceLock::new();
 
@@ -83,6 +84,12 @@ pub struct Config {
   pub window: ConfigWindow,
   #[serde(default)]
   pub templates: ConfigTemplates,
+  #[serde(default)]
+  pub validation: ConfigValidation,
+  #[serde(default)]
+  pub watch: ConfigWatch,
+  #[serde(default)]
+  pub server: ConfigServer,
 }
 
 impl Default for Config {
@@ -106,6 +113,29 @@ impl Default for Config {
       update: Default::default(),
       window: Default::default(),
       templates: Default::default(),
+      validation: Default::default(),
+      watch: Default::default(),
+      server: Default::default(),
+    }
+  }
+}
+
+/// The opt-in API server. It only ever binds to 127.0.0.1, and every request
+/// must carry `token`, which is generated on first start when left empty.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct ConfigServer {
+  pub enabled: bool,
+  pub port: u16,
+  pub token: String,
+}
+
+impl Default for ConfigServer {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      port: 8484,
+      token: String::new(),
     }
   }
 }
@@ -138,6 +168,112 @@ pub struct ConfigTemplates {
   pub menu: ConfigTemplateGroup,
 }
 
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ConfigValidation {
+  #[serde(default)]
+  pub profiles: Vec<ConfigValidationProfile>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct ConfigValidationProfile {
+  #[serde(default)]
+  pub name: String,
+  #[serde(default)]
+  pub rules: Vec<ConfigValidationRule>,
+}
+
+/// A single check of a validation profile, applied to every stream of `stream`
+/// kind. `value` is compared as text, or as a number for `Min`/`Max`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ConfigValidationRule {
+  pub stream: MediaInfoStreamKind,
+  pub property: String,
+  #[serde(default)]
+  pub operator: ValidationOperator,
+  #[serde(default)]
+  pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ValidationOperator {
+  Exists,
+  Equals,
+  NotEquals,
+  Contains,
+  Min,
+  Max,
+}
+
+impl Default for ValidationOperator {
+  fn default() -> Self {
+    Self::Exists
+  }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ConfigWatch {
+  #[serde(default)]
+  pub folders: Vec<ConfigWatchFolder>,
+}
+
+/// A folder whose new media files are analyzed automatically once they stop
+/// growing. `polling` forces the polling watcher, which is the only reliable
+/// option on network shares.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct ConfigWatchFolder {
+  pub path: String,
+  pub enabled: bool,
+  pub recursive: bool,
+  pub polling: bool,
+  #[serde(rename = "pollIntervalSeconds")]
+  pub poll_interval_seconds: u64,
+  #[serde(rename = "settleSeconds")]
+  pub settle_seconds: u64,
+  /// Extensions to pick up; empty means every audio, image and video extension.
+  pub extensions: Vec<String>,
+  /// Names of the validation profiles to run on each new file.
+  pub profiles: Vec<String>,
+  pub hash: bool,
+  pub report: WatchReportTarget,
+  /// Log file appended to when `report` is `Log`; empty means
+  /// `BetterMediaInfo.log` inside the watched folder.
+  #[serde(rename = "logFile")]
+  pub log_file: String,
+}
+
+impl Default for ConfigWatchFolder {
+  fn default() -> Self {
+    Self {
+      path: String::new(),
+      enabled: true,
+      recursive: false,
+      polling: false,
+      poll_interval_seconds: 10,
+      settle_seconds: 5,
+      extensions: Vec::new(),
+      profiles: Vec::new(),
+      hash: false,
+      report: Default::default(),
+      log_file: String::new(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum WatchReportTarget {
+  /// A `<file>.mediainfo.json` report next to each analyzed file.
+  Folder,
+  /// One JSON line per analyzed file appended to a log file.
+  Log,
+}
+
+impl Default for WatchReportTarget {
+  fn default() -> Self {
+    Self::Folder
+  }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct ConfigWindow {
@@ -460,14 +596,52 @@ impl Default for ConfigMpcHc {
   }
 }
 
+/// Codec of an audio export preset.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum AudioCodec {
+  Flac,
+  Opus,
+  Aac,
+  Wav,
+}
+
+/// A named codec setting offered by the audio export of the FFmpeg Tools window.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ConfigAudioPreset {
+  pub name: String,
+  pub codec: AudioCodec,
+  /// Bitrate in kbps of the lossy codecs; ignored by FLAC and WAV.
+  #[serde(default)]
+  pub bitrate: u32,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct ConfigFfmpeg {
   #[serde(default = "ConfigFfmpeg::default_path")]
   pub path: String,
+  #[serde(rename = "audioPresets", default = "ConfigFfmpeg::default_audio_presets")]
+  pub audio_presets: Vec<ConfigAudioPreset>,
 }
 
 impl ConfigFfmpeg {
+  fn default_audio_presets() -> Vec<ConfigAudioPreset> {
+    [
+      ("FLAC", AudioCodec::Flac, 0),
+      ("Opus 128 kbps", AudioCodec::Opus, 128),
+      ("Opus 64 kbps (speech)", AudioCodec::Opus, 64),
+      ("AAC 256 kbps", AudioCodec::Aac, 256),
+      ("WAV", AudioCodec::Wav, 0),
+    ]
+    .into_iter()
+    .map(|(name, codec, bitrate)| ConfigAudioPreset {
+      name: name.to_owned(),
+      codec,
+      bitrate,
+    })
+    .collect()
+  }
+
   fn default_path() -> String {
     // FFmpeg ships as a bare CLI binary (`ffmpeg`/`ffmpeg.exe`), not a GUI app
     // bundle, so the conventional install locations differ from the other
@@ -487,6 +661,7 @@ impl Default for ConfigFfmpeg {
   fn default() -> Self {
     Self {
       path: Self::default_path(),
+      audio_presets: Self::default_audio_presets(),
     }
   }
 }
@@ -971,6 +1146,11 @@ pub fn get_config() -> Config {
     .clone()
 }
 
+/// The directory holding the config file, also used for other app-managed files.
+pub fn get_config_dir() -> PathBuf {
+  Config::get_config_dir()
+}
+
 fn normalize_locale_tag(locale: &str) -> Option<String> {
   let locale = locale
     .split(':')
@@ -1080,6 +1260,7 @@ mod tests {
     assert_eq!(config.window.position.y, -1);
     assert_eq!(config.window.size.width, 1200);
     assert_eq!(config.window.size.height, 900);
+    assert_eq!(config.ffmpeg.audio_presets, ConfigFfmpeg::default().audio_presets);
     assert!(matches!(config.video.bit_rate.precision, FormatPrecision::Two));
     assert!(matches!(config.video.bit_rate.unit, FormatUnit::KMGT));
     assert_eq!(config.mkv.languages.preferred, ConfigMkvLanguages::default().preferred);
@@ -0,0 +1,432 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::{self, FilterType};
+use image::{ImageFormat, Rgb, RgbImage};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::bitmap_font::{self, GLYPH_SIZE};
+use crate::ffmpeg;
+use crate::ffprobe;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{
+  ContactSheetFormat, ContactSheetOptions, ContactSheetPositions, ContactSheetResult, StreamProperty, StreamPropertyMap,
+};
+
+const BACKGROUND: Rgb<u8> = Rgb([0x20, 0x20, 0x20]);
+const FOREGROUND: Rgb<u8> = Rgb([0xE0, 0xE0, 0xE0]);
+const HEADER_PROPERTIES: &[(MediaInfoStreamKind, &[&str])] = &[
+  (
+    MediaInfoStreamKind::General,
+    &["Format", "FileSize", "Duration", "OverallBitRate"],
+  ),
+  (
+    MediaInfoStreamKind::Video,
+    &["Format", "Width", "Height", "FrameRate", "BitRate", "Language"],
+  ),
+  (
+    MediaInfoStreamKind::Audio,
+    &["Format", "Channel(s)", "SamplingRate", "BitRate", "Language"],
+  ),
+  (MediaInfoStreamKind::Text, &["Format", "Language"]),
+];
+const JPEG_QUALITY: u8 = 90;
+const LABEL_BACKGROUND: Rgb<u8> = Rgb([0x00, 0x00, 0x00]);
+const MAX_COLUMNS: u32 = 16;
+const MAX_FRAMES: u32 = 400;
+const MIN_TILE_WIDTH: u32 = 32;
+
+/// Pixel geometry of a sheet: the header block on top, then the tiles row by row.
+#[derive(Debug, PartialEq)]
+struct Layout {
+  padding: u32,
+  text_scale: u32,
+  header_height: u32,
+  tile_width: u32,
+  tile_height: u32,
+  columns: u32,
+  width: u32,
+  height: u32,
+}
+
+impl Layout {
+  fn new(width: u32, columns: u32, frames: u32, aspect: f64, header_lines: usize) -> Result<Self> {
+    let columns = columns.clamp(1, MAX_COLUMNS).min(frames.max(1));
+    let padding = (width / 200).max(4);
+    let tile_width = width.saturating_sub(padding * (columns + 1)) / columns;
+    if tile_width < MIN_TILE_WIDTH {
+      return Err(anyhow::anyhow!("CONTACT_SHEET_TOO_NARROW:{}:{}", width, columns));
+    }
+    let tile_height = ((tile_width as f64 / aspect).round() as u32).max(1);
+    let text_scale = if width >= 1600 { 2 } else { 1 };
+    let line_height = GLYPH_SIZE * text_scale + padding / 2;
+    let header_height = if header_lines > 0 {
+      padding + header_lines as u32 * line_height
+    } else {
+      0
+    };
+    let rows = frames.div_ceil(columns);
+    Ok(Self {
+      padding,
+      text_scale,
+      header_height,
+      tile_width,
+      tile_height,
+      columns,
+      width,
+      height: header_height + rows * (tile_height + padding) + padding,
+    })
+  }
+
+  fn line_height(&self) -> u32 {
+    GLYPH_SIZE * self.text_scale + self.padding / 2
+  }
+
+  /// Top-left corner of the `index`th tile.
+  fn tile_origin(&self, index: u32) -> (u32, u32) {
+    let (row, column) = (index / self.columns, index % self.columns);
+    (
+      self.padding + column * (self.tile_width + self.padding),
+      self.header_height + self.padding + row * (self.tile_height + self.padding),
+    )
+  }
+}
+
+/// A frame to take: where, and what to print over it.
+#[derive(Debug, PartialEq)]
+struct Position {
+  seconds: f64,
+  label: String,
+}
+
+/// Capture the frames `options` ask for with `ffmpeg::capture_frame`, lay
+/// them out in a grid with an optional header from `properties` (the
+/// `header_properties` of `file`), and write the sheet to `options.output`.
+pub fn create(
+  file: &str,
+  options: &ContactSheetOptions,
+  properties: &[StreamPropertyMap],
+) -> Result<ContactSheetResult> {
+  let positions = match options.positions {
+    ContactSheetPositions::Even => {
+      let duration = general_value(properties, "Duration")
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|ms| *ms > 0.0)
+        .ok_or_else(|| anyhow::anyhow!("CONTACT_SHEET_NO_DURATION:{}", file))?;
+      even_positions(duration / 1000.0, options.count.clamp(1, MAX_FRAMES))
+    }
+    ContactSheetPositions::Chapters => {
+      let chapters = ffprobe::chapters(file)?;
+      if chapters.is_empty() {
+        return Err(anyhow::anyhow!("CONTACT_SHEET_NO_CHAPTERS:{}", file));
+      }
+      chapters
+        .into_iter()
+        .take(MAX_FRAMES as usize)
+        .map(|chapter| Position {
+          // Titles the bitmap font can't print are left out rather than drawn as `?`.
+          label: match bitmap_font::ascii(&chapter.title) {
+            Some(title) if !title.is_empty() => format!("{} {}", format_timestamp(chapter.start), title),
+            _ => format_timestamp(chapter.start),
+          },
+          seconds: chapter.start,
+        })
+        .collect()
+    }
+  };
+  let header = if options.header {
+    header_lines(file, properties)
+  } else {
+    Vec::new()
+  };
+  let frames = positions.len() as u32;
+  let columns = options.columns.clamp(1, MAX_COLUMNS).min(frames);
+  // The tile width doesn't depend on the aspect ratio, so the first capture can size the rest.
+  let probe_layout = Layout::new(options.width, columns, frames, 16.0 / 9.0, header.len())?;
+  let mut tiles = Vec::with_capacity(positions.len());
+  for position in positions.iter() {
+    let bytes = ffmpeg::capture_frame(file.to_owned(), position.seconds, probe_layout.tile_width)?;
+    tiles.push(image::load_from_memory(&bytes)?.to_rgb8());
+  }
+  let aspect = tiles
+    .first()
+    .map(|tile| tile.width() as f64 / tile.height().max(1) as f64)
+    .unwrap_or(16.0 / 9.0);
+  let layout = Layout::new(options.width, columns, frames, aspect, header.len())?;
+  let mut sheet = RgbImage::from_pixel(layout.width, layout.height, BACKGROUND);
+  for (index, line) in header.iter().enumerate() {
+    let line = bitmap_font::truncate(line, layout.width - layout.padding * 2, layout.text_scale);
+    bitmap_font::draw_text(
+      &mut sheet,
+      layout.padding as i64,
+      (layout.padding + index as u32 * layout.line_height()) as i64,
+      &line,
+      layout.text_scale,
+      FOREGROUND,
+    );
+  }
+  for (index, (tile, position)) in tiles.iter().zip(positions.iter()).enumerate() {
+    let (x, y) = layout.tile_origin(index as u32);
+    let tile = imageops::resize(tile, layout.tile_width, layout.tile_height, FilterType::Triangle);
+    imageops::replace(&mut sheet, &tile, x as i64, y as i64);
+    if options.timestamps {
+      draw_label(&mut sheet, &layout, x, y, &position.label);
+    }
+  }
+  write_image(&sheet, Path::new(&options.output), options.format)?;
+  Ok(ContactSheetResult {
+    output: options.output.clone(),
+    frames,
+    width: layout.width,
+    height: layout.height,
+  })
+}
+
+/// Print `label` on a dark strip along the bottom-left of the tile at (`x`, `y`).
+fn draw_label(sheet: &mut RgbImage, layout: &Layout, x: u32, y: u32, label: &str) {
+  let scale = if layout.tile_width >= 480 { 2 } else { 1 };
+  let margin = 2 * scale;
+  let label = bitmap_font::truncate(label, layout.tile_width.saturating_sub(margin * 2), scale);
+  let strip_width = (bitmap_font::text_width(&label, scale) + margin * 2).min(layout.tile_width);
+  let strip_height = (GLYPH_SIZE * scale + margin * 2).min(layout.tile_height);
+  let strip_y = y + layout.tile_height - strip_height;
+  for py in strip_y..strip_y + strip_height {
+    for px in x..x + strip_width {
+      sheet.put_pixel(px, py, LABEL_BACKGROUND);
+    }
+  }
+  bitmap_font::draw_text(
+    sheet,
+    (x + margin) as i64,
+    (strip_y + margin) as i64,
+    &label,
+    scale,
+    FOREGROUND,
+  );
+}
+
+/// `count` positions spread evenly over `duration` seconds, each in the
+/// middle of its slice so the first isn't a fade-in and the last isn't past
+/// the final frame.
+fn even_positions(duration: f64, count: u32) -> Vec<Position> {
+  (0..count)
+    .map(|index| {
+      let seconds = duration * (index as f64 + 0.5) / count as f64;
+      Position {
+        seconds,
+        label: format_timestamp(seconds),
+      }
+    })
+    .collect()
+}
+
+fn format_bit_rate(value: &str) -> Option<String> {
+  let bps = value.parse::<f64>().ok()?;
+  Some(if bps >= 10_000_000.0 {
+    format!("{:.1} Mb/s", bps / 1_000_000.0)
+  } else {
+    format!("{:.0} kb/s", bps / 1000.0)
+  })
+}
+
+fn format_size(value: &str) -> Option<String> {
+  let mut size = value.parse::<f64>().ok()?;
+  let mut unit = "B";
+  for next in ["KiB", "MiB", "GiB", "TiB"] {
+    if size < 1024.0 {
+      break;
+    }
+    size /= 1024.0;
+    unit = next;
+  }
+  Some(if unit == "B" {
+    format!("{} B", size)
+  } else {
+    format!("{:.2} {}", size, unit)
+  })
+}
+
+/// `H:MM:SS` (or `M:SS` under an hour) for a position in seconds.
+fn format_timestamp(seconds: f64) -> String {
+  let total = seconds.max(0.0) as u64;
+  let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+  if hours > 0 {
+    format!("{}:{:02}:{:02}", hours, minutes, seconds)
+  } else {
+    format!("{}:{:02}", minutes, seconds)
+  }
+}
+
+fn general_value<'a>(properties: &'a [StreamPropertyMap], name: &str) -> Option<&'a str> {
+  properties
+    .iter()
+    .find(|map| map.stream == MediaInfoStreamKind::General)
+    .and_then(|map| map.property_map.get(name))
+    .map(|value| value.as_str())
+    .filter(|value| !value.is_empty())
+}
+
+/// One line for the file, one for the container and one per video, audio and
+/// text stream, from the `header_properties` values MediaInfo returned. The
+/// file line is left out when its name has characters the bitmap font can't
+/// print.
+fn header_lines(file: &str, properties: &[StreamPropertyMap]) -> Vec<String> {
+  let name = Path::new(file)
+    .file_name()
+    .map_or(file.to_owned(), |name| name.to_string_lossy().to_string());
+  let mut lines: Vec<String> = bitmap_font::ascii(&name).into_iter().collect();
+  let general: Vec<String> = [
+    general_value(properties, "Format").map(|format| format.to_owned()),
+    general_value(properties, "FileSize").and_then(format_size),
+    general_value(properties, "Duration")
+      .and_then(|ms| ms.parse::<f64>().ok())
+      .map(|ms| format_timestamp(ms / 1000.0)),
+    general_value(properties, "OverallBitRate").and_then(format_bit_rate),
+  ]
+  .into_iter()
+  .flatten()
+  .collect();
+  if !general.is_empty() {
+    lines.push(general.join("  "));
+  }
+  let mut streams: Vec<&StreamPropertyMap> = properties
+    .iter()
+    .filter(|map| map.stream != MediaInfoStreamKind::General)
+    .collect();
+  streams.sort_by_key(|map| (map.stream as i32, map.num));
+  for map in streams {
+    let get = |name: &str| map.property_map.get(name).filter(|value| !value.is_empty());
+    let mut parts: Vec<String> = Vec::new();
+    parts.extend(get("Format").cloned());
+    match map.stream {
+      MediaInfoStreamKind::Video => {
+        if let (Some(width), Some(height)) = (get("Width"), get("Height")) {
+          parts.push(format!("{}x{}", width, height));
+        }
+        parts.extend(get("FrameRate").map(|rate| format!("{} fps", rate)));
+      }
+      MediaInfoStreamKind::Audio => {
+        parts.extend(get("Channel(s)").map(|channels| format!("{} ch", channels)));
+        parts.extend(get("SamplingRate").map(|rate| format!("{} Hz", rate)));
+      }
+      _ => {}
+    }
+    parts.extend(get("BitRate").and_then(|value| format_bit_rate(value)));
+    parts.extend(get("Language").cloned());
+    if !parts.is_empty() {
+      lines.push(format!("{} #{}: {}", map.stream.get_name(), map.num + 1, parts.join(" ")));
+    }
+  }
+  lines
+}
+
+/// The MediaInfo properties the header block is built from.
+pub fn header_properties() -> Vec<StreamProperty> {
+  HEADER_PROPERTIES
+    .iter()
+    .flat_map(|(kind, properties)| {
+      properties.iter().map(|property| StreamProperty {
+        stream: *kind,
+        property: property.to_string(),
+      })
+    })
+    .collect()
+}
+
+fn write_image(sheet: &RgbImage, output: &Path, format: ContactSheetFormat) -> Result<()> {
+  if let Some(parent) = output.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+    std::fs::create_dir_all(parent)?;
+  }
+  let mut writer = BufWriter::new(File::create(output)?);
+  match format {
+    ContactSheetFormat::Png => sheet.write_to(&mut writer, ImageFormat::Png)?,
+    ContactSheetFormat::Jpeg => JpegEncoder::new_with_quality(&mut writer, JPEG_QUALITY).encode_image(sheet)?,
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashMap;
+
+  fn stream(stream: MediaInfoStreamKind, num: i32, pairs: &[(&str, &str)]) -> StreamPropertyMap {
+    StreamPropertyMap {
+      stream,
+      num,
+      property_map: pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>(),
+      source: Default::default(),
+    }
+  }
+
+  #[test]
+  fn even_positions_sit_mid_slice() {
+    let positions = even_positions(100.0, 4);
+    let seconds: Vec<f64> = positions.iter().map(|position| position.seconds).collect();
+    assert_eq!(seconds, vec![12.5, 37.5, 62.5, 87.5]);
+    assert_eq!(positions[3].label, "1:27");
+    assert_eq!(format_timestamp(3723.9), "1:02:03");
+  }
+
+  #[test]
+  fn header_lines_summarize_streams() {
+    let properties = vec![
+      stream(MediaInfoStreamKind::Audio, 0, &[("Format", "AC-3"), ("Channel(s)", "6"), ("Language", "en")]),
+      stream(
+        MediaInfoStreamKind::General,
+        0,
+        &[("Format", "Matroska"), ("FileSize", "1610612736"), ("Duration", "5400000")],
+      ),
+      stream(
+        MediaInfoStreamKind::Video,
+        0,
+        &[("Format", "AVC"), ("Width", "1920"), ("Height", "1080"), ("FrameRate", "23.976")],
+      ),
+    ];
+    assert_eq!(
+      header_lines("/media/movie.mkv", &properties),
+      vec![
+        "movie.mkv",
+        "Matroska  1.50 GiB  1:30:00",
+        "Video #1: AVC 1920x1080 23.976 fps",
+        "Audio #1: AC-3 6 ch en",
+      ]
+    );    assert_eq!(header_lines("/media/Am\u{e9}lie.mkv", &properties)[0], "Amelie.mkv");
+    assert_eq!(
+      header_lines("/media/\u{6771}\u{4eac}.mkv", &properties)[0],
+      "Matroska  1.50 GiB  1:30:00"
+    );
+  }
+
+  #[test]
+  fn layout_fits_tiles_under_header() {
+    let layout = Layout::new(1000, 4, 10, 16.0 / 9.0, 3).unwrap();
+    assert_eq!(layout.padding, 5);
+    assert_eq!(layout.tile_width, 243);
+    assert_eq!(layout.tile_height, 137);
+    assert_eq!(layout.tile_origin(5), (253, layout.header_height + 5 + 142));
+    assert_eq!(layout.height, layout.header_height + 3 * 142 + 5);
+    assert!(Layout::new(100, 16, 16, 1.0, 0).is_err());
+  }
+}
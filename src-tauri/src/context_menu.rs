@@ -15,8 +15,14 @@
 * limitations under the License.
 */
 
+#[cfg(not(target_os = "linux"))]
 use anyhow::Result;
 
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+pub use linux::*;
+
 #[cfg(target_os = "windows")]
 use crate::constants::APP_NAME;
 
@@ -32,7 +38,7 @@ pub fn are_extensions_context_menu_registered(extensions: Vec<String>) -> bool {
   filtered.iter().all(|e| shell_entry_exists(&extension_shell_path(e)))
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn are_extensions_context_menu_registered(_extensions: Vec<String>) -> bool {
   false
 }
@@ -83,7 +89,7 @@ pub fn is_folder_context_menu_registered() -> bool {
   folder_shell_paths().iter().all(|p| shell_entry_exists(p))
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn is_folder_context_menu_registered() -> bool {
   false
 }
@@ -107,10 +113,10 @@ pub fn register_extensions_context_menu(extensions: Vec<String>) -> Result<()> {
   Ok(())
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn register_extensions_context_menu(_extensions: Vec<String>) -> Result<()> {
   Err(anyhow::anyhow!(
-    "Context menu registration is only supported on Windows."
+    "Context menu registration is only supported on Windows and Linux."
   ))
 }
 
@@ -124,10 +130,10 @@ pub fn register_folder_context_menu() -> Result<()> {
   Ok(())
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn register_folder_context_menu() -> Result<()> {
   Err(anyhow::anyhow!(
-    "Context menu registration is only supported on Windows."
+    "Context menu registration is only supported on Windows and Linux."
   ))
 }
 
@@ -150,10 +156,10 @@ pub fn unregister_extensions_context_menu(extensions: Vec<String>) -> Result<()>
   Ok(())
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn unregister_extensions_context_menu(_extensions: Vec<String>) -> Result<()> {
   Err(anyhow::anyhow!(
-    "Context menu registration is only supported on Windows."
+    "Context menu registration is only supported on Windows and Linux."
   ))
 }
 
@@ -165,10 +171,10 @@ pub fn unregister_folder_context_menu() -> Result<()> {
   Ok(())
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn unregister_folder_context_menu() -> Result<()> {
   Err(anyhow::anyhow!(
-    "Context menu registration is only supported on Windows."
+    "Context menu registration is only supported on Windows and Linux."
   ))
 }
 
@@ -0,0 +1,525 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::constants::APP_NAME;
+
+const MENU_LABEL: &str = "Open with BetterMediaInfo";
+const ENTRY_ID: &str = "com.caoccao.better-media-info.open-with";
+const ICON_NAME: &str = "com.caoccao.better-media-info";
+const ICON_PNG: &[u8] = include_bytes!("../../icons/128x128.png");
+const KEY_EXTENSIONS: &str = "X-BetterMediaInfo-Extensions";
+const KEY_FOLDER: &str = "X-BetterMediaInfo-Folder";
+const THUNAR_FILES_ID: &str = "bettermediainfo-files";
+const THUNAR_FOLDER_ID: &str = "bettermediainfo-folder";
+
+/// What is registered, as recorded in the `.desktop` entry itself. Every other
+/// file is regenerated from it, so the entry is the single source of truth.
+#[derive(Debug, Default, PartialEq)]
+struct Registration {
+  extensions: BTreeSet<String>,
+  folder: bool,
+}
+
+impl Registration {
+  fn is_empty(&self) -> bool {
+    self.extensions.is_empty() && !self.folder
+  }
+
+  fn mime_types(&self) -> Vec<String> {
+    let mut mime_types: BTreeSet<&str> = self.extensions.iter().filter_map(|ext| known_mime_type(ext)).collect();
+    if self.folder {
+      mime_types.insert("inode/directory");
+    }
+    mime_types.into_iter().map(str::to_owned).collect()
+  }
+}
+
+/// Every path a registration may have written, whatever it contains.
+fn all_paths() -> Vec<PathBuf> {
+  let data = xdg_data_home();
+  let mut paths = vec![
+    entry_path(),
+    icon_path(),
+    data.join("nautilus").join("scripts").join(MENU_LABEL),
+  ];
+  for kind in ["files", "folder"] {
+    paths.push(data.join("nemo").join("actions").join(format!("bettermediainfo-{}.nemo_action", kind)));
+    paths.push(data.join("kio").join("servicemenus").join(format!("bettermediainfo-{}.desktop", kind)));
+  }
+  paths
+}
+
+pub fn are_extensions_context_menu_registered(extensions: Vec<String>) -> bool {
+  let filtered: Vec<String> = extensions.iter().map(|e| normalize_ext(e)).filter(|e| !e.is_empty()).collect();
+  if filtered.is_empty() {
+    return false;
+  }
+  let registration = read_registration();
+  filtered.iter().all(|e| registration.extensions.contains(e)) && files_present(&registration)
+}
+
+/// The app's own `.desktop` entry. `NoDisplay` keeps it out of the app menu;
+/// it only provides "Open With" and the MIME associations.
+fn desktop_entry(registration: &Registration, exec: &str) -> String {
+  let extensions: Vec<&str> = registration.extensions.iter().map(String::as_str).collect();
+  format!(
+    "[Desktop Entry]\nType=Application\nName={}\nGenericName={}\nIcon={}\nExec={} %F\nTerminal=false\nNoDisplay=true\nMimeType={};\n{}={};\n{}={}\n",
+    APP_NAME,
+    MENU_LABEL,
+    ICON_NAME,
+    exec,
+    registration.mime_types().join(";"),
+    KEY_EXTENSIONS,
+    extensions.join(";"),
+    KEY_FOLDER,
+    registration.folder
+  )
+}
+
+fn dolphin_service_menu(mime_types: &[String], exec: &str) -> String {
+  format!(
+    "[Desktop Entry]\nType=Service\nMimeType={};\nActions=openWithBetterMediaInfo\nX-KDE-Priority=TopLevel\n\n[Desktop Action openWithBetterMediaInfo]\nName={}\nIcon={}\nExec={} %F\n",
+    mime_types.join(";"),
+    MENU_LABEL,
+    ICON_NAME,
+    exec
+  )
+}
+
+fn entry_path() -> PathBuf {
+  xdg_data_home().join("applications").join(format!("{}.desktop", ENTRY_ID))
+}
+
+/// The program the menus launch. An AppImage is launched through the image
+/// itself because `current_exe` points into its temporary mount.
+fn exe_path() -> Result<PathBuf> {
+  match std::env::var_os("APPIMAGE") {
+    Some(appimage) => Ok(PathBuf::from(appimage)),
+    None => Ok(std::env::current_exe()?),
+  }
+}
+
+fn exec_target(entry: &str) -> Option<PathBuf> {
+  let exec = entry.lines().find_map(|line| line.strip_prefix("Exec="))?;
+  let quoted = exec.strip_prefix('"')?;
+  let mut target = String::new();
+  let mut chars = quoted.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '\\' => target.push(chars.next()?),
+      '"' => return Some(PathBuf::from(target)),
+      _ => target.push(c),
+    }
+  }
+  None
+}
+
+/// Every file a registration consists of, with its content and whether it must
+/// be executable. Thunar's `uca.xml` is shared with the user's own actions and
+/// is edited in place instead, see `update_thunar`.
+fn files(registration: &Registration, exec: &str) -> Vec<(PathBuf, String, bool)> {
+  let data = xdg_data_home();
+  let mut files = vec![(entry_path(), desktop_entry(registration, exec), false)];
+  let mut file_menus = Vec::new();
+  if !registration.extensions.is_empty() {
+    let mime_types: Vec<String> = registration
+      .extensions
+      .iter()
+      .filter_map(|ext| known_mime_type(ext))
+      .map(str::to_owned)
+      .collect();
+    let extensions: Vec<&str> = registration.extensions.iter().map(String::as_str).collect();
+    file_menus.push(("files", mime_types, extensions.join(";")));
+  }
+  if registration.folder {
+    file_menus.push(("folder", vec!["inode/directory".to_owned()], "dir".to_owned()));
+  }
+  for (kind, mime_types, nemo_extensions) in file_menus {
+    files.push((
+      data.join("nemo").join("actions").join(format!("bettermediainfo-{}.nemo_action", kind)),
+      nemo_action(&nemo_extensions, exec),
+      false,
+    ));
+    // Dolphin matches by MIME type only, so extensions without a well-known
+    // type are left to the menus that match by extension.
+    if !mime_types.is_empty() {
+      files.push((
+        data.join("kio").join("servicemenus").join(format!("bettermediainfo-{}.desktop", kind)),
+        dolphin_service_menu(&mime_types, exec),
+        true,
+      ));
+    }
+  }
+  // Nautilus scripts can't be limited to file types, so one script serves both.
+  files.push((
+    data.join("nautilus").join("scripts").join(MENU_LABEL),
+    nautilus_script(exec),
+    true,
+  ));
+  files
+}
+
+/// Whether the entry still launches this app and every generated file is in
+/// place, so a half-deleted registration isn't reported as working.
+fn files_present(registration: &Registration) -> bool {
+  let Ok(entry) = std::fs::read_to_string(entry_path()) else {
+    return false;
+  };
+  if !exec_target(&entry).map(|target| target.exists()).unwrap_or(false) {
+    return false;
+  }
+  let thunar = std::fs::read_to_string(thunar_path()).unwrap_or_default();
+  (registration.extensions.is_empty() || thunar.contains(THUNAR_FILES_ID))
+    && (!registration.folder || thunar.contains(THUNAR_FOLDER_ID))
+    && files(registration, "")
+      .iter()
+      .all(|(path, _, _)| path.exists())
+}
+
+fn icon_path() -> PathBuf {
+  xdg_data_home()
+    .join("icons")
+    .join("hicolor")
+    .join("128x128")
+    .join("apps")
+    .join(format!("{}.png", ICON_NAME))
+}
+
+pub fn is_folder_context_menu_registered() -> bool {
+  let registration = read_registration();
+  registration.folder && files_present(&registration)
+}
+
+/// The well-known MIME type the desktop matches `ext` by. Extensions without
+/// one are only matched by the menus that take file extensions.
+fn known_mime_type(ext: &str) -> Option<&'static str> {
+  let mime_type = match ext {
+    "aac" => "audio/aac",
+    "ac3" => "audio/ac3",
+    "aif" | "aiff" => "audio/x-aiff",
+    "ape" => "audio/x-ape",
+    "dts" => "audio/vnd.dts",
+    "flac" => "audio/flac",
+    "m4a" => "audio/mp4",
+    "mka" => "audio/x-matroska",
+    "mp3" => "audio/mpeg",
+    "ogg" | "oga" => "audio/ogg",
+    "opus" => "audio/x-opus+ogg",
+    "wav" => "audio/x-wav",
+    "wma" => "audio/x-ms-wma",
+    "avif" => "image/avif",
+    "bmp" => "image/bmp",
+    "gif" => "image/gif",
+    "heic" | "heif" => "image/heif",
+    "jpeg" | "jpg" => "image/jpeg",
+    "png" => "image/png",
+    "tif" | "tiff" => "image/tiff",
+    "webp" => "image/webp",
+    "3gp" => "video/3gpp",
+    "avi" => "video/x-msvideo",
+    "flv" => "video/x-flv",
+    "m2ts" | "mts" | "ts" => "video/mp2t",
+    "m4v" | "mp4" => "video/mp4",
+    "mkv" => "video/x-matroska",
+    "mov" => "video/quicktime",
+    "mpeg" | "mpg" | "vob" => "video/mpeg",
+    "ogv" => "video/ogg",
+    "webm" => "video/webm",
+    "wmv" => "video/x-ms-wmv",
+    _ => return None,
+  };
+  Some(mime_type)
+}
+
+fn nautilus_script(exec: &str) -> String {
+  // Nautilus passes the selected paths as arguments; `exec` is already quoted
+  // for a desktop entry, which is also valid for the shell.
+  format!("#!/bin/sh\nexec {} \"$@\"\n", exec)
+}
+
+fn nemo_action(extensions: &str, exec: &str) -> String {
+  format!(
+    "[Nemo Action]\nName={}\nComment={}\nExec={} %F\nIcon-Name={}\nSelection=NotNone\nExtensions={};\n",
+    MENU_LABEL, MENU_LABEL, exec, ICON_NAME, extensions
+  )
+}
+
+fn normalize_ext(ext: &str) -> String {
+  let trimmed = ext.trim().trim_start_matches('.');
+  trimmed.to_ascii_lowercase()
+}
+
+/// Quote a path for an `Exec` key: double-quoted with `"`, `` ` ``, `$` and
+/// `\` backslash-escaped, per the Desktop Entry spec.
+fn quote_exec(path: &str) -> String {
+  let mut quoted = String::from("\"");
+  for c in path.chars() {
+    if matches!(c, '"' | '`' | '$' | '\\') {
+      quoted.push('\\');
+    }
+    quoted.push(c);
+  }
+  quoted.push('"');
+  quoted
+}
+
+fn read_registration() -> Registration {
+  let Ok(entry) = std::fs::read_to_string(entry_path()) else {
+    return Registration::default();
+  };
+  let value = |key: &str| {
+    entry
+      .lines()
+      .find_map(|line| line.strip_prefix(key).and_then(|rest| rest.strip_prefix('=')))
+      .unwrap_or_default()
+      .to_owned()
+  };
+  Registration {
+    extensions: value(KEY_EXTENSIONS)
+      .split(';')
+      .map(normalize_ext)
+      .filter(|e| !e.is_empty())
+      .collect(),
+    folder: value(KEY_FOLDER) == "true",
+  }
+}
+
+/// Run the cache tool that makes new entries show up without a re-login. It's
+/// optional, so a failure is only logged.
+fn refresh_caches() {
+  let dir = xdg_data_home().join("applications");
+  if !dir.exists() {
+    return;
+  }
+  if let Err(err) = std::process::Command::new("update-desktop-database").arg(&dir).output() {
+    log::debug!("Skipped update-desktop-database because {}", err);
+  }
+}
+
+pub fn register_extensions_context_menu(extensions: Vec<String>) -> Result<()> {
+  let mut registration = read_registration();
+  registration
+    .extensions
+    .extend(extensions.iter().map(|e| normalize_ext(e)).filter(|e| !e.is_empty()));
+  write_registration(&registration)
+}
+
+pub fn register_folder_context_menu() -> Result<()> {
+  let mut registration = read_registration();
+  registration.folder = true;
+  write_registration(&registration)
+}
+
+/// Remove the `<action>` whose `<unique-id>` is `id` from a Thunar `uca.xml`.
+fn remove_thunar_action(xml: &str, id: &str) -> String {
+  let marker = format!("<unique-id>{}</unique-id>", id);
+  let mut result = String::with_capacity(xml.len());
+  let mut rest = xml;
+  while let Some(start) = rest.find("<action>") {
+    let Some(len) = rest[start..].find("</action>").map(|end| end + "</action>".len()) else {
+      break;
+    };
+    let action = &rest[start..start + len];
+    if action.contains(&marker) {
+      // Drop the action together with the indentation and newline around it.
+      result.push_str(rest[..start].trim_end_matches([' ', '\t']));
+      rest = rest[start + len..].strip_prefix('\n').unwrap_or(&rest[start + len..]);
+    } else {
+      result.push_str(&rest[..start + len]);
+      rest = &rest[start + len..];
+    }
+  }
+  result.push_str(rest);
+  result
+}
+
+fn shell_quote(value: &str) -> String {
+  format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn thunar_action(id: &str, exec: &str, patterns: &str, conditions: &str) -> String {
+  format!(
+    "<action>\n\t<icon>{}</icon>\n\t<name>{}</name>\n\t<submenu></submenu>\n\t<unique-id>{}</unique-id>\n\t<command>{} %F</command>\n\t<description>{}</description>\n\t<range></range>\n\t<patterns>{}</patterns>\n\t{}\n</action>\n",
+    ICON_NAME,
+    MENU_LABEL,
+    id,
+    xml_escape(exec),
+    MENU_LABEL,
+    xml_escape(patterns),
+    conditions
+  )
+}
+
+fn thunar_path() -> PathBuf {
+  xdg_config_home().join("Thunar").join("uca.xml")
+}
+
+pub fn unregister_extensions_context_menu(extensions: Vec<String>) -> Result<()> {
+  let mut registration = read_registration();
+  for ext in extensions {
+    registration.extensions.remove(&normalize_ext(&ext));
+  }
+  write_registration(&registration)
+}
+
+pub fn unregister_folder_context_menu() -> Result<()> {
+  let mut registration = read_registration();
+  registration.folder = false;
+  write_registration(&registration)
+}
+
+/// Add or remove our two actions in Thunar's `uca.xml`. A missing file starts
+/// from the system default so the stock actions don't disappear.
+fn update_thunar(registration: &Registration, shell_exec: &str) -> Result<()> {
+  let path = thunar_path();
+  let mut xml = match std::fs::read_to_string(&path) {
+    Ok(xml) => xml,
+    Err(_) if registration.is_empty() => return Ok(()),
+    Err(_) => std::fs::read_to_string("/etc/xdg/Thunar/uca.xml")
+      .unwrap_or_else(|_| "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<actions>\n</actions>\n".to_owned()),
+  };
+  xml = remove_thunar_action(&xml, THUNAR_FILES_ID);
+  xml = remove_thunar_action(&xml, THUNAR_FOLDER_ID);
+  let mut actions = String::new();
+  if !registration.extensions.is_empty() {
+    let patterns: Vec<String> = registration.extensions.iter().map(|ext| format!("*.{}", ext)).collect();
+    actions.push_str(&thunar_action(
+      THUNAR_FILES_ID,
+      shell_exec,
+      &patterns.join(";"),
+      "<audio-files/>\n\t<image-files/>\n\t<video-files/>\n\t<other-files/>",
+    ));
+  }
+  if registration.folder {
+    actions.push_str(&thunar_action(THUNAR_FOLDER_ID, shell_exec, "*", "<directories/>"));
+  }
+  let Some(end) = xml.rfind("</actions>") else {
+    return Err(anyhow::anyhow!("{} is not a Thunar actions file.", path.display()));
+  };
+  xml.insert_str(end, &actions);
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  std::fs::write(&path, xml)?;
+  Ok(())
+}
+
+fn write_file(path: &Path, content: &[u8], executable: bool) -> Result<()> {
+  use std::os::unix::fs::PermissionsExt;
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  std::fs::write(path, content)?;
+  if executable {
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+  }
+  Ok(())
+}
+
+fn write_registration(registration: &Registration) -> Result<()> {
+  let exe = exe_path()?.to_string_lossy().to_string();
+  let exec = quote_exec(&exe);
+  // Remove everything first so a shrinking registration leaves no stale files.
+  for path in all_paths() {
+    match std::fs::remove_file(&path) {
+      Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+      _ => {}
+    }
+  }
+  update_thunar(registration, &shell_quote(&exe))?;
+  if !registration.is_empty() {
+    write_file(&icon_path(), ICON_PNG, false)?;
+    for (path, content, executable) in files(registration, &exec) {
+      write_file(&path, content.as_bytes(), executable)?;
+    }
+  }
+  refresh_caches();
+  Ok(())
+}
+
+fn xdg_config_home() -> PathBuf {
+  xdg_home("XDG_CONFIG_HOME", ".config")
+}
+
+fn xdg_data_home() -> PathBuf {
+  xdg_home("XDG_DATA_HOME", ".local/share")
+}
+
+fn xdg_home(var: &str, fallback: &str) -> PathBuf {
+  match std::env::var_os(var) {
+    Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+    _ => PathBuf::from(std::env::var_os("HOME").unwrap_or_default()).join(fallback),
+  }
+}
+
+fn xml_escape(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn quote_exec_round_trips_through_exec_target() {
+    let path = "/opt/Better Media \"Info\"/$bin\\bettermi";
+    let exec = quote_exec(path);
+    assert_eq!(exec, "\"/opt/Better Media \\\"Info\\\"/\\$bin\\\\bettermi\"");
+    assert_eq!(exec_target(&format!("Exec={} %F", exec)), Some(PathBuf::from(path)));
+  }
+
+  #[test]
+  fn remove_thunar_action_keeps_other_actions() {
+    let xml = "<actions>\n<action>\n\t<unique-id>1-1</unique-id>\n</action>\n<action>\n\t<unique-id>bettermediainfo-files</unique-id>\n</action>\n</actions>\n";
+    assert_eq!(
+      remove_thunar_action(xml, THUNAR_FILES_ID),
+      "<actions>\n<action>\n\t<unique-id>1-1</unique-id>\n</action>\n</actions>\n"
+    );
+  }
+
+  #[test]
+  fn mime_types_skip_unknown_extensions() {
+    let registration = Registration {
+      extensions: ["mkv".to_owned(), "ts".to_owned(), "xyz".to_owned()].into(),
+      folder: true,
+    };
+    assert_eq!(
+      registration.mime_types(),
+      vec!["inode/directory", "video/mp2t", "video/x-matroska"]
+    );
+  }
+
+  #[test]
+  fn files_skip_dolphin_menu_without_known_mime_types() {
+    let registration = Registration {
+      extensions: ["xyz".to_owned()].into(),
+      folder: false,
+    };
+    let files = files(&registration, "bettermi");
+    assert!(files.iter().any(|(path, _, _)| path.ends_with("bettermediainfo-files.nemo_action")));
+    assert!(!files.iter().any(|(path, _, _)| path.ends_with("bettermediainfo-files.desktop")));
+    assert!(!files.iter().any(|(_, content, _)| content.contains("x-bettermediainfo")));
+  }
+}
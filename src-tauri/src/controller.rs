@@ -26,20 +26,39 @@ use std::process::Child;
 use std::sync::{Arc, Mutex};
 use tauri::{Emitter, EventTarget, Window};
 
+use crate::audio;
 use crate::batchmkvextract;
 use crate::bd;
 use crate::bdmaster;
+use crate::bitrate;
+use crate::capture;
+use crate::clip;
+use crate::compare;
 use crate::config;
 use crate::constants::APP_NAME;
+use crate::contact_sheet;
 use crate::context_menu;
+use crate::crop;
+use crate::encoder;
 use crate::ffmpeg;
+use crate::ffprobe;
+use crate::gop;
+use crate::hdr;
+use crate::loudness;
 use crate::media_info::*;
 use crate::mkvtoolnix;
 use crate::mpchc;
 use crate::protocol::*;
+use crate::qc;
+use crate::quality;
+use crate::scenes;
+use crate::server;
+use crate::spectrogram;
+use crate::spreadsheet;
 use crate::streams::*;
 #[cfg(target_os = "windows")]
 use crate::taskbar;
+use crate::watch;
 
 /// Shared map of running child processes keyed by the owning window label.
 pub type ChildMap = Arc<Mutex<HashMap<String, Child>>>;
@@ -67,6 +86,45 @@ static ALL_PROPERTIES_MAP: Lazy<HashMap<MediaInfoStreamKind, Vec<String>>> = Laz
   all_properties_map
 });
 
+/// Bitrate over time of the video and audio streams of `file`, from the
+/// sizes of its packets.
+pub async fn analyze_bitrate(file: String, window: f64) -> Result<BitrateGraph> {
+  tokio::task::spawn_blocking(move || {
+    let streams = ffprobe::probe(&file, FfprobeFrames::None)?;
+    let packets = ffprobe::packets(&file)?;
+    bitrate::analyze(&file, window, &streams, &packets)
+  })
+  .await?
+}
+
+/// Keyframes and GOP structure of the first video stream of `file`, from its
+/// packets and, as `frames` asks, its decoded frames.
+pub async fn analyze_gop(file: String, frames: FfprobeFrames) -> Result<GopReport> {
+  tokio::task::spawn_blocking(move || {
+    let packets = ffprobe::video_packets(&file)?;
+    let duration = packets
+      .iter()
+      .filter_map(|packet| packet.pts)
+      .fold(0.0, f64::max);
+    let video_frames = ffprobe::video_frames(&file, frames, duration)?;
+    gop::analyze(&file, &packets, frames, &video_frames)
+  })
+  .await?
+}
+
+/// HDR report of every video stream of `file`: MediaInfo's HDR fields, the
+/// ffprobe stream side data and, as `frames` asks, the frame side data.
+pub async fn analyze_hdr(file: String, frames: FfprobeFrames) -> Result<HdrReport> {
+  let properties = get_properties(file.clone(), Some(hdr::properties())).await?;
+  tokio::task::spawn_blocking(move || {
+    let streams = ffprobe::video_streams(&file)?;
+    let mut tally = hdr::Tally::default();
+    ffprobe::frame_side_data(&file, frames, hdr::duration(&properties), |frame| tally.add(frame))?;
+    hdr::analyze(&file, frames, &properties, &streams, &tally)
+  })
+  .await?
+}
+
 pub async fn are_extensions_context_menu_registered(extensions: Vec<String>) -> Result<bool> {
   Ok(context_menu::are_extensions_context_menu_registered(extensions))
 }
@@ -124,6 +182,227 @@ pub fn check_for_updates() -> Result<UpdateCheckResult> {
   })
 }
 
+/// Parsed encoder settings of the first video stream of `file` and, with
+/// `other`, every parameter side by side with those of `other`.
+pub async fn compare_encoder_settings(file: String, other: Option<String>) -> Result<EncoderComparison> {
+  let properties = get_properties(file.clone(), Some(encoder::properties())).await?;
+  let settings = encoder::settings(&file, &properties)?;
+  let other = match other {
+    Some(other) => {
+      let properties = get_properties(other.clone(), Some(encoder::properties())).await?;
+      Some(encoder::settings(&other, &properties)?)
+    }
+    None => None,
+  };
+  let fields = other
+    .as_ref()
+    .map(|other| encoder::compare(&settings, other))
+    .unwrap_or_default();
+  Ok(EncoderComparison { settings, other, fields })
+}
+
+/// Capture matching frames of `reference` and `distorted` and save each pair,
+/// its side by side and its difference image, streaming the `capture` phase of
+/// `ffmpeg-capture-progress` events to `window`. `cancel_ffmpeg_capture` stops
+/// it between pairs.
+pub async fn compare_frames(
+  window: Window,
+  reference: String,
+  distorted: String,
+  options: FrameCompareOptions,
+  cancels: CancelMap,
+) -> Result<FrameComparison> {
+  let reference_properties = get_properties(reference.clone(), Some(compare::properties())).await?;
+  let distorted_properties = get_properties(distorted.clone(), Some(compare::properties())).await?;
+  let label = window.label().to_owned();
+  let target = EventTarget::webview_window(&label);
+  tokio::task::spawn_blocking(move || {
+    let emit = |percent: u32, current: u32, total: u32, done: bool, cancelled: bool, error: Option<String>| {
+      let event = FfmpegCaptureProgressEvent {
+        percent,
+        done,
+        cancelled,
+        error,
+        phase: "capture".to_string(),
+        current,
+        total,
+      };
+      let _ = window.emit_to(target.clone(), "ffmpeg-capture-progress", event);
+    };
+    let cancel_flag = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    cancels.lock().unwrap().insert(label.clone(), cancel_flag.clone());
+    let result = compare::compare(
+      &reference,
+      &distorted,
+      &options,
+      &reference_properties,
+      &distorted_properties,
+      |current, total| {
+        emit(current * 100 / total.max(1), current, total, false, false, None);
+        !cancel_flag.load(std::sync::atomic::Ordering::SeqCst)
+      },
+    );
+    cancels.lock().unwrap().remove(&label);
+    let error = result.as_ref().err().map(|error| error.to_string());
+    let cancelled = error.as_deref() == Some("FFMPEG_ANALYSIS_CANCELLED");
+    emit(100, 0, 0, true, cancelled, error.filter(|_| !cancelled));
+    result
+  })
+  .await?
+}
+
+/// MediaInfo's and ffprobe's view of the key fields of `file`, side by side.
+pub async fn compare_with_ffprobe(file: String, frames: FfprobeFrames) -> Result<ProbeComparison> {
+  let media_info = get_properties(file.clone(), Some(ffprobe::key_properties())).await?;
+  let ffprobe = tokio::task::spawn_blocking(move || ffprobe::probe(&file, frames)).await??;
+  let fields = ffprobe::compare(&media_info, &ffprobe);
+  Ok(ProbeComparison { ffprobe, fields })
+}
+
+pub async fn create_contact_sheet(file: String, options: ContactSheetOptions) -> Result<ContactSheetResult> {
+  let properties = get_properties(file.clone(), Some(contact_sheet::header_properties())).await?;
+  tokio::task::spawn_blocking(move || contact_sheet::create(&file, &options, &properties)).await?
+}
+
+/// Encode an animated preview clip of `file`, streaming `ffmpeg-capture-progress`
+/// events to `window` in the `clip` phase, `current` being the encode attempt,
+/// then the clip itself as an `ffmpeg-capture-frame`. `cancel_ffmpeg_capture`
+/// stops it.
+pub async fn create_preview_clip(
+  window: Window,
+  file: String,
+  options: ClipOptions,
+  children: ChildMap,
+) -> Result<ClipResult> {
+  let properties = get_properties(file.clone(), Some(clip::properties())).await?;
+  let label = window.label().to_owned();
+  let target = EventTarget::webview_window(&label);
+  tokio::task::spawn_blocking(move || {
+    let result = clip::create(&file, &options, &properties, |attempt, args, duration| {
+      ffmpeg::run_analysis(args, duration, &label, &children, |percent| {
+        let event = FfmpegCaptureProgressEvent {
+          percent,
+          done: false,
+          cancelled: false,
+          error: None,
+          phase: "clip".to_string(),
+          current: attempt,
+          total: 0,
+        };
+        let _ = window.emit_to(target.clone(), "ffmpeg-capture-progress", event);
+      })
+    })?;
+    if let Ok(bytes) = std::fs::read(&result.output) {
+      let _ = window.emit_to(target.clone(), "ffmpeg-capture-frame", FfmpegCaptureFrameEvent { bytes });
+    }
+    Ok(result)
+  })
+  .await?
+}
+
+/// Find a stable crop for the letterbox/pillarbox borders of the first video stream of `file`.
+pub async fn detect_crop(file: String) -> Result<CropDetection> {
+  let properties = get_properties(file.clone(), Some(crop::properties())).await?;
+  tokio::task::spawn_blocking(move || crop::detect(&file, &properties)).await?
+}
+
+/// Extract or transcode one audio stream of `file` with the preset, mix and
+/// range in `options`, reporting progress like a capture.
+pub async fn export_audio(
+  window: Window,
+  file: String,
+  options: AudioExportOptions,
+  children: ChildMap,
+) -> Result<AudioExportResult> {
+  let properties = get_properties(file.clone(), Some(audio::properties())).await?;
+  let label = window.label().to_owned();
+  let target = EventTarget::webview_window(&label);
+  tokio::task::spawn_blocking(move || {
+    audio::export(&file, &options, &properties, |args, duration| {
+      ffmpeg::run_analysis(args, duration, &label, &children, |percent| {
+        let event = FfmpegCaptureProgressEvent {
+          percent,
+          done: false,
+          cancelled: false,
+          error: None,
+          phase: "audio".to_string(),
+          current: 0,
+          total: 0,
+        };
+        let _ = window.emit_to(target.clone(), "ffmpeg-capture-progress", event);
+      })
+    })
+  })
+  .await?
+}
+
+/// Report the progress of an ffmpeg analysis to the calling window.
+fn emit_analysis_progress(window: &Window, analysis: &str, percent: u32) {
+  let event = FfmpegAnalysisProgressEvent {
+    analysis: analysis.to_string(),
+    percent,
+  };
+  let _ = window.emit_to(
+    EventTarget::webview_window(window.label()),
+    "ffmpeg-analysis-progress",
+    event,
+  );
+}
+
+/// Analyze every file `files` expands to (the same expansion as [`get_files`])
+/// and write the results as a spreadsheet. Only the properties the exported
+/// columns need are queried, unless a template group is empty, in which case
+/// every property of that stream kind is.
+pub async fn export_spreadsheet(
+  files: Vec<String>,
+  options: SpreadsheetExportOptions,
+) -> Result<SpreadsheetExportResult> {
+  let files = get_files(files).await?;
+  let templates = config::get_config().templates;
+  let stream_kinds: &[MediaInfoStreamKind] = if options.per_stream {
+    MediaInfoStreamKind::values()
+  } else {
+    &[MediaInfoStreamKind::General]
+  };
+  let mut properties: Vec<StreamProperty> = Vec::new();
+  for stream_kind in stream_kinds {
+    let Some(group) = spreadsheet::template_group(&templates, *stream_kind) else {
+      continue;
+    };
+    let names = if group.properties.is_empty() {
+      ALL_PROPERTIES_MAP.get(stream_kind).cloned().unwrap_or_default()
+    } else {
+      group.properties.clone()
+    };
+    properties.extend(names.into_iter().map(|property| StreamProperty {
+      stream: *stream_kind,
+      property,
+    }));
+  }
+  let mut analyses = Vec::new();
+  let mut failed = Vec::new();
+  for file in files {
+    match get_properties(file.clone(), Some(properties.clone())).await {
+      Ok(streams) => analyses.push(spreadsheet::FileAnalysis { file, streams }),
+      Err(err) => {
+        log::warn!("Couldn't analyze {} for the spreadsheet export because {}", file, err);
+        failed.push(file);
+      }
+    }
+  }
+  let file_count = analyses.len() as u32;
+  let outputs = tokio::task::spawn_blocking(move || {
+    let sheets = spreadsheet::build_sheets(&analyses, &templates, options.per_stream);
+    spreadsheet::export(&sheets, &options)
+  })
+  .await??;
+  Ok(SpreadsheetExportResult {
+    outputs,
+    files: file_count,
+    failed,
+  })
+}
+
 pub async fn get_about() -> Result<About> {
   let media_info = MediaInfo::new();
   let media_info_version = media_info.getOption(MediaInfoGetOption::InfoVersion)?;
@@ -150,10 +429,40 @@ pub async fn get_bdmaster_status(path: String, check_running: bool) -> Result<BD
   bdmaster::get_bdmaster_status(path, check_running).await
 }
 
+/// Frames to capture in `file` by its structure: each chapter start (from
+/// MediaInfo's Menu streams, or ffprobe when MediaInfo lists none; `mkvmerge -J`
+/// only counts chapters), each keyframe, or each of `timestamps`.
+pub async fn get_capture_points(
+  file: String,
+  source: CapturePointSource,
+  timestamps: Vec<f64>,
+) -> Result<Vec<CapturePoint>> {
+  tokio::task::spawn_blocking(move || match source {
+    CapturePointSource::Chapters => {
+      let path = Path::new(file.as_str());
+      validate_path_as_file(path)?;
+      let chapters = capture::menu_chapters(&MediaInfoFile::new(path).media_info.getInformation());
+      let chapters = if chapters.is_empty() {
+        ffprobe::chapters(&file)?
+      } else {
+        chapters
+      };
+      Ok(capture::chapter_points(&chapters))
+    }
+    CapturePointSource::Keyframes => Ok(capture::keyframe_points(&ffprobe::video_packets(&file)?)),
+    CapturePointSource::Timestamps => Ok(capture::timestamp_points(&timestamps)),
+  })
+  .await?
+}
+
 pub async fn get_config() -> Result<config::Config> {
   Ok(config::get_config())
 }
 
+pub async fn get_ffmpeg_candidates() -> Result<Vec<ToolCandidate>> {
+  ffmpeg::get_ffmpeg_candidates().await
+}
+
 pub async fn get_ffmpeg_status(path: String) -> Result<FfmpegStatus> {
   ffmpeg::get_ffmpeg_status(path).await
 }
@@ -210,6 +519,10 @@ pub async fn get_mkv_tracks(file: String) -> Result<Vec<MkvTrack>> {
   mkvtoolnix::get_mkv_tracks(file).await
 }
 
+pub async fn get_mkvtoolnix_candidates() -> Result<Vec<ToolCandidate>> {
+  mkvtoolnix::get_mkvtoolnix_candidates().await
+}
+
 pub async fn get_mkvtoolnix_status(path: String, check_running: bool) -> Result<MkvToolNixStatus> {
   mkvtoolnix::get_mkvtoolnix_status(path, check_running).await
 }
@@ -268,10 +581,14 @@ pub async fn get_properties(file: String, properties: Option<Vec<StreamProperty>
               property_map.insert(parameter.to_owned(), value);
             }
           }
+          if *stream_kind == MediaInfoStreamKind::Video {
+            encoder::add_properties(&mut property_map);
+          }
           stream_property_maps.push(StreamPropertyMap {
             stream: stream_kind.clone(),
             num,
             property_map,
+            source: PropertySource::MediaInfo,
           })
         }
       }
@@ -354,6 +671,30 @@ pub async fn register_folder_context_menu() -> Result<()> {
   context_menu::register_folder_context_menu()
 }
 
+pub async fn render_bitrate_graph(graph: BitrateGraph, output: String, width: u32, height: u32) -> Result<()> {
+  tokio::task::spawn_blocking(move || bitrate::render(&graph, Path::new(&output), width, height)).await?
+}
+
+pub async fn render_spectrogram(
+  window: Window,
+  file: String,
+  options: SpectrogramOptions,
+  children: ChildMap,
+) -> Result<SpectrogramReport> {
+  let properties = get_properties(file.clone(), Some(spectrogram::properties())).await?;
+  let label = window.label().to_owned();
+  tokio::task::spawn_blocking(move || {
+    spectrogram::analyze(
+      &file,
+      &options,
+      &properties,
+      |args, on_samples| ffmpeg::run_pcm_decode(args, &label, &children, on_samples),
+      |percent| emit_analysis_progress(&window, "spectrogram", percent),
+    )
+  })
+  .await?
+}
+
 pub async fn run_ffmpeg_capture(
   window: Window,
   args: Vec<String>,
@@ -368,6 +709,66 @@ pub async fn run_ffmpeg_capture(
   // width (the same width the seek preview uses), so high-resolution captures
   // don't flood the IPC channel with multi-megabyte payloads. Clamped defensively.
   let preview_width = preview_width.clamp(160, 7680);
+  let label = window.label().to_owned();
+  let target = EventTarget::webview_window(&label);
+  let window_clone = window.clone();
+  let frame_target = target.clone();
+  let frame_window = window.clone();
+  #[cfg(target_os = "windows")]
+  let hwnd_raw: Option<isize> = window.hwnd().ok().map(|h| h.0 as isize);
+
+  tokio::task::spawn_blocking(move || {
+    run_ffmpeg_capture_job(
+      args,
+      output_pattern,
+      duration_seconds,
+      trim,
+      label,
+      children,
+      cancels,
+      |event| {
+        #[cfg(target_os = "windows")]
+        if let Some(hwnd) = hwnd_raw {
+          if !event.done {
+            taskbar::set_progress(hwnd, event.percent);
+          } else if event.error.is_some() {
+            taskbar::set_error(hwnd);
+          } else {
+            taskbar::clear_progress(hwnd);
+          }
+        }
+        let _ = window_clone.emit_to(target.clone(), "ffmpeg-capture-progress", event);
+      },
+      |path| {
+        if let Ok(bytes) = std::fs::read(path) {
+          if !bytes.is_empty() {
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            let bytes = ffmpeg::downscale_for_preview(&bytes, ext, preview_width);
+            let _ = frame_window.emit_to(frame_target.clone(), "ffmpeg-capture-frame", FfmpegCaptureFrameEvent { bytes });
+          }
+        }
+      },
+    )
+  })
+  .await?
+}
+
+/// The blocking body of an FFmpeg capture: run ffmpeg, then the optional trim
+/// pass, reporting progress through `on_progress` and newly produced frames
+/// (throttled) through `on_frame`. `key` identifies the job in `children` and
+/// `cancels`, which is how `cancel_ffmpeg_capture` reaches it. Shared by the
+/// FFmpeg Tools window and the API server's capture jobs.
+pub fn run_ffmpeg_capture_job(
+  args: Vec<String>,
+  output_pattern: String,
+  duration_seconds: f64,
+  trim: Option<TrimOptions>,
+  key: String,
+  children: ChildMap,
+  cancels: CancelMap,
+  on_progress: impl Fn(FfmpegCaptureProgressEvent),
+  on_frame: impl Fn(&Path),
+) -> Result<()> {
   // Global options first, the frontend-built capture args in the middle, and
   // `-progress` last so ffmpeg streams machine-readable progress to stdout.
   let mut full_args: Vec<String> = vec![
@@ -387,229 +788,186 @@ pub async fn run_ffmpeg_capture(
     .take()
     .ok_or_else(|| anyhow::anyhow!("Failed to capture stdout"))?;
   let stderr = child.stderr.take();
-  let label = window.label().to_owned();
-  children.lock().unwrap().insert(label.clone(), child);
+  children.lock().unwrap().insert(key.clone(), child);
   // Register a fresh cancellation flag for the trim pass (the capture pass is
   // cancelled by killing the child above). Replaces any stale flag from a prior run.
   let cancel_flag = Arc::new(std::sync::atomic::AtomicBool::new(false));
-  cancels.lock().unwrap().insert(label.clone(), cancel_flag.clone());
-  let cancels_arc = cancels.clone();
-  let children_arc = children.clone();
-  let window_clone = window.clone();
+  cancels.lock().unwrap().insert(key.clone(), cancel_flag.clone());
   // The capture output filename pattern we handed ffmpeg (e.g.
   // `/dir/name_shot_%04d.png`). The produced frames are discovered by matching
   // this pattern by name — deterministic, unlike scanning by modification time.
   let pattern = std::path::PathBuf::from(&output_pattern);
-  #[cfg(target_os = "windows")]
-  let hwnd_raw: Option<isize> = window.hwnd().ok().map(|h| h.0 as isize);
 
-  tokio::task::spawn_blocking(move || {
-    let target = EventTarget::webview_window(&label);
-    // Drain stderr on a side thread so a chatty ffmpeg can't deadlock on a full pipe.
-    let stderr_handle = stderr.map(|mut s| {
-      std::thread::spawn(move || {
-        let mut buf = String::new();
-        let _ = std::io::Read::read_to_string(&mut s, &mut buf);
-        buf
-      })
-    });
-    #[cfg(target_os = "windows")]
-    if let Some(hwnd) = hwnd_raw {
-      taskbar::set_progress(hwnd, 0);
-    }
-    let mut last_frame_emit = std::time::Instant::now() - std::time::Duration::from_secs(1);
-    let mut last_emitted: Option<std::path::PathBuf> = None;
-    ffmpeg::read_capture_progress(stdout, |out_time| {
-      let percent = if duration_seconds > 0.0 {
-        ((out_time / duration_seconds) * 100.0).clamp(0.0, 100.0) as u32
-      } else {
-        0
-      };
-      #[cfg(target_os = "windows")]
-      if let Some(hwnd) = hwnd_raw {
-        taskbar::set_progress(hwnd, percent);
-      }
-      let _ = window_clone.emit_to(
-        target.clone(),
-        "ffmpeg-capture-progress",
-        FfmpegCaptureProgressEvent {
-          percent,
-          done: false,
-          cancelled: false,
-          error: None,
-          phase: "capture".to_string(),
-          current: 0,
-          total: 0,
-        },
-      );
-      // Throttle the live-frame preview so we don't re-read large images every tick.
-      if last_frame_emit.elapsed() >= std::time::Duration::from_millis(400) {
-        if let Some(path) = ffmpeg::newest_image_for_pattern(&pattern) {
-          if last_emitted.as_deref() != Some(path.as_path()) {
-            if let Ok(bytes) = std::fs::read(&path) {
-              if !bytes.is_empty() {
-                let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-                let bytes = ffmpeg::downscale_for_preview(&bytes, ext, preview_width);
-                let _ = window_clone.emit_to(target.clone(), "ffmpeg-capture-frame", FfmpegCaptureFrameEvent { bytes });
-                last_emitted = Some(path);
-              }
-            }
-          }
-        }
-        last_frame_emit = std::time::Instant::now();
-      }
-    });
-
-    let child = children_arc.lock().unwrap().remove(&label);
-    let (mut cancelled, mut error) = match child {
-      Some(mut c) => match c.wait() {
-        Ok(status) if status.success() => (false, None),
-        Ok(status) => (
-          false,
-          Some(format!("ffmpeg exited with code {}", status.code().unwrap_or(-1))),
-        ),
-        Err(e) => (false, Some(e.to_string())),
-      },
-      None => (true, None),
+  // Drain stderr on a side thread so a chatty ffmpeg can't deadlock on a full pipe.
+  let stderr_handle = stderr.map(|mut s| {
+    std::thread::spawn(move || {
+      let mut buf = String::new();
+      let _ = std::io::Read::read_to_string(&mut s, &mut buf);
+      buf
+    })
+  });
+  on_progress(FfmpegCaptureProgressEvent {
+    percent: 0,
+    done: false,
+    cancelled: false,
+    error: None,
+    phase: "capture".to_string(),
+    current: 0,
+    total: 0,
+  });
+  let mut last_frame_emit = std::time::Instant::now() - std::time::Duration::from_secs(1);
+  let mut last_emitted: Option<std::path::PathBuf> = None;
+  ffmpeg::read_capture_progress(stdout, |out_time| {
+    let percent = if duration_seconds > 0.0 {
+      ((out_time / duration_seconds) * 100.0).clamp(0.0, 100.0) as u32
+    } else {
+      0
     };
-    let stderr_text = stderr_handle.and_then(|h| h.join().ok());
-    if error.is_some() {
-      if let Some(text) = stderr_text {
-        let text = text.trim();
-        if !text.is_empty() {
-          error = Some(text.to_string());
+    on_progress(FfmpegCaptureProgressEvent {
+      percent,
+      done: false,
+      cancelled: false,
+      error: None,
+      phase: "capture".to_string(),
+      current: 0,
+      total: 0,
+    });
+    // Throttle the live-frame preview so we don't re-read large images every tick.
+    if last_frame_emit.elapsed() >= std::time::Duration::from_millis(400) {
+      if let Some(path) = ffmpeg::newest_image_for_pattern(&pattern) {
+        if last_emitted.as_deref() != Some(path.as_path()) {
+          on_frame(&path);
+          last_emitted = Some(path);
         }
       }
+      last_frame_emit = std::time::Instant::now();
     }
-    #[cfg(target_os = "windows")]
-    if let Some(hwnd) = hwnd_raw {
-      if error.is_some() {
-        taskbar::set_error(hwnd);
-      } else {
-        taskbar::clear_progress(hwnd);
+  });
+
+  let child = children.lock().unwrap().remove(&key);
+  let (mut cancelled, mut error) = match child {
+    Some(mut c) => match c.wait() {
+      Ok(status) if status.success() => (false, None),
+      Ok(status) => (
+        false,
+        Some(format!("ffmpeg exited with code {}", status.code().unwrap_or(-1))),
+      ),
+      Err(e) => (false, Some(e.to_string())),
+    },
+    None => (true, None),
+  };
+  let stderr_text = stderr_handle.and_then(|h| h.join().ok());
+  if error.is_some() {
+    if let Some(text) = stderr_text {
+      let text = text.trim();
+      if !text.is_empty() {
+        error = Some(text.to_string());
       }
     }
-    // Second pass: trim borders from every image this run produced. This is its
-    // own pass with its own progress, distinct from the ffmpeg capture above, so
-    // the UI drives the progress bar from the per-image counts reported here and
-    // flips its label to the trim phase. Runs across `threads` workers and
-    // contains per-frame panics so one bad frame can't abort the capture.
-    let mut trimmed_pass = false;
-    if !cancelled && error.is_none() {
-      if let Some(options) = trim.as_ref().filter(|t| t.enabled) {
-        if let Some(rgb) = ffmpeg::parse_hex_color(&options.color) {
-          let images = ffmpeg::images_for_pattern(&pattern);
-          let total = images.len();
-          if total > 0 {
-            trimmed_pass = true;
-            // Announce the start of the trim pass so the UI resets the bar (it was
-            // left at 100% by the capture pass) and switches its label.
-            let _ = window_clone.emit_to(
-              target.clone(),
-              "ffmpeg-capture-progress",
-              FfmpegCaptureProgressEvent {
-                percent: 0,
-                done: false,
-                cancelled: false,
-                error: None,
-                phase: "trim".to_string(),
-                current: 0,
-                total: total as u32,
-              },
-            );
-            #[cfg(target_os = "windows")]
-            if let Some(hwnd) = hwnd_raw {
-              taskbar::set_progress(hwnd, 0);
-            }
-            // 0 / missing => fall back to the core count, matching the frontend default.
-            let threads = if options.threads == 0 {
-              std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
-            } else {
-              options.threads
-            };
-            let mut last_trim_emit = std::time::Instant::now() - std::time::Duration::from_secs(1);
-            let stats = ffmpeg::trim_images(images, rgb, options.tolerance, threads, cancel_flag.clone(), |completed, total, path, result| {
-              let percent = ((completed as f64 / total as f64) * 100.0).clamp(0.0, 100.0) as u32;
-              #[cfg(target_os = "windows")]
-              if let Some(hwnd) = hwnd_raw {
-                taskbar::set_progress(hwnd, percent);
-              }
-              let _ = window_clone.emit_to(
-                target.clone(),
-                "ffmpeg-capture-progress",
-                FfmpegCaptureProgressEvent {
-                  percent,
-                  done: false,
-                  cancelled: false,
-                  error: None,
-                  phase: "trim".to_string(),
-                  current: completed as u32,
-                  total: total as u32,
-                },
-              );
-              // Show the frame just trimmed in the preview (skip removed blanks).
-              // Throttled so a fast multi-threaded pass doesn't flood the channel.
-              if matches!(result, Some(ffmpeg::TrimResult::Trimmed) | Some(ffmpeg::TrimResult::Unchanged))
-                && last_trim_emit.elapsed() >= std::time::Duration::from_millis(200)
-              {
-                if let Ok(bytes) = std::fs::read(path) {
-                  if !bytes.is_empty() {
-                    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-                    let bytes = ffmpeg::downscale_for_preview(&bytes, ext, preview_width);
-                    let _ = window_clone.emit_to(target.clone(), "ffmpeg-capture-frame", FfmpegCaptureFrameEvent { bytes });
-                  }
-                }
-                last_trim_emit = std::time::Instant::now();
-              }
+  }
+  // Second pass: trim borders from every image this run produced. This is its
+  // own pass with its own progress, distinct from the ffmpeg capture above, so
+  // the UI drives the progress bar from the per-image counts reported here and
+  // flips its label to the trim phase. Runs across `threads` workers and
+  // contains per-frame panics so one bad frame can't abort the capture.
+  let mut trimmed_pass = false;
+  if !cancelled && error.is_none() {
+    if let Some(options) = trim.as_ref().filter(|t| t.enabled) {
+      if let Some(rgb) = ffmpeg::parse_hex_color(&options.color) {
+        let images = ffmpeg::images_for_pattern(&pattern);
+        let total = images.len();
+        if total > 0 {
+          trimmed_pass = true;
+          // Announce the start of the trim pass so the UI resets the bar (it was
+          // left at 100% by the capture pass) and switches its label.
+          on_progress(FfmpegCaptureProgressEvent {
+            percent: 0,
+            done: false,
+            cancelled: false,
+            error: None,
+            phase: "trim".to_string(),
+            current: 0,
+            total: total as u32,
+          });
+          // 0 / missing => fall back to the core count, matching the frontend default.
+          let threads = if options.threads == 0 {
+            std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
+          } else {
+            options.threads
+          };
+          let mut last_trim_emit = std::time::Instant::now() - std::time::Duration::from_secs(1);
+          let stats = ffmpeg::trim_images(images, rgb, options.tolerance, options.mode, threads, cancel_flag.clone(), |completed, total, path, result| {
+            let percent = ((completed as f64 / total as f64) * 100.0).clamp(0.0, 100.0) as u32;
+            on_progress(FfmpegCaptureProgressEvent {
+              percent,
+              done: false,
+              cancelled: false,
+              error: None,
+              phase: "trim".to_string(),
+              current: completed as u32,
+              total: total as u32,
             });
-            log::debug!(
-              "trim pass: {} trimmed, {} unchanged, {} removed, {} failed",
-              stats.trimmed,
-              stats.unchanged,
-              stats.removed,
-              stats.failed
-            );
-            // If cancel was requested during the trim pass, report the run as
-            // cancelled so the UI suppresses the "complete" notification.
-            if cancel_flag.load(std::sync::atomic::Ordering::SeqCst) {
-              cancelled = true;
+            // Show the frame just trimmed in the preview (skip removed blanks).
+            // Throttled so a fast multi-threaded pass doesn't flood the channel.
+            if matches!(result, Some(ffmpeg::TrimResult::Trimmed) | Some(ffmpeg::TrimResult::Unchanged))
+              && last_trim_emit.elapsed() >= std::time::Duration::from_millis(200)
+            {
+              on_frame(path);
+              last_trim_emit = std::time::Instant::now();
             }
+          });
+          log::debug!(
+            "trim pass: {} trimmed, {} unchanged, {} removed, {} failed, crop {:?}",
+            stats.trimmed,
+            stats.unchanged,
+            stats.removed,
+            stats.failed,
+            stats.rect
+          );
+          // If cancel was requested during the trim pass, report the run as
+          // cancelled so the UI suppresses the "complete" notification.
+          if cancel_flag.load(std::sync::atomic::Ordering::SeqCst) {
+            cancelled = true;
           }
         }
       }
     }
-    // This run's cancel flag is no longer needed; drop it from the shared map.
-    cancels_arc.lock().unwrap().remove(&label);
-    // Emit the last produced frame so the preview lands on the final capture.
-    if !cancelled {
-      if let Some(path) = ffmpeg::newest_image_for_pattern(&pattern) {
-        if let Ok(bytes) = std::fs::read(&path) {
-          if !bytes.is_empty() {
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-            let bytes = ffmpeg::downscale_for_preview(&bytes, ext, preview_width);
-            let _ = window_clone.emit_to(target.clone(), "ffmpeg-capture-frame", FfmpegCaptureFrameEvent { bytes });
-          }
-        }
-      }
+  }
+  // This run's cancel flag is no longer needed; drop it from the shared map.
+  cancels.lock().unwrap().remove(&key);
+  // Emit the last produced frame so the preview lands on the final capture.
+  if !cancelled {
+    if let Some(path) = ffmpeg::newest_image_for_pattern(&pattern) {
+      on_frame(&path);
     }
-    let _ = window_clone.emit_to(
-      target,
-      "ffmpeg-capture-progress",
-      FfmpegCaptureProgressEvent {
-        percent: 100,
-        done: true,
-        cancelled,
-        error,
-        phase: if trimmed_pass { "trim".to_string() } else { "capture".to_string() },
-        current: 0,
-        total: 0,
-      },
-    );
-  })
-  .await?;
+  }
+  on_progress(FfmpegCaptureProgressEvent {
+    percent: 100,
+    done: true,
+    cancelled,
+    error,
+    phase: if trimmed_pass { "trim".to_string() } else { "capture".to_string() },
+    current: 0,
+    total: 0,
+  });
   Ok(())
 }
 
+/// Measure the loudness of every audio stream of `file`, streaming
+/// `ffmpeg-analysis-progress` events to `window`. `cancel_ffmpeg_analysis` stops it.
+pub async fn run_loudness_analysis(window: Window, file: String, children: ChildMap) -> Result<LoudnessReport> {
+  let properties = get_properties(file.clone(), Some(loudness::properties())).await?;
+  let label = window.label().to_owned();
+  tokio::task::spawn_blocking(move || {
+    loudness::analyze(&file, &properties, |args, duration| {
+      ffmpeg::run_analysis(args, duration, &label, &children, |percent| {
+        emit_analysis_progress(&window, "loudness", percent)
+      })
+    })
+  })
+  .await?
+}
+
 pub async fn run_mkvextract(window: Window, file: String, args: Vec<String>, children: ChildMap) -> Result<()> {
   let mut child = mkvtoolnix::spawn_mkvextract(&file, &args)?;
   let stdout = child
@@ -752,8 +1110,125 @@ pub async fn run_mkvmerge(window: Window, args: Vec<String>, children: ChildMap)
   Ok(())
 }
 
+/// Run the QC detectors over `file`, streaming `ffmpeg-analysis-progress`
+/// events to `window`. `cancel_ffmpeg_analysis` stops it through `children`.
+pub async fn run_qc_analysis(
+  window: Window,
+  file: String,
+  options: QcOptions,
+  children: ChildMap,
+) -> Result<QcTimeline> {
+  let properties = get_properties(file.clone(), Some(qc::properties())).await?;
+  let label = window.label().to_owned();
+  tokio::task::spawn_blocking(move || {
+    qc::analyze(&file, &options, &properties, |args, duration| {
+      ffmpeg::run_analysis(args, duration, &label, &children, |percent| {
+        emit_analysis_progress(&window, "qc", percent)
+      })
+    })
+  })
+  .await?
+}
+
+/// Compare `distorted` against `reference` and capture its worst frames,
+/// streaming `ffmpeg-capture-progress` events to `window`: the `quality` phase
+/// while ffmpeg computes the metrics, then the `capture` phase per worst frame.
+/// `cancel_ffmpeg_capture` stops either, like a capture.
+pub async fn run_quality_comparison(
+  window: Window,
+  reference: String,
+  distorted: String,
+  options: QualityOptions,
+  children: ChildMap,
+  cancels: CancelMap,
+) -> Result<QualityReport> {
+  let reference_properties = get_properties(reference.clone(), Some(quality::properties())).await?;
+  let distorted_properties = get_properties(distorted.clone(), Some(quality::properties())).await?;
+  let label = window.label().to_owned();
+  let target = EventTarget::webview_window(&label);
+  tokio::task::spawn_blocking(move || {
+    let emit = |phase: &str, percent: u32, current: u32, total: u32| {
+      let event = FfmpegCaptureProgressEvent {
+        percent,
+        done: false,
+        cancelled: false,
+        error: None,
+        phase: phase.to_string(),
+        current,
+        total,
+      };
+      let _ = window.emit_to(target.clone(), "ffmpeg-capture-progress", event);
+    };
+    let cancel_flag = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    cancels.lock().unwrap().insert(label.clone(), cancel_flag.clone());
+    let result = quality::compare(
+      &reference,
+      &distorted,
+      &options,
+      &reference_properties,
+      &distorted_properties,
+      |args, duration| {
+        ffmpeg::run_analysis(args, duration, &label, &children, |percent| {
+          emit("quality", percent, 0, 0)
+        })
+      },
+      |current, total| {
+        emit("capture", current * 100 / total.max(1), current, total);
+        !cancel_flag.load(std::sync::atomic::Ordering::SeqCst)
+      },
+    );
+    cancels.lock().unwrap().remove(&label);
+    let error = result.as_ref().err().map(|error| error.to_string());
+    let cancelled = error.as_deref() == Some("FFMPEG_ANALYSIS_CANCELLED");
+    let _ = window.emit_to(
+      target.clone(),
+      "ffmpeg-capture-progress",
+      FfmpegCaptureProgressEvent {
+        percent: 100,
+        done: true,
+        cancelled,
+        error: error.filter(|_| !cancelled),
+        phase: "capture".to_string(),
+        current: 0,
+        total: 0,
+      },
+    );
+    result
+  })
+  .await?
+}
+
+/// Find the scene cuts of `file` and propose chapters from them, streaming
+/// `ffmpeg-analysis-progress` events to `window`. `cancel_ffmpeg_analysis` stops it.
+pub async fn run_scene_detection(
+  window: Window,
+  file: String,
+  options: SceneOptions,
+  children: ChildMap,
+) -> Result<SceneChapters> {
+  let properties = get_properties(file.clone(), Some(scenes::properties())).await?;
+  let label = window.label().to_owned();
+  tokio::task::spawn_blocking(move || {
+    let keyframes = if options.snap_to_keyframes {
+      ffprobe::keyframes(&file)?
+    } else {
+      Vec::new()
+    };
+    scenes::analyze(&file, &options, &properties, &keyframes, |args, duration| {
+      ffmpeg::run_analysis(args, duration, &label, &children, |percent| {
+        emit_analysis_progress(&window, "scenes", percent)
+      })
+    })
+  })
+  .await?
+}
+
 pub async fn set_config(config: config::Config) -> Result<config::Config> {
   config::set_config(config)?;
+  let config = config::get_config();
+  watch::reload(&config.watch);
+  // Enabling the server may generate and save its token, so re-read afterwards.
+  server::reload(&config.server);
   Ok(config::get_config())
 }
 
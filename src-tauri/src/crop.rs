@@ -0,0 +1,277 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use std::io::{BufRead, BufReader};
+
+use crate::ffmpeg;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{CropDetection, StreamProperty, StreamPropertyMap};
+
+/// Frames `cropdetect` looks at per sample. It skips the first couple of
+/// frames after a seek, so a few more than one are needed for a reading.
+const FRAMES_PER_SAMPLE: u32 = 6;
+/// Luma below which `cropdetect` treats a pixel as border, on the 0-255 scale.
+const LIMIT: u32 = 24;
+/// A sample whose content box is smaller than this share of the frame in
+/// either direction is a dark or fading scene, not a reading of the bars.
+const MIN_CONTENT_SHARE: f64 = 0.25;
+/// Valid samples needed before a crop is reported.
+const MIN_SAMPLES: u32 = 3;
+/// Readings at most this many pixels inside the reported crop still agree with it.
+const AGREEMENT_TOLERANCE: u32 = 4;
+/// Share of valid samples allowed to show more picture than the reported
+/// crop keeps, e.g. a bright logo or subtitle drawn into the bars.
+const OUTLIER_SHARE: f64 = 0.1;
+const PROPERTIES: &[(MediaInfoStreamKind, &[&str])] = &[
+  (MediaInfoStreamKind::General, &["Duration"]),
+  (
+    MediaInfoStreamKind::Video,
+    &["Width", "Height", "PixelAspectRatio", "Duration"],
+  ),
+];
+const SAMPLES: u32 = 24;
+
+/// One `cropdetect` reading: the inclusive bounds of the non-border pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+  x1: i64,
+  x2: i64,
+  y1: i64,
+  y2: i64,
+}
+
+/// Find the letterbox/pillarbox borders of the first video stream of `file`
+/// by running ffmpeg `cropdetect` at `SAMPLES` positions spread over the
+/// file, and aggregate the readings into one crop. `properties` are the
+/// `properties()` of `file`.
+pub fn detect(file: &str, properties: &[StreamPropertyMap]) -> Result<CropDetection> {
+  let video = properties
+    .iter()
+    .find(|map| map.stream == MediaInfoStreamKind::Video && map.num == 0)
+    .ok_or_else(|| anyhow::anyhow!("CROP_DETECT_NO_VIDEO:{}", file))?;
+  let get = |name: &str| video.property_map.get(name).and_then(|value| value.parse::<f64>().ok());
+  let (Some(width), Some(height)) = (get("Width"), get("Height")) else {
+    return Err(anyhow::anyhow!("CROP_DETECT_NO_VIDEO:{}", file));
+  };
+  let pixel_aspect = get("PixelAspectRatio").filter(|par| *par > 0.0).unwrap_or(1.0);
+  let duration = get("Duration")
+    .or_else(|| {
+      properties
+        .iter()
+        .find(|map| map.stream == MediaInfoStreamKind::General)
+        .and_then(|map| map.property_map.get("Duration"))
+        .and_then(|value| value.parse::<f64>().ok())
+    })
+    .unwrap_or_default()
+    / 1000.0;
+  let positions: Vec<f64> = if duration > 0.0 {
+    (0..SAMPLES)
+      .map(|index| duration * (index as f64 + 0.5) / SAMPLES as f64)
+      .collect()
+  } else {
+    vec![0.0]
+  };
+  let mut readings = Vec::with_capacity(positions.len());
+  for position in positions {
+    readings.push(sample(file, position)?);
+  }
+  summarize(width as u32, height as u32, pixel_aspect, &readings)
+}
+
+/// The last `x1:… x2:… y1:… y2:…` reading in a `cropdetect` log line.
+fn parse_cropdetect_line(line: &str) -> Option<Bounds> {
+  if !line.contains("cropdetect") {
+    return None;
+  }
+  let value = |key: &str| -> Option<i64> {
+    line
+      .split_whitespace()
+      .find_map(|token| token.strip_prefix(key))
+      .and_then(|value| value.parse().ok())
+  };
+  Some(Bounds {
+    x1: value("x1:")?,
+    x2: value("x2:")?,
+    y1: value("y1:")?,
+    y2: value("y2:")?,
+  })
+}
+
+/// The MediaInfo properties `detect` needs.
+pub fn properties() -> Vec<StreamProperty> {
+  PROPERTIES
+    .iter()
+    .flat_map(|(kind, properties)| {
+      properties.iter().map(|property| StreamProperty {
+        stream: *kind,
+        property: property.to_string(),
+      })
+    })
+    .collect()
+}
+
+/// Run `cropdetect` on `FRAMES_PER_SAMPLE` frames from `position` seconds and
+/// return its final reading, if it printed one.
+fn sample(file: &str, position: f64) -> Result<Option<Bounds>> {
+  let args: Vec<String> = [
+    "-hide_banner",
+    "-nostats",
+    "-ss",
+    &format!("{:.3}", position),
+    "-i",
+    file,
+    "-map",
+    "0:v:0",
+    "-an",
+    "-sn",
+    "-frames:v",
+    &FRAMES_PER_SAMPLE.to_string(),
+    "-vf",
+    &format!("cropdetect=limit={}:round=2:reset=0", LIMIT),
+    "-f",
+    "null",
+    "-",
+  ]
+  .iter()
+  .map(|arg| arg.to_string())
+  .collect();
+  let mut child = ffmpeg::spawn_ffmpeg(&args)?;
+  let mut last = None;
+  if let Some(stderr) = child.stderr.take() {
+    for line in BufReader::new(stderr).lines().map_while(|line| line.ok()) {
+      if let Some(bounds) = parse_cropdetect_line(&line) {
+        last = Some(bounds);
+      }
+    }
+  }
+  let status = child.wait()?;
+  if !status.success() && last.is_none() {
+    return Err(anyhow::anyhow!("CROP_DETECT_FAILED:{}", status));
+  }
+  Ok(last)
+}
+
+/// Aggregate per-sample readings into one crop. Readings with no or little
+/// content (dark scenes) are ignored; of the rest, each edge keeps the crop
+/// that all but the `OUTLIER_SHARE` brightest readings allow, so a dim scene
+/// can't over-crop and a stray bright pixel in the bars can't cancel the crop.
+/// Edges are rounded down to even pixels for 4:2:0 chroma.
+fn summarize(width: u32, height: u32, pixel_aspect: f64, readings: &[Option<Bounds>]) -> Result<CropDetection> {
+  let (w, h) = (width as i64, height as i64);
+  let valid: Vec<[u32; 4]> = readings
+    .iter()
+    .flatten()
+    .filter(|bounds| {
+      let content_w = bounds.x2 - bounds.x1 + 1;
+      let content_h = bounds.y2 - bounds.y1 + 1;
+      content_w as f64 >= w as f64 * MIN_CONTENT_SHARE && content_h as f64 >= h as f64 * MIN_CONTENT_SHARE
+    })
+    .map(|bounds| {
+      [
+        bounds.x1.clamp(0, w) as u32,
+        bounds.y1.clamp(0, h) as u32,
+        (w - 1 - bounds.x2).clamp(0, w) as u32,
+        (h - 1 - bounds.y2).clamp(0, h) as u32,
+      ]
+    })
+    .collect();
+  if (valid.len() as u32) < MIN_SAMPLES.min(readings.len() as u32).max(1) {
+    return Err(anyhow::anyhow!(
+      "CROP_DETECT_TOO_DARK:{}:{}",
+      valid.len(),
+      readings.len()
+    ));
+  }
+  let outliers = (valid.len() as f64 * OUTLIER_SHARE).floor() as usize;
+  let mut edges = [0u32; 4];
+  for (edge, value) in edges.iter_mut().enumerate() {
+    let mut crops: Vec<u32> = valid.iter().map(|crop| crop[edge]).collect();
+    crops.sort_unstable();
+    *value = crops[outliers] & !1;
+  }
+  let [left, top, right, bottom] = edges;
+  let agreeing = valid
+    .iter()
+    .filter(|crop| {
+      crop
+        .iter()
+        .zip(edges.iter())
+        .all(|(sample, edge)| sample.abs_diff(*edge) <= AGREEMENT_TOLERANCE)
+    })
+    .count();
+  let crop_width = width - left - right;
+  let crop_height = height - top - bottom;
+  Ok(CropDetection {
+    width,
+    height,
+    left,
+    top,
+    right,
+    bottom,
+    aspect_ratio: crop_width as f64 * pixel_aspect / crop_height as f64,
+    samples: valid.len() as u32,
+    ignored: (readings.len() - valid.len()) as u32,
+    agreement: agreeing as f64 / valid.len() as f64,
+    mkvmerge_cropping: format!("{},{},{},{}", left, top, right, bottom),
+    ffmpeg_crop: format!("crop={}:{}:{}:{}", crop_width, crop_height, left, top),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn bounds(x1: i64, x2: i64, y1: i64, y2: i64) -> Option<Bounds> {
+    Some(Bounds { x1, x2, y1, y2 })
+  }
+
+  #[test]
+  fn parse_cropdetect_line_reads_bounds() {
+    let line = "[Parsed_cropdetect_0 @ 0x5581] x1:0 x2:1919 y1:138 y2:941 w:1920 h:804 x:0 y:138 pts:1001 t:0.041708 limit:0.094118 crop=1920:804:0:138";
+    assert_eq!(parse_cropdetect_line(line), bounds(0, 1919, 138, 941));
+    assert_eq!(parse_cropdetect_line("frame=    6 fps=0.0 q=-0.0"), None);
+  }
+
+  #[test]
+  fn summarize_ignores_dark_and_dim_scenes() {
+    let mut readings = vec![bounds(0, 1919, 140, 939); 8];
+    // A black frame reports an empty box; a dim scene a box inside the picture.
+    readings.push(bounds(1919, 0, 1079, 0));
+    readings.push(bounds(200, 1700, 300, 800));
+    let detection = summarize(1920, 1080, 1.0, &readings).unwrap();
+    assert_eq!(
+      (detection.left, detection.top, detection.right, detection.bottom),
+      (0, 140, 0, 140)
+    );
+    assert_eq!(detection.ignored, 1);
+    assert_eq!(detection.samples, 9);
+    assert_eq!(detection.mkvmerge_cropping, "0,140,0,140");
+    assert_eq!(detection.ffmpeg_crop, "crop=1920:800:0:140");
+    assert!((detection.aspect_ratio - 2.4).abs() < 1e-9);
+    assert!((detection.agreement - 8.0 / 9.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn summarize_rounds_to_even_and_applies_pixel_aspect() {
+    let readings = vec![bounds(9, 710, 0, 479); 3];
+    let detection = summarize(720, 480, 32.0 / 27.0, &readings).unwrap();
+    assert_eq!((detection.left, detection.right), (8, 8));
+    assert!((detection.aspect_ratio - 704.0 * 32.0 / 27.0 / 480.0).abs() < 1e-9);
+    assert!(summarize(720, 480, 1.0, &[None, None, bounds(0, 0, 0, 0)]).is_err());
+  }
+}
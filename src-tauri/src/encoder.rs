@@ -0,0 +1,508 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{
+  EncoderField, EncoderKind, EncoderPreset, EncoderRateControl, EncoderSetting, EncoderSettingGroup, EncoderSettings,
+  StreamProperty, StreamPropertyMap,
+};
+
+/// Parameter names by group, as x264 (`_`), x265 and SVT-AV1 (`-`) print them.
+const GROUPS: &[(EncoderSettingGroup, &[&str])] = &[
+  (
+    EncoderSettingGroup::RateControl,
+    &[
+      "rc",
+      "crf",
+      "qp",
+      "bitrate",
+      "tbr",
+      "mbr",
+      "vbv_maxrate",
+      "vbv_bufsize",
+      "vbv-maxrate",
+      "vbv-bufsize",
+      "vbv-init",
+      "qcomp",
+      "qpmin",
+      "qpmax",
+      "qpstep",
+      "ip_ratio",
+      "pb_ratio",
+      "ipratio",
+      "pbratio",
+      "mbtree",
+      "cutree",
+      "rc_lookahead",
+      "rc-lookahead",
+      "lookahead",
+      "stats-read",
+      "stats-write",
+      "pass",
+      "crf-max",
+    ],
+  ),
+  (
+    EncoderSettingGroup::FrameStructure,
+    &[
+      "ref",
+      "bframes",
+      "b_pyramid",
+      "b-pyramid",
+      "b_adapt",
+      "b-adapt",
+      "b_bias",
+      "bframe-bias",
+      "keyint",
+      "keyint_min",
+      "min-keyint",
+      "scenecut",
+      "scd",
+      "open_gop",
+      "open-gop",
+      "weightb",
+      "weightp",
+      "direct",
+      "intra_refresh",
+      "irefresh-type",
+      "hierarchical-levels",
+      "interlaced",
+      "ctu",
+      "min-cu-size",
+    ],
+  ),
+  (
+    EncoderSettingGroup::AdaptiveQuantization,
+    &[
+      "aq",
+      "aq-mode",
+      "aq-strength",
+      "psy",
+      "psy_rd",
+      "psy-rd",
+      "psy-rdoq",
+      "deadzone",
+      "qg-size",
+      "enable-qm",
+      "qm-min",
+      "qm-max",
+      "enable-variance-boost",
+      "variance-boost-strength",
+      "variance-octile",
+      "sharpness",
+    ],
+  ),
+  (
+    EncoderSettingGroup::MotionEstimation,
+    &[
+      "me",
+      "subme",
+      "me_range",
+      "merange",
+      "chroma_me",
+      "mixed_ref",
+      "analyse",
+      "trellis",
+      "max-merge",
+      "rect",
+      "amp",
+      "early-skip",
+      "rd",
+      "rdoq-level",
+      "limit-refs",
+      "limit-modes",
+      "tskip",
+    ],
+  ),
+];
+
+const PRESETS: &[&str] = &[
+  "ultrafast",
+  "superfast",
+  "veryfast",
+  "faster",
+  "fast",
+  "medium",
+  "slow",
+  "slower",
+  "veryslow",
+  "placebo",
+];
+
+const PROPERTIES: &[&str] = &["Encoded_Library", "Encoded_Library_Name", "Encoded_Library_Settings"];
+
+/// What each x264 preset sets, from `ultrafast` to `placebo`.
+const X264_PRESETS: &[(&str, [&str; 10])] = &[
+  ("subme", ["0", "1", "2", "4", "6", "7", "8", "9", "10", "11"]),
+  (
+    "me",
+    ["dia", "dia", "hex", "hex", "hex", "hex", "umh", "umh", "umh", "tesa"],
+  ),
+  ("ref", ["1", "1", "1", "2", "2", "3", "5", "8", "16", "16"]),
+  ("trellis", ["0", "0", "0", "1", "1", "1", "2", "2", "2", "2"]),
+  (
+    "rc_lookahead",
+    ["0", "0", "10", "20", "30", "40", "50", "60", "60", "60"],
+  ),
+  ("bframes", ["0", "3", "3", "3", "3", "3", "3", "3", "8", "16"]),
+  ("b_adapt", ["0", "1", "1", "1", "1", "1", "2", "2", "2", "2"]),
+  ("direct", ["1", "1", "1", "1", "1", "1", "3", "3", "3", "3"]),
+];
+
+/// What each x265 preset sets, from `ultrafast` to `placebo`. x265 prints
+/// `me` as a number: 0 dia, 1 hex, 2 umh, 3 star.
+const X265_PRESETS: &[(&str, [&str; 10])] = &[
+  ("ref", ["1", "1", "2", "2", "3", "3", "4", "5", "5", "5"]),
+  ("rd", ["2", "2", "2", "2", "2", "3", "4", "6", "6", "6"]),
+  ("subme", ["0", "1", "1", "2", "2", "2", "3", "4", "4", "5"]),
+  ("me", ["0", "1", "1", "1", "1", "1", "3", "3", "3", "3"]),
+  (
+    "rc-lookahead",
+    ["5", "10", "15", "15", "15", "20", "25", "40", "40", "60"],
+  ),
+  ("bframes", ["3", "3", "4", "4", "4", "4", "4", "8", "8", "8"]),
+  ("b-adapt", ["0", "0", "0", "0", "0", "2", "2", "2", "2", "2"]),
+  ("max-merge", ["2", "2", "2", "2", "2", "2", "3", "3", "4", "5"]),
+  ("merange", ["57", "57", "57", "57", "57", "57", "57", "57", "57", "92"]),
+  ("early-skip", ["1", "1", "1", "1", "0", "0", "0", "0", "0", "0"]),
+  ("rect", ["0", "0", "0", "0", "0", "0", "1", "1", "1", "1"]),
+  ("amp", ["0", "0", "0", "0", "0", "0", "0", "1", "1", "1"]),
+];
+
+/// Add the parsed `Encoded_Library_Settings` of a video stream to its
+/// `property_map`: one `Encoded_Library_Settings/<key>` per parameter, plus
+/// `Encoded_Library_RateControl` and `Encoded_Library_Preset`.
+pub fn add_properties(property_map: &mut HashMap<String, String>) {
+  let Some(text) = property_map.get("Encoded_Library_Settings") else {
+    return;
+  };
+  let library = property_map
+    .get("Encoded_Library_Name")
+    .or_else(|| property_map.get("Encoded_Library"));
+  let settings = parse(library.map(String::as_str), text);
+  let mut extra: Vec<(String, String)> = settings
+    .settings
+    .iter()
+    .map(|setting| {
+      (
+        format!("Encoded_Library_Settings/{}", setting.key),
+        setting.value.clone(),
+      )
+    })
+    .collect();
+  if let Some(rate_control) = rate_control_label(&settings) {
+    extra.push(("Encoded_Library_RateControl".to_owned(), rate_control));
+  }
+  if let Some(preset) = &settings.preset {
+    let preset = if preset.compared == 0 {
+      preset.name.clone()
+    } else {
+      format!("{} ({}/{})", preset.name, preset.matched, preset.compared)
+    };
+    extra.push(("Encoded_Library_Preset".to_owned(), preset));
+  }
+  property_map.extend(extra);
+}
+
+/// Every parameter `left` or `right` sets, by group, in the order `left`
+/// lists them and then the ones only `right` has.
+pub fn compare(left: &EncoderSettings, right: &EncoderSettings) -> Vec<EncoderField> {
+  let lookup = |settings: &EncoderSettings| -> HashMap<String, String> {
+    settings
+      .settings
+      .iter()
+      .map(|setting| (setting.key.clone(), setting.value.clone()))
+      .collect()
+  };
+  let left_values = lookup(left);
+  let right_values = lookup(right);
+  let mut fields: Vec<EncoderField> = left
+    .settings
+    .iter()
+    .chain(
+      right
+        .settings
+        .iter()
+        .filter(|setting| !left_values.contains_key(&setting.key)),
+    )
+    .map(|setting| {
+      let left = left_values.get(&setting.key).cloned();
+      let right = right_values.get(&setting.key).cloned();
+      EncoderField {
+        key: setting.key.clone(),
+        group: setting.group,
+        mismatch: left != right,
+        left,
+        right,
+      }
+    })
+    .collect();
+  fields.sort_by_key(|field| field.group);
+  fields
+}
+
+fn detect(library: Option<&str>, values: &HashMap<&str, &str>) -> EncoderKind {
+  let library = library.unwrap_or_default().to_ascii_lowercase();
+  if library.contains("x264") || (library.is_empty() && values.contains_key("analyse")) {
+    EncoderKind::X264
+  } else if library.contains("x265") || (library.is_empty() && values.contains_key("ctu")) {
+    EncoderKind::X265
+  } else if library.contains("svt") || (library.is_empty() && values.contains_key("enable-qm")) {
+    EncoderKind::SvtAv1
+  } else {
+    EncoderKind::Unknown
+  }
+}
+
+fn group(key: &str) -> EncoderSettingGroup {
+  GROUPS
+    .iter()
+    .find(|(_, keys)| keys.contains(&key))
+    .map(|(group, _)| *group)
+    .unwrap_or(EncoderSettingGroup::Other)
+}
+
+/// Parse an encoder settings string: `key=value` pairs split by ` / ` (x264,
+/// x265) or whitespace. A bare `flag` is `flag=1` and a bare `no-flag` is
+/// `flag=0`, as x265 prints its switches.
+pub fn parse(library: Option<&str>, text: &str) -> EncoderSettings {
+  let tokens: Vec<&str> = if text.contains(" / ") {
+    text.split(" / ").collect()
+  } else {
+    text.split_whitespace().collect()
+  };
+  let settings: Vec<EncoderSetting> = tokens
+    .into_iter()
+    .map(str::trim)
+    .filter(|token| !token.is_empty())
+    .map(|token| {
+      let (key, value) = match token.split_once('=') {
+        Some((key, value)) => (key.trim(), value.trim()),
+        None => match token.strip_prefix("no-") {
+          Some(flag) => (flag, "0"),
+          None => (token, "1"),
+        },
+      };
+      EncoderSetting {
+        key: key.to_owned(),
+        value: value.to_owned(),
+        group: group(key),
+      }
+    })
+    .collect();
+  let values: HashMap<&str, &str> = settings
+    .iter()
+    .map(|setting| (setting.key.as_str(), setting.value.as_str()))
+    .collect();
+  let number = |key: &str| values.get(key).and_then(|value| value.parse::<f64>().ok());
+  let encoder = detect(library, &values);
+  let rate_control = rate_control(&values);
+  let quality = match rate_control {
+    Some(EncoderRateControl::Crf) => number("crf"),
+    Some(EncoderRateControl::Cqp) => number("qp").or_else(|| number("crf")),
+    _ => None,
+  };
+  let bitrate = match rate_control {
+    Some(EncoderRateControl::Crf | EncoderRateControl::Cqp) => None,
+    _ => number("bitrate").or_else(|| number("tbr")),
+  };
+  let preset = match encoder {
+    EncoderKind::X264 => guess_preset(&values, X264_PRESETS),
+    EncoderKind::X265 => guess_preset(&values, X265_PRESETS),
+    EncoderKind::SvtAv1 => values.get("preset").map(|preset| EncoderPreset {
+      name: preset.to_string(),
+      matched: 0,
+      compared: 0,
+    }),
+    EncoderKind::Unknown => None,
+  };
+  EncoderSettings {
+    encoder,
+    library: library.map(str::to_owned),
+    rate_control,
+    quality,
+    bitrate,
+    preset,
+    settings,
+  }
+}
+
+/// The preset whose parameters the most `values` match. Ties go to the
+/// faster preset.
+fn guess_preset(values: &HashMap<&str, &str>, table: &[(&str, [&str; 10])]) -> Option<EncoderPreset> {
+  let compared = table.iter().filter(|(key, _)| values.contains_key(key)).count() as u32;
+  if compared == 0 {
+    return None;
+  }
+  let matched = |preset: usize| {
+    table
+      .iter()
+      .filter(|(key, presets)| values.get(key) == Some(&presets[preset]))
+      .count() as u32
+  };
+  let mut best = 0;
+  for preset in 1..PRESETS.len() {
+    if matched(preset) > matched(best) {
+      best = preset;
+    }
+  }
+  Some(EncoderPreset {
+    name: PRESETS[best].to_owned(),
+    matched: matched(best),
+    compared,
+  })
+}
+
+pub fn properties() -> Vec<StreamProperty> {
+  PROPERTIES
+    .iter()
+    .map(|property| StreamProperty {
+      stream: MediaInfoStreamKind::Video,
+      property: property.to_string(),
+    })
+    .collect()
+}
+
+/// `rc` as x264 (`crf`, `cqp`, `abr`, `cbr`, `2pass`), x265 (`2 pass`) or
+/// SVT-AV1 (`0` constant quality, `1` VBR, `2` CBR) prints it, or what the
+/// other parameters imply without one.
+fn rate_control(values: &HashMap<&str, &str>) -> Option<EncoderRateControl> {
+  match values.get("rc").copied() {
+    Some("crf") => Some(EncoderRateControl::Crf),
+    Some("cqp") => Some(EncoderRateControl::Cqp),
+    Some("abr") => Some(EncoderRateControl::Abr),
+    Some("cbr") | Some("2") => Some(EncoderRateControl::Cbr),
+    Some("2pass") | Some("2 pass") => Some(EncoderRateControl::TwoPass),
+    Some("1") => Some(EncoderRateControl::Vbr),
+    Some("0") if values.contains_key("crf") => Some(EncoderRateControl::Crf),
+    Some("0") => Some(EncoderRateControl::Cqp),
+    _ if values.contains_key("crf") => Some(EncoderRateControl::Crf),
+    _ if values.contains_key("qp") => Some(EncoderRateControl::Cqp),
+    _ if values.contains_key("bitrate") => Some(EncoderRateControl::Abr),
+    _ => None,
+  }
+}
+
+/// `CRF 18.0`, `ABR 5000 kb/s`, or just the mode.
+fn rate_control_label(settings: &EncoderSettings) -> Option<String> {
+  let mode = match settings.rate_control? {
+    EncoderRateControl::Crf => "CRF",
+    EncoderRateControl::Cqp => "CQP",
+    EncoderRateControl::Abr => "ABR",
+    EncoderRateControl::Cbr => "CBR",
+    EncoderRateControl::Vbr => "VBR",
+    EncoderRateControl::TwoPass => "2-pass",
+  };
+  Some(match (settings.quality, settings.bitrate) {
+    (Some(quality), _) => format!("{} {:.1}", mode, quality),
+    (None, Some(bitrate)) => format!("{} {} kb/s", mode, bitrate),
+    (None, None) => mode.to_owned(),
+  })
+}
+
+/// The parsed settings of the first video stream in the MediaInfo `properties` of `file`.
+pub fn settings(file: &str, properties: &[StreamPropertyMap]) -> Result<EncoderSettings> {
+  let property_map = properties
+    .iter()
+    .filter(|map| map.stream == MediaInfoStreamKind::Video)
+    .min_by_key(|map| map.num)
+    .map(|map| &map.property_map);
+  let text = property_map
+    .and_then(|map| map.get("Encoded_Library_Settings"))
+    .ok_or_else(|| anyhow::anyhow!("ENCODER_NO_SETTINGS:{}", file))?;
+  let library = property_map.and_then(|map| map.get("Encoded_Library"));
+  Ok(parse(library.map(String::as_str), text))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const X264: &str = "cabac=1 / ref=5 / deblock=1:0:0 / analyse=0x3:0x113 / me=umh / subme=8 / psy=1 / \
+    psy_rd=1.00:0.00 / mixed_ref=1 / me_range=16 / chroma_me=1 / trellis=2 / 8x8dct=1 / bframes=3 / b_pyramid=2 / \
+    b_adapt=2 / direct=3 / weightb=1 / keyint=250 / rc_lookahead=50 / rc=crf / mbtree=1 / crf=18.0 / qcomp=0.60 / \
+    aq=1:1.00";
+
+  #[test]
+  fn test_parse_x264() {
+    let settings = parse(Some("x264 - core 164 r3095 baee400"), X264);
+    assert_eq!(EncoderKind::X264, settings.encoder);
+    assert_eq!(Some(EncoderRateControl::Crf), settings.rate_control);
+    assert_eq!(Some(18.0), settings.quality);
+    assert_eq!(None, settings.bitrate);
+    let preset = settings.preset.unwrap();
+    assert_eq!(("slow", 8, 8), (preset.name.as_str(), preset.matched, preset.compared));
+    let aq = settings.settings.iter().find(|setting| setting.key == "aq").unwrap();
+    assert_eq!(
+      ("1:1.00", EncoderSettingGroup::AdaptiveQuantization),
+      (aq.value.as_str(), aq.group)
+    );
+  }
+
+  #[test]
+  fn test_parse_x265_flags_and_compare() {
+    let left = parse(
+      Some("x265"),
+      "ctu=64 / ref=3 / no-early-skip / rect / rc=abr / bitrate=5000 / aq-mode=2",
+    );
+    assert_eq!(Some(EncoderRateControl::Abr), left.rate_control);
+    assert_eq!(Some(5000.0), left.bitrate);
+    let values: Vec<(&str, &str)> = left
+      .settings
+      .iter()
+      .map(|setting| (setting.key.as_str(), setting.value.as_str()))
+      .collect();
+    assert!(values.contains(&("early-skip", "0")) && values.contains(&("rect", "1")));
+    let right = parse(Some("x265"), "ctu=64 / ref=4 / rc=crf / crf=20.0 / aq-mode=2");
+    let fields = compare(&left, &right);
+    let mismatched: Vec<&str> = fields
+      .iter()
+      .filter(|field| field.mismatch)
+      .map(|field| field.key.as_str())
+      .collect();
+    assert_eq!(vec!["rc", "bitrate", "crf", "ref", "early-skip", "rect"], mismatched);
+  }
+
+  #[test]
+  fn test_add_properties() {
+    let mut property_map: HashMap<String, String> = [
+      ("Encoded_Library_Name", "SVT-AV1"),
+      ("Encoded_Library_Settings", "preset=6 crf=30 rc=0 enable-qm=1"),
+    ]
+    .iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+    add_properties(&mut property_map);
+    assert_eq!(
+      Some("CRF 30.0"),
+      property_map.get("Encoded_Library_RateControl").map(String::as_str)
+    );
+    assert_eq!(
+      Some("6"),
+      property_map.get("Encoded_Library_Preset").map(String::as_str)
+    );
+    assert_eq!(
+      Some("1"),
+      property_map
+        .get("Encoded_Library_Settings/enable-qm")
+        .map(String::as_str)
+    );
+  }
+}
@@ -16,31 +16,73 @@
 */
 
 use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
-use std::process::{Child, ChildStdout, Command, Stdio};
+use std::process::{Child, ChildStdout, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
-use crate::config;
-use crate::protocol::FfmpegStatus;
+use crate::config::Config;
+use crate::protocol::{FfmpegLibraryVersion, FfmpegStatus, FfmpegVersion, ToolCandidate, TrimMode, VersionNumber};
+use crate::tools::{self, Tool};
 
-const TOOL_STEM: &str = "ffmpeg";
+/// Parsed builds per binary with the modification time they were read at.
+type BuildMap = HashMap<PathBuf, (Option<SystemTime>, FfmpegVersion)>;
 
-fn binary_path(dir: &Path) -> Option<PathBuf> {
-  // macOS/Linux carry the bare `ffmpeg` name; Windows uses the `.exe` suffix.
-  // Unlike the GUI tools there is no running-process or app-bundle detection,
-  // because FFmpeg is a command-line binary rather than a launchable app.
-  let direct = dir.join(TOOL_STEM);
-  if direct.exists() && direct.is_file() {
-    return Some(direct);
+/// An entry is refreshed when the binary's modification time changes, e.g.
+/// after an upgrade in place.
+static BUILDS: Lazy<Mutex<BuildMap>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Releases and the libavutil version each shipped with, newest first. A git
+/// snapshot is placed at the newest release its libavutil has reached.
+const LIBAVUTIL_RELEASES: &[((u32, u32), VersionNumber)] = &[
+  ((60, 8), VersionNumber::new(8, 0, 0)),
+  ((59, 39), VersionNumber::new(7, 1, 0)),
+  ((59, 8), VersionNumber::new(7, 0, 0)),
+  ((58, 29), VersionNumber::new(6, 1, 0)),
+  ((58, 2), VersionNumber::new(6, 0, 0)),
+  ((57, 28), VersionNumber::new(5, 1, 0)),
+  ((57, 17), VersionNumber::new(5, 0, 0)),
+  ((56, 70), VersionNumber::new(4, 4, 0)),
+  ((56, 51), VersionNumber::new(4, 3, 0)),
+  ((56, 31), VersionNumber::new(4, 2, 0)),
+  ((56, 22), VersionNumber::new(4, 1, 0)),
+  ((56, 14), VersionNumber::new(4, 0, 0)),
+];
+
+/// Options older releases reject as unrecognized, with the release that
+/// introduced them.
+const OPTION_MIN_VERSIONS: &[(&str, VersionNumber)] = &[("-fps_mode", VersionNumber::new(5, 1, 0))];
+
+/// FFmpeg, found in the configured directory or on `PATH`.
+pub struct Ffmpeg;
+
+impl Tool for Ffmpeg {
+  fn name(&self) -> &'static str {
+    "FFmpeg"
   }
-  #[cfg(target_os = "windows")]
-  {
-    let exe = dir.join(format!("{}.exe", TOOL_STEM));
-    if exe.exists() && exe.is_file() {
-      return Some(exe);
-    }
+
+  fn binaries(&self) -> &'static [&'static str] {
+    &["ffmpeg"]
+  }
+
+  fn config_path(&self, config: &Config) -> String {
+    config.ffmpeg.path.clone()
+  }
+
+  fn set_config_path(&self, config: &mut Config, path: String) {
+    config.ffmpeg.path = path;
+  }
+
+  fn search_path(&self) -> bool {
+    true
+  }
+
+  fn version_args(&self) -> Option<&'static [&'static str]> {
+    Some(&["-version"])
   }
-  None
 }
 
 /// Grab a single frame at `position_seconds` as PNG bytes, downscaled to at
@@ -96,6 +138,36 @@ pub fn capture_frame(file: String, position_seconds: f64, max_width: u32) -> Res
   Ok(buf)
 }
 
+/// Fail early, with an error the frontend can explain, when `args` use an
+/// option newer than the ffmpeg at `exe` or a filter its build leaves out
+/// (e.g. `mpdecimate` needs `--enable-gpl`). Unknown builds are let through.
+fn check_ffmpeg_args(exe: &Path, args: &[String]) -> Result<()> {
+  let Some(build) = ffmpeg_build(exe) else {
+    return Ok(());
+  };
+  for (option, required) in OPTION_MIN_VERSIONS {
+    if args.iter().any(|arg| arg == option) {
+      tools::require_version("FFMPEG_TOO_OLD", option, build.number, *required)?;
+    }
+  }
+  if build.filters.is_empty() {
+    return Ok(());
+  }
+  let graphs = args.windows(2).filter_map(|pair| match pair[0].as_str() {
+    "-vf" | "-af" | "-filter:v" | "-filter:a" | "-filter_complex" | "-lavfi" => Some(pair[1].as_str()),
+    _ => None,
+  });
+  for graph in graphs {
+    if let Some(name) = filter_names(graph)
+      .into_iter()
+      .find(|name| !build.filters.contains(name))
+    {
+      return Err(anyhow::anyhow!("FFMPEG_FILTER_MISSING:{}", name));
+    }
+  }
+  Ok(())
+}
+
 /// Squared Euclidean distance between a pixel's RGB and a reference color, with
 /// each channel normalized to `0..=1` (so the result is directly comparable to a
 /// `(percent / 100)^2` fuzz threshold).
@@ -231,35 +303,111 @@ pub fn downscale_for_preview(bytes: &[u8], ext: &str, max_width: u32) -> Vec<u8>
 }
 
 /// Resolve the configured FFmpeg binary (the directory in config + `ffmpeg[.exe]`).
-fn ffmpeg_binary() -> Result<PathBuf> {
-  let cfg = config::get_config();
-  let (resolved, found) = resolve(&cfg.ffmpeg.path);
-  if !found {
-    return Err(anyhow::anyhow!("FFMPEG_NOT_AVAILABLE:{}", resolved.display()));
-  }
-  binary_path(&resolved).ok_or_else(|| anyhow::anyhow!("FFMPEG_NOT_AVAILABLE:{}", resolved.display()))
+pub fn ffmpeg_binary() -> Result<PathBuf> {
+  tools::locate(&Ffmpeg, "ffmpeg").map_err(|dir| anyhow::anyhow!("FFMPEG_NOT_AVAILABLE:{}", dir.display()))
 }
 
-pub async fn get_ffmpeg_status(path: String) -> Result<FfmpegStatus> {
-  let trimmed = path.trim();
-  if trimmed.is_empty() {
-    return Ok(FfmpegStatus {
-      found: false,
-      path: String::new(),
-    });
+/// The parsed `-version` and `-filters` output of the ffmpeg at `exe`, cached
+/// per binary.
+fn ffmpeg_build(exe: &Path) -> Option<FfmpegVersion> {
+  ffmpeg_build_with(exe, None)
+}
+
+/// `ffmpeg_build`, reusing `version_output` when the caller already ran
+/// `-version`, so a cache miss only starts `-filters`.
+fn ffmpeg_build_with(exe: &Path, version_output: Option<String>) -> Option<FfmpegVersion> {
+  let modified = std::fs::metadata(exe).and_then(|metadata| metadata.modified()).ok();
+  if let Some((cached_modified, build)) = BUILDS.lock().unwrap().get(exe)
+    && *cached_modified == modified
+  {
+    return Some(build.clone());
   }
-  let (resolved, found) = resolve(trimmed);
-  if found {
-    persist_path(&resolved)?;
+  let run = |args: &[&str]| -> Option<String> {
+    let output = tools::command(exe).args(args).stdin(Stdio::null()).output().ok()?;
+    output
+      .status
+      .success()
+      .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+  };
+  let version_output = match version_output {
+    Some(output) => output,
+    None => run(&["-version"])?,
+  };
+  let mut build = parse_ffmpeg_version(&version_output);
+  build.filters = run(&["-hide_banner", "-filters"])
+    .map(|output| parse_ffmpeg_filters(&output))
+    .unwrap_or_default();
+  BUILDS
+    .lock()
+    .unwrap()
+    .insert(exe.to_path_buf(), (modified, build.clone()));
+  Some(build)
+}
+
+/// Filter names in a filtergraph such as `[0:v]select='gt(scene,0.3)',scale=320:-1[out]`.
+/// Commas and semicolons inside quotes or after a backslash belong to the
+/// filter's arguments.
+fn filter_names(graph: &str) -> Vec<String> {
+  let mut segments = Vec::new();
+  let mut current = String::new();
+  let mut quoted = false;
+  let mut chars = graph.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '\\' => {
+        current.push(c);
+        if let Some(next) = chars.next() {
+          current.push(next);
+        }
+      }
+      '\'' => {
+        quoted = !quoted;
+        current.push(c);
+      }
+      ',' | ';' if !quoted => segments.push(std::mem::take(&mut current)),
+      _ => current.push(c),
+    }
   }
+  segments.push(current);
+  segments
+    .iter()
+    .filter_map(|segment| {
+      let mut rest = segment.trim();
+      while let Some(stripped) = rest.strip_prefix('[') {
+        rest = stripped.split_once(']').map_or("", |(_, after)| after).trim_start();
+      }
+      let end = rest.find(['=', '@', '[']).unwrap_or(rest.len());
+      let name = rest[..end].trim();
+      (!name.is_empty()).then(|| name.to_owned())
+    })
+    .collect()
+}
+
+pub async fn get_ffmpeg_candidates() -> Result<Vec<ToolCandidate>> {
+  Ok(tools::discover(&Ffmpeg))
+}
+
+pub async fn get_ffmpeg_status(path: String) -> Result<FfmpegStatus> {
+  let status = tools::status(&Ffmpeg, &path, false)?;
+  let version = if status.found {
+    tools::binary_path(Path::new(&status.path), "ffmpeg").and_then(|exe| ffmpeg_build_with(&exe, status.version_output))
+  } else {
+    None
+  };
   Ok(FfmpegStatus {
-    found,
-    path: resolved.to_string_lossy().to_string(),
+    found: status.found,
+    path: status.path,
+    version,
   })
 }
 
-fn has_executable(path: &Path) -> bool {
-  binary_path(path).is_some()
+/// Whether the configured ffmpeg lists `name` in `ffmpeg -filters`. False
+/// when ffmpeg is missing or its filters can't be read.
+pub fn has_filter(name: &str) -> bool {
+  ffmpeg_binary()
+    .ok()
+    .and_then(|exe| ffmpeg_build(&exe))
+    .is_some_and(|build| build.filters.iter().any(|filter| filter == name))
 }
 
 /// Collect every file a capture run produced, identified purely by the ffmpeg
@@ -333,6 +481,82 @@ pub fn newest_image_for_pattern(pattern: &Path) -> Option<PathBuf> {
   }
 }
 
+/// Filter names from `ffmpeg -filters`, skipping the legend. Each entry reads
+/// like ` TSC acompressor       A->A       Audio compressor.`.
+pub(crate) fn parse_ffmpeg_filters(output: &str) -> Vec<String> {
+  output
+    .lines()
+    .filter_map(|line| {
+      let mut tokens = line.split_whitespace();
+      let flags = tokens.next()?;
+      let name = tokens.next()?;
+      let io = tokens.next()?;
+      let is_flags = flags.len() == 3 && flags.chars().all(|c| matches!(c, 'T' | 'S' | 'C' | '.'));
+      (is_flags && io.contains("->")).then(|| name.to_owned())
+    })
+    .collect()
+}
+
+/// Parse `ffmpeg -version`: the release from the first line, the configure
+/// arguments and the library versions. Git snapshots (`N-118000-g...`,
+/// date-stamped builds) get their release from libavutil's version.
+pub(crate) fn parse_ffmpeg_version(output: &str) -> FfmpegVersion {
+  let text = output
+    .lines()
+    .map(str::trim)
+    .find(|line| !line.is_empty())
+    .unwrap_or_default()
+    .to_owned();
+  let release = text
+    .split_whitespace()
+    .skip_while(|token| *token != "version")
+    .nth(1)
+    .and_then(tools::parse_version_number);
+  let mut configuration = Vec::new();
+  let mut libraries = Vec::new();
+  for line in output.lines().map(str::trim) {
+    if let Some(rest) = line.strip_prefix("configuration:") {
+      configuration = rest.split_whitespace().map(str::to_owned).collect();
+    } else if line.starts_with("lib") {
+      let Some((name, rest)) = line.split_once(char::is_whitespace) else {
+        continue;
+      };
+      let version: String = rest
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+      if version.contains('.') {
+        libraries.push(FfmpegLibraryVersion {
+          name: name.to_owned(),
+          version,
+        });
+      }
+    }
+  }
+  let enabled = configuration
+    .iter()
+    .filter_map(|arg| arg.strip_prefix("--enable-"))
+    .map(str::to_owned)
+    .collect();
+  let number = release.or_else(|| {
+    let libavutil = libraries.iter().find(|library| library.name == "libavutil")?;
+    let mut parts = libavutil.version.split('.').map(|part| part.parse::<u32>().ok());
+    release_for_libavutil(parts.next()??, parts.next()??)
+  });
+  FfmpegVersion {
+    text,
+    number,
+    snapshot: release.is_none(),
+    configuration,
+    enabled,
+    libraries,
+    filters: Vec::new(),
+  }
+}
+
 /// Parse a `#RRGGBB` (or `#RGB`) hex color into an `(r, g, b)` triple.
 pub fn parse_hex_color(value: &str) -> Option<(u8, u8, u8)> {
   let hex = value.trim().trim_start_matches('#');
@@ -354,17 +578,6 @@ pub fn parse_hex_color(value: &str) -> Option<(u8, u8, u8)> {
   }
 }
 
-fn persist_path(path: &Path) -> Result<()> {
-  let new_path = path.to_string_lossy().to_string();
-  let mut cfg = config::get_config();
-  if cfg.ffmpeg.path == new_path {
-    return Ok(());
-  }
-  cfg.ffmpeg.path = new_path;
-  config::set_config(cfg)?;
-  Ok(())
-}
-
 /// Read ffmpeg's `-progress pipe:1` stream, invoking `on_time` with the output
 /// position (in seconds) every time a progress block reports `out_time_us`.
 pub fn read_capture_progress<F: FnMut(f64)>(stdout: ChildStdout, mut on_time: F) {
@@ -384,15 +597,6 @@ pub fn read_capture_progress<F: FnMut(f64)>(stdout: ChildStdout, mut on_time: F)
   }
 }
 
-fn resolve(path: &str) -> (PathBuf, bool) {
-  let trimmed = path.trim();
-  let configured = PathBuf::from(trimmed);
-  if has_executable(&configured) {
-    return (configured, true);
-  }
-  (configured, false)
-}
-
 /// Outcome of trimming a single captured image.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrimResult {
@@ -404,22 +608,135 @@ pub enum TrimResult {
   Removed,
 }
 
+fn release_for_libavutil(major: u32, minor: u32) -> Option<VersionNumber> {
+  LIBAVUTIL_RELEASES
+    .iter()
+    .find(|(libavutil, _)| *libavutil <= (major, minor))
+    .map(|(_, release)| *release)
+}
+
+/// Run an analysis pass of ffmpeg (filters that log what they find, output
+/// discarded) and return its stderr, where the filters report. Progress is read
+/// from `-progress pipe:1` like a capture and reported to `on_percent` against
+/// `duration_seconds`. The child is registered in `children` under `key` while it
+/// runs; killing it from there (see `controller::cancel_child`) makes this return
+/// `FFMPEG_ANALYSIS_CANCELLED`.
+pub fn run_analysis(
+  args: &[String],
+  duration_seconds: f64,
+  key: &str,
+  children: &Arc<Mutex<HashMap<String, Child>>>,
+  mut on_percent: impl FnMut(u32),
+) -> Result<String> {
+  let mut full_args = vec!["-hide_banner".to_string(), "-nostats".to_string()];
+  full_args.extend_from_slice(args);
+  full_args.push("-progress".to_string());
+  full_args.push("pipe:1".to_string());
+  let mut child = spawn_ffmpeg(&full_args)?;
+  let stdout = child
+    .stdout
+    .take()
+    .ok_or_else(|| anyhow::anyhow!("Failed to capture stdout"))?;
+  // Drain stderr on a side thread so a chatty filter can't deadlock on a full pipe.
+  let stderr_handle = child.stderr.take().map(|mut stderr| {
+    std::thread::spawn(move || {
+      let mut buf = String::new();
+      let _ = stderr.read_to_string(&mut buf);
+      buf
+    })
+  });
+  children.lock().unwrap().insert(key.to_owned(), child);
+  on_percent(0);
+  read_capture_progress(stdout, |out_time| {
+    if duration_seconds > 0.0 {
+      on_percent(((out_time / duration_seconds) * 100.0).clamp(0.0, 100.0) as u32);
+    }
+  });
+  let child = children.lock().unwrap().remove(key);
+  let stderr = stderr_handle.and_then(|handle| handle.join().ok()).unwrap_or_default();
+  let Some(mut child) = child else {
+    return Err(anyhow::anyhow!("FFMPEG_ANALYSIS_CANCELLED"));
+  };
+  let status = child.wait()?;
+  if !status.success() {
+    let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+    let tail: Vec<&str> = tail.into_iter().rev().collect();
+    return Err(anyhow::anyhow!("FFMPEG_ANALYSIS_FAILED:{}", tail.join("\n").trim()));
+  }
+  on_percent(100);
+  Ok(stderr)
+}
+
+/// Run ffmpeg with `args`, which must write raw `f32le` samples to `pipe:1`,
+/// and hand the samples to `on_samples` in chunks as they arrive. The child is
+/// registered in `children` under `key` like `run_analysis`, and cancelling it
+/// returns `FFMPEG_ANALYSIS_CANCELLED`.
+pub fn run_pcm_decode(
+  args: &[String],
+  key: &str,
+  children: &Arc<Mutex<HashMap<String, Child>>>,
+  mut on_samples: impl FnMut(&[f32]),
+) -> Result<()> {
+  let mut full_args = vec!["-hide_banner".to_string(), "-nostats".to_string()];
+  full_args.extend_from_slice(args);
+  let mut child = spawn_ffmpeg(&full_args)?;
+  let mut stdout = child
+    .stdout
+    .take()
+    .ok_or_else(|| anyhow::anyhow!("Failed to capture stdout"))?;
+  let stderr_handle = child.stderr.take().map(|mut stderr| {
+    std::thread::spawn(move || {
+      let mut buf = String::new();
+      let _ = stderr.read_to_string(&mut buf);
+      buf
+    })
+  });
+  children.lock().unwrap().insert(key.to_owned(), child);
+  let mut buf = vec![0u8; 1 << 16];
+  let mut pending = 0;
+  let mut samples = Vec::with_capacity(buf.len() / 4);
+  loop {
+    let read = match stdout.read(&mut buf[pending..]) {
+      Ok(0) | Err(_) => break,
+      Ok(read) => read,
+    };
+    let available = pending + read;
+    let whole = available / 4 * 4;
+    samples.clear();
+    samples.extend(
+      buf[..whole]
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+    );
+    on_samples(&samples);
+    // Keep a sample split across two reads for the next one.
+    buf.copy_within(whole..available, 0);
+    pending = available - whole;
+  }
+  let child = children.lock().unwrap().remove(key);
+  let stderr = stderr_handle.and_then(|handle| handle.join().ok()).unwrap_or_default();
+  let Some(mut child) = child else {
+    return Err(anyhow::anyhow!("FFMPEG_ANALYSIS_CANCELLED"));
+  };
+  let status = child.wait()?;
+  if !status.success() {
+    let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+    let tail: Vec<&str> = tail.into_iter().rev().collect();
+    return Err(anyhow::anyhow!("FFMPEG_ANALYSIS_FAILED:{}", tail.join("\n").trim()));
+  }
+  Ok(())
+}
+
 /// Spawn ffmpeg with the given arguments, piping stdout/stderr. Mirrors the
 /// process-spawning convention used for mkvextract (hidden window on Windows).
 pub fn spawn_ffmpeg(args: &[String]) -> Result<Child> {
   let exe = ffmpeg_binary()?;
-  let mut cmd = Command::new(&exe);
-  cmd
+  check_ffmpeg_args(&exe, args)?;
+  tools::command(&exe)
     .args(args)
     .stdin(Stdio::null())
     .stdout(Stdio::piped())
-    .stderr(Stdio::piped());
-  #[cfg(target_os = "windows")]
-  {
-    use std::os::windows::process::CommandExt;
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-  }
-  cmd
+    .stderr(Stdio::piped())
     .spawn()
     .map_err(|e| anyhow::anyhow!("FFMPEG_NOT_AVAILABLE:{}: {}", exe.display(), e))
 }
@@ -432,6 +749,51 @@ pub struct TrimStats {
   pub unchanged: usize,
   pub removed: usize,
   pub failed: usize,
+  /// The crop applied to every image by a uniform pass; `None` when each image
+  /// was cropped to its own content, or a uniform pass found no content at all.
+  pub rect: Option<TrimRect>,
+}
+
+/// A crop rectangle in pixels, shared by every image of a uniform trim pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrimRect {
+  pub x: u32,
+  pub y: u32,
+  pub width: u32,
+  pub height: u32,
+}
+
+impl TrimRect {
+  /// Combine the per-image content boxes `(x, y, width, height)` into one crop:
+  /// their union for [`TrimMode::Union`], so no frame loses content, or the
+  /// median of each edge for [`TrimMode::Median`], so a few dark or busy frames
+  /// can't drag the crop in or out. `None` when there are no boxes.
+  fn combine(boxes: &[(u32, u32, u32, u32)], mode: TrimMode) -> Option<Self> {
+    if boxes.is_empty() {
+      return None;
+    }
+    let edge = |f: fn(&(u32, u32, u32, u32)) -> u32, pick_max: bool| -> u32 {
+      let mut values: Vec<u32> = boxes.iter().map(f).collect();
+      values.sort_unstable();
+      match mode {
+        TrimMode::Median => values[values.len() / 2],
+        _ if pick_max => values[values.len() - 1],
+        _ => values[0],
+      }
+    };
+    let left = edge(|b| b.0, false);
+    let top = edge(|b| b.1, false);
+    let right = edge(|b| b.0 + b.2, true);
+    let bottom = edge(|b| b.1 + b.3, true);
+    // Every box has right > left, so the k-th smallest right edge is past the
+    // k-th smallest left edge and the median box is never empty.
+    Some(Self {
+      x: left,
+      y: top,
+      width: right - left,
+      height: bottom - top,
+    })
+  }
 }
 
 /// Split an ffmpeg image2 output filename pattern around its frame-number token,
@@ -463,19 +825,44 @@ fn split_pattern(file_pat: &str) -> Option<(String, String)> {
 /// content (see [`content_bounds`]). When the image has no content at all it is
 /// a blank capture and the file is deleted instead.
 pub fn trim_image_file(path: &Path, color: (u8, u8, u8), tolerance_percent: f64) -> Result<TrimResult> {
-  let image = image::open(path)?;
-  let rgba = image.to_rgba8();
-  let (width, height) = rgba.dimensions();
-  let Some((x, y, crop_w, crop_h)) = content_bounds(&rgba, color, tolerance_percent) else {
+  let rgba = image::open(path)?.to_rgba8();
+  let Some(bounds) = content_bounds(&rgba, color, tolerance_percent) else {
     // Entirely background — a blank frame. Remove it rather than keep it.
     std::fs::remove_file(path)?;
     return Ok(TrimResult::Removed);
   };
+  write_cropped(path, &rgba, bounds)
+}
+
+/// Crop an image in place to `rect`, clipped to the image. Used by the uniform
+/// trim modes, where every frame gets the same crop whatever its own content.
+fn crop_image_file(path: &Path, rect: TrimRect) -> Result<TrimResult> {
+  let rgba = image::open(path)?.to_rgba8();
+  let (width, height) = rgba.dimensions();
+  let x = rect.x.min(width);
+  let y = rect.y.min(height);
+  let crop_w = rect.width.min(width - x);
+  let crop_h = rect.height.min(height - y);
+  // A frame of a different size can fall outside the shared crop entirely.
+  if crop_w == 0 || crop_h == 0 {
+    return Ok(TrimResult::Unchanged);
+  }
+  write_cropped(path, &rgba, (x, y, crop_w, crop_h))
+}
+
+/// Rewrite `path` with the `(x, y, width, height)` region of its decoded
+/// `rgba`, or leave it alone when the region is the whole image.
+fn write_cropped(
+  path: &Path,
+  rgba: &image::RgbaImage,
+  (x, y, crop_w, crop_h): (u32, u32, u32, u32),
+) -> Result<TrimResult> {
+  let (width, height) = rgba.dimensions();
   // Already tight against the content; nothing to crop.
   if x == 0 && y == 0 && crop_w == width && crop_h == height {
     return Ok(TrimResult::Unchanged);
   }
-  let cropped = image::imageops::crop_imm(&rgba, x, y, crop_w, crop_h).to_image();
+  let cropped = image::imageops::crop_imm(rgba, x, y, crop_w, crop_h).to_image();
   // Re-encode in the source format. JPEG is re-saved at high quality to limit the
   // generational loss inherent in decoding and re-encoding.
   let ext = path
@@ -512,11 +899,19 @@ pub fn trim_image_file(path: &Path, color: (u8, u8, u8), tolerance_percent: f64)
 /// can't abort the whole batch. Re-encoding the trimmed frames is CPU-bound, so a
 /// large capture would otherwise stall for a long time on a single thread.
 ///
+/// With [`TrimMode::Each`] every image is cropped to its own content box. The
+/// uniform modes first measure the content box of every image, combine them
+/// into one [`TrimRect`] (reported in the stats) and then apply that same crop
+/// to every image, so a series comes out at one size; blank frames are still
+/// removed.
+///
 /// `on_progress(completed, total, last_path, result)` is invoked once per finished
 /// image on the *calling* thread (the workers funnel their results through a
 /// channel), so the caller can drive a progress bar and preview without any
-/// cross-thread synchronization of its own. `result` is `None` when that image
-/// failed or panicked. Returns the aggregate [`TrimStats`].
+/// cross-thread synchronization of its own. A uniform pass counts every image
+/// twice, once measured and once cropped, so `total` is double the image count
+/// there. `result` is `None` when that image failed or panicked, or has only
+/// been measured so far. Returns the aggregate [`TrimStats`].
 ///
 /// `cancel` is polled by each worker before it picks up the next image; once set,
 /// the workers stop pulling new work and the pass winds down early (images
@@ -525,6 +920,7 @@ pub fn trim_images<F>(
   paths: Vec<PathBuf>,
   color: (u8, u8, u8),
   tolerance_percent: f64,
+  mode: TrimMode,
   threads: usize,
   cancel: std::sync::Arc<std::sync::atomic::AtomicBool>,
   mut on_progress: F,
@@ -532,71 +928,144 @@ pub fn trim_images<F>(
 where
   F: FnMut(usize, usize, &Path, Option<TrimResult>),
 {
-  use std::sync::atomic::Ordering;
-  let total = paths.len();
+  let count = paths.len();
   let mut stats = TrimStats::default();
-  if total == 0 {
+  if count == 0 {
     return stats;
   }
   // The frontend caps the request at the core count; clamp defensively and never
   // spawn more workers than there are images.
-  let workers = threads.clamp(1, 256).min(total);
-  let queue = std::sync::Arc::new(std::sync::Mutex::new(paths));
-  let (tx, rx) = std::sync::mpsc::channel::<(PathBuf, Option<TrimResult>)>();
-  let mut handles = Vec::with_capacity(workers);
-  for _ in 0..workers {
-    let queue = queue.clone();
-    let tx = tx.clone();
-    let cancel = cancel.clone();
-    handles.push(std::thread::spawn(move || loop {
-      if cancel.load(Ordering::SeqCst) {
-        break;
-      }
-      let path = {
-        let mut guard = queue.lock().unwrap();
-        guard.pop()
-      };
-      let Some(path) = path else {
-        break;
-      };
-      let outcome =
-        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| trim_image_file(&path, color, tolerance_percent)));
-      let result = match outcome {
-        Ok(Ok(r)) => Some(r),
-        Ok(Err(e)) => {
-          log::warn!("Failed to trim {}: {}", path.display(), e);
-          None
+  let workers = threads.clamp(1, 256).min(count);
+  let mut completed = 0usize;
+  let tally = |stats: &mut TrimStats, result: Option<TrimResult>| match result {
+    Some(TrimResult::Trimmed) => stats.trimmed += 1,
+    Some(TrimResult::Unchanged) => stats.unchanged += 1,
+    Some(TrimResult::Removed) => stats.removed += 1,
+    None => stats.failed += 1,
+  };
+  if mode == TrimMode::Each {
+    for_each_image(
+      paths,
+      workers,
+      &cancel,
+      |path| trim_image_file(path, color, tolerance_percent),
+      |path, result| {
+        completed += 1;
+        tally(&mut stats, result);
+        on_progress(completed, count, &path, result);
+      },
+    );
+    return stats;
+  }
+
+  let total = count * 2;
+  let mut bounds: HashMap<PathBuf, Option<(u32, u32, u32, u32)>> = HashMap::with_capacity(count);
+  for_each_image(
+    paths,
+    workers,
+    &cancel,
+    |path| Ok(content_bounds(&image::open(path)?.to_rgba8(), color, tolerance_percent)),
+    |path, measured| {
+      completed += 1;
+      on_progress(completed, total, &path, None);
+      match measured {
+        Some(b) => {
+          bounds.insert(path, b);
         }
-        Err(_) => {
-          log::warn!("Trimming {} panicked; skipping", path.display());
-          None
+        None => {
+          stats.failed += 1;
         }
-      };
-      // A send error means the receiver hung up (caller dropped); stop working.
-      if tx.send((path, result)).is_err() {
-        break;
       }
-    }));
-  }
-  // Drop the original sender so the channel closes once every worker is done.
-  drop(tx);
-  let mut completed = 0usize;
-  while let Ok((path, result)) = rx.recv() {
-    completed += 1;
-    match result {
-      Some(TrimResult::Trimmed) => stats.trimmed += 1,
-      Some(TrimResult::Unchanged) => stats.unchanged += 1,
-      Some(TrimResult::Removed) => stats.removed += 1,
-      None => stats.failed += 1,
-    }
-    on_progress(completed, total, &path, result);
-  }
-  for handle in handles {
-    let _ = handle.join();
+    },
+  );
+  if cancel.load(std::sync::atomic::Ordering::SeqCst) {
+    return stats;
   }
+  let boxes: Vec<(u32, u32, u32, u32)> = bounds.values().flatten().copied().collect();
+  stats.rect = TrimRect::combine(&boxes, mode);
+  // Images that failed to measure are skipped below; count them as done.
+  completed += stats.failed;
+  let rect = stats.rect;
+  let paths: Vec<PathBuf> = bounds.keys().cloned().collect();
+  for_each_image(
+    paths,
+    workers,
+    &cancel,
+    |path| match (bounds.get(path).copied().flatten(), rect) {
+      (Some(_), Some(rect)) => crop_image_file(path, rect),
+      _ => {
+        // Entirely background — a blank frame, removed as in the per-image mode.
+        std::fs::remove_file(path)?;
+        Ok(TrimResult::Removed)
+      }
+    },
+    |path, result| {
+      completed += 1;
+      tally(&mut stats, result);
+      on_progress(completed, total, &path, result);
+    },
+  );
   stats
 }
 
+/// Run `work` on every path across `workers` scoped threads, containing any
+/// error or panic (logged, and reported as `None`), and hand each outcome to
+/// `on_done` on the calling thread. Workers stop picking up paths once `cancel`
+/// is set.
+fn for_each_image<T, W, D>(
+  paths: Vec<PathBuf>,
+  workers: usize,
+  cancel: &std::sync::atomic::AtomicBool,
+  work: W,
+  mut on_done: D,
+) where
+  T: Send,
+  W: Fn(&Path) -> Result<T> + Sync,
+  D: FnMut(PathBuf, Option<T>),
+{
+  use std::sync::atomic::Ordering;
+  let queue = Mutex::new(paths);
+  let (tx, rx) = std::sync::mpsc::channel::<(PathBuf, Option<T>)>();
+  std::thread::scope(|scope| {
+    for _ in 0..workers {
+      let (queue, work, tx) = (&queue, &work, tx.clone());
+      scope.spawn(move || loop {
+        if cancel.load(Ordering::SeqCst) {
+          break;
+        }
+        let path = {
+          let mut guard = queue.lock().unwrap();
+          guard.pop()
+        };
+        let Some(path) = path else {
+          break;
+        };
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| work(&path)));
+        let result = match outcome {
+          Ok(Ok(r)) => Some(r),
+          Ok(Err(e)) => {
+            log::warn!("Failed to trim {}: {}", path.display(), e);
+            None
+          }
+          Err(_) => {
+            log::warn!("Trimming {} panicked; skipping", path.display());
+            None
+          }
+        };
+        // A send error means the receiver hung up (caller dropped); stop working.
+        if tx.send((path, result)).is_err() {
+          break;
+        }
+      });
+    }
+    // Drop the original sender so the channel closes once every worker is done.
+    drop(tx);
+    while let Ok((path, result)) = rx.recv() {
+      on_done(path, result);
+    }
+  });
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -834,7 +1303,7 @@ mod tests {
       .unwrap();
     let cancel = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
     let mut progressed = 0usize;
-    let stats = trim_images(vec![crop.clone()], (0, 0, 0), 10.0, 2, cancel, |_, _, _, _| progressed += 1);
+    let stats = trim_images(vec![crop.clone()], (0, 0, 0), 10.0, TrimMode::Each, 2, cancel, |_, _, _, _| progressed += 1);
     assert_eq!(progressed, 0);
     assert_eq!(stats, TrimStats::default());
     // Untouched: still the original 20x16 dimensions.
@@ -860,6 +1329,7 @@ mod tests {
       vec![crop.clone(), blank.clone()],
       (0, 0, 0),
       10.0,
+      TrimMode::Each,
       2,
       cancel,
       |completed, total, _, result| progressed.push((completed, total, result)),
@@ -875,4 +1345,101 @@ mod tests {
     assert_eq!(stats.removed, 1);
     let _ = std::fs::remove_file(&crop);
   }
+
+  #[test]
+  fn trim_images_uniform_applies_one_crop() {
+    // Two letterboxed frames with different content boxes plus a blank frame:
+    // the union mode crops both to the box that covers both, and reports it.
+    let wide = temp_path("uniform_wide.png");
+    let small = temp_path("uniform_small.png");
+    let blank = temp_path("uniform_blank.png");
+    bordered_rgba(20, 16, (4, 3, 12, 10))
+      .save_with_format(&wide, image::ImageFormat::Png)
+      .unwrap();
+    bordered_rgba(20, 16, (6, 5, 6, 4))
+      .save_with_format(&small, image::ImageFormat::Png)
+      .unwrap();
+    image::RgbaImage::from_pixel(20, 16, image::Rgba([0, 0, 0, 255]))
+      .save_with_format(&blank, image::ImageFormat::Png)
+      .unwrap();
+    let cancel = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let mut last = (0, 0);
+    let stats = trim_images(
+      vec![wide.clone(), small.clone(), blank.clone()],
+      (0, 0, 0),
+      10.0,
+      TrimMode::Union,
+      2,
+      cancel,
+      |completed, total, _, _| last = (completed, total),
+    );
+    let rect = TrimRect {
+      x: 4,
+      y: 3,
+      width: 12,
+      height: 10,
+    };
+    assert_eq!(stats.rect, Some(rect));
+    assert_eq!(image::open(&wide).unwrap().to_rgba8().dimensions(), (12, 10));
+    assert_eq!(image::open(&small).unwrap().to_rgba8().dimensions(), (12, 10));
+    assert!(!blank.exists(), "blank frame should have been removed");
+    assert_eq!((stats.trimmed, stats.removed), (2, 1));
+    assert_eq!(last, (6, 6));
+    let _ = std::fs::remove_file(&wide);
+    let _ = std::fs::remove_file(&small);
+  }
+
+  #[test]
+  fn trim_rect_median_ignores_outliers() {
+    // The dark frame's small box is outvoted by the two full ones.
+    let boxes = [(4, 3, 12, 10), (4, 3, 12, 10), (8, 6, 2, 2)];
+    let rect = TrimRect::combine(&boxes, TrimMode::Median).unwrap();
+    assert_eq!((rect.x, rect.y, rect.width, rect.height), (4, 3, 12, 10));
+    let rect = TrimRect::combine(&boxes, TrimMode::Union).unwrap();
+    assert_eq!((rect.x, rect.y, rect.width, rect.height), (4, 3, 12, 10));
+    assert_eq!(TrimRect::combine(&[], TrimMode::Median), None);
+  }
+
+  #[test]
+  fn filter_names_ignore_quoted_and_escaped_separators() {
+    assert_eq!(
+      filter_names("[0:v]select='gt(scene,0.3)',scale=320:-1[out];[out]tile=4x4"),
+      vec!["select", "scale", "tile"]
+    );
+    assert_eq!(filter_names("select=eq(pict_type\\,I),mpdecimate"), vec!["select", "mpdecimate"]);
+  }
+
+  #[test]
+  fn parse_ffmpeg_version_reads_release_configuration_and_libraries() {
+    let output = "ffmpeg version 7.1.1 Copyright (c) 2000-2025 the FFmpeg developers
+built with gcc 14 (GCC)
+configuration: --prefix=/usr --enable-gpl --enable-libx264 --disable-debug
+libavutil      59. 39.100 / 59. 39.100
+libavcodec     61. 19.101 / 61. 19.101
+";
+    let version = parse_ffmpeg_version(output);
+    assert_eq!(version.number, Some(VersionNumber::new(7, 1, 1)));
+    assert!(!version.snapshot);
+    assert_eq!(version.enabled, vec!["gpl", "libx264"]);
+    assert_eq!(version.libraries.len(), 2);
+    assert_eq!(version.libraries[1].name, "libavcodec");
+    assert_eq!(version.libraries[1].version, "61.19.101");
+
+    let snapshot = parse_ffmpeg_version(
+      "ffmpeg version N-113000-g1234abcd Copyright (c) 2000-2024\nlibavutil      58. 30.100 / 58. 30.100\n",
+    );
+    assert!(snapshot.snapshot);
+    assert_eq!(snapshot.number, Some(VersionNumber::new(6, 1, 0)));
+  }
+
+  #[test]
+  fn parse_ffmpeg_filters_skips_legend() {
+    let output = "Filters:
+  T.. = Timeline support
+  ..C = Command support
+ TSC acompressor       A->A       Audio compressor.
+ ... mpdecimate        V->V       Remove near-duplicate frames.
+";
+    assert_eq!(parse_ffmpeg_filters(output), vec!["acompressor", "mpdecimate"]);
+  }
 }
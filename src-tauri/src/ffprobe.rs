@@ -0,0 +1,934 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use crate::ffmpeg;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{FfprobeFrames, ProbeField, PropertySource, StreamProperty, StreamPropertyMap};
+use crate::tools;
+
+/// ffprobe codec names whose MediaInfo `Format` differs by more than letter case.
+const FORMAT_NAMES: &[(&str, &str)] = &[
+  ("aac", "AAC"),
+  ("ac3", "AC-3"),
+  ("dvd_subtitle", "VobSub"),
+  ("eac3", "E-AC-3"),
+  ("h264", "AVC"),
+  ("hdmv_pgs_subtitle", "PGS"),
+  ("mov_text", "Timed Text"),
+  ("mp2", "MPEG Audio"),
+  ("mp3", "MPEG Audio"),
+  ("mpeg1video", "MPEG Video"),
+  ("mpeg2video", "MPEG Video"),
+  ("mpeg4", "MPEG-4 Visual"),
+  ("pcm_bluray", "PCM"),
+  ("pcm_dvd", "PCM"),
+  ("pcm_s16be", "PCM"),
+  ("pcm_s16le", "PCM"),
+  ("pcm_s24be", "PCM"),
+  ("pcm_s24le", "PCM"),
+  ("subrip", "UTF-8"),
+  ("truehd", "MLP FBA"),
+  ("vc1", "VC-1"),
+];
+
+/// Frame fields read per video frame: its stream, its timestamp (`pts_time`
+/// when the best-effort one is missing) and its interlacing flags.
+const FRAME_ENTRIES: &str = "frame=stream_index,best_effort_timestamp_time,pts_time,interlaced_frame,top_field_first";
+
+/// The fields compared between MediaInfo and ffprobe, named as MediaInfo names them.
+const KEY_FIELDS: &[(MediaInfoStreamKind, &[&str])] = &[
+  (
+    MediaInfoStreamKind::General,
+    &["Duration", "VideoCount", "AudioCount", "TextCount"],
+  ),
+  (
+    MediaInfoStreamKind::Video,
+    &[
+      "Format",
+      "Width",
+      "Height",
+      "FrameRate",
+      "FrameRate_Mode",
+      "FrameCount",
+      "ScanType",
+      "Duration",
+    ],
+  ),
+  (
+    MediaInfoStreamKind::Audio,
+    &["Format", "Channel(s)", "SamplingRate", "Duration"],
+  ),
+  (MediaInfoStreamKind::Text, &["Format"]),
+];
+
+/// Seconds decoded per window with `FfprobeFrames::Sampled`.
+const SAMPLE_SECONDS: u32 = 2;
+/// Windows decoded with `FfprobeFrames::Sampled`, spread evenly over the file.
+const SAMPLE_WINDOWS: u32 = 10;
+
+/// A chapter mark: where it starts, in seconds, and its title if it has one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+  pub start: f64,
+  pub title: String,
+}
+
+/// The side data of one decoded video frame: its ffprobe stream index, its
+/// time in seconds, and each side data entry as its `key=value` fields, keys
+/// flattened the way `-print_format flat` prints them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameSideData {
+  pub index: i64,
+  pub time: Option<f64>,
+  pub side_data: Vec<HashMap<String, String>>,
+}
+
+/// What the decoded frames of one video stream say about it.
+#[derive(Debug, Default)]
+struct FrameStats {
+  frames: u64,
+  interlaced: u64,
+  top_field_first: u64,
+  timestamps: Vec<f64>,
+}
+
+impl FrameStats {
+  /// Overwrite the header-derived fields with what the frames show. The frame
+  /// count is only known when every frame was read.
+  fn apply(&self, frames: FfprobeFrames, property_map: &mut HashMap<String, String>) {
+    if self.frames == 0 {
+      return;
+    }
+    if frames == FfprobeFrames::All {
+      property_map.insert("FrameCount".to_owned(), self.frames.to_string());
+    }
+    if let Some(mode) = frame_rate_mode(&self.timestamps) {
+      property_map.insert("FrameRate_Mode".to_owned(), mode.to_owned());
+    }
+    let scan_type = if self.interlaced == 0 {
+      "Progressive"
+    } else if self.interlaced == self.frames {
+      "Interlaced"
+    } else {
+      "Mixed"
+    };
+    property_map.insert("ScanType".to_owned(), scan_type.to_owned());
+    if self.interlaced > 0 {
+      let scan_order = if self.top_field_first * 2 >= self.interlaced {
+        "TFF"
+      } else {
+        "BFF"
+      };
+      property_map.insert("ScanOrder".to_owned(), scan_order.to_owned());
+    }
+  }
+}
+
+/// One decoded video frame, in presentation order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoFrame {
+  /// `I`, `P`, `B`, or `?` when the decoder didn't say.
+  pub pict_type: char,
+  pub key: bool,
+}
+
+/// One packet of any stream: its ffprobe stream index, when it is shown (or
+/// decoded, without a pts) in seconds, and its size in bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamPacket {
+  pub index: i64,
+  pub time: Option<f64>,
+  pub size: u64,
+}
+
+/// One video packet, in decode order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoPacket {
+  pub pts: Option<f64>,
+  pub key: bool,
+}
+
+/// One video stream: its ffprobe stream index, its fields (as `flatten`
+/// names them), and each stream side data entry as its fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoStream {
+  pub index: i64,
+  pub fields: HashMap<String, String>,
+  pub side_data: Vec<HashMap<String, String>>,
+}
+
+/// The chapters of `file` in start order, as ffprobe reads them from the container.
+pub fn chapters(file: &str) -> Result<Vec<Chapter>> {
+  let json = run_json(&ffprobe_binary()?, file, &["-show_chapters"])?;
+  let mut chapters: Vec<Chapter> = json["chapters"]
+    .as_array()
+    .into_iter()
+    .flatten()
+    .filter_map(|chapter| {
+      let start = number(&chapter["start_time"])?;
+      let title = chapter["tags"]["title"].as_str().unwrap_or_default().to_owned();
+      Some(Chapter { start, title })
+    })
+    .collect();
+  chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+  Ok(chapters)
+}
+
+/// One `ProbeField` per key field either tool reports, stream by stream.
+pub fn compare(media_info: &[StreamPropertyMap], ffprobe: &[StreamPropertyMap]) -> Vec<ProbeField> {
+  let find = |maps: &[StreamPropertyMap], kind: MediaInfoStreamKind, num: i32| -> Option<HashMap<String, String>> {
+    maps
+      .iter()
+      .find(|map| map.stream == kind && map.num == num)
+      .map(|map| map.property_map.clone())
+  };
+  let mut fields = Vec::new();
+  for (kind, properties) in KEY_FIELDS {
+    let count = media_info
+      .iter()
+      .chain(ffprobe)
+      .filter(|map| map.stream == *kind)
+      .map(|map| map.num + 1)
+      .max()
+      .unwrap_or(0);
+    for num in 0..count {
+      let left = find(media_info, *kind, num).unwrap_or_default();
+      let right = find(ffprobe, *kind, num).unwrap_or_default();
+      for property in properties.iter() {
+        let media_info_value = left.get(*property).cloned();
+        let ffprobe_value = right.get(*property).cloned();
+        let mismatch = match (&media_info_value, &ffprobe_value) {
+          (Some(a), Some(b)) => !values_match(property, a, b),
+          (None, None) => continue,
+          _ => false,
+        };
+        fields.push(ProbeField {
+          stream: *kind,
+          num,
+          property: property.to_string(),
+          media_info: media_info_value,
+          ffprobe: ffprobe_value,
+          mismatch,
+        });
+      }
+    }
+  }
+  fields
+}
+
+/// ffprobe from the directory of the configured ffmpeg.
+fn ffprobe_binary() -> Result<PathBuf> {
+  let ffmpeg = ffmpeg::ffmpeg_binary()?;
+  let dir = ffmpeg.parent().unwrap_or(Path::new(""));
+  tools::binary_path(dir, "ffprobe").ok_or_else(|| anyhow::anyhow!("FFPROBE_NOT_AVAILABLE:{}", dir.display()))
+}
+
+/// Every scalar of an ffprobe stream or format object, under ffprobe's own
+/// names, with tags as `TAG:<name>` and dispositions as `DISPOSITION:<name>`
+/// like ffprobe's flat output.
+fn flatten(object: &Value) -> HashMap<String, String> {
+  let mut property_map = HashMap::new();
+  for (key, value) in object.as_object().into_iter().flatten() {
+    match (key.as_str(), value) {
+      ("tags", Value::Object(tags)) => {
+        for (tag, value) in tags {
+          if let Some(value) = scalar(value) {
+            property_map.insert(format!("TAG:{}", tag), value);
+          }
+        }
+      }
+      ("disposition", Value::Object(dispositions)) => {
+        for (disposition, value) in dispositions {
+          if let Some(value) = scalar(value) {
+            property_map.insert(format!("DISPOSITION:{}", disposition), value);
+          }
+        }
+      }
+      _ => {
+        if let Some(value) = scalar(value) {
+          property_map.insert(key.to_owned(), value);
+        }
+      }
+    }
+  }
+  property_map
+}
+
+/// `CFR` or `VFR` from presentation timestamps. Deltas within a few
+/// milliseconds or 5% of each other are rounding (e.g. 41/42 ms at 23.976 fps
+/// in Matroska's 1 ms time base); gaps between sampled windows are ignored.
+fn frame_rate_mode(timestamps: &[f64]) -> Option<&'static str> {
+  let mut timestamps = timestamps.to_vec();
+  timestamps.sort_by(f64::total_cmp);
+  let mut deltas: Vec<f64> = timestamps
+    .windows(2)
+    .map(|pair| pair[1] - pair[0])
+    .filter(|delta| *delta > 0.0)
+    .collect();
+  if deltas.len() < 2 {
+    return None;
+  }
+  deltas.sort_by(f64::total_cmp);
+  let median = deltas[deltas.len() / 2];
+  deltas.retain(|delta| *delta <= median * 4.0);
+  let spread = deltas[deltas.len() - 1] - deltas[0];
+  Some(if spread > 0.0025 && spread > deltas[0] * 0.05 {
+    "VFR"
+  } else {
+    "CFR"
+  })
+}
+
+/// Decode the video frames of `file` (sampled over `duration` seconds as
+/// `frames` asks) and hand each one's side data to `on_frame`. Output is read
+/// line by line in the flat format, so reading every frame stays cheap on memory.
+pub fn frame_side_data(
+  file: &str,
+  frames: FfprobeFrames,
+  duration: f64,
+  mut on_frame: impl FnMut(FrameSideData),
+) -> Result<()> {
+  let mut args = vec![
+    "-select_streams".to_string(),
+    "v".to_string(),
+    "-show_entries".to_string(),
+    "frame=stream_index,best_effort_timestamp_time,pts_time:side_data_list".to_string(),
+  ];
+  match frames {
+    FfprobeFrames::None => return Ok(()),
+    FfprobeFrames::Sampled => args.extend(["-read_intervals".to_string(), sample_intervals(duration)]),
+    FfprobeFrames::All => {}
+  }
+  let exe = ffprobe_binary()?;
+  let mut child = tools::command(&exe)
+    .args(["-v", "error", "-print_format", "flat"])
+    .args(&args)
+    .arg(file)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(|e| anyhow::anyhow!("FFPROBE_NOT_AVAILABLE:{}: {}", exe.display(), e))?;
+  let mut current: Option<(String, FrameSideData)> = None;
+  if let Some(stdout) = child.stdout.take() {
+    for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+      let Some((key, value)) = line.strip_prefix("frames.frame.").and_then(|line| line.split_once('=')) else {
+        continue;
+      };
+      let Some((frame, key)) = key.split_once('.') else {
+        continue;
+      };
+      if current.as_ref().is_none_or(|(number, _)| number != frame) {
+        if let Some((_, previous)) = current.take() {
+          on_frame(previous);
+        }
+        current = Some((frame.to_owned(), FrameSideData::default()));
+      }
+      let Some((_, side_data)) = current.as_mut() else {
+        continue;
+      };
+      let value = value.trim_matches('"').replace("\\\"", "\"");
+      match key {
+        "stream_index" => side_data.index = value.parse().unwrap_or_default(),
+        "best_effort_timestamp_time" => side_data.time = value.parse().ok().or(side_data.time),
+        "pts_time" => side_data.time = side_data.time.or(value.parse().ok()),
+        _ => {
+          let Some((entry, key)) = key
+            .strip_prefix("side_data_list.side_data.")
+            .and_then(|key| key.split_once('.'))
+          else {
+            continue;
+          };
+          let Ok(entry) = entry.parse::<usize>() else {
+            continue;
+          };
+          if side_data.side_data.len() <= entry {
+            side_data.side_data.resize_with(entry + 1, HashMap::new);
+          }
+          side_data.side_data[entry].insert(key.to_owned(), value);
+        }
+      }
+    }
+  }
+  if let Some((_, last)) = current {
+    on_frame(last);
+  }
+  let status = child.wait()?;
+  if !status.success() {
+    return Err(anyhow::anyhow!("FFPROBE_FAILED:{}", status));
+  }
+  Ok(())
+}
+
+/// The MediaInfo properties `compare` needs.
+pub fn key_properties() -> Vec<StreamProperty> {
+  KEY_FIELDS
+    .iter()
+    .flat_map(|(kind, properties)| {
+      properties.iter().map(|property| StreamProperty {
+        stream: *kind,
+        property: property.to_string(),
+      })
+    })
+    .collect()
+}
+
+/// Timestamps of the keyframes of the first video stream of `file`, in
+/// order. Read from the packet flags, so nothing is decoded.
+pub fn keyframes(file: &str) -> Result<Vec<f64>> {
+  let mut keyframes: Vec<f64> = video_packets(file)?
+    .into_iter()
+    .filter(|packet| packet.key)
+    .filter_map(|packet| packet.pts)
+    .collect();
+  keyframes.sort_by(f64::total_cmp);
+  keyframes.dedup();
+  Ok(keyframes)
+}
+
+/// Add the MediaInfo-named fields `compare` looks at to a flattened stream.
+fn map_stream_fields(kind: MediaInfoStreamKind, stream: &Value, property_map: &mut HashMap<String, String>) {
+  let mut set = |name: &str, value: Option<String>| {
+    if let Some(value) = value.filter(|value| !value.is_empty()) {
+      property_map.insert(name.to_owned(), value);
+    }
+  };
+  let tag = |name: &str| -> Option<String> {
+    stream["tags"].as_object().and_then(|tags| {
+      tags
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name) || key.to_uppercase().starts_with(&format!("{}-", name)))
+        .and_then(|(_, value)| scalar(value))
+    })
+  };
+  set(
+    "Format",
+    stream["codec_name"].as_str().map(|codec| {
+      FORMAT_NAMES
+        .iter()
+        .find(|(name, _)| *name == codec)
+        .map_or(codec.to_owned(), |(_, format)| format.to_string())
+    }),
+  );
+  set(
+    "Duration",
+    number(&stream["duration"])
+      .or_else(|| tag("DURATION").and_then(|value| parse_clock(&value)))
+      .map(|seconds| format!("{:.3}", seconds * 1000.0)),
+  );
+  set("BitRate", scalar(&stream["bit_rate"]).or_else(|| tag("BPS")));
+  set("Language", tag("language"));
+  set("Title", tag("title"));
+  match kind {
+    MediaInfoStreamKind::Video => {
+      set("Width", scalar(&stream["width"]));
+      set("Height", scalar(&stream["height"]));
+      let frame_rate = stream["avg_frame_rate"]
+        .as_str()
+        .and_then(parse_rational)
+        .or_else(|| stream["r_frame_rate"].as_str().and_then(parse_rational));
+      set("FrameRate", frame_rate.map(|rate| format!("{:.3}", rate)));
+      set(
+        "FrameCount",
+        scalar(&stream["nb_frames"]).or_else(|| tag("NUMBER_OF_FRAMES")),
+      );
+      let field_order = stream["field_order"].as_str().unwrap_or_default();
+      set(
+        "ScanType",
+        match field_order {
+          "progressive" => Some("Progressive".to_owned()),
+          "tt" | "bb" | "tb" | "bt" => Some("Interlaced".to_owned()),
+          _ => None,
+        },
+      );
+      set(
+        "ScanOrder",
+        match field_order {
+          "tt" | "tb" => Some("TFF".to_owned()),
+          "bb" | "bt" => Some("BFF".to_owned()),
+          _ => None,
+        },
+      );
+    }
+    MediaInfoStreamKind::Audio => {
+      set("Channel(s)", scalar(&stream["channels"]));
+      set("SamplingRate", scalar(&stream["sample_rate"]));
+    }
+    _ => {}
+  }
+}
+
+/// Map ffprobe's JSON onto `StreamPropertyMap`s numbered the way MediaInfo
+/// numbers streams. Cover art and attachments aren't streams to MediaInfo, so
+/// they are left out.
+fn map_streams(json: &Value, frames: FfprobeFrames, frame_stats: &HashMap<i64, FrameStats>) -> Vec<StreamPropertyMap> {
+  let mut maps = Vec::new();
+  let mut counts: HashMap<MediaInfoStreamKind, i32> = HashMap::new();
+  for stream in json["streams"].as_array().into_iter().flatten() {
+    let kind = match stream["codec_type"].as_str() {
+      Some("video") if stream["disposition"]["attached_pic"].as_i64() == Some(1) => continue,
+      Some("video") => MediaInfoStreamKind::Video,
+      Some("audio") => MediaInfoStreamKind::Audio,
+      Some("subtitle") => MediaInfoStreamKind::Text,
+      Some("data") => MediaInfoStreamKind::Other,
+      _ => continue,
+    };
+    let count = counts.entry(kind).or_insert(0);
+    let num = *count;
+    *count += 1;
+    let mut property_map = flatten(stream);
+    map_stream_fields(kind, stream, &mut property_map);
+    if let Some(stats) = stream["index"].as_i64().and_then(|index| frame_stats.get(&index)) {
+      stats.apply(frames, &mut property_map);
+    }
+    maps.push(StreamPropertyMap {
+      stream: kind,
+      num,
+      property_map,
+      source: PropertySource::Ffprobe,
+    });
+  }
+  let format = &json["format"];
+  let mut property_map = flatten(format);
+  if let Some(seconds) = number(&format["duration"]) {
+    property_map.insert("Duration".to_owned(), format!("{:.3}", seconds * 1000.0));
+  }
+  if let Some(bit_rate) = scalar(&format["bit_rate"]) {
+    property_map.insert("OverallBitRate".to_owned(), bit_rate);
+  }
+  if let Some(size) = scalar(&format["size"]) {
+    property_map.insert("FileSize".to_owned(), size);
+  }
+  for (kind, name) in [
+    (MediaInfoStreamKind::Video, "VideoCount"),
+    (MediaInfoStreamKind::Audio, "AudioCount"),
+    (MediaInfoStreamKind::Text, "TextCount"),
+  ] {
+    if let Some(count) = counts.get(&kind) {
+      property_map.insert(name.to_owned(), count.to_string());
+    }
+  }
+  maps.insert(
+    0,
+    StreamPropertyMap {
+      stream: MediaInfoStreamKind::General,
+      num: 0,
+      property_map,
+      source: PropertySource::Ffprobe,
+    },
+  );
+  maps
+}
+
+/// `value` as a number, whether ffprobe printed it as a JSON number or string.
+fn number(value: &Value) -> Option<f64> {
+  value
+    .as_f64()
+    .or_else(|| value.as_str().and_then(|text| text.parse().ok()))
+}
+
+/// Every packet of `file`, in file order. Nothing is decoded.
+pub fn packets(file: &str) -> Result<Vec<StreamPacket>> {
+  let args = [
+    "-show_entries".to_string(),
+    "packet=stream_index,pts_time,dts_time,size".to_string(),
+  ];
+  let mut packets = Vec::new();
+  read_compact(file, &args, |fields| {
+    let index = fields.get("stream_index").and_then(|index| index.parse::<i64>().ok());
+    let size = fields.get("size").and_then(|size| size.parse::<u64>().ok());
+    if let (Some(index), Some(size)) = (index, size) {
+      let time = |key: &str| fields.get(key).and_then(|time| time.parse::<f64>().ok());
+      packets.push(StreamPacket {
+        index,
+        time: time("pts_time").or_else(|| time("dts_time")),
+        size,
+      });
+    }
+  })?;
+  Ok(packets)
+}
+
+/// Seconds from a Matroska `DURATION` tag like `01:23:45.678000000`.
+fn parse_clock(value: &str) -> Option<f64> {
+  let mut seconds = 0.0;
+  for part in value.trim().split(':') {
+    seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+  }
+  Some(seconds)
+}
+
+fn parse_rational(value: &str) -> Option<f64> {
+  let (numerator, denominator) = value.split_once('/')?;
+  let numerator: f64 = numerator.parse().ok()?;
+  let denominator: f64 = denominator.parse().ok()?;
+  (numerator > 0.0 && denominator > 0.0).then(|| numerator / denominator)
+}
+
+/// Run ffprobe on `file` (`-show_streams -show_format`, then the video frames
+/// as `frames` asks) and map the result onto `StreamPropertyMap`s tagged
+/// `PropertySource::Ffprobe`.
+pub fn probe(file: &str, frames: FfprobeFrames) -> Result<Vec<StreamPropertyMap>> {
+  let exe = ffprobe_binary()?;
+  let json = run_json(&exe, file, &["-show_streams", "-show_format"])?;
+  let frame_stats = if frames == FfprobeFrames::None {
+    HashMap::new()
+  } else {
+    read_frames(&exe, file, frames, number(&json["format"]["duration"]))?
+  };
+  Ok(map_streams(&json, frames, &frame_stats))
+}
+
+/// Run ffprobe on `file` with `args`, in the compact format, and hand every
+/// line to `on_line` as its `key=value` fields.
+fn read_compact(file: &str, args: &[String], mut on_line: impl FnMut(&HashMap<&str, &str>)) -> Result<()> {
+  let exe = ffprobe_binary()?;
+  let mut child = tools::command(&exe)
+    .args(["-v", "error", "-print_format", "compact=p=0"])
+    .args(args)
+    .arg(file)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(|e| anyhow::anyhow!("FFPROBE_NOT_AVAILABLE:{}: {}", exe.display(), e))?;
+  if let Some(stdout) = child.stdout.take() {
+    for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+      let fields: HashMap<&str, &str> = line.split('|').filter_map(|field| field.split_once('=')).collect();
+      on_line(&fields);
+    }
+  }
+  let status = child.wait()?;
+  if !status.success() {
+    return Err(anyhow::anyhow!("FFPROBE_FAILED:{}", status));
+  }
+  Ok(())
+}
+
+/// Decode the video frames (`-show_frames`, limited to `FRAME_ENTRIES`) and
+/// tally them per stream index. Output is read line by line in the compact
+/// format, so reading every frame of a long file stays cheap on memory.
+fn read_frames(
+  exe: &Path,
+  file: &str,
+  frames: FfprobeFrames,
+  duration: Option<f64>,
+) -> Result<HashMap<i64, FrameStats>> {
+  let mut cmd = tools::command(exe);
+  cmd.args([
+    "-v",
+    "error",
+    "-select_streams",
+    "v",
+    "-show_entries",
+    FRAME_ENTRIES,
+    "-print_format",
+    "compact=p=0",
+  ]);
+  if frames == FfprobeFrames::Sampled {
+    cmd
+      .arg("-read_intervals")
+      .arg(sample_intervals(duration.unwrap_or_default()));
+  }
+  let mut child = cmd
+    .arg(file)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(|e| anyhow::anyhow!("FFPROBE_NOT_AVAILABLE:{}: {}", exe.display(), e))?;
+  let mut stats: HashMap<i64, FrameStats> = HashMap::new();
+  if let Some(stdout) = child.stdout.take() {
+    for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+      let fields: HashMap<&str, &str> = line.split('|').filter_map(|field| field.split_once('=')).collect();
+      let Some(index) = fields.get("stream_index").and_then(|index| index.parse::<i64>().ok()) else {
+        continue;
+      };
+      let entry = stats.entry(index).or_default();
+      entry.frames += 1;
+      if fields.get("interlaced_frame") == Some(&"1") {
+        entry.interlaced += 1;
+        if fields.get("top_field_first") == Some(&"1") {
+          entry.top_field_first += 1;
+        }
+      }
+      let timestamp = fields
+        .get("best_effort_timestamp_time")
+        .or_else(|| fields.get("pts_time"))
+        .and_then(|time| time.parse::<f64>().ok());
+      if let Some(timestamp) = timestamp {
+        entry.timestamps.push(timestamp);
+      }
+    }
+  }
+  let status = child.wait()?;
+  if !status.success() {
+    return Err(anyhow::anyhow!("FFPROBE_FAILED:{}", status));
+  }
+  Ok(stats)
+}
+
+/// Run ffprobe on `file` with `-print_format json` and the `-show_*` options in `sections`.
+fn run_json(exe: &Path, file: &str, sections: &[&str]) -> Result<Value> {
+  if !Path::new(file).is_file() {
+    return Err(anyhow::anyhow!("File not found: {}", file));
+  }
+  let output = tools::command(exe)
+    .args(["-v", "error", "-print_format", "json"])
+    .args(sections)
+    .arg(file)
+    .stdin(Stdio::null())
+    .output()
+    .map_err(|e| anyhow::anyhow!("FFPROBE_NOT_AVAILABLE:{}: {}", exe.display(), e))?;
+  if !output.status.success() {
+    return Err(anyhow::anyhow!(
+      "FFPROBE_FAILED:{}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    ));
+  }
+  Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// `-read_intervals` for `SAMPLE_WINDOWS` windows of `SAMPLE_SECONDS` each,
+/// e.g. `0.000%+2,600.000%+2,...`. Without a known duration only the start is read.
+fn sample_intervals(duration: f64) -> String {
+  if duration <= 0.0 {
+    return format!("%+{}", SAMPLE_SECONDS * SAMPLE_WINDOWS);
+  }
+  (0..SAMPLE_WINDOWS)
+    .map(|window| {
+      format!(
+        "{:.3}%+{}",
+        duration * window as f64 / SAMPLE_WINDOWS as f64,
+        SAMPLE_SECONDS
+      )
+    })
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+fn scalar(value: &Value) -> Option<String> {
+  match value {
+    Value::String(text) => Some(text.to_owned()),
+    Value::Number(number) => Some(number.to_string()),
+    Value::Bool(flag) => Some(flag.to_string()),
+    _ => None,
+  }
+}
+
+/// Whether the two tools agree on `property`, within the tolerance it allows.
+fn values_match(property: &str, media_info: &str, ffprobe: &str) -> bool {
+  let numbers = media_info
+    .trim()
+    .parse::<f64>()
+    .ok()
+    .zip(ffprobe.trim().parse::<f64>().ok());
+  match property {
+    "Format" => media_info.trim().eq_ignore_ascii_case(ffprobe.trim()),
+    // MediaInfo names the flavor of interlacing; ffprobe only sees interlaced frames.
+    "ScanType" => {
+      let normalize = |value: &str| match value.trim() {
+        "MBAFF" | "PAFF" => "Interlaced".to_owned(),
+        other => other.to_owned(),
+      };
+      normalize(media_info) == normalize(ffprobe)
+    }
+    "FrameRate" => numbers.is_some_and(|(a, b)| (a - b).abs() <= 0.01),
+    // Durations in ms; the tools disagree by a frame or a packet routinely.
+    "Duration" => numbers.is_some_and(|(a, b)| (a - b).abs() <= 50f64.max(a.max(b) * 0.005)),
+    _ => match numbers {
+      Some((a, b)) => a == b,
+      None => media_info.trim() == ffprobe.trim(),
+    },
+  }
+}
+
+/// Decode the first video stream of `file` and list its frame types. With
+/// `FfprobeFrames::Sampled` only the sample windows over `duration` seconds
+/// are decoded; with `FfprobeFrames::None` nothing is.
+pub fn video_frames(file: &str, frames: FfprobeFrames, duration: f64) -> Result<Vec<VideoFrame>> {
+  let mut args = vec![
+    "-select_streams".to_string(),
+    "v:0".to_string(),
+    "-show_entries".to_string(),
+    "frame=key_frame,pict_type".to_string(),
+  ];
+  match frames {
+    FfprobeFrames::None => return Ok(Vec::new()),
+    FfprobeFrames::Sampled => args.extend(["-read_intervals".to_string(), sample_intervals(duration)]),
+    FfprobeFrames::All => {}
+  }
+  let mut video_frames = Vec::new();
+  read_compact(file, &args, |fields| {
+    if let Some(pict_type) = fields.get("pict_type") {
+      video_frames.push(VideoFrame {
+        pict_type: pict_type.chars().next().filter(|c| c.is_ascii_alphabetic()).unwrap_or('?'),
+        key: fields.get("key_frame") == Some(&"1"),
+      });
+    }
+  })?;
+  Ok(video_frames)
+}
+
+/// The packets of the first video stream of `file` in decode order, with
+/// their keyframe flag. Nothing is decoded.
+pub fn video_packets(file: &str) -> Result<Vec<VideoPacket>> {
+  let args = [
+    "-select_streams".to_string(),
+    "v:0".to_string(),
+    "-show_entries".to_string(),
+    "packet=pts_time,flags".to_string(),
+  ];
+  let mut packets = Vec::new();
+  read_compact(file, &args, |fields| {
+    if let Some(flags) = fields.get("flags") {
+      packets.push(VideoPacket {
+        pts: fields.get("pts_time").and_then(|time| time.parse::<f64>().ok()),
+        key: flags.starts_with('K'),
+      });
+    }
+  })?;
+  Ok(packets)
+}
+
+/// The video streams of `file` as ffprobe reports them (`-show_streams`),
+/// with their stream side data.
+pub fn video_streams(file: &str) -> Result<Vec<VideoStream>> {
+  let json = run_json(&ffprobe_binary()?, file, &["-select_streams", "v", "-show_streams"])?;
+  Ok(
+    json["streams"]
+      .as_array()
+      .into_iter()
+      .flatten()
+      .map(|stream| VideoStream {
+        index: stream["index"].as_i64().unwrap_or_default(),
+        fields: flatten(stream),
+        side_data: stream["side_data_list"]
+          .as_array()
+          .into_iter()
+          .flatten()
+          .map(flatten)
+          .collect(),
+      })
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn compare_flags_mismatches_within_tolerance() {
+    let map = |source, pairs: &[(&str, &str)]| StreamPropertyMap {
+      stream: MediaInfoStreamKind::Video,
+      num: 0,
+      property_map: pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect(),
+      source,
+    };
+    let media_info = [map(
+      PropertySource::MediaInfo,
+      &[
+        ("Format", "AVC"),
+        ("FrameRate", "23.976"),
+        ("FrameRate_Mode", "CFR"),
+        ("ScanType", "MBAFF"),
+        ("Duration", "5005.000"),
+        ("FrameCount", "120"),
+      ],
+    )];
+    let ffprobe = [map(
+      PropertySource::Ffprobe,
+      &[
+        ("Format", "avc"),
+        ("FrameRate", "23.976"),
+        ("FrameRate_Mode", "VFR"),
+        ("ScanType", "Interlaced"),
+        ("Duration", "5042.000"),
+        ("Width", "1920"),
+      ],
+    )];
+    let fields = compare(&media_info, &ffprobe);
+    let mismatched: Vec<&str> = fields
+      .iter()
+      .filter(|field| field.mismatch)
+      .map(|field| field.property.as_str())
+      .collect();
+    assert_eq!(mismatched, vec!["FrameRate_Mode"]);
+    // Reported by one tool only: listed, but not a mismatch.
+    assert!(fields.iter().any(|field| field.property == "Width" && !field.mismatch));
+    assert!(fields
+      .iter()
+      .any(|field| field.property == "FrameCount" && !field.mismatch));
+  }
+
+  #[test]
+  fn frame_rate_mode_tolerates_rounding_and_window_gaps() {
+    // 23.976 fps in a 1 ms time base, sampled in two windows.
+    let mut timestamps: Vec<f64> = (0..48).map(|i| (i as f64 * 41.708).round() / 1000.0).collect();
+    timestamps.extend((0..48).map(|i| 600.0 + (i as f64 * 41.708).round() / 1000.0));
+    assert_eq!(frame_rate_mode(&timestamps), Some("CFR"));
+    // 24 fps switching to 30 fps.
+    let mut timestamps: Vec<f64> = (0..24).map(|i| i as f64 / 24.0).collect();
+    timestamps.extend((0..30).map(|i| 1.0 + i as f64 / 30.0));
+    assert_eq!(frame_rate_mode(&timestamps), Some("VFR"));
+  }
+
+  #[test]
+  fn map_streams_numbers_like_media_info() {
+    let json: Value = serde_json::from_str(
+      r#"{
+        "streams": [
+          {"index": 0, "codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080,
+           "avg_frame_rate": "24000/1001", "field_order": "progressive",
+           "tags": {"DURATION": "00:01:00.000000000", "NUMBER_OF_FRAMES-eng": "1438"}},
+          {"index": 1, "codec_type": "audio", "codec_name": "eac3", "channels": 6, "sample_rate": "48000"},
+          {"index": 2, "codec_type": "video", "codec_name": "mjpeg", "disposition": {"attached_pic": 1}},
+          {"index": 3, "codec_type": "attachment", "codec_name": "ttf"}
+        ],
+        "format": {"duration": "60.021000", "bit_rate": "5000000"}
+      }"#,
+    )
+    .unwrap();
+    let maps = map_streams(&json, FfprobeFrames::None, &HashMap::new());
+    assert_eq!(maps.len(), 3);
+    let general = &maps[0].property_map;
+    assert_eq!(general["Duration"], "60021.000");
+    assert_eq!(general["VideoCount"], "1");
+    let video = &maps[1].property_map;
+    assert_eq!(video["Format"], "AVC");
+    assert_eq!(video["FrameRate"], "23.976");
+    assert_eq!(video["FrameCount"], "1438");
+    assert_eq!(video["Duration"], "60000.000");
+    assert_eq!(video["ScanType"], "Progressive");
+    let audio = &maps[2];
+    assert_eq!(audio.stream, MediaInfoStreamKind::Audio);
+    assert_eq!(audio.property_map["Format"], "E-AC-3");
+    assert_eq!(audio.property_map["Channel(s)"], "6");
+    assert_eq!(audio.source, PropertySource::Ffprobe);
+  }
+}
@@ -0,0 +1,226 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::ffprobe::{VideoFrame, VideoPacket};
+use crate::protocol::{FfprobeFrames, GopFrameTypes, GopReport, GopStats};
+
+/// Longest GOP pattern reported before it is cut short with `…`.
+const MAX_PATTERN: usize = 60;
+
+/// Summarize the GOP structure of a video stream from its `packets` (decode
+/// order) and, when `frames` isn't `FfprobeFrames::None`, its decoded
+/// `video_frames` (display order). Packets before the first keyframe belong
+/// to no GOP.
+pub fn analyze(
+  file: &str,
+  packets: &[VideoPacket],
+  frames: FfprobeFrames,
+  video_frames: &[VideoFrame],
+) -> Result<GopReport> {
+  if packets.is_empty() {
+    return Err(anyhow::anyhow!("GOP_NO_VIDEO:{}", file));
+  }
+  let starts: Vec<usize> = packets
+    .iter()
+    .enumerate()
+    .filter(|(_, packet)| packet.key)
+    .map(|(index, _)| index)
+    .collect();
+  let mut keyframes: Vec<f64> = starts.iter().filter_map(|index| packets[*index].pts).collect();
+  keyframes.sort_by(f64::total_cmp);
+  let mut open_gops = 0;
+  let mut lengths_frames = Vec::new();
+  let mut lengths_seconds = Vec::new();
+  for (gop, start) in starts.iter().enumerate() {
+    let end = starts.get(gop + 1).copied().unwrap_or(packets.len());
+    let key_pts = packets[*start].pts;
+    let members = &packets[*start + 1..end];
+    // Leading frames decoded after the keyframe but shown before it.
+    if let Some(key_pts) = key_pts
+      && members.iter().any(|packet| packet.pts.is_some_and(|pts| pts < key_pts))
+    {
+      open_gops += 1;
+    }
+    let last = end == packets.len();
+    if last && starts.len() > 1 {
+      continue;
+    }
+    lengths_frames.push((end - start) as f64);
+    let next_pts = if last {
+      members.iter().filter_map(|packet| packet.pts).max_by(f64::total_cmp)
+    } else {
+      packets[end].pts
+    };
+    if let (Some(key_pts), Some(next_pts)) = (key_pts, next_pts) {
+      lengths_seconds.push(next_pts - key_pts);
+    }
+  }
+  let gops = starts.len() as u32;
+  Ok(GopReport {
+    keyframes,
+    gops,
+    open_gops,
+    closed_gops: gops - open_gops,
+    length_frames: stats(&lengths_frames),
+    length_seconds: stats(&lengths_seconds),
+    frame_types: (frames != FfprobeFrames::None).then(|| frame_types(frames, video_frames)),
+  })
+}
+
+fn frame_types(frames: FfprobeFrames, video_frames: &[VideoFrame]) -> GopFrameTypes {
+  let count = |pict_type: char| video_frames.iter().filter(|frame| frame.pict_type == pict_type).count() as u32;
+  let mut max_consecutive_b = 0;
+  let mut run = 0;
+  for frame in video_frames {
+    run = if frame.pict_type == 'B' { run + 1 } else { 0 };
+    max_consecutive_b = max_consecutive_b.max(run);
+  }
+  GopFrameTypes {
+    frames,
+    i_frames: count('I'),
+    p_frames: count('P'),
+    b_frames: count('B'),
+    idr_frames: video_frames
+      .iter()
+      .filter(|frame| frame.pict_type == 'I' && frame.key)
+      .count() as u32,
+    non_idr_i_frames: video_frames
+      .iter()
+      .filter(|frame| frame.pict_type == 'I' && !frame.key)
+      .count() as u32,
+    max_consecutive_b,
+    pattern: pattern(video_frames),
+  }
+}
+
+/// The most common run of frame types from one keyframe to the next; on a
+/// tie the one seen first. Runs not starting at a keyframe (the start of a
+/// sample window that missed it) don't count.
+fn pattern(video_frames: &[VideoFrame]) -> Option<String> {
+  let mut patterns: Vec<String> = Vec::new();
+  let mut current: Option<String> = None;
+  for frame in video_frames {
+    if frame.key {
+      patterns.extend(current.take());
+      current = Some(String::new());
+    }
+    if let Some(current) = current.as_mut() {
+      current.push(frame.pict_type);
+    }
+  }
+  patterns.extend(current);
+  let mut counts: HashMap<&str, usize> = HashMap::new();
+  for pattern in &patterns {
+    *counts.entry(pattern).or_default() += 1;
+  }
+  let mut best: Option<(&str, usize)> = None;
+  for pattern in &patterns {
+    let count = counts[pattern.as_str()];
+    if best.is_none_or(|(_, best_count)| count > best_count) {
+      best = Some((pattern, count));
+    }
+  }
+  best.map(|(pattern, _)| {
+    if pattern.chars().count() > MAX_PATTERN {
+      format!("{}…", pattern.chars().take(MAX_PATTERN).collect::<String>())
+    } else {
+      pattern.to_string()
+    }
+  })
+}
+
+fn stats(values: &[f64]) -> GopStats {
+  if values.is_empty() {
+    return GopStats {
+      min: 0.0,
+      max: 0.0,
+      mean: 0.0,
+    };
+  }
+  GopStats {
+    min: values.iter().copied().fold(f64::INFINITY, f64::min),
+    max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+    mean: values.iter().sum::<f64>() / values.len() as f64,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn packets(pts: &[(f64, bool)]) -> Vec<VideoPacket> {
+    pts
+      .iter()
+      .map(|(pts, key)| VideoPacket {
+        pts: Some(*pts),
+        key: *key,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_gops() {
+    // Decode order: closed GOP I P B B, open GOP I B B P (leading Bs shown
+    // before the I), then a short final GOP.
+    let packets = packets(&[
+      (0.0, true),
+      (0.3, false),
+      (0.1, false),
+      (0.2, false),
+      (0.7, true),
+      (0.5, false),
+      (0.6, false),
+      (1.0, false),
+      (1.1, true),
+      (1.2, false),
+    ]);
+    let report = analyze("in.mkv", &packets, FfprobeFrames::None, &[]).unwrap();
+    assert_eq!(report.keyframes, vec![0.0, 0.7, 1.1]);
+    assert_eq!((report.gops, report.open_gops, report.closed_gops), (3, 1, 2));
+    assert_eq!(
+      report.length_frames,
+      GopStats {
+        min: 4.0,
+        max: 4.0,
+        mean: 4.0
+      }
+    );
+    assert!((report.length_seconds.min - 0.4).abs() < 1e-9);
+    assert!((report.length_seconds.max - 0.7).abs() < 1e-9);
+    assert!(report.frame_types.is_none());
+    assert!(analyze("in.mkv", &[], FfprobeFrames::None, &[]).is_err());
+  }
+
+  #[test]
+  fn test_frame_types() {
+    let frames: Vec<VideoFrame> = "BIBBPBBPIBBPBBPIBBPBBPiBBP"
+      .chars()
+      .map(|c| VideoFrame {
+        pict_type: c.to_ascii_uppercase(),
+        key: c == 'I',
+      })
+      .collect();
+    let types = frame_types(FfprobeFrames::All, &frames);
+    assert_eq!((types.i_frames, types.p_frames, types.b_frames), (4, 7, 15));
+    assert_eq!((types.idr_frames, types.non_idr_i_frames), (3, 1));
+    assert_eq!(types.max_consecutive_b, 2);
+    assert_eq!(types.pattern.as_deref(), Some("IBBPBBP"));
+  }
+}
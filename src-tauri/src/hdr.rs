@@ -0,0 +1,537 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::ffprobe::{FrameSideData, VideoStream};
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{
+  FfprobeFrames, HdrDolbyVision, HdrLuminance, HdrMasteringDisplay, HdrReport, HdrScene, HdrStream, HdrTransfer,
+  StreamProperty, StreamPropertyMap,
+};
+
+/// Scenes listed per stream; frames past them still count towards the totals.
+const MAX_SCENES: usize = 5000;
+
+/// Mastering display primaries by name, as `[red, green, blue]` CIE 1931 `[x, y]`.
+const NAMED_PRIMARIES: &[(&str, [[f64; 2]; 3])] = &[
+  ("BT.2020", [[0.708, 0.292], [0.170, 0.797], [0.131, 0.046]]),
+  ("Display P3", [[0.680, 0.320], [0.265, 0.690], [0.150, 0.060]]),
+  ("BT.709", [[0.640, 0.330], [0.300, 0.600], [0.150, 0.060]]),
+];
+
+const PROPERTIES: &[&str] = &[
+  "BitDepth",
+  "colour_primaries",
+  "Duration",
+  "HDR_Format",
+  "HDR_Format_Compatibility",
+  "HDR_Format_Level",
+  "HDR_Format_Profile",
+  "HDR_Format_Settings",
+  "MasteringDisplay_ColorPrimaries",
+  "MasteringDisplay_Luminance",
+  "matrix_coefficients",
+  "MaxCLL",
+  "MaxFALL",
+  "transfer_characteristics",
+];
+
+/// A gap between frames, in seconds, that starts a new scene, e.g. between
+/// the windows of `FfprobeFrames::Sampled`.
+const SCENE_GAP: f64 = 1.0;
+/// Luminance values closer than this, in nits, belong to the same scene.
+const SCENE_TOLERANCE: f64 = 0.01;
+
+/// What the decoded frames of one video stream carried.
+#[derive(Debug, Default)]
+struct StreamTally {
+  frames: u32,
+  rpu_frames: u32,
+  hdr10_plus_frames: u32,
+  mastering_display: Option<HdrMasteringDisplay>,
+  max_cll: Option<f64>,
+  max_fall: Option<f64>,
+  source: Option<&'static str>,
+  scenes: Vec<HdrScene>,
+  truncated: bool,
+  peak: f64,
+  average_sum: f64,
+  luminance_frames: u32,
+}
+
+impl StreamTally {
+  /// Extend the last scene with a frame at `time`, or start a new one when the
+  /// metadata changed.
+  fn add_luminance(&mut self, source: &'static str, time: f64, max_nits: f64, avg_nits: f64) {
+    self.source.get_or_insert(source);
+    self.peak = self.peak.max(max_nits);
+    self.average_sum += avg_nits;
+    self.luminance_frames += 1;
+    let listed = self.scenes.len();
+    match self.scenes.last_mut() {
+      Some(scene)
+        if !self.truncated
+          && time - scene.end <= SCENE_GAP
+          && (scene.max_nits - max_nits).abs() < SCENE_TOLERANCE
+          && (scene.avg_nits - avg_nits).abs() < SCENE_TOLERANCE =>
+      {
+        scene.end = time;
+        scene.frames += 1;
+      }
+      _ if listed < MAX_SCENES => self.scenes.push(HdrScene {
+        start: time,
+        end: time,
+        frames: 1,
+        max_nits,
+        avg_nits,
+      }),
+      _ => self.truncated = true,
+    }
+  }
+
+  fn luminance(&self) -> Option<HdrLuminance> {
+    let source = self.source?;
+    Some(HdrLuminance {
+      source: source.to_owned(),
+      max_nits: self.peak,
+      avg_nits: self.average_sum / self.luminance_frames.max(1) as f64,
+      scenes: self.scenes.clone(),
+      truncated: self.truncated,
+    })
+  }
+}
+
+/// The side data of the decoded video frames, by ffprobe stream index.
+#[derive(Debug, Default)]
+pub struct Tally {
+  streams: HashMap<i64, StreamTally>,
+}
+
+impl Tally {
+  pub fn add(&mut self, frame: FrameSideData) {
+    let tally = self.streams.entry(frame.index).or_default();
+    tally.frames += 1;
+    let mut dolby_vision = None;
+    let mut hdr10_plus = None;
+    for entry in &frame.side_data {
+      let kind = entry.get("side_data_type").map(String::as_str).unwrap_or_default();
+      if kind.contains("Mastering display") {
+        if tally.mastering_display.is_none() {
+          tally.mastering_display = Some(mastering_display(entry));
+        }
+      } else if kind.contains("Content light level") {
+        tally.max_cll = tally.max_cll.or(field(entry, "max_content"));
+        tally.max_fall = tally.max_fall.or(field(entry, "max_average"));
+      } else if kind.contains("Dolby Vision") {
+        tally.rpu_frames += 1;
+        dolby_vision = dolby_vision.or(dolby_vision_l1(entry));
+      } else if kind.contains("HDR10+") || kind.contains("2094-40") {
+        tally.hdr10_plus_frames += 1;
+        hdr10_plus = hdr10_plus.or(hdr10_plus_luminance(entry));
+      }
+    }
+    let luminance = dolby_vision
+      .map(|luminance| ("Dolby Vision L1", luminance))
+      .or(hdr10_plus.map(|luminance| ("HDR10+", luminance)));
+    if let Some((source, (max_nits, avg_nits))) = luminance {
+      tally.add_luminance(source, frame.time.unwrap_or_default(), max_nits, avg_nits);
+    }
+  }
+}
+
+/// Combine the MediaInfo `properties`, the ffprobe `streams` and the frame
+/// side data in `tally` into one HDR report per video stream. MediaInfo
+/// names things; ffprobe fills in what MediaInfo leaves out.
+pub fn analyze(
+  file: &str,
+  frames: FfprobeFrames,
+  properties: &[StreamPropertyMap],
+  streams: &[VideoStream],
+  tally: &Tally,
+) -> Result<HdrReport> {
+  let mut maps: Vec<&StreamPropertyMap> = properties
+    .iter()
+    .filter(|map| map.stream == MediaInfoStreamKind::Video)
+    .collect();
+  maps.sort_by_key(|map| map.num);
+  let count = maps.len().max(streams.len());
+  if count == 0 {
+    return Err(anyhow::anyhow!("HDR_NO_VIDEO:{}", file));
+  }
+  let empty = HashMap::new();
+  let streams = (0..count)
+    .map(|num| {
+      let media_info = maps.get(num).map(|map| &map.property_map).unwrap_or(&empty);
+      let probe = streams.get(num);
+      let stream_tally = probe.and_then(|stream| tally.streams.get(&stream.index));
+      hdr_stream(num as i32, media_info, probe, stream_tally)
+    })
+    .collect();
+  Ok(HdrReport { frames, streams })
+}
+
+/// The Dolby Vision of a stream, from its configuration record and the
+/// MediaInfo `HDR_Format` entry at the same ` / ` position.
+fn dolby_vision(
+  media_info: &HashMap<String, String>,
+  config: Option<&HashMap<String, String>>,
+  rpu_frames: u32,
+) -> Option<HdrDolbyVision> {
+  let position = media_info
+    .get("HDR_Format")
+    .and_then(|format| format.split(" / ").position(|part| part.contains("Dolby Vision")));
+  if position.is_none() && config.is_none() && rpu_frames == 0 {
+    return None;
+  }
+  let part = |property: &str| {
+    position
+      .and_then(|position| media_info.get(property)?.split(" / ").nth(position))
+      .map(|part| part.trim().to_owned())
+  };
+  let config_field = |key: &str| config.and_then(|config| field(config, key)).map(|value| value as u32);
+  let config_flag = |key: &str| config_field(key).map(|value| value == 1);
+  let settings = part("HDR_Format_Settings").unwrap_or_default();
+  Some(HdrDolbyVision {
+    profile: config_field("dv_profile").or_else(|| part("HDR_Format_Profile")?.rsplit('.').next()?.parse().ok()),
+    level: config_field("dv_level").or_else(|| part("HDR_Format_Level")?.parse().ok()),
+    compatibility_id: config_field("dv_bl_signal_compatibility_id"),
+    rpu: config_flag("rpu_present_flag").unwrap_or(settings.contains("RPU")) || rpu_frames > 0,
+    enhancement_layer: config_flag("el_present_flag").unwrap_or(settings.contains("EL")),
+    rpu_frames,
+  })
+}
+
+/// Peak and average nits from the level 1 metadata of a Dolby Vision RPU,
+/// whose 12-bit PQ codes ffprobe prints as `max_pq` and `avg_pq`.
+fn dolby_vision_l1(entry: &HashMap<String, String>) -> Option<(f64, f64)> {
+  let code = |name: &str| {
+    entry
+      .iter()
+      .find(|(key, _)| key.rsplit('.').next() == Some(name))
+      .and_then(|(_, value)| value.parse::<f64>().ok())
+  };
+  let max = code("max_pq")?;
+  let avg = code("avg_pq")?;
+  Some((pq_to_nits(max / 4095.0), pq_to_nits(avg / 4095.0)))
+}
+
+/// Seconds of video the MediaInfo `properties` report, for sampling frames.
+pub fn duration(properties: &[StreamPropertyMap]) -> f64 {
+  properties
+    .iter()
+    .filter(|map| map.stream == MediaInfoStreamKind::Video)
+    .filter_map(|map| map.property_map.get("Duration")?.parse::<f64>().ok())
+    .fold(0.0, f64::max)
+    / 1000.0
+}
+
+fn field(entry: &HashMap<String, String>, key: &str) -> Option<f64> {
+  entry.get(key).and_then(|value| rational(value))
+}
+
+/// Peak and average nits from HDR10+ metadata: the largest of the `maxscl`
+/// components and `average_maxrgb`, both relative to 10,000 nits.
+fn hdr10_plus_luminance(entry: &HashMap<String, String>) -> Option<(f64, f64)> {
+  let values = |name: &str| {
+    entry
+      .iter()
+      .filter(|(key, _)| key.rsplit('.').next().is_some_and(|key| key.starts_with(name)))
+      .filter_map(|(_, value)| rational(value))
+      .collect::<Vec<_>>()
+  };
+  let max = values("maxscl").into_iter().reduce(f64::max)?;
+  let avg = values("average_maxrgb").into_iter().reduce(f64::max)?;
+  Some((max * 10000.0, avg * 10000.0))
+}
+
+fn hdr_stream(
+  num: i32,
+  media_info: &HashMap<String, String>,
+  probe: Option<&VideoStream>,
+  tally: Option<&StreamTally>,
+) -> HdrStream {
+  let get = |property: &str| {
+    media_info
+      .get(property)
+      .filter(|value| !value.trim().is_empty())
+      .cloned()
+  };
+  let probe_get = |key: &str| probe.and_then(|probe| probe.fields.get(key)).cloned();
+  let side_data = |kind: &str| {
+    probe.and_then(|probe| {
+      probe
+        .side_data
+        .iter()
+        .find(|entry| entry.get("side_data_type").is_some_and(|value| value.contains(kind)))
+    })
+  };
+  let content_light = side_data("Content light level");
+  let hdr_format = get("HDR_Format");
+  let compatibility = get("HDR_Format_Compatibility");
+  let hdr10_plus_frames = tally.map(|tally| tally.hdr10_plus_frames).unwrap_or_default();
+  let hdr10_plus = hdr10_plus_frames > 0
+    || hdr_format.as_ref().is_some_and(|format| format.contains("2094 App 4"))
+    || compatibility
+      .as_ref()
+      .is_some_and(|compatibility| compatibility.contains("HDR10+"));
+  let transfer_name = get("transfer_characteristics").or_else(|| probe_get("color_transfer"));
+  HdrStream {
+    num,
+    transfer: transfer_name.as_deref().map(transfer).unwrap_or(HdrTransfer::Sdr),
+    transfer_name,
+    color_primaries: get("colour_primaries").or_else(|| probe_get("color_primaries")),
+    matrix_coefficients: get("matrix_coefficients").or_else(|| probe_get("color_space")),
+    bit_depth: get("BitDepth")
+      .or_else(|| probe_get("bits_per_raw_sample"))
+      .and_then(|depth| depth.parse().ok()),
+    mastering_display: merge_mastering_display(
+      side_data("Mastering display")
+        .map(mastering_display)
+        .or_else(|| tally.and_then(|tally| tally.mastering_display.clone())),
+      get("MasteringDisplay_ColorPrimaries"),
+      get("MasteringDisplay_Luminance"),
+    ),
+    max_cll: get("MaxCLL")
+      .and_then(|value| nits(&value))
+      .or_else(|| content_light.and_then(|entry| field(entry, "max_content")))
+      .or_else(|| tally.and_then(|tally| tally.max_cll)),
+    max_fall: get("MaxFALL")
+      .and_then(|value| nits(&value))
+      .or_else(|| content_light.and_then(|entry| field(entry, "max_average")))
+      .or_else(|| tally.and_then(|tally| tally.max_fall)),
+    dolby_vision: dolby_vision(
+      media_info,
+      side_data("DOVI configuration"),
+      tally.map(|tally| tally.rpu_frames).unwrap_or_default(),
+    ),
+    hdr_format,
+    compatibility,
+    hdr10_plus,
+    hdr10_plus_frames,
+    decoded_frames: tally.map(|tally| tally.frames).unwrap_or_default(),
+    luminance: tally.and_then(StreamTally::luminance),
+  }
+}
+
+/// The mastering display from a side data entry; chromaticities and
+/// luminance are printed as rationals like `34000/50000`.
+fn mastering_display(entry: &HashMap<String, String>) -> HdrMasteringDisplay {
+  let point = |color: &str| {
+    Some([
+      field(entry, &format!("{color}_x"))?,
+      field(entry, &format!("{color}_y"))?,
+    ])
+  };
+  HdrMasteringDisplay {
+    primaries: None,
+    red: point("red"),
+    green: point("green"),
+    blue: point("blue"),
+    white_point: point("white_point"),
+    min_luminance: field(entry, "min_luminance"),
+    max_luminance: field(entry, "max_luminance"),
+  }
+}
+
+/// The ffprobe mastering display with MediaInfo's name for its primaries and,
+/// when ffprobe has none, MediaInfo's `min: 0.0050 cd/m2, max: 1000 cd/m2`.
+fn merge_mastering_display(
+  probe: Option<HdrMasteringDisplay>,
+  primaries: Option<String>,
+  luminance: Option<String>,
+) -> Option<HdrMasteringDisplay> {
+  if probe.is_none() && primaries.is_none() && luminance.is_none() {
+    return None;
+  }
+  let mut display = probe.unwrap_or(HdrMasteringDisplay {
+    primaries: None,
+    red: None,
+    green: None,
+    blue: None,
+    white_point: None,
+    min_luminance: None,
+    max_luminance: None,
+  });
+  let luminance_part = |name: &str| {
+    luminance
+      .as_deref()?
+      .split(',')
+      .find_map(|part| part.trim().strip_prefix(name))
+      .and_then(nits)
+  };
+  display.min_luminance = display.min_luminance.or_else(|| luminance_part("min:"));
+  display.max_luminance = display.max_luminance.or_else(|| luminance_part("max:"));
+  display.primaries = primaries.or_else(|| {
+    let coordinates = [display.red?, display.green?, display.blue?];
+    NAMED_PRIMARIES
+      .iter()
+      .find(|(_, named)| {
+        named
+          .iter()
+          .zip(coordinates.iter())
+          .all(|(named, point)| (named[0] - point[0]).abs() < 0.005 && (named[1] - point[1]).abs() < 0.005)
+      })
+      .map(|(name, _)| name.to_string())
+  });
+  Some(display)
+}
+
+/// The leading number of a MediaInfo value like `1000 cd/m2`.
+fn nits(value: &str) -> Option<f64> {
+  value.split_whitespace().next()?.parse().ok()
+}
+
+/// Nits from a normalized PQ (SMPTE ST 2084) signal value.
+fn pq_to_nits(signal: f64) -> f64 {
+  const M1: f64 = 2610.0 / 16384.0;
+  const M2: f64 = 2523.0 / 4096.0 * 128.0;
+  const C1: f64 = 3424.0 / 4096.0;
+  const C2: f64 = 2413.0 / 4096.0 * 32.0;
+  const C3: f64 = 2392.0 / 4096.0 * 32.0;
+  let power = signal.clamp(0.0, 1.0).powf(1.0 / M2);
+  10000.0 * ((power - C1).max(0.0) / (C2 - C3 * power)).powf(1.0 / M1)
+}
+
+pub fn properties() -> Vec<StreamProperty> {
+  PROPERTIES
+    .iter()
+    .map(|property| StreamProperty {
+      stream: MediaInfoStreamKind::Video,
+      property: property.to_string(),
+    })
+    .collect()
+}
+
+/// A number printed as `numerator/denominator` or plainly.
+fn rational(value: &str) -> Option<f64> {
+  match value.split_once('/') {
+    Some((numerator, denominator)) => {
+      let denominator: f64 = denominator.trim().parse().ok()?;
+      (denominator != 0.0).then_some(numerator.trim().parse::<f64>().ok()? / denominator)
+    }
+    None => value.trim().parse().ok(),
+  }
+}
+
+/// PQ, HLG or SDR from a transfer name as MediaInfo (`PQ`, `HLG`) or ffprobe
+/// (`smpte2084`, `arib-std-b67`) reports it.
+fn transfer(name: &str) -> HdrTransfer {
+  let name = name.to_ascii_lowercase();
+  if name.contains("pq") || name.contains("2084") {
+    HdrTransfer::Pq
+  } else if name.contains("hlg") || name.contains("b67") {
+    HdrTransfer::Hlg
+  } else {
+    HdrTransfer::Sdr
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::protocol::PropertySource;
+
+  fn entry(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+      .iter()
+      .map(|(key, value)| (key.to_string(), value.to_string()))
+      .collect()
+  }
+
+  #[test]
+  fn test_analyze_combines_media_info_and_ffprobe() {
+    let properties = vec![StreamPropertyMap {
+      stream: MediaInfoStreamKind::Video,
+      num: 0,
+      property_map: entry(&[
+        ("HDR_Format", "Dolby Vision / SMPTE ST 2086"),
+        ("HDR_Format_Profile", "dvhe.08 / "),
+        ("HDR_Format_Level", "06 / "),
+        ("HDR_Format_Settings", "BL+RPU / "),
+        ("HDR_Format_Compatibility", "HDR10 / HDR10"),
+        ("transfer_characteristics", "PQ"),
+        ("MasteringDisplay_Luminance", "min: 0.0050 cd/m2, max: 1000 cd/m2"),
+        ("MaxCLL", "1000 cd/m2"),
+      ]),
+      source: PropertySource::MediaInfo,
+    }];
+    let streams = vec![VideoStream {
+      index: 0,
+      fields: entry(&[("color_transfer", "smpte2084")]),
+      side_data: vec![entry(&[
+        ("side_data_type", "Mastering display metadata"),
+        ("red_x", "34000/50000"),
+        ("red_y", "16000/50000"),
+        ("green_x", "13250/50000"),
+        ("green_y", "34500/50000"),
+        ("blue_x", "7500/50000"),
+        ("blue_y", "3000/50000"),
+      ])],
+    }];
+    let mut tally = Tally::default();
+    for (time, max_pq) in [(0.0, "3079"), (0.04, "3079"), (0.08, "2081")] {
+      tally.add(FrameSideData {
+        index: 0,
+        time: Some(time),
+        side_data: vec![entry(&[
+          ("side_data_type", "Dolby Vision Metadata"),
+          ("dm_data.0.level", "1"),
+          ("dm_data.0.max_pq", max_pq),
+          ("dm_data.0.avg_pq", "1229"),
+        ])],
+      });
+    }
+    let report = analyze("a.mkv", FfprobeFrames::All, &properties, &streams, &tally).unwrap();
+    let stream = &report.streams[0];
+    assert_eq!(HdrTransfer::Pq, stream.transfer);
+    let display = stream.mastering_display.as_ref().unwrap();
+    assert_eq!(Some("Display P3"), display.primaries.as_deref());
+    assert_eq!(Some(0.005), display.min_luminance);
+    assert_eq!(Some(1000.0), display.max_luminance);
+    assert_eq!(Some(1000.0), stream.max_cll);
+    let dolby_vision = stream.dolby_vision.as_ref().unwrap();
+    assert_eq!((Some(8), Some(6)), (dolby_vision.profile, dolby_vision.level));
+    assert!(dolby_vision.rpu && !dolby_vision.enhancement_layer);
+    assert_eq!(3, dolby_vision.rpu_frames);
+    assert!(!stream.hdr10_plus);
+    let luminance = stream.luminance.as_ref().unwrap();
+    assert_eq!(2, luminance.scenes.len());
+    assert_eq!(2, luminance.scenes[0].frames);
+    assert!((luminance.max_nits - 1000.0).abs() < 5.0);
+    assert!((luminance.scenes[1].max_nits - 100.0).abs() < 2.0);
+  }
+
+  #[test]
+  fn test_analyze_no_video() {
+    let error = analyze("a.m4a", FfprobeFrames::None, &[], &[], &Tally::default()).unwrap_err();
+    assert!(error.to_string().starts_with("HDR_NO_VIDEO:"));
+  }
+
+  #[test]
+  fn test_hdr10_plus_luminance() {
+    let frame = entry(&[
+      ("side_data_type", "HDR Dynamic Metadata SMPTE2094-40 (HDR10+)"),
+      ("maxscl", "40000/100000"),
+      ("average_maxrgb", "1000/100000"),
+    ]);
+    assert_eq!(Some((4000.0, 100.0)), hdr10_plus_luminance(&frame));
+    assert_eq!(HdrTransfer::Hlg, transfer("arib-std-b67"));
+    assert_eq!(HdrTransfer::Sdr, transfer("BT.709"));
+  }
+}
@@ -18,24 +18,51 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
+mod analysis;
+mod audio;
 mod batchmkvextract;
 mod bd;
 mod bdmaster;
+mod bitmap_font;
+mod bitrate;
+mod capture;
+mod chart;
+mod clip;
+mod compare;
 mod config;
 mod constants;
+mod contact_sheet;
 mod context_menu;
 mod controller;
+mod crop;
+mod encoder;
 mod ffmpeg;
+mod ffprobe;
+mod gop;
+mod hdr;
+mod loudness;
 mod media_info;
 mod mkvtoolnix;
 mod mpchc;
 mod protocol;
+mod qc;
+mod quality;
+mod scenes;
+mod server;
+mod spectrogram;
+mod spreadsheet;
 mod streams;
 #[cfg(target_os = "windows")]
 mod taskbar;
+mod tools;
+mod validation;
+mod watch;
 mod window;
 
-use protocol::{FfmpegCaptureState, MkvextractState, MkvmergeState, TrimOptions, UpdateCheckResult, UpdateCheckState};
+use protocol::{
+  FfmpegAnalysisState, FfmpegCaptureState, MkvextractState, MkvmergeState, TrimOptions, UpdateCheckResult,
+  UpdateCheckState,
+};
 
 #[tauri::command]
 async fn are_extensions_context_menu_registered(extensions: Vec<String>) -> Result<bool, String> {
@@ -48,6 +75,31 @@ async fn are_extensions_context_menu_registered(extensions: Vec<String>) -> Resu
 // Tauri command handlers (IPC entry points). Each one is an async wrapper that
 // delegates to a `controller` method; keep them ordered alphabetically by name.
 
+#[tauri::command]
+async fn analyze_bitrate(file: String, window: f64) -> Result<protocol::BitrateGraph, String> {
+  log::debug!("analyze_bitrate({}, {})", file, window);
+  controller::analyze_bitrate(file, window).await.map_err(convert_error)
+}
+
+#[tauri::command]
+async fn analyze_gop(file: String, frames: protocol::FfprobeFrames) -> Result<protocol::GopReport, String> {
+  log::debug!("analyze_gop({}, {:?})", file, frames);
+  controller::analyze_gop(file, frames).await.map_err(convert_error)
+}
+
+#[tauri::command]
+async fn analyze_hdr(file: String, frames: protocol::FfprobeFrames) -> Result<protocol::HdrReport, String> {
+  log::debug!("analyze_hdr({}, {:?})", file, frames);
+  controller::analyze_hdr(file, frames).await.map_err(convert_error)
+}
+
+#[tauri::command]
+async fn cancel_ffmpeg_analysis(window: tauri::Window, state: tauri::State<'_, FfmpegAnalysisState>) -> Result<(), String> {
+  log::debug!("cancel_ffmpeg_analysis({})", window.label());
+  controller::cancel_child(&window, &state.children).await;
+  Ok(())
+}
+
 #[tauri::command]
 async fn cancel_ffmpeg_capture(window: tauri::Window, state: tauri::State<'_, FfmpegCaptureState>) -> Result<(), String> {
   log::debug!("cancel_ffmpeg_capture({})", window.label());
@@ -77,10 +129,100 @@ async fn capture_ffmpeg_frame(file: String, position_seconds: f64, max_width: u3
     .map_err(convert_error)
 }
 
+#[tauri::command]
+async fn compare_encoder_settings(
+  file: String,
+  other: Option<String>,
+) -> Result<protocol::EncoderComparison, String> {
+  log::debug!("compare_encoder_settings({}, {:?})", file, other);
+  controller::compare_encoder_settings(file, other)
+    .await
+    .map_err(convert_error)
+}
+
+#[tauri::command]
+async fn compare_frames(
+  window: tauri::Window,
+  reference: String,
+  distorted: String,
+  options: protocol::FrameCompareOptions,
+  state: tauri::State<'_, FfmpegCaptureState>,
+) -> Result<protocol::FrameComparison, String> {
+  log::debug!("compare_frames({}, {}, {:?})", reference, distorted, options);
+  controller::compare_frames(window, reference, distorted, options, state.cancels.clone())
+    .await
+    .map_err(convert_error)
+}
+
+#[tauri::command]
+async fn compare_with_ffprobe(
+  file: String,
+  frames: protocol::FfprobeFrames,
+) -> Result<protocol::ProbeComparison, String> {
+  log::debug!("compare_with_ffprobe({}, {:?})", file, frames);
+  controller::compare_with_ffprobe(file, frames)
+    .await
+    .map_err(convert_error)
+}
+
 fn convert_error(error: anyhow::Error) -> String {
   error.to_string()
 }
 
+#[tauri::command]
+async fn create_contact_sheet(
+  file: String,
+  options: protocol::ContactSheetOptions,
+) -> Result<protocol::ContactSheetResult, String> {
+  log::debug!("create_contact_sheet({}, {:?})", file, options);
+  controller::create_contact_sheet(file, options)
+    .await
+    .map_err(convert_error)
+}
+
+#[tauri::command]
+async fn create_preview_clip(
+  window: tauri::Window,
+  file: String,
+  options: protocol::ClipOptions,
+  state: tauri::State<'_, FfmpegCaptureState>,
+) -> Result<protocol::ClipResult, String> {
+  log::debug!("create_preview_clip({}, {:?})", file, options);
+  controller::create_preview_clip(window, file, options, state.children.clone())
+    .await
+    .map_err(convert_error)
+}
+
+#[tauri::command]
+async fn detect_crop(file: String) -> Result<protocol::CropDetection, String> {
+  log::debug!("detect_crop({})", file);
+  controller::detect_crop(file).await.map_err(convert_error)
+}
+
+#[tauri::command]
+async fn export_audio(
+  window: tauri::Window,
+  file: String,
+  options: protocol::AudioExportOptions,
+  state: tauri::State<'_, FfmpegCaptureState>,
+) -> Result<protocol::AudioExportResult, String> {
+  log::debug!("export_audio({}, {:?})", file, options);
+  controller::export_audio(window, file, options, state.children.clone())
+    .await
+    .map_err(convert_error)
+}
+
+#[tauri::command]
+async fn export_spreadsheet(
+  files: Vec<String>,
+  options: protocol::SpreadsheetExportOptions,
+) -> Result<protocol::SpreadsheetExportResult, String> {
+  log::debug!("export_spreadsheet({:?}, {:?})", files, options);
+  controller::export_spreadsheet(files, options)
+    .await
+    .map_err(convert_error)
+}
+
 #[tauri::command]
 async fn get_about() -> Result<protocol::About, String> {
   log::debug!("get_about");
@@ -112,12 +254,30 @@ async fn get_bdmaster_status(path: String, check_running: bool) -> Result<protoc
     .map_err(convert_error)
 }
 
+#[tauri::command]
+async fn get_capture_points(
+  file: String,
+  source: protocol::CapturePointSource,
+  timestamps: Vec<f64>,
+) -> Result<Vec<protocol::CapturePoint>, String> {
+  log::debug!("get_capture_points({}, {:?}, {:?})", file, source, timestamps);
+  controller::get_capture_points(file, source, timestamps)
+    .await
+    .map_err(convert_error)
+}
+
 #[tauri::command]
 async fn get_config() -> Result<config::Config, String> {
   log::debug!("get_config");
   controller::get_config().await.map_err(convert_error)
 }
 
+#[tauri::command]
+async fn get_ffmpeg_candidates() -> Result<Vec<protocol::ToolCandidate>, String> {
+  log::debug!("get_ffmpeg_candidates");
+  controller::get_ffmpeg_candidates().await.map_err(convert_error)
+}
+
 #[tauri::command]
 async fn get_ffmpeg_status(path: String) -> Result<protocol::FfmpegStatus, String> {
   log::debug!("get_ffmpeg_status({})", path);
@@ -142,6 +302,12 @@ async fn get_mkv_tracks(file: String) -> Result<Vec<protocol::MkvTrack>, String>
   controller::get_mkv_tracks(file).await.map_err(convert_error)
 }
 
+#[tauri::command]
+async fn get_mkvtoolnix_candidates() -> Result<Vec<protocol::ToolCandidate>, String> {
+  log::debug!("get_mkvtoolnix_candidates");
+  controller::get_mkvtoolnix_candidates().await.map_err(convert_error)
+}
+
 #[tauri::command]
 async fn get_mkvtoolnix_status(path: String, check_running: bool) -> Result<protocol::MkvToolNixStatus, String> {
   log::debug!("get_mkvtoolnix_status({}, {})", path, check_running);
@@ -233,6 +399,32 @@ async fn register_folder_context_menu() -> Result<(), String> {
   controller::register_folder_context_menu().await.map_err(convert_error)
 }
 
+#[tauri::command]
+async fn render_bitrate_graph(
+  graph: protocol::BitrateGraph,
+  output: String,
+  width: u32,
+  height: u32,
+) -> Result<(), String> {
+  log::debug!("render_bitrate_graph({}, {}x{})", output, width, height);
+  controller::render_bitrate_graph(graph, output, width, height)
+    .await
+    .map_err(convert_error)
+}
+
+#[tauri::command]
+async fn render_spectrogram(
+  window: tauri::Window,
+  file: String,
+  options: protocol::SpectrogramOptions,
+  state: tauri::State<'_, FfmpegAnalysisState>,
+) -> Result<protocol::SpectrogramReport, String> {
+  log::debug!("render_spectrogram({}, {:?})", file, options);
+  controller::render_spectrogram(window, file, options, state.children.clone())
+    .await
+    .map_err(convert_error)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   env_logger::init();
@@ -246,6 +438,9 @@ pub fn run() {
   tauri::async_runtime::set(runtime.handle().clone());
 
   tauri::Builder::default()
+    // Registered first so a second launch hands its paths over and exits
+    // before any other plugin or window is set up.
+    .plugin(tauri_plugin_single_instance::init(window::on_second_instance))
     .manage(MkvextractState {
       children: Arc::new(Mutex::new(HashMap::new())),
     })
@@ -256,6 +451,9 @@ pub fn run() {
       children: Arc::new(Mutex::new(HashMap::new())),
       cancels: Arc::new(Mutex::new(HashMap::new())),
     })
+    .manage(FfmpegAnalysisState {
+      children: Arc::new(Mutex::new(HashMap::new())),
+    })
     .manage(UpdateCheckState {
       result: Arc::new(Mutex::new(None)),
     })
@@ -265,20 +463,35 @@ pub fn run() {
     .setup(window::setup)
     .on_window_event(window::on_window_event)
     .invoke_handler(tauri::generate_handler![
+      analyze_bitrate,
+      analyze_gop,
+      analyze_hdr,
       are_extensions_context_menu_registered,
+      cancel_ffmpeg_analysis,
       cancel_ffmpeg_capture,
       cancel_mkvextract,
       cancel_mkvmerge,
       capture_ffmpeg_frame,
+      compare_encoder_settings,
+      compare_frames,
+      compare_with_ffprobe,
+      create_contact_sheet,
+      create_preview_clip,
+      detect_crop,
+      export_audio,
+      export_spreadsheet,
       get_about,
       get_batchmkvextract_status,
       get_bd_status,
       get_bdmaster_status,
+      get_capture_points,
       get_config,
+      get_ffmpeg_candidates,
       get_ffmpeg_status,
       get_files,
       get_launch_args,
       get_mkv_tracks,
+      get_mkvtoolnix_candidates,
       get_mkvtoolnix_status,
       get_mpchc_status,
       get_parameters,
@@ -292,9 +505,15 @@ pub fn run() {
       open_mpchc,
       register_extensions_context_menu,
       register_folder_context_menu,
+      render_bitrate_graph,
+      render_spectrogram,
       run_ffmpeg_capture,
+      run_loudness_analysis,
       run_mkvextract,
       run_mkvmerge,
+      run_qc_analysis,
+      run_quality_comparison,
+      run_scene_detection,
       set_config,
       skip_version,
       suggest_merge_output_path,
@@ -332,6 +551,18 @@ async fn run_ffmpeg_capture(
   .map_err(convert_error)
 }
 
+#[tauri::command]
+async fn run_loudness_analysis(
+  window: tauri::Window,
+  file: String,
+  state: tauri::State<'_, FfmpegAnalysisState>,
+) -> Result<protocol::LoudnessReport, String> {
+  log::debug!("run_loudness_analysis({})", file);
+  controller::run_loudness_analysis(window, file, state.children.clone())
+    .await
+    .map_err(convert_error)
+}
+
 #[tauri::command]
 async fn run_mkvextract(
   window: tauri::Window,
@@ -357,6 +588,53 @@ async fn run_mkvmerge(
     .map_err(convert_error)
 }
 
+#[tauri::command]
+async fn run_qc_analysis(
+  window: tauri::Window,
+  file: String,
+  options: protocol::QcOptions,
+  state: tauri::State<'_, FfmpegAnalysisState>,
+) -> Result<protocol::QcTimeline, String> {
+  log::debug!("run_qc_analysis({}, {:?})", file, options);
+  controller::run_qc_analysis(window, file, options, state.children.clone())
+    .await
+    .map_err(convert_error)
+}
+
+#[tauri::command]
+async fn run_quality_comparison(
+  window: tauri::Window,
+  reference: String,
+  distorted: String,
+  options: protocol::QualityOptions,
+  state: tauri::State<'_, FfmpegCaptureState>,
+) -> Result<protocol::QualityReport, String> {
+  log::debug!("run_quality_comparison({}, {}, {:?})", reference, distorted, options);
+  controller::run_quality_comparison(
+    window,
+    reference,
+    distorted,
+    options,
+    state.children.clone(),
+    state.cancels.clone(),
+  )
+  .await
+  .map_err(convert_error)
+}
+
+#[tauri::command]
+async fn run_scene_detection(
+  window: tauri::Window,
+  file: String,
+  options: protocol::SceneOptions,
+  state: tauri::State<'_, FfmpegAnalysisState>,
+) -> Result<protocol::SceneChapters, String> {
+  log::debug!("run_scene_detection({}, {:?})", file, options);
+  controller::run_scene_detection(window, file, options, state.children.clone())
+    .await
+    .map_err(convert_error)
+}
+
 #[tauri::command]
 async fn set_config(config: config::Config) -> Result<config::Config, String> {
   log::debug!("set_config({:?})", config);
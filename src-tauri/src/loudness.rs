@@ -0,0 +1,261 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+
+use crate::analysis;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{
+  LoudnessCheck, LoudnessMeasurement, LoudnessReport, LoudnessTarget, StreamProperty, StreamPropertyMap,
+};
+
+const PROPERTIES: &[&str] = &["Duration", "AudioCount"];
+/// Each target's integrated loudness (LUFS/LKFS), its tolerance (LU) and the
+/// highest true peak allowed (dBTP).
+const TARGETS: &[(LoudnessTarget, f64, f64, f64)] = &[
+  (LoudnessTarget::EbuR128, -23.0, 0.5, -1.0),
+  (LoudnessTarget::AtscA85, -24.0, 2.0, -2.0),
+  (LoudnessTarget::Streaming14, -14.0, 1.0, -1.0),
+  (LoudnessTarget::Streaming16, -16.0, 1.0, -1.0),
+];
+
+/// What one `ebur128` instance logged: the running maxima of its per-frame
+/// lines and the values of its final summary.
+#[derive(Debug, Default)]
+struct Meter {
+  momentary_max: Option<f64>,
+  short_term_max: Option<f64>,
+  integrated: Option<f64>,
+  lra: Option<f64>,
+  true_peak: Option<f64>,
+}
+
+/// Measure every audio stream of `file` in a single ffmpeg pass. `properties`
+/// are the `properties()` of `file`. `run` executes the pass and returns its
+/// stderr (see `ffmpeg::run_analysis`).
+pub fn analyze(
+  file: &str,
+  properties: &[StreamPropertyMap],
+  run: impl FnOnce(&[String], f64) -> Result<String>,
+) -> Result<LoudnessReport> {
+  let get = |name: &str| analysis::general_f64(properties, name);
+  let duration = get("Duration") / 1000.0;
+  let streams = get("AudioCount") as usize;
+  if streams == 0 {
+    return Err(anyhow::anyhow!("LOUDNESS_NO_AUDIO:{}", file));
+  }
+  let stderr = run(&args(file, streams), duration)?;
+  let meters = parse(&stderr, streams);
+  Ok(LoudnessReport {
+    duration,
+    streams: meters
+      .into_iter()
+      .enumerate()
+      .map(|(num, meter)| measurement(num as u32, meter))
+      .collect(),
+  })
+}
+
+/// The ffmpeg arguments (after the global options) that meter each of the
+/// `streams` audio streams with its own `ebur128` instance, named
+/// `Parsed_ebur128_<n>` in the log, and discard the output.
+pub fn args(file: &str, streams: usize) -> Vec<String> {
+  let graph = (0..streams)
+    .map(|num| format!("[0:a:{num}]ebur128=peak=true[a{num}]"))
+    .collect::<Vec<_>>()
+    .join(";");
+  let mut args = vec!["-i".to_string(), file.to_string(), "-filter_complex".to_string(), graph];
+  for num in 0..streams {
+    args.extend([
+      "-map".to_string(),
+      format!("[a{num}]"),
+      "-f".to_string(),
+      "null".to_string(),
+      "-".to_string(),
+    ]);
+  }
+  args
+}
+
+/// Integrated loudness (LUFS/LKFS) and true peak ceiling (dBTP) of `target`.
+pub fn levels(target: LoudnessTarget) -> (f64, f64) {
+  TARGETS
+    .iter()
+    .find(|(candidate, ..)| *candidate == target)
+    .map(|(_, integrated, _, true_peak)| (*integrated, *true_peak))
+    .unwrap_or((-23.0, -1.0))
+}
+
+fn measurement(num: u32, meter: Meter) -> LoudnessMeasurement {
+  let checks = TARGETS
+    .iter()
+    .map(|(target, integrated, tolerance, max_true_peak)| {
+      let integrated_ok = meter
+        .integrated
+        .is_some_and(|value| (value - integrated).abs() <= *tolerance);
+      // A stream with no measurable peak (digital silence) can't exceed it.
+      let true_peak_ok = meter.true_peak.is_none_or(|value| value <= *max_true_peak);
+      LoudnessCheck {
+        target: *target,
+        integrated_ok,
+        true_peak_ok,
+        passed: integrated_ok && true_peak_ok,
+      }
+    })
+    .collect();
+  LoudnessMeasurement {
+    num,
+    integrated: meter.integrated,
+    lra: meter.lra,
+    true_peak: meter.true_peak,
+    momentary_max: meter.momentary_max,
+    short_term_max: meter.short_term_max,
+    checks,
+  }
+}
+
+/// Parse the log of `streams` `ebur128` instances. Per-frame lines carry the
+/// instance's tag; the summary is one multi-line message, so only its first
+/// line does and the lines after it belong to the last tagged instance.
+fn parse(stderr: &str, streams: usize) -> Vec<Meter> {
+  let mut meters: Vec<Meter> = (0..streams).map(|_| Meter::default()).collect();
+  let mut current: Option<usize> = None;
+  let mut section = "";
+  for line in stderr.lines() {
+    if let Some(rest) = line.split("[Parsed_ebur128_").nth(1) {
+      current = rest
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .and_then(|index| index.parse::<usize>().ok())
+        .filter(|index| *index < streams);
+      section = "";
+      if let Some(meter) = current.and_then(|index| meters.get_mut(index)) {
+        if let Some(momentary) = value_after(line, "M:") {
+          meter.momentary_max = Some(meter.momentary_max.map_or(momentary, |max| max.max(momentary)));
+        }
+        if let Some(short_term) = value_after(line, "S:") {
+          meter.short_term_max = Some(meter.short_term_max.map_or(short_term, |max| max.max(short_term)));
+        }
+      }
+      continue;
+    }
+    let Some(meter) = current.and_then(|index| meters.get_mut(index)) else {
+      continue;
+    };
+    let trimmed = line.trim();
+    match trimmed {
+      "Integrated loudness:" | "Loudness range:" | "True peak:" => section = trimmed,
+      _ => match section {
+        "Integrated loudness:" if trimmed.starts_with("I:") => meter.integrated = value_after(trimmed, "I:"),
+        "Loudness range:" if trimmed.starts_with("LRA:") => meter.lra = value_after(trimmed, "LRA:"),
+        "True peak:" if trimmed.starts_with("Peak:") => meter.true_peak = value_after(trimmed, "Peak:"),
+        _ => {}
+      },
+    }
+  }
+  meters
+}
+
+/// The MediaInfo properties `analyze` needs.
+pub fn properties() -> Vec<StreamProperty> {
+  analysis::properties(MediaInfoStreamKind::General, PROPERTIES)
+}
+
+/// The finite number right after `key`, e.g. `M: -22.1` or `Peak:  -1.2 dBFS`.
+/// `-inf` and ebur128's -120.7 floor for not-yet-measured windows read as none.
+fn value_after(line: &str, key: &str) -> Option<f64> {
+  let rest = line[line.find(key)? + key.len()..].trim_start();
+  let value: f64 = rest.split_whitespace().next()?.parse().ok()?;
+  (value.is_finite() && value > -120.0).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const LOG: &str = "\
+[Parsed_ebur128_0 @ 0x5601] t: 0.4       TARGET:-23 LUFS    M:-120.7 S:-120.7     I: -70.0 LUFS       LRA:   0.0 LU  FTPK: -9.1 dBFS  TPK: -9.1 dBFS
+[Parsed_ebur128_1 @ 0x5602] t: 0.4       TARGET:-23 LUFS    M: -18.5 S:-120.7     I: -18.5 LUFS       LRA:   0.0 LU  FTPK: -3.0 dBFS  TPK: -3.0 dBFS
+[Parsed_ebur128_0 @ 0x5601] t: 3.0       TARGET:-23 LUFS    M: -21.7 S: -22.9     I: -23.1 LUFS       LRA:   3.1 LU  FTPK: -2.5 dBFS  TPK: -2.5 dBFS
+[Parsed_ebur128_1 @ 0x5602] t: 3.0       TARGET:-23 LUFS    M: -13.2 S: -14.1     I: -14.2 LUFS       LRA:   2.0 LU  FTPK: -0.4 dBFS  TPK: -0.4 dBFS
+[Parsed_ebur128_0 @ 0x5601] Summary:
+
+  Integrated loudness:
+    I:         -23.2 LUFS
+    Threshold: -33.4 LUFS
+
+  Loudness range:
+    LRA:         4.6 LU
+    Threshold:  -43.5 LUFS
+    LRA low:    -26.0 LUFS
+    LRA high:   -21.4 LUFS
+
+  True peak:
+    Peak:        -2.5 dBFS
+[Parsed_ebur128_1 @ 0x5602] Summary:
+
+  Integrated loudness:
+    I:         -14.2 LUFS
+    Threshold: -24.3 LUFS
+
+  Loudness range:
+    LRA:         2.0 LU
+    Threshold:  -34.3 LUFS
+    LRA low:    -15.0 LUFS
+    LRA high:   -13.0 LUFS
+
+  True peak:
+    Peak:        -0.4 dBFS
+";
+
+  #[test]
+  fn test_parse() {
+    let meters = parse(LOG, 2);
+    assert_eq!(meters[0].integrated, Some(-23.2));
+    assert_eq!(meters[0].lra, Some(4.6));
+    assert_eq!(meters[0].true_peak, Some(-2.5));
+    assert_eq!(meters[0].momentary_max, Some(-21.7));
+    assert_eq!(meters[0].short_term_max, Some(-22.9));
+    assert_eq!(meters[1].integrated, Some(-14.2));
+    assert_eq!(meters[1].true_peak, Some(-0.4));
+    assert_eq!(meters[1].momentary_max, Some(-13.2));
+  }
+
+  #[test]
+  fn test_checks() {
+    let meters = parse(LOG, 2);
+    let passed = |meter: Meter| -> Vec<LoudnessTarget> {
+      measurement(0, meter)
+        .checks
+        .into_iter()
+        .filter(|check| check.passed)
+        .map(|check| check.target)
+        .collect()
+    };
+    let mut meters = meters.into_iter();
+    assert_eq!(
+      passed(meters.next().unwrap()),
+      vec![LoudnessTarget::EbuR128, LoudnessTarget::AtscA85]
+    );
+    // -14.2 LUFS meets the streaming level but its -0.4 dBTP peak is too hot.
+    assert!(passed(meters.next().unwrap()).is_empty());
+    assert_eq!(
+      args("in.mkv", 2)[3],
+      "[0:a:0]ebur128=peak=true[a0];[0:a:1]ebur128=peak=true[a1]"
+    );
+  }
+}
@@ -16,15 +16,80 @@
 */
 
 use anyhow::Result;
+use once_cell::sync::Lazy;
 #[cfg(target_os = "macos")]
 use std::cmp::Ordering;
+use std::collections::HashMap;
 #[cfg(target_os = "macos")]
 use std::fs;
 use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
 
-use crate::config;
-use crate::protocol::{MkvToolNixStatus, MkvTrack};
+use crate::config::Config;
+use crate::protocol::{MkvToolNixStatus, MkvToolNixVersion, MkvTrack, ToolCandidate, VersionNumber};
+use crate::tools::{self, Tool};
+
+/// mkvextract 17.0 introduced the syntax with the source file ahead of the
+/// extraction modes, which is the order `spawn_mkvextract` passes them in.
+const MKVEXTRACT_SOURCE_FIRST: VersionNumber = VersionNumber::new(17, 0, 0);
+
+/// Parsed `mkvmerge --version` numbers per binary with the modification time
+/// they were read at.
+type VersionMap = HashMap<PathBuf, (Option<SystemTime>, Option<VersionNumber>)>;
+
+/// An entry is refreshed when the binary's modification time changes, e.g.
+/// after an upgrade in place.
+static VERSIONS: Lazy<Mutex<VersionMap>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// MKVToolNix. The command-line tools are found on `PATH` too, and on macOS a
+/// versioned `/Applications/MKVToolNix-<version>.app` stands in for the
+/// default install location.
+pub struct MkvToolNix;
+
+impl Tool for MkvToolNix {
+  fn name(&self) -> &'static str {
+    "MKVToolNix"
+  }
+
+  fn binaries(&self) -> &'static [&'static str] {
+    &["mkvmerge", "mkvextract"]
+  }
+
+  fn config_path(&self, config: &Config) -> String {
+    config.mkv.mkv_toolnix_path.clone()
+  }
+
+  fn set_config_path(&self, config: &mut Config, path: String) {
+    config.mkv.mkv_toolnix_path = path;
+  }
+
+  fn process_name(&self) -> Option<&'static str> {
+    Some("mkvtoolnix-gui")
+  }
+
+  fn search_path(&self) -> bool {
+    true
+  }
+
+  fn version_args(&self) -> Option<&'static [&'static str]> {
+    Some(&["--version"])
+  }
+
+  #[cfg(target_os = "macos")]
+  fn fallback_dirs(&self, configured: &Path, binaries: &[&str]) -> Vec<PathBuf> {
+    if is_default_macos_mkvtoolnix_path(&configured.to_string_lossy()) {
+      find_latest_versioned_macos_mkvtoolnix_path(binaries).into_iter().collect()
+    } else {
+      Vec::new()
+    }
+  }
+
+  fn flatpak_app_ids(&self) -> &'static [&'static str] {
+    &["org.bunkus.mkvtoolnix-gui"]
+  }
+}
 
 #[cfg(target_os = "macos")]
 fn compare_version_parts(left: &[u32], right: &[u32]) -> Ordering {
@@ -56,14 +121,8 @@ fn derive_attachment_subtype(file_name: &str, content_type: &str) -> String {
   String::new()
 }
 
-struct MkvToolNixResolution {
-  path: PathBuf,
-  auto_detected: bool,
-  found: bool,
-}
-
 #[cfg(target_os = "macos")]
-fn find_latest_versioned_macos_mkvtoolnix_path(tools: &[&str]) -> Option<PathBuf> {
+fn find_latest_versioned_macos_mkvtoolnix_path(binaries: &[&str]) -> Option<PathBuf> {
   let entries = fs::read_dir("/Applications").ok()?;
   let mut latest: Option<(Vec<u32>, PathBuf)> = None;
   for entry in entries.flatten() {
@@ -81,7 +140,7 @@ fn find_latest_versioned_macos_mkvtoolnix_path(tools: &[&str]) -> Option<PathBuf
       continue;
     }
     let mkvtoolnix_path = entry.path().join("Contents").join("MacOS");
-    if !tools.iter().all(|t| has_tool(&mkvtoolnix_path, t)) {
+    if !tools::has_binaries(&mkvtoolnix_path, binaries) {
       continue;
     }
     match &latest {
@@ -96,37 +155,13 @@ fn find_latest_versioned_macos_mkvtoolnix_path(tools: &[&str]) -> Option<PathBuf
   latest.map(|(_, path)| path)
 }
 
-fn find_running_process_dir(exe_name: &str) -> Option<PathBuf> {
-  let sys = sysinfo::System::new_all();
-  for process in sys.processes().values() {
-    let name = process.name().to_string_lossy();
-    if !name.eq_ignore_ascii_case(exe_name) {
-      continue;
-    }
-    if let Some(exe) = process.exe() {
-      if let Some(parent) = exe.parent() {
-        return Some(parent.to_path_buf());
-      }
-    }
-  }
-  None
-}
-
 pub async fn get_mkv_tracks(file: String) -> Result<Vec<MkvTrack>> {
   let path = Path::new(file.as_str());
   validate_path_as_file(path)?;
-  let cfg = config::get_config();
-  let resolution = resolve_mkvtoolnix(&cfg.mkv.mkv_toolnix_path, &["mkvmerge"]);
-  persist_mkvtoolnix_path_if_auto_detected(&resolution)?;
-  let mkvmerge_path = get_tool_path(&resolution.path, "mkvmerge");
-  let mut cmd = std::process::Command::new(&mkvmerge_path);
-  cmd.arg("-J").arg(&file);
-  #[cfg(target_os = "windows")]
-  {
-    use std::os::windows::process::CommandExt;
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-  }
-  let output = cmd
+  let mkvmerge_path = locate("mkvmerge", "MKVMERGE_NOT_AVAILABLE")?;
+  let output = tools::command(&mkvmerge_path)
+    .arg("-J")
+    .arg(&file)
     .output()
     .map_err(|e| anyhow::anyhow!("MKVMERGE_NOT_AVAILABLE:{}: {}", mkvmerge_path.display(), e))?;
   if !output.status.success() {
@@ -194,78 +229,45 @@ pub async fn get_mkv_tracks(file: String) -> Result<Vec<MkvTrack>> {
   Ok(tracks)
 }
 
+pub async fn get_mkvtoolnix_candidates() -> Result<Vec<ToolCandidate>> {
+  Ok(tools::discover(&MkvToolNix))
+}
+
 pub async fn get_mkvtoolnix_status(path: String, check_running: bool) -> Result<MkvToolNixStatus> {
-  if check_running {
-    if let Some(dir) = find_running_process_dir(mkvtoolnix_gui_process_name()) {
-      let has_tools = ["mkvmerge", "mkvextract"].iter().all(|t| has_tool(&dir, t));
-      if has_tools {
-        let path_string = dir.to_string_lossy().to_string();
-        let mut cfg = config::get_config();
-        if cfg.mkv.mkv_toolnix_path != path_string {
-          cfg.mkv.mkv_toolnix_path = path_string.clone();
-          config::set_config(cfg)?;
-        }
-        return Ok(MkvToolNixStatus {
-          found: true,
-          mkv_toolnix_path: path_string,
-        });
-      }
-    }
-  }
-  let trimmed_path = path.trim();
-  if trimmed_path.is_empty() {
-    return Ok(MkvToolNixStatus {
-      found: false,
-      mkv_toolnix_path: String::new(),
-    });
-  }
-  let resolution = resolve_mkvtoolnix(trimmed_path, &["mkvmerge", "mkvextract"]);
-  if resolution.found {
-    persist_mkvtoolnix_path_if_auto_detected(&resolution)?;
-  }
+  let status = tools::status(&MkvToolNix, &path, check_running)?;
   Ok(MkvToolNixStatus {
-    found: resolution.found,
-    mkv_toolnix_path: resolution.path.to_string_lossy().to_string(),
+    found: status.found,
+    mkv_toolnix_path: status.path,
+    version: status.version_output.as_deref().and_then(parse_mkvtoolnix_version),
   })
 }
 
-fn get_tool_path(path: &Path, tool: &str) -> PathBuf {
-  #[cfg(target_os = "windows")]
-  {
-    let exe_path = path.join(format!("{}.exe", tool));
-    if exe_path.exists() && exe_path.is_file() {
-      return exe_path;
-    }
-  }
-  path.join(tool)
-}
-
-fn has_tool(path: &Path, tool: &str) -> bool {
-  let tool_path = path.join(tool);
-  if tool_path.exists() && tool_path.is_file() {
-    return true;
-  }
-  #[cfg(target_os = "windows")]
-  {
-    let tool_exe_path = path.join(format!("{}.exe", tool));
-    if tool_exe_path.exists() && tool_exe_path.is_file() {
-      return true;
-    }
-  }
-  false
-}
-
 #[cfg(target_os = "macos")]
 fn is_default_macos_mkvtoolnix_path(path: &str) -> bool {
   path.trim().trim_end_matches('/') == "/Applications/MKVToolNix.app/Contents/MacOS"
 }
 
-fn mkvtoolnix_gui_process_name() -> &'static str {
-  if cfg!(target_os = "windows") {
-    "mkvtoolnix-gui.exe"
-  } else {
-    "mkvtoolnix-gui"
+/// The path of one MKVToolNix binary, or a `<code>:<path>` error the frontend
+/// recognizes when it can't be found.
+fn locate(binary: &str, code: &str) -> Result<PathBuf> {
+  tools::locate(&MkvToolNix, binary).map_err(|dir| anyhow::anyhow!("{}:{}", code, dir.join(binary).display()))
+}
+
+/// The version number of the MKVToolNix in `dir`, cached per `mkvmerge` binary
+/// so a batch of extracts doesn't run `--version` once per file.
+fn mkvtoolnix_version_number(dir: &Path) -> Option<VersionNumber> {
+  let exe = tools::binary_path(dir, MkvToolNix.binaries()[0])?;
+  let modified = std::fs::metadata(&exe).and_then(|metadata| metadata.modified()).ok();
+  if let Some((cached_modified, number)) = VERSIONS.lock().unwrap().get(&exe)
+    && *cached_modified == modified
+  {
+    return *number;
   }
+  let number = tools::version_output(&MkvToolNix, dir)
+    .and_then(|output| parse_mkvtoolnix_version(&output))
+    .and_then(|version| version.number);
+  VERSIONS.lock().unwrap().insert(exe, (modified, number));
+  number
 }
 
 pub(crate) fn parse_mkvextract_progress(line: &str) -> Option<u32> {
@@ -286,6 +288,15 @@ pub(crate) fn parse_mkvmerge_progress(line: &str) -> Option<u32> {
   parse_mkvextract_progress(line)
 }
 
+/// Parse `mkvmerge --version`, e.g. `mkvmerge v82.0 ('I'm The President') 64-bit`.
+fn parse_mkvtoolnix_version(output: &str) -> Option<MkvToolNixVersion> {
+  let text = output.lines().map(str::trim).find(|line| !line.is_empty())?.to_owned();
+  Some(MkvToolNixVersion {
+    number: tools::parse_version_number(&text),
+    text,
+  })
+}
+
 #[cfg(target_os = "macos")]
 fn parse_version_parts(version: &str) -> Vec<u32> {
   version
@@ -301,20 +312,6 @@ fn parse_version_parts(version: &str) -> Vec<u32> {
     .collect()
 }
 
-fn persist_mkvtoolnix_path_if_auto_detected(resolution: &MkvToolNixResolution) -> Result<()> {
-  if !resolution.auto_detected {
-    return Ok(());
-  }
-  let path = resolution.path.to_string_lossy().to_string();
-  let mut cfg = config::get_config();
-  if cfg.mkv.mkv_toolnix_path == path {
-    return Ok(());
-  }
-  cfg.mkv.mkv_toolnix_path = path;
-  config::set_config(cfg)?;
-  Ok(())
-}
-
 pub(crate) fn read_mkvextract_output<F>(reader: impl Read, mut on_line: F)
 where
   F: FnMut(&str),
@@ -352,74 +349,27 @@ where
   read_mkvextract_output(reader, on_line)
 }
 
-fn resolve_mkvtoolnix(path: &str, tools: &[&str]) -> MkvToolNixResolution {
-  let trimmed_path = path.trim();
-  let configured_path = PathBuf::from(trimmed_path);
-  if tools.iter().all(|t| has_tool(&configured_path, t)) {
-    return MkvToolNixResolution {
-      path: configured_path,
-      auto_detected: false,
-      found: true,
-    };
-  }
-  #[cfg(target_os = "macos")]
-  {
-    if is_default_macos_mkvtoolnix_path(trimmed_path) {
-      if let Some(latest_path) = find_latest_versioned_macos_mkvtoolnix_path(tools) {
-        return MkvToolNixResolution {
-          path: latest_path,
-          auto_detected: true,
-          found: true,
-        };
-      }
-    }
-  }
-  MkvToolNixResolution {
-    path: configured_path,
-    auto_detected: false,
-    found: false,
-  }
-}
-
 pub fn spawn_mkvextract(file: &str, args: &[String]) -> Result<std::process::Child> {
   let path = Path::new(file);
   validate_path_as_file(path)?;
-  let cfg = config::get_config();
-  let resolution = resolve_mkvtoolnix(&cfg.mkv.mkv_toolnix_path, &["mkvextract"]);
-  persist_mkvtoolnix_path_if_auto_detected(&resolution)?;
-  let mkvextract_path = get_tool_path(&resolution.path, "mkvextract");
-  let mut cmd = std::process::Command::new(&mkvextract_path);
-  cmd
+  let mkvextract_path = locate("mkvextract", "MKVEXTRACT_NOT_AVAILABLE")?;
+  let version = mkvextract_path.parent().and_then(mkvtoolnix_version_number);
+  tools::require_version("MKVTOOLNIX_TOO_OLD", "mkvextract", version, MKVEXTRACT_SOURCE_FIRST)?;
+  tools::command(&mkvextract_path)
     .arg(file)
     .args(args)
     .stdout(std::process::Stdio::piped())
-    .stderr(std::process::Stdio::piped());
-  #[cfg(target_os = "windows")]
-  {
-    use std::os::windows::process::CommandExt;
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-  }
-  cmd
+    .stderr(std::process::Stdio::piped())
     .spawn()
     .map_err(|e| anyhow::anyhow!("MKVEXTRACT_NOT_AVAILABLE:{}: {}", mkvextract_path.display(), e))
 }
 
 pub fn spawn_mkvmerge(args: &[String]) -> Result<std::process::Child> {
-  let cfg = config::get_config();
-  let resolution = resolve_mkvtoolnix(&cfg.mkv.mkv_toolnix_path, &["mkvmerge"]);
-  persist_mkvtoolnix_path_if_auto_detected(&resolution)?;
-  let mkvmerge_path = get_tool_path(&resolution.path, "mkvmerge");
-  let mut cmd = std::process::Command::new(&mkvmerge_path);
-  cmd
+  let mkvmerge_path = locate("mkvmerge", "MKVMERGE_NOT_AVAILABLE")?;
+  tools::command(&mkvmerge_path)
     .args(args)
     .stdout(std::process::Stdio::piped())
-    .stderr(std::process::Stdio::piped());
-  #[cfg(target_os = "windows")]
-  {
-    use std::os::windows::process::CommandExt;
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-  }
-  cmd
+    .stderr(std::process::Stdio::piped())
     .spawn()
     .map_err(|e| anyhow::anyhow!("MKVMERGE_NOT_AVAILABLE:{}: {}", mkvmerge_path.display(), e))
 }
@@ -427,22 +377,12 @@ pub fn spawn_mkvmerge(args: &[String]) -> Result<std::process::Child> {
 pub fn spawn_mkvtoolnix_gui(file: &str) -> Result<()> {
   let path = Path::new(file);
   validate_path_as_file(path)?;
-  let cfg = config::get_config();
-  let resolution = resolve_mkvtoolnix(&cfg.mkv.mkv_toolnix_path, &["mkvtoolnix-gui"]);
-  persist_mkvtoolnix_path_if_auto_detected(&resolution)?;
-  let gui_path = get_tool_path(&resolution.path, "mkvtoolnix-gui");
-  let mut cmd = std::process::Command::new(&gui_path);
-  cmd
+  let gui_path = locate("mkvtoolnix-gui", "MKVTOOLNIX_GUI_NOT_AVAILABLE")?;
+  tools::command(&gui_path)
     .arg(file)
     .stdin(std::process::Stdio::null())
     .stdout(std::process::Stdio::null())
-    .stderr(std::process::Stdio::null());
-  #[cfg(target_os = "windows")]
-  {
-    use std::os::windows::process::CommandExt;
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-  }
-  cmd
+    .stderr(std::process::Stdio::null())
     .spawn()
     .map_err(|e| anyhow::anyhow!("MKVTOOLNIX_GUI_NOT_AVAILABLE:{}: {}", gui_path.display(), e))?;
   Ok(())
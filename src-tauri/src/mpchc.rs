@@ -16,113 +16,44 @@
 */
 
 use anyhow::Result;
-use std::path::{Path, PathBuf};
 
-use crate::config;
+use crate::config::Config;
 use crate::protocol::MpcHcStatus;
+use crate::tools::{self, Tool};
 
-const TOOL_STEM: &str = "mpc-hc64";
-const PROCESS_NAME: &str = "mpc-hc64.exe";
+/// MPC-HC, the 64-bit Windows build.
+pub struct MpcHc;
 
-fn binary_path(dir: &Path) -> Option<PathBuf> {
-  let exe = dir.join(format!("{}.exe", TOOL_STEM));
-  if exe.exists() && exe.is_file() {
-    return Some(exe);
+impl Tool for MpcHc {
+  fn name(&self) -> &'static str {
+    "MPC HC"
   }
-  None
-}
 
-fn find_running_process_dir() -> Option<PathBuf> {
-  let sys = sysinfo::System::new_all();
-  for process in sys.processes().values() {
-    let name = process.name().to_string_lossy();
-    if !name.eq_ignore_ascii_case(PROCESS_NAME) {
-      continue;
-    }
-    if let Some(exe) = process.exe() {
-      if let Some(parent) = exe.parent() {
-        return Some(parent.to_path_buf());
-      }
-    }
+  fn binaries(&self) -> &'static [&'static str] {
+    &["mpc-hc64"]
   }
-  None
-}
 
-pub async fn get_mpchc_status(path: String, check_running: bool) -> Result<MpcHcStatus> {
-  if check_running {
-    if let Some(dir) = find_running_process_dir() {
-      if has_executable(&dir) {
-        persist_path(&dir)?;
-        return Ok(MpcHcStatus {
-          found: true,
-          path: dir.to_string_lossy().to_string(),
-        });
-      }
-    }
-  }
-  let trimmed = path.trim();
-  if trimmed.is_empty() {
-    return Ok(MpcHcStatus {
-      found: false,
-      path: String::new(),
-    });
-  }
-  let (resolved, found) = resolve(trimmed);
-  if found {
-    persist_path(&resolved)?;
+  fn config_path(&self, config: &Config) -> String {
+    config.mpc_hc.path.clone()
   }
-  Ok(MpcHcStatus {
-    found,
-    path: resolved.to_string_lossy().to_string(),
-  })
-}
 
-fn has_executable(path: &Path) -> bool {
-  binary_path(path).is_some()
-}
+  fn set_config_path(&self, config: &mut Config, path: String) {
+    config.mpc_hc.path = path;
+  }
 
-fn persist_path(path: &Path) -> Result<()> {
-  let new_path = path.to_string_lossy().to_string();
-  let mut cfg = config::get_config();
-  if cfg.mpc_hc.path == new_path {
-    return Ok(());
+  fn process_name(&self) -> Option<&'static str> {
+    Some("mpc-hc64")
   }
-  cfg.mpc_hc.path = new_path;
-  config::set_config(cfg)?;
-  Ok(())
 }
 
-fn resolve(path: &str) -> (PathBuf, bool) {
-  let trimmed = path.trim();
-  let configured = PathBuf::from(trimmed);
-  if has_executable(&configured) {
-    return (configured, true);
-  }
-  (configured, false)
+pub async fn get_mpchc_status(path: String, check_running: bool) -> Result<MpcHcStatus> {
+  let status = tools::status(&MpcHc, &path, check_running)?;
+  Ok(MpcHcStatus {
+    found: status.found,
+    path: status.path,
+  })
 }
 
 pub fn spawn_mpchc(file: &str) -> Result<()> {
-  let file_path = Path::new(file);
-  if !file_path.exists() {
-    return Err(anyhow::anyhow!("File {} does not exist.", file));
-  }
-  let cfg = config::get_config();
-  let dir = PathBuf::from(&cfg.mpc_hc.path);
-  let bin = binary_path(&dir).ok_or_else(|| anyhow::anyhow!("MPC HC executable not found under {}.", dir.display()))?;
-
-  let mut cmd = std::process::Command::new(&bin);
-  cmd
-    .arg(file)
-    .stdin(std::process::Stdio::null())
-    .stdout(std::process::Stdio::null())
-    .stderr(std::process::Stdio::null());
-  #[cfg(target_os = "windows")]
-  {
-    use std::os::windows::process::CommandExt;
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-  }
-  cmd
-    .spawn()
-    .map_err(|e| anyhow::anyhow!("Failed to launch MPC HC: {}", e))?;
-  Ok(())
+  tools::launch(&MpcHc, file)
 }
@@ -17,8 +17,10 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::{Arc, Mutex};
 
+use crate::config::ConfigAudioPreset;
 use crate::media_info;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -54,6 +56,486 @@ pub struct StreamPropertyMap {
   pub num: i32,
   #[serde(rename = "propertyMap")]
   pub property_map: HashMap<String, String>,
+  #[serde(default)]
+  pub source: PropertySource,
+}
+
+/// The analyzer a `StreamPropertyMap` came from.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum PropertySource {
+  MediaInfo,
+  Ffprobe,
+}
+
+impl Default for PropertySource {
+  fn default() -> Self {
+    Self::MediaInfo
+  }
+}
+
+/// How much of the video ffprobe decodes frame by frame: nothing, a few short
+/// windows spread over the file, or every frame (slow on long files).
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum FfprobeFrames {
+  None,
+  Sampled,
+  All,
+}
+
+/// MediaInfo and ffprobe side by side for one file.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeComparison {
+  pub ffprobe: Vec<StreamPropertyMap>,
+  /// The key fields both tools were asked about, stream by stream.
+  pub fields: Vec<ProbeField>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeField {
+  pub stream: media_info::MediaInfoStreamKind,
+  pub num: i32,
+  pub property: String,
+  #[serde(rename = "mediaInfo")]
+  pub media_info: Option<String>,
+  pub ffprobe: Option<String>,
+  /// Both tools report the field and disagree beyond its tolerance.
+  pub mismatch: bool,
+}
+
+/// Channel layout of an audio export: the source's, or mixed down.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum AudioDownmix {
+  Keep,
+  Stereo,
+  Mono,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AudioExportOptions {
+  pub output: String,
+  /// Audio stream index, as in MediaInfo's and ffmpeg's `a:<num>`.
+  pub stream: u32,
+  pub preset: ConfigAudioPreset,
+  pub downmix: AudioDownmix,
+  /// Loudness target to normalize to with `loudnorm`, None to keep the levels.
+  pub normalize: Option<LoudnessTarget>,
+  /// Range in seconds; an end of 0 runs to the end of the file.
+  pub start: f64,
+  pub end: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioExportResult {
+  pub output: String,
+  pub size: u64,
+  /// Seconds of audio exported.
+  pub duration: f64,
+}
+
+/// A frame to capture by time, and the label its file name is built from.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CapturePoint {
+  pub seconds: f64,
+  pub label: String,
+}
+
+/// What a structure-driven capture takes its frames from.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum CapturePointSource {
+  Chapters,
+  Keyframes,
+  Timestamps,
+}
+
+/// Container of an animated preview clip.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ClipFormat {
+  Gif,
+  Webp,
+  Apng,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClipOptions {
+  pub output: String,
+  pub format: ClipFormat,
+  pub segments: ClipSegments,
+  /// Range in seconds with `ClipSegments::Range`.
+  pub start: f64,
+  pub end: f64,
+  /// Segments of `length` seconds each with `ClipSegments::Even`.
+  pub count: u32,
+  pub length: f64,
+  /// Frames per second of the clip, capped at the source's.
+  pub fps: f64,
+  /// Size limit in pixels; the source is never scaled up.
+  #[serde(rename = "maxWidth")]
+  pub max_width: u32,
+  #[serde(rename = "maxHeight")]
+  pub max_height: u32,
+  /// File size to stay under in bytes, 0 for none.
+  #[serde(rename = "targetSize")]
+  pub target_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipResult {
+  pub output: String,
+  pub size: u64,
+  pub width: u32,
+  pub height: u32,
+  pub fps: f64,
+  pub duration: f64,
+  /// Encodes it took to get under `ClipOptions::target_size`.
+  pub attempts: u32,
+  /// False when the smallest encode tried is still over the target size.
+  #[serde(rename = "targetMet")]
+  pub target_met: bool,
+}
+
+/// Where a preview clip is taken from: one time range, or short segments
+/// spread evenly over the duration and joined.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ClipSegments {
+  Range,
+  Even,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ContactSheetFormat {
+  Png,
+  Jpeg,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ContactSheetOptions {
+  pub output: String,
+  pub format: ContactSheetFormat,
+  pub positions: ContactSheetPositions,
+  /// Frames to take with `ContactSheetPositions::Even`; one per chapter otherwise.
+  pub count: u32,
+  pub columns: u32,
+  /// Width of the whole sheet in pixels.
+  pub width: u32,
+  #[serde(default)]
+  pub header: bool,
+  #[serde(default)]
+  pub timestamps: bool,
+}
+
+/// Where a contact sheet takes its frames: evenly over the duration or at each chapter mark.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ContactSheetPositions {
+  Even,
+  Chapters,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContactSheetResult {
+  pub output: String,
+  pub frames: u32,
+  pub width: u32,
+  pub height: u32,
+}
+
+/// Bitrate over time of the video and audio streams of a file, binned into
+/// windows of `window` seconds from the first packet.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BitrateGraph {
+  pub window: f64,
+  pub duration: f64,
+  pub series: Vec<BitrateSeries>,
+}
+
+/// The bitrate of one stream per window, in kbit/s.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BitrateSeries {
+  pub stream: media_info::MediaInfoStreamKind,
+  /// Index among the streams of its kind, matching `StreamPropertyMap::num`.
+  pub num: i32,
+  pub codec: String,
+  pub points: Vec<f64>,
+  pub average: f64,
+  /// Lowest and highest window; the last, usually partial, window doesn't count.
+  pub min: f64,
+  pub max: f64,
+}
+
+/// A stable crop of the letterbox/pillarbox borders of a video stream, as
+/// found by sampling frames with ffmpeg `cropdetect`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CropDetection {
+  /// Coded size of the stream.
+  pub width: u32,
+  pub height: u32,
+  /// Pixels to remove on each edge.
+  pub left: u32,
+  pub top: u32,
+  pub right: u32,
+  pub bottom: u32,
+  /// Display aspect ratio of the cropped picture.
+  #[serde(rename = "aspectRatio")]
+  pub aspect_ratio: f64,
+  /// Samples the crop was aggregated from.
+  pub samples: u32,
+  /// Samples dropped as too dark to show the borders.
+  pub ignored: u32,
+  /// Share of the samples within a few pixels of the crop on every edge.
+  pub agreement: f64,
+  /// `left,top,right,bottom` for mkvmerge `--cropping`.
+  #[serde(rename = "mkvmergeCropping")]
+  pub mkvmerge_cropping: String,
+  /// `crop=w:h:x:y` for an ffmpeg filter graph.
+  #[serde(rename = "ffmpegCrop")]
+  pub ffmpeg_crop: String,
+}
+
+/// The settings of the first video stream of a file and, when a second file
+/// was given, of that file, with every parameter either of them sets.
+#[derive(Debug, Clone, Serialize)]
+pub struct EncoderComparison {
+  pub settings: EncoderSettings,
+  pub other: Option<EncoderSettings>,
+  pub fields: Vec<EncoderField>,
+}
+
+/// One parameter of two encodes side by side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EncoderField {
+  pub key: String,
+  pub group: EncoderSettingGroup,
+  pub left: Option<String>,
+  pub right: Option<String>,
+  /// Both set it to different values, or only one of them sets it.
+  pub mismatch: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EncoderKind {
+  X264,
+  X265,
+  SvtAv1,
+  Unknown,
+}
+
+/// The preset an encode most likely used. x264 and x265 don't store it, so
+/// it is guessed from the parameters each preset sets; `compared` is 0 when
+/// the encoder stored it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EncoderPreset {
+  pub name: String,
+  pub matched: u32,
+  pub compared: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EncoderRateControl {
+  Crf,
+  Cqp,
+  Abr,
+  Cbr,
+  Vbr,
+  TwoPass,
+}
+
+/// One `key=value` of an encoder settings string.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EncoderSetting {
+  pub key: String,
+  pub value: String,
+  pub group: EncoderSettingGroup,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum EncoderSettingGroup {
+  RateControl,
+  FrameStructure,
+  AdaptiveQuantization,
+  MotionEstimation,
+  Other,
+}
+
+/// MediaInfo `Encoded_Library_Settings`, parsed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EncoderSettings {
+  pub encoder: EncoderKind,
+  /// MediaInfo `Encoded_Library`, e.g. `x264 - core 164 r3095 baee400`.
+  pub library: Option<String>,
+  #[serde(rename = "rateControl")]
+  pub rate_control: Option<EncoderRateControl>,
+  /// The CRF or QP of a constant quality encode.
+  pub quality: Option<f64>,
+  /// The target bitrate of a bitrate driven encode, in kb/s.
+  pub bitrate: Option<f64>,
+  pub preset: Option<EncoderPreset>,
+  pub settings: Vec<EncoderSetting>,
+}
+
+/// Frame types of the decoded frames of a GOP analysis.
+#[derive(Debug, Clone, Serialize)]
+pub struct GopFrameTypes {
+  /// How many frames were decoded: sampled windows or all of them.
+  pub frames: FfprobeFrames,
+  #[serde(rename = "iFrames")]
+  pub i_frames: u32,
+  #[serde(rename = "pFrames")]
+  pub p_frames: u32,
+  #[serde(rename = "bFrames")]
+  pub b_frames: u32,
+  /// I-frames the decoder flags as keyframes (IDR, or CRA/BLA for HEVC).
+  #[serde(rename = "idrFrames")]
+  pub idr_frames: u32,
+  /// I-frames a decoder can't start from.
+  #[serde(rename = "nonIdrIFrames")]
+  pub non_idr_i_frames: u32,
+  #[serde(rename = "maxConsecutiveB")]
+  pub max_consecutive_b: u32,
+  /// The most common GOP in display order, e.g. `IBBPBBPBBP`.
+  pub pattern: Option<String>,
+}
+
+/// Keyframes and GOP structure of the first video stream of a file.
+#[derive(Debug, Clone, Serialize)]
+pub struct GopReport {
+  /// Keyframe timestamps in seconds, in order.
+  pub keyframes: Vec<f64>,
+  pub gops: u32,
+  /// GOPs whose leading frames are shown before their keyframe, so they
+  /// reference the previous GOP.
+  #[serde(rename = "openGops")]
+  pub open_gops: u32,
+  #[serde(rename = "closedGops")]
+  pub closed_gops: u32,
+  /// GOP length in packets and in seconds; the last GOP only counts when it
+  /// is the only one.
+  #[serde(rename = "lengthFrames")]
+  pub length_frames: GopStats,
+  #[serde(rename = "lengthSeconds")]
+  pub length_seconds: GopStats,
+  #[serde(rename = "frameTypes")]
+  pub frame_types: Option<GopFrameTypes>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GopStats {
+  pub min: f64,
+  pub max: f64,
+  pub mean: f64,
+}
+
+/// Dolby Vision of one video stream, from its configuration record or
+/// MediaInfo, and how many decoded frames carried an RPU.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HdrDolbyVision {
+  pub profile: Option<u32>,
+  pub level: Option<u32>,
+  /// The base layer signal compatibility: 1 HDR10, 2 SDR, 4 HLG, 6 Blu-ray HDR10.
+  #[serde(rename = "compatibilityId")]
+  pub compatibility_id: Option<u32>,
+  pub rpu: bool,
+  #[serde(rename = "enhancementLayer")]
+  pub enhancement_layer: bool,
+  #[serde(rename = "rpuFrames")]
+  pub rpu_frames: u32,
+}
+
+/// Per-scene luminance from the dynamic metadata of the decoded frames.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HdrLuminance {
+  /// `Dolby Vision L1` or `HDR10+`.
+  pub source: String,
+  /// The brightest scene peak, in nits.
+  #[serde(rename = "maxNits")]
+  pub max_nits: f64,
+  /// The scene averages weighted by their frames, in nits.
+  #[serde(rename = "avgNits")]
+  pub avg_nits: f64,
+  pub scenes: Vec<HdrScene>,
+  /// Whether later scenes were left out to keep the list short.
+  pub truncated: bool,
+}
+
+/// Mastering display color volume (SMPTE ST 2086). Chromaticities are CIE
+/// 1931 `[x, y]`, luminance is in nits.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HdrMasteringDisplay {
+  pub primaries: Option<String>,
+  pub red: Option<[f64; 2]>,
+  pub green: Option<[f64; 2]>,
+  pub blue: Option<[f64; 2]>,
+  #[serde(rename = "whitePoint")]
+  pub white_point: Option<[f64; 2]>,
+  #[serde(rename = "minLuminance")]
+  pub min_luminance: Option<f64>,
+  #[serde(rename = "maxLuminance")]
+  pub max_luminance: Option<f64>,
+}
+
+/// The HDR report of every video stream of a file.
+#[derive(Debug, Clone, Serialize)]
+pub struct HdrReport {
+  /// How many frames were decoded for the side data pass.
+  pub frames: FfprobeFrames,
+  pub streams: Vec<HdrStream>,
+}
+
+/// A run of frames sharing the same dynamic metadata, usually one shot.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HdrScene {
+  pub start: f64,
+  /// The time of its last decoded frame.
+  pub end: f64,
+  pub frames: u32,
+  #[serde(rename = "maxNits")]
+  pub max_nits: f64,
+  #[serde(rename = "avgNits")]
+  pub avg_nits: f64,
+}
+
+/// The HDR signaling of one video stream, MediaInfo and ffprobe combined.
+#[derive(Debug, Clone, Serialize)]
+pub struct HdrStream {
+  pub num: i32,
+  /// MediaInfo `HDR_Format`, e.g. `Dolby Vision / SMPTE ST 2086`.
+  #[serde(rename = "hdrFormat")]
+  pub hdr_format: Option<String>,
+  pub compatibility: Option<String>,
+  pub transfer: HdrTransfer,
+  /// The transfer characteristics as reported, e.g. `PQ` or `smpte2084`.
+  #[serde(rename = "transferName")]
+  pub transfer_name: Option<String>,
+  #[serde(rename = "colorPrimaries")]
+  pub color_primaries: Option<String>,
+  #[serde(rename = "matrixCoefficients")]
+  pub matrix_coefficients: Option<String>,
+  #[serde(rename = "bitDepth")]
+  pub bit_depth: Option<u32>,
+  #[serde(rename = "masteringDisplay")]
+  pub mastering_display: Option<HdrMasteringDisplay>,
+  /// MaxCLL in nits.
+  #[serde(rename = "maxCll")]
+  pub max_cll: Option<f64>,
+  /// MaxFALL in nits.
+  #[serde(rename = "maxFall")]
+  pub max_fall: Option<f64>,
+  #[serde(rename = "dolbyVision")]
+  pub dolby_vision: Option<HdrDolbyVision>,
+  /// Whether HDR10+ (SMPTE ST 2094-40) dynamic metadata is present.
+  #[serde(rename = "hdr10Plus")]
+  pub hdr10_plus: bool,
+  #[serde(rename = "hdr10PlusFrames")]
+  pub hdr10_plus_frames: u32,
+  /// Decoded frames of this stream.
+  #[serde(rename = "decodedFrames")]
+  pub decoded_frames: u32,
+  pub luminance: Option<HdrLuminance>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum HdrTransfer {
+  Sdr,
+  Pq,
+  Hlg,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -75,6 +557,15 @@ pub struct MkvToolNixStatus {
   pub found: bool,
   #[serde(rename = "mkvToolNixPath")]
   pub mkv_toolnix_path: String,
+  /// Parsed `mkvmerge --version`, when found.
+  pub version: Option<MkvToolNixVersion>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MkvToolNixVersion {
+  /// The line printed by `mkvmerge --version`, e.g. `mkvmerge v82.0 ('I'm The President') 64-bit`.
+  pub text: String,
+  pub number: Option<VersionNumber>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -99,6 +590,110 @@ pub struct MpcHcStatus {
 pub struct FfmpegStatus {
   pub found: bool,
   pub path: String,
+  /// Parsed `ffmpeg -version` and `ffmpeg -filters`, when found.
+  pub version: Option<FfmpegVersion>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FfmpegVersion {
+  /// First line of `ffmpeg -version`, e.g. `ffmpeg version 7.1.1 Copyright ...`.
+  pub text: String,
+  /// The release, or for a git snapshot the newest release whose libavutil
+  /// it is at least as new as.
+  pub number: Option<VersionNumber>,
+  pub snapshot: bool,
+  /// The `./configure` arguments the build was made with.
+  pub configuration: Vec<String>,
+  /// Names from the `--enable-*` arguments, e.g. `gpl` or `libx264`.
+  pub enabled: Vec<String>,
+  pub libraries: Vec<FfmpegLibraryVersion>,
+  /// Names listed by `ffmpeg -filters`.
+  pub filters: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FfmpegLibraryVersion {
+  /// e.g. `libavcodec`.
+  pub name: String,
+  /// e.g. `61.19.100`.
+  pub version: String,
+}
+
+/// A dotted release version. Field order makes the derived ordering compare
+/// major, then minor, then patch.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VersionNumber {
+  pub major: u32,
+  pub minor: u32,
+  pub patch: u32,
+}
+
+impl VersionNumber {
+  pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+    Self { major, minor, patch }
+  }
+}
+
+impl fmt::Display for VersionNumber {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.patch == 0 {
+      write!(f, "{}.{}", self.major, self.minor)
+    } else {
+      write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+  }
+}
+
+/// One installation of an external tool found by discovery, offered to the
+/// user to pick from.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolCandidate {
+  /// Directory holding the tool's binaries, as stored in `Config`.
+  pub path: String,
+  pub source: ToolCandidateSource,
+  pub version: Option<String>,
+}
+
+/// Where a `ToolCandidate` was found.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ToolCandidateSource {
+  /// The directory currently in `Config`.
+  Configured,
+  /// The directory of a running instance.
+  Running,
+  /// A tool-specific location, e.g. a versioned macOS app bundle.
+  Fallback,
+  Path,
+  /// A package-manager prefix such as Homebrew, Nix, Snap, Scoop or Chocolatey.
+  PackageManager,
+  /// A Flatpak app, run through wrapper scripts in the config directory.
+  Flatpak,
+}
+
+/// Batch spreadsheet export request. `per_stream` switches from one row per
+/// file to one row per stream (one sheet per stream kind).
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpreadsheetExportOptions {
+  pub format: SpreadsheetFormat,
+  pub output: String,
+  #[serde(rename = "perStream", default)]
+  pub per_stream: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpreadsheetExportResult {
+  /// Paths of the files written (several for a per-stream CSV export).
+  pub outputs: Vec<String>,
+  /// Number of files analyzed successfully.
+  pub files: u32,
+  /// Inputs MediaInfo couldn't analyze; they are left out of the export.
+  pub failed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum SpreadsheetFormat {
+  Csv,
+  Xlsx,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -141,6 +736,25 @@ pub struct FfmpegCaptureState {
   pub cancels: Arc<Mutex<HashMap<String, Arc<std::sync::atomic::AtomicBool>>>>,
 }
 
+/// Running ffmpeg analysis passes (QC detectors, loudness, ...) keyed by window label.
+pub struct FfmpegAnalysisState {
+  pub children: Arc<Mutex<HashMap<String, std::process::Child>>>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct FfmpegAnalysisProgressEvent {
+  /// Which analysis the progress refers to, e.g. "qc".
+  pub analysis: String,
+  pub percent: u32,
+}
+
+/// Paths handed over by a second launch of the app, see
+/// `window::on_second_instance`.
+#[derive(Serialize, Clone)]
+pub struct FilesOpenedEvent {
+  pub files: Vec<String>,
+}
+
 #[derive(Serialize, Clone)]
 pub struct FfmpegCaptureProgressEvent {
   pub percent: u32,
@@ -162,6 +776,335 @@ pub struct FfmpegCaptureFrameEvent {
   pub bytes: Vec<u8>,
 }
 
+/// How the positions of a frame comparison are read: as seconds, or as frame
+/// numbers counted at each file's own frame rate.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum FrameAlignment {
+  Timestamp,
+  Frame,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FrameCompareOptions {
+  pub alignment: FrameAlignment,
+  /// Seconds or frame numbers, by `alignment`.
+  pub positions: Vec<f64>,
+  /// Where the images of every pair are saved as PNG.
+  #[serde(rename = "outputDir")]
+  pub output_dir: String,
+  /// Factor the difference image is amplified by.
+  pub gain: f64,
+}
+
+/// The result of capturing matching frames from a reference and a distorted file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FrameComparison {
+  /// Size of the reference, which the distorted frames are scaled to.
+  pub width: u32,
+  pub height: u32,
+  /// Whether the distorted frames were scaled to the reference's size.
+  pub scaled: bool,
+  pub pairs: Vec<FramePair>,
+}
+
+/// One position of a frame comparison, captured from both files.
+#[derive(Debug, Clone, Serialize)]
+pub struct FramePair {
+  pub position: f64,
+  /// Seconds from the start of each file the frame stands for.
+  #[serde(rename = "referenceTime")]
+  pub reference_time: f64,
+  #[serde(rename = "distortedTime")]
+  pub distorted_time: f64,
+  /// PSNR of the RGB channels in dB.
+  pub psnr: f64,
+  /// The saved images: each frame, both side by side, and the amplified difference.
+  pub reference: String,
+  pub distorted: String,
+  #[serde(rename = "sideBySide")]
+  pub side_by_side: String,
+  pub difference: String,
+}
+
+/// How one audio stream fares against one loudness target.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoudnessCheck {
+  pub target: LoudnessTarget,
+  /// Integrated loudness within the target's tolerance.
+  #[serde(rename = "integratedOk")]
+  pub integrated_ok: bool,
+  /// True peak at or below the target's ceiling.
+  #[serde(rename = "truePeakOk")]
+  pub true_peak_ok: bool,
+  pub passed: bool,
+}
+
+/// `ebur128` results for one audio stream. Values are `None` when the stream
+/// never rose above the meter's floor (e.g. digital silence).
+#[derive(Debug, Clone, Serialize)]
+pub struct LoudnessMeasurement {
+  /// Audio stream index, as in MediaInfo's and ffmpeg's `a:<num>`.
+  pub num: u32,
+  /// Integrated loudness in LUFS.
+  pub integrated: Option<f64>,
+  /// Loudness range in LU.
+  pub lra: Option<f64>,
+  /// True peak in dBTP.
+  #[serde(rename = "truePeak")]
+  pub true_peak: Option<f64>,
+  /// Highest momentary (400 ms) and short-term (3 s) loudness in LUFS.
+  #[serde(rename = "momentaryMax")]
+  pub momentary_max: Option<f64>,
+  #[serde(rename = "shortTermMax")]
+  pub short_term_max: Option<f64>,
+  /// One check per `LoudnessTarget`, so the target can be switched without measuring again.
+  pub checks: Vec<LoudnessCheck>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoudnessReport {
+  pub duration: f64,
+  pub streams: Vec<LoudnessMeasurement>,
+}
+
+/// Delivery specs a loudness measurement is checked against: EBU R128
+/// (-23 LUFS), ATSC A/85 (-24 LKFS) and the common streaming levels.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum LoudnessTarget {
+  EbuR128,
+  AtscA85,
+  Streaming14,
+  Streaming16,
+}
+
+/// A QC finding kind: black picture, frozen picture or silent audio.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum QcEventKind {
+  Black,
+  Freeze,
+  Silence,
+}
+
+/// One interval a QC detector reported, in seconds from the start of the file.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct QcInterval {
+  pub kind: QcEventKind,
+  pub start: f64,
+  pub end: f64,
+  pub duration: f64,
+}
+
+/// Which ffmpeg detectors a QC analysis runs and their thresholds.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QcOptions {
+  pub black: bool,
+  pub freeze: bool,
+  pub silence: bool,
+  /// `blackdetect` minimum duration (seconds) and pixel luma threshold (0-1).
+  #[serde(rename = "blackMinDuration")]
+  pub black_min_duration: f64,
+  #[serde(rename = "blackPixelThreshold")]
+  pub black_pixel_threshold: f64,
+  /// `freezedetect` noise tolerance (dB) and minimum duration (seconds).
+  #[serde(rename = "freezeNoise")]
+  pub freeze_noise: f64,
+  #[serde(rename = "freezeMinDuration")]
+  pub freeze_min_duration: f64,
+  /// `silencedetect` noise floor (dB) and minimum duration (seconds).
+  #[serde(rename = "silenceNoise")]
+  pub silence_noise: f64,
+  #[serde(rename = "silenceMinDuration")]
+  pub silence_min_duration: f64,
+}
+
+impl Default for QcOptions {
+  fn default() -> Self {
+    Self {
+      black: true,
+      freeze: true,
+      silence: true,
+      black_min_duration: 2.0,
+      black_pixel_threshold: 0.1,
+      freeze_noise: -60.0,
+      freeze_min_duration: 2.0,
+      silence_noise: -50.0,
+      silence_min_duration: 2.0,
+    }
+  }
+}
+
+/// The result of a QC analysis: every interval found, ordered by start.
+#[derive(Debug, Clone, Serialize)]
+pub struct QcTimeline {
+  pub duration: f64,
+  pub intervals: Vec<QcInterval>,
+  /// Requested detectors that were not run because the file has no stream for them.
+  pub skipped: Vec<QcEventKind>,
+}
+
+/// One worst-scoring frame of a quality comparison, captured from both files.
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityFrame {
+  /// Index in the aligned frame sequence, from 0.
+  pub frame: u32,
+  /// Seconds from the start.
+  pub time: f64,
+  pub score: f64,
+  /// The captured images; empty when no output directory was given.
+  pub reference: String,
+  pub distorted: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum QualityMetric {
+  Psnr,
+  Ssim,
+  Vmaf,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QualityOptions {
+  pub metrics: Vec<QualityMetric>,
+  /// Number of worst frames to report and capture.
+  #[serde(rename = "worstFrames")]
+  pub worst_frames: u32,
+  /// Where the worst frames are saved as PNG; empty to skip capturing them.
+  #[serde(rename = "outputDir")]
+  pub output_dir: String,
+}
+
+impl Default for QualityOptions {
+  fn default() -> Self {
+    Self {
+      metrics: vec![QualityMetric::Psnr, QualityMetric::Ssim, QualityMetric::Vmaf],
+      worst_frames: 5,
+      output_dir: String::new(),
+    }
+  }
+}
+
+/// The result of comparing a distorted file against its reference.
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityReport {
+  /// Size and frame rate both files were compared at, those of the reference.
+  pub width: u32,
+  pub height: u32,
+  #[serde(rename = "frameRate")]
+  pub frame_rate: f64,
+  pub frames: u32,
+  /// Whether the distorted file was scaled to the reference's size.
+  pub scaled: bool,
+  /// Whether the distorted file was converted to the reference's frame rate.
+  pub resampled: bool,
+  pub scores: Vec<QualityScore>,
+  /// The metric the worst frames were picked by: VMAF, else SSIM, else PSNR.
+  #[serde(rename = "worstMetric")]
+  pub worst_metric: Option<QualityMetric>,
+  #[serde(rename = "worstFrames")]
+  pub worst_frames: Vec<QualityFrame>,
+  /// Requested metrics the ffmpeg build can't compute, e.g. VMAF without libvmaf.
+  pub skipped: Vec<QualityMetric>,
+}
+
+/// One metric over the whole comparison, with its value per frame.
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityScore {
+  pub metric: QualityMetric,
+  pub mean: f64,
+  pub min: f64,
+  pub max: f64,
+  pub series: Vec<f64>,
+}
+
+/// One proposed chapter of a scene detection.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SceneChapter {
+  /// Seconds from the start; the first chapter always starts at 0.
+  pub start: f64,
+  /// The `scene` score (0-1) of the cut that opened the chapter, 0 for the first.
+  pub score: f64,
+  /// Whether `start` was moved from the cut onto the nearest keyframe.
+  pub snapped: bool,
+}
+
+/// The result of a scene detection: the chapters proposed from its cuts.
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneChapters {
+  pub duration: f64,
+  /// Number of cuts found above the threshold, before spacing was applied.
+  pub cuts: u32,
+  /// Number of keyframes the cuts could snap to.
+  pub keyframes: u32,
+  pub chapters: Vec<SceneChapter>,
+}
+
+/// How a scene detection picks its cuts and turns them into chapters.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SceneOptions {
+  /// `select` scene score (0-1) above which a frame counts as a cut.
+  pub threshold: f64,
+  /// Minimum seconds between two chapters, and from either end of the file.
+  #[serde(rename = "minSpacing")]
+  pub min_spacing: f64,
+  #[serde(rename = "snapToKeyframes")]
+  pub snap_to_keyframes: bool,
+}
+
+impl Default for SceneOptions {
+  fn default() -> Self {
+    Self {
+      threshold: 0.4,
+      min_spacing: 60.0,
+      snap_to_keyframes: true,
+    }
+  }
+}
+
+/// Which audio stream a spectrogram decodes and where the PNG goes.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpectrogramOptions {
+  /// Index among the audio streams, matching `StreamPropertyMap::num`.
+  pub stream: u32,
+  pub output: String,
+  /// Size of the whole image in pixels, spectrogram and waveform together.
+  pub width: u32,
+  pub height: u32,
+}
+
+/// What was found while rendering a spectrogram.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpectrogramReport {
+  pub output: String,
+  #[serde(rename = "sampleRate")]
+  pub sample_rate: u32,
+  pub duration: f64,
+  /// Highest absolute sample of any channel, in dBFS.
+  pub peak: f64,
+  /// Samples at or beyond full scale in all channels, a sign of clipping.
+  #[serde(rename = "clippedSamples")]
+  pub clipped_samples: u64,
+  /// The peak and the clipped samples of each channel.
+  #[serde(rename = "channelPeaks")]
+  pub channel_peaks: Vec<f64>,
+  #[serde(rename = "channelClipped")]
+  pub channel_clipped: Vec<u64>,
+  /// Frequency in Hz above which the spectrum falls off a cliff, as left by
+  /// the lowpass of a lossy encoder; none when the spectrum runs to Nyquist.
+  pub cutoff: Option<f64>,
+}
+
+/// How a trim pass picks the crop of each captured image.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum TrimMode {
+  /// Crop every image to its own content box.
+  #[default]
+  Each,
+  /// Crop every image to the union of all content boxes.
+  Union,
+  /// Crop every image to the median of each edge over all content boxes.
+  Median,
+}
+
 /// Border-trim request supplied by the FFmpeg Tools window. When `enabled`, each
 /// captured image is cropped to its content area: edges whose pixels are within
 /// `tolerance` (percent) of `color` are removed, mirroring ImageMagick `-trim`.
@@ -172,6 +1115,9 @@ pub struct TrimOptions {
   pub color: String,
   /// Fuzz tolerance as a percentage (0–100); 0 trims only exact matches.
   pub tolerance: f64,
+  /// Whether each image gets its own crop or all share one (defaults to each).
+  #[serde(default)]
+  pub mode: TrimMode,
   /// Number of worker threads to use for the trim pass. The frontend defaults
   /// this to the CPU core count; `0` (or a missing field) falls back to the core
   /// count on the backend.
@@ -179,6 +1125,40 @@ pub struct TrimOptions {
   pub threads: usize,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationResult {
+  pub profile: String,
+  pub passed: bool,
+  pub failures: Vec<String>,
+}
+
+/// What a watch folder records for every file it analyzes, either as a JSON
+/// file next to the media or as one line of the folder's log.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchReport {
+  pub file: String,
+  pub size: u64,
+  /// Seconds since the Unix epoch.
+  #[serde(rename = "analyzedAt")]
+  pub analyzed_at: u64,
+  pub sha256: Option<String>,
+  pub streams: Vec<StreamPropertyMap>,
+  pub validation: Vec<ValidationResult>,
+}
+
+/// Emitted to every window as `watch-folder-report` once a new file in a watch
+/// folder has been analyzed (or failed to be).
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchFolderReportEvent {
+  pub folder: String,
+  pub file: String,
+  /// Where the report was written: the JSON file or the log file.
+  pub report: String,
+  /// False when any validation profile failed.
+  pub passed: bool,
+  pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct UpdateCheckResult {
   #[serde(rename = "hasUpdate")]
@@ -0,0 +1,219 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+
+use crate::analysis;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{QcEventKind, QcInterval, QcOptions, QcTimeline, StreamProperty, StreamPropertyMap};
+
+const PROPERTIES: &[&str] = &["Duration", "VideoCount", "AudioCount"];
+
+/// Run the QC detectors `options` asks for over `file` and collect what they
+/// report. `properties` are the `properties()` of `file`; detectors for a
+/// stream kind the file lacks are listed in `skipped` instead of failing.
+/// `run` executes the ffmpeg pass and returns its stderr (see
+/// `ffmpeg::run_analysis`).
+pub fn analyze(
+  file: &str,
+  options: &QcOptions,
+  properties: &[StreamPropertyMap],
+  run: impl FnOnce(&[String], f64) -> Result<String>,
+) -> Result<QcTimeline> {
+  let get = |name: &str| analysis::general_f64(properties, name);
+  let duration = get("Duration") / 1000.0;
+  let has_video = get("VideoCount") > 0.0;
+  let has_audio = get("AudioCount") > 0.0;
+  let mut skipped = Vec::new();
+  for (kind, requested, present) in [
+    (QcEventKind::Black, options.black, has_video),
+    (QcEventKind::Freeze, options.freeze, has_video),
+    (QcEventKind::Silence, options.silence, has_audio),
+  ] {
+    if requested && !present {
+      skipped.push(kind);
+    }
+  }
+  let runs = (options.black || options.freeze) && has_video || options.silence && has_audio;
+  if !runs {
+    return Err(anyhow::anyhow!("QC_NOTHING_TO_ANALYZE:{}", file));
+  }
+  let stderr = run(&args(file, options, has_video, has_audio), duration)?;
+  Ok(QcTimeline {
+    duration,
+    intervals: parse(&stderr, duration),
+    skipped,
+  })
+}
+
+/// The ffmpeg arguments (after the global options) that run the enabled
+/// detectors over the first video and first audio stream in a single pass.
+pub fn args(file: &str, options: &QcOptions, has_video: bool, has_audio: bool) -> Vec<String> {
+  let mut args = vec!["-i".to_string(), file.to_string()];
+  let mut video_filters = Vec::new();
+  if has_video && options.black {
+    video_filters.push(format!(
+      "blackdetect=d={}:pix_th={}",
+      options.black_min_duration, options.black_pixel_threshold
+    ));
+  }
+  if has_video && options.freeze {
+    video_filters.push(format!(
+      "freezedetect=n={}dB:d={}",
+      options.freeze_noise, options.freeze_min_duration
+    ));
+  }
+  if !video_filters.is_empty() {
+    args.extend([
+      "-map".to_string(),
+      "0:v:0".to_string(),
+      "-vf".to_string(),
+      video_filters.join(","),
+    ]);
+  }
+  if has_audio && options.silence {
+    args.extend([
+      "-map".to_string(),
+      "0:a:0".to_string(),
+      "-af".to_string(),
+      format!(
+        "silencedetect=n={}dB:d={}",
+        options.silence_noise, options.silence_min_duration
+      ),
+    ]);
+  }
+  args.extend(["-f".to_string(), "null".to_string(), "-".to_string()]);
+  args
+}
+
+/// Parse the detectors' stderr log into intervals ordered by start. An
+/// interval still open at the end of the log (freezedetect and older
+/// silencedetect don't close it at EOF) runs to `duration`.
+pub fn parse(stderr: &str, duration: f64) -> Vec<QcInterval> {
+  let mut intervals = Vec::new();
+  let mut freeze_start: Option<f64> = None;
+  let mut silence_start: Option<f64> = None;
+  let interval = |kind, start: f64, end: f64| QcInterval {
+    kind,
+    start,
+    end,
+    duration: (end - start).max(0.0),
+  };
+  for line in stderr.lines() {
+    if line.contains("[blackdetect") {
+      if let (Some(start), Some(end)) = (value_after(line, "black_start:"), value_after(line, "black_end:")) {
+        intervals.push(interval(QcEventKind::Black, start, end));
+      }
+    } else if line.contains("[freezedetect") {
+      if let Some(start) = value_after(line, "freeze_start:") {
+        freeze_start = Some(start);
+      } else if let (Some(start), Some(end)) = (freeze_start, value_after(line, "freeze_end:")) {
+        intervals.push(interval(QcEventKind::Freeze, start, end));
+        freeze_start = None;
+      }
+    } else if line.contains("[silencedetect") {
+      if let Some(start) = value_after(line, "silence_start:") {
+        silence_start = Some(start);
+      } else if let (Some(start), Some(end)) = (silence_start, value_after(line, "silence_end:")) {
+        intervals.push(interval(QcEventKind::Silence, start.max(0.0), end));
+        silence_start = None;
+      }
+    }
+  }
+  if let Some(start) = freeze_start {
+    intervals.push(interval(QcEventKind::Freeze, start, duration.max(start)));
+  }
+  if let Some(start) = silence_start {
+    intervals.push(interval(QcEventKind::Silence, start.max(0.0), duration.max(start)));
+  }
+  intervals.sort_by(|a, b| a.start.total_cmp(&b.start));
+  intervals
+}
+
+/// The MediaInfo properties `analyze` needs.
+pub fn properties() -> Vec<StreamProperty> {
+  analysis::properties(MediaInfoStreamKind::General, PROPERTIES)
+}
+
+/// The number right after `key` in a filter log line, e.g. `black_start:12.5`
+/// or `silence_end: 12.5 | ...`.
+fn value_after(line: &str, key: &str) -> Option<f64> {
+  let rest = line[line.find(key)? + key.len()..].trim_start();
+  let end = rest
+    .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | 'e' | '+')))
+    .unwrap_or(rest.len());
+  rest[..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::protocol::PropertySource;
+
+  const LOG: &str = "\
+[blackdetect @ 0x55d0] black_start:0 black_end:2.002 black_duration:2.002
+[freezedetect @ 0x55d1] lavfi.freezedetect.freeze_start: 5.005
+[freezedetect @ 0x55d1] lavfi.freezedetect.freeze_duration: 2.002
+[freezedetect @ 0x55d1] lavfi.freezedetect.freeze_end: 7.007
+[silencedetect @ 0x55d2] silence_start: -0.0123
+[silencedetect @ 0x55d2] silence_end: 3.5 | silence_duration: 3.5123
+[freezedetect @ 0x55d1] lavfi.freezedetect.freeze_start: 55
+[silencedetect @ 0x55d2] silence_start: 58.25
+";
+
+  #[test]
+  fn test_parse() {
+    let intervals = parse(LOG, 60.0);
+    let summary: Vec<(QcEventKind, f64, f64)> = intervals.iter().map(|i| (i.kind, i.start, i.end)).collect();
+    assert_eq!(
+      summary,
+      vec![
+        (QcEventKind::Black, 0.0, 2.002),
+        (QcEventKind::Silence, 0.0, 3.5),
+        (QcEventKind::Freeze, 5.005, 7.007),
+        (QcEventKind::Freeze, 55.0, 60.0),
+        (QcEventKind::Silence, 58.25, 60.0),
+      ]
+    );
+    assert!((intervals[2].duration - 2.002).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_args_and_skipped() {
+    let options = QcOptions::default();
+    let args = args("in.mkv", &options, true, false);
+    assert!(args.contains(&"blackdetect=d=2:pix_th=0.1,freezedetect=n=-60dB:d=2".to_string()));
+    assert!(!args.iter().any(|arg| arg.starts_with("silencedetect")));
+
+    let general = StreamPropertyMap {
+      stream: MediaInfoStreamKind::General,
+      num: 0,
+      property_map: [("Duration", "60000"), ("VideoCount", "1")]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect(),
+      source: PropertySource::MediaInfo,
+    };
+    let timeline = analyze("in.mkv", &options, &[general], |_, duration| {
+      assert_eq!(duration, 60.0);
+      Ok(LOG.to_string())
+    })
+    .unwrap();
+    assert_eq!(timeline.skipped, vec![QcEventKind::Silence]);
+    assert_eq!(timeline.intervals.len(), 5);
+  }
+}
@@ -0,0 +1,428 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::ffmpeg;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{
+  QualityFrame, QualityMetric, QualityOptions, QualityReport, QualityScore, StreamProperty, StreamPropertyMap,
+};
+
+/// PSNR of identical frames is infinite; it is reported as this many dB.
+pub const MAX_PSNR: f64 = 100.0;
+/// Metrics in the order the worst frames are picked by, best judge first.
+const PRIORITY: &[QualityMetric] = &[QualityMetric::Vmaf, QualityMetric::Ssim, QualityMetric::Psnr];
+const PROPERTIES: &[&str] = &["Width", "Height", "FrameRate", "Duration", "BitDepth"];
+/// Numbers the log directories of comparisons running at the same time.
+static RUNS: AtomicU32 = AtomicU32::new(0);
+/// Worst frames are at least this many seconds apart, so they don't all come
+/// from one bad scene.
+const WORST_SPACING: f64 = 1.0;
+
+/// How the distorted file is brought in line with the reference: both are
+/// compared at the reference's size and frame rate.
+#[derive(Debug, Clone, PartialEq)]
+struct Alignment {
+  width: u32,
+  height: u32,
+  frame_rate: f64,
+  /// `fps` filter value for the reference's frame rate, exact for NTSC rates.
+  rate: String,
+  scaled: bool,
+  resampled: bool,
+  /// The shorter of the two, which bounds the comparison.
+  duration: f64,
+  pixel_format: &'static str,
+}
+
+/// Compare `distorted` against `reference` with the metrics `options` ask for
+/// in one ffmpeg pass, then capture the worst frames of both. The properties
+/// are the `properties()` of each file. `run` executes the pass and returns
+/// its stderr (see `ffmpeg::run_analysis`). `on_capture` is told before each worst frame is captured
+/// and returns false to cancel.
+pub fn compare(
+  reference: &str,
+  distorted: &str,
+  options: &QualityOptions,
+  reference_properties: &[StreamPropertyMap],
+  distorted_properties: &[StreamPropertyMap],
+  run: impl FnOnce(&[String], f64) -> Result<String>,
+  mut on_capture: impl FnMut(u32, u32) -> bool,
+) -> Result<QualityReport> {
+  let alignment = align(reference, distorted, reference_properties, distorted_properties)?;
+  let (metrics, skipped): (Vec<QualityMetric>, Vec<QualityMetric>) = PRIORITY
+    .iter()
+    .rev()
+    .copied()
+    .filter(|metric| options.metrics.contains(metric))
+    .partition(|metric| *metric != QualityMetric::Vmaf || ffmpeg::has_filter("libvmaf"));
+  if metrics.is_empty() {
+    return Err(anyhow::anyhow!("QUALITY_NO_METRICS"));
+  }
+  let work_dir = std::env::temp_dir().join(format!(
+    "bmi_quality_{}_{}",
+    std::process::id(),
+    RUNS.fetch_add(1, Ordering::SeqCst)
+  ));
+  std::fs::create_dir_all(&work_dir)?;
+  let logs: Vec<(QualityMetric, std::path::PathBuf)> = metrics
+    .iter()
+    .map(|metric| (*metric, work_dir.join(format!("{:?}.log", metric).to_lowercase())))
+    .collect();
+  let scores = run(&args(reference, distorted, &alignment, &logs), alignment.duration).and_then(|_| {
+    logs
+      .iter()
+      .map(|(metric, log)| {
+        let text = std::fs::read_to_string(log)?;
+        let series = match metric {
+          QualityMetric::Psnr => parse_stats(&text, "psnr_avg:"),
+          QualityMetric::Ssim => parse_stats(&text, "All:"),
+          QualityMetric::Vmaf => parse_vmaf(&text)?,
+        };
+        Ok(score(*metric, series))
+      })
+      .collect::<Result<Vec<QualityScore>>>()
+  });
+  let _ = std::fs::remove_dir_all(&work_dir);
+  let scores = scores?;
+  let frames = scores.iter().map(|score| score.series.len()).min().unwrap_or_default() as u32;
+  if frames == 0 {
+    return Err(anyhow::anyhow!("QUALITY_NO_FRAMES:{}", distorted));
+  }
+  let judge = PRIORITY
+    .iter()
+    .find_map(|metric| scores.iter().find(|score| score.metric == *metric));
+  let mut worst_frames = Vec::new();
+  if let Some(judge) = judge {
+    let spacing = (alignment.frame_rate * WORST_SPACING).round() as usize;
+    let picked = worst(&judge.series, options.worst_frames as usize, spacing);
+    let total = picked.len() as u32;
+    for (rank, frame) in picked.into_iter().enumerate() {
+      if !on_capture(rank as u32, total) {
+        return Err(anyhow::anyhow!("FFMPEG_ANALYSIS_CANCELLED"));
+      }
+      let time = frame as f64 / alignment.frame_rate;
+      let (reference_image, distorted_image) = if options.output_dir.is_empty() {
+        (String::new(), String::new())
+      } else {
+        let dir = Path::new(&options.output_dir);
+        std::fs::create_dir_all(dir)?;
+        let stem = Path::new(distorted)
+          .file_stem()
+          .map(|stem| stem.to_string_lossy().into_owned())
+          .unwrap_or_default();
+        let capture = |file: &str, role: &str| -> Result<String> {
+          let bytes = ffmpeg::capture_frame(file.to_owned(), time, alignment.width)?;
+          let path = dir.join(format!("{}_worst{:02}_f{}_{}.png", stem, rank + 1, frame, role));
+          std::fs::write(&path, bytes)?;
+          Ok(path.to_string_lossy().into_owned())
+        };
+        (capture(reference, "reference")?, capture(distorted, "distorted")?)
+      };
+      worst_frames.push(QualityFrame {
+        frame: frame as u32,
+        time,
+        score: judge.series[frame],
+        reference: reference_image,
+        distorted: distorted_image,
+      });
+    }
+  }
+  Ok(QualityReport {
+    width: alignment.width,
+    height: alignment.height,
+    frame_rate: alignment.frame_rate,
+    frames,
+    scaled: alignment.scaled,
+    resampled: alignment.resampled,
+    worst_metric: judge.map(|score| score.metric),
+    scores,
+    worst_frames,
+    skipped,
+  })
+}
+
+fn align(
+  reference: &str,
+  distorted: &str,
+  reference_properties: &[StreamPropertyMap],
+  distorted_properties: &[StreamPropertyMap],
+) -> Result<Alignment> {
+  let video = |file: &str, properties: &[StreamPropertyMap]| {
+    properties
+      .iter()
+      .find(|map| map.stream == MediaInfoStreamKind::Video && map.num == 0)
+      .map(|map| map.property_map.clone())
+      .ok_or_else(|| anyhow::anyhow!("QUALITY_NO_VIDEO:{}", file))
+  };
+  let reference_video = video(reference, reference_properties)?;
+  let distorted_video = video(distorted, distorted_properties)?;
+  let get = |map: &std::collections::HashMap<String, String>, name: &str| -> f64 {
+    map
+      .get(name)
+      .and_then(|value| value.parse::<f64>().ok())
+      .unwrap_or_default()
+  };
+  let (width, height) = (
+    get(&reference_video, "Width") as u32,
+    get(&reference_video, "Height") as u32,
+  );
+  let frame_rate = get(&reference_video, "FrameRate");
+  if width == 0 || height == 0 || frame_rate <= 0.0 {
+    return Err(anyhow::anyhow!("QUALITY_NO_VIDEO:{}", reference));
+  }
+  let distorted_rate = get(&distorted_video, "FrameRate");
+  let durations = [get(&reference_video, "Duration"), get(&distorted_video, "Duration")];
+  Ok(Alignment {
+    width,
+    height,
+    frame_rate,
+    rate: rate(frame_rate),
+    scaled: get(&distorted_video, "Width") as u32 != width || get(&distorted_video, "Height") as u32 != height,
+    resampled: (distorted_rate - frame_rate).abs() > 0.001,
+    duration: durations
+      .into_iter()
+      .filter(|ms| *ms > 0.0)
+      .fold(f64::INFINITY, f64::min)
+      / 1000.0,
+    pixel_format: if get(&reference_video, "BitDepth") > 8.0 {
+      "yuv420p10le"
+    } else {
+      "yuv420p"
+    },
+  })
+}
+
+/// Both inputs from their first frame at the reference's rate, size and pixel
+/// format, split once per metric. Each metric stops at the shorter input and
+/// writes its per-frame log into `logs`.
+fn args(
+  reference: &str,
+  distorted: &str,
+  alignment: &Alignment,
+  logs: &[(QualityMetric, std::path::PathBuf)],
+) -> Vec<String> {
+  let count = logs.len();
+  let labels = |prefix: &str| {
+    (0..count)
+      .map(|index| format!("[{}{}]", prefix, index))
+      .collect::<String>()
+  };
+  let mut distorted_chain = "setpts=PTS-STARTPTS".to_string();
+  if alignment.resampled {
+    distorted_chain.push_str(&format!(",fps={}", alignment.rate));
+  }
+  if alignment.scaled {
+    distorted_chain.push_str(&format!(
+      ",scale={}:{}:flags=bicubic",
+      alignment.width, alignment.height
+    ));
+  }
+  let mut graph = format!(
+    "[0:v:0]{},format={},split={}{};[1:v:0]setpts=PTS-STARTPTS,format={},split={}{}",
+    distorted_chain,
+    alignment.pixel_format,
+    count,
+    labels("d"),
+    alignment.pixel_format,
+    count,
+    labels("r"),
+  );
+  let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+  for (index, (metric, log)) in logs.iter().enumerate() {
+    let log = filter_path(log);
+    let filter = match metric {
+      QualityMetric::Psnr => format!("psnr=stats_file={}:shortest=1", log),
+      QualityMetric::Ssim => format!("ssim=stats_file={}:shortest=1", log),
+      QualityMetric::Vmaf => format!("libvmaf=log_fmt=json:log_path={}:n_threads={}:shortest=1", log, threads),
+    };
+    graph.push_str(&format!(";[d{}][r{}]{}", index, index, filter));
+  }
+  [
+    "-i", distorted, "-i", reference, "-lavfi", &graph, "-an", "-sn", "-f", "null", "-",
+  ]
+  .iter()
+  .map(|arg| arg.to_string())
+  .collect()
+}
+
+/// A path as a filter option value: forward slashes, `:` escaped for the
+/// option parser, and quoted for the filtergraph parser.
+fn filter_path(path: &Path) -> String {
+  let path = path.to_string_lossy().replace('\\', "/").replace(':', "\\:");
+  format!("'{}'", path.replace('\'', "'\\''"))
+}
+
+/// The per-frame values after `key` in a `psnr`/`ssim` stats file, e.g.
+/// `psnr_avg:38.52` or `All:0.981 (17.2)`. `inf` counts as `MAX_PSNR`.
+fn parse_stats(text: &str, key: &str) -> Vec<f64> {
+  text
+    .lines()
+    .filter_map(|line| {
+      let value = line.split_whitespace().find_map(|field| field.strip_prefix(key))?;
+      if value == "inf" {
+        Some(MAX_PSNR)
+      } else {
+        value.parse::<f64>().ok().map(|value| value.min(MAX_PSNR))
+      }
+    })
+    .collect()
+}
+
+/// The per-frame `vmaf` values of a `libvmaf` JSON log.
+fn parse_vmaf(text: &str) -> Result<Vec<f64>> {
+  let json: serde_json::Value = serde_json::from_str(text)?;
+  Ok(
+    json["frames"]
+      .as_array()
+      .map(|frames| {
+        frames
+          .iter()
+          .filter_map(|frame| frame["metrics"]["vmaf"].as_f64())
+          .collect()
+      })
+      .unwrap_or_default(),
+  )
+}
+
+pub fn properties() -> Vec<StreamProperty> {
+  PROPERTIES
+    .iter()
+    .map(|property| StreamProperty {
+      stream: MediaInfoStreamKind::Video,
+      property: property.to_string(),
+    })
+    .collect()
+}
+
+/// `fps` value for `frame_rate`: the exact fraction for NTSC rates such as
+/// 23.976 (24000/1001), the number itself otherwise.
+fn rate(frame_rate: f64) -> String {
+  let nominal = (frame_rate * 1.001).round();
+  if (frame_rate - frame_rate.round()).abs() > 0.001 && (frame_rate - nominal * 1000.0 / 1001.0).abs() < 0.001 {
+    format!("{}/1001", nominal as u64 * 1000)
+  } else {
+    format!("{}", frame_rate)
+  }
+}
+
+fn score(metric: QualityMetric, series: Vec<f64>) -> QualityScore {
+  let count = series.len().max(1) as f64;
+  QualityScore {
+    metric,
+    mean: series.iter().sum::<f64>() / count,
+    min: series.iter().copied().fold(f64::INFINITY, f64::min),
+    max: series.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+    series,
+  }
+}
+
+/// The indices of the `count` lowest values of `series`, lowest first, each at
+/// least `spacing` frames from the others.
+fn worst(series: &[f64], count: usize, spacing: usize) -> Vec<usize> {
+  let mut order: Vec<usize> = (0..series.len()).collect();
+  order.sort_by(|a, b| series[*a].total_cmp(&series[*b]));
+  let mut picked: Vec<usize> = Vec::new();
+  for index in order {
+    if picked.len() >= count {
+      break;
+    }
+    if picked.iter().all(|other| other.abs_diff(index) >= spacing.max(1)) {
+      picked.push(index);
+    }
+  }
+  picked
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::protocol::PropertySource;
+  use std::collections::HashMap;
+
+  fn video(pairs: &[(&str, &str)]) -> Vec<StreamPropertyMap> {
+    vec![StreamPropertyMap {
+      stream: MediaInfoStreamKind::Video,
+      num: 0,
+      property_map: pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>(),
+      source: PropertySource::MediaInfo,
+    }]
+  }
+
+  #[test]
+  fn test_align() {
+    let reference = video(&[
+      ("Width", "1920"),
+      ("Height", "1080"),
+      ("FrameRate", "23.976"),
+      ("Duration", "60000"),
+      ("BitDepth", "10"),
+    ]);
+    let distorted = video(&[
+      ("Width", "1280"),
+      ("Height", "720"),
+      ("FrameRate", "25.000"),
+      ("Duration", "57600"),
+    ]);
+    let alignment = align("ref.mkv", "dist.mp4", &reference, &distorted).unwrap();
+    assert_eq!(alignment.rate, "24000/1001");
+    assert!(alignment.scaled && alignment.resampled);
+    assert_eq!(alignment.duration, 57.6);
+    let logs = vec![
+      (QualityMetric::Psnr, std::path::PathBuf::from("C:\\tmp\\psnr.log")),
+      (QualityMetric::Ssim, std::path::PathBuf::from("/tmp/ssim.log")),
+    ];
+    let args = args("ref.mkv", "dist.mp4", &alignment, &logs);
+    assert_eq!(&args[..4], &["-i", "dist.mp4", "-i", "ref.mkv"]);
+    assert_eq!(
+      args[5],
+      "[0:v:0]setpts=PTS-STARTPTS,fps=24000/1001,scale=1920:1080:flags=bicubic,format=yuv420p10le,split=2[d0][d1];\
+       [1:v:0]setpts=PTS-STARTPTS,format=yuv420p10le,split=2[r0][r1];\
+       [d0][r0]psnr=stats_file='C\\:/tmp/psnr.log':shortest=1;[d1][r1]ssim=stats_file='/tmp/ssim.log':shortest=1"
+    );
+    assert_eq!(rate(25.0), "25");
+    assert_eq!(rate(59.94), "60000/1001");
+    assert!(align("a", "b", &[], &distorted).is_err());
+  }
+
+  #[test]
+  fn test_parse() {
+    let psnr = "n:1 mse_avg:0.00 mse_y:0.00 psnr_avg:inf psnr_y:inf\nn:2 mse_avg:3.10 psnr_avg:43.21 psnr_y:44.00\n";
+    assert_eq!(parse_stats(psnr, "psnr_avg:"), vec![MAX_PSNR, 43.21]);
+    let ssim = "n:1 Y:0.990 U:0.995 V:0.996 All:0.992 (20.969)\nn:2 Y:0.950 U:0.960 V:0.970 All:0.955 (13.468)\n";
+    assert_eq!(parse_stats(ssim, "All:"), vec![0.992, 0.955]);
+    let vmaf = r#"{"version":"2.3.1","frames":[{"frameNum":0,"metrics":{"integer_adm2":0.9,"vmaf":95.5}},
+      {"frameNum":1,"metrics":{"vmaf":88.25}}],"pooled_metrics":{"vmaf":{"mean":91.875}}}"#;
+    assert_eq!(parse_vmaf(vmaf).unwrap(), vec![95.5, 88.25]);
+    let score = score(QualityMetric::Vmaf, vec![95.5, 88.25]);
+    assert_eq!((score.mean, score.min, score.max), (91.875, 88.25, 95.5));
+  }
+
+  #[test]
+  fn test_worst() {
+    let series = [90.0, 40.0, 41.0, 80.0, 85.0, 50.0, 95.0, 30.0];
+    assert_eq!(worst(&series, 3, 1), vec![7, 1, 2]);
+    assert_eq!(worst(&series, 3, 2), vec![7, 1, 5]);
+    assert_eq!(worst(&series, 10, 3), vec![7, 1, 4]);
+  }
+}
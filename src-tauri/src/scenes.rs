@@ -0,0 +1,197 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+
+use crate::analysis;
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{SceneChapter, SceneChapters, SceneOptions, StreamProperty, StreamPropertyMap};
+
+/// A cut further than this (seconds) from every keyframe keeps its own time.
+const MAX_SNAP: f64 = 5.0;
+const PROPERTIES: &[&str] = &["Duration", "VideoCount"];
+
+/// Find the scene cuts of the first video stream of `file` and propose
+/// chapters from them. `properties` are the `properties()` of `file` and
+/// `keyframes` its keyframe timestamps (see `ffprobe::keyframes`), empty when
+/// `options` doesn't snap. `run` executes the ffmpeg pass and returns its
+/// stderr (see `ffmpeg::run_analysis`).
+pub fn analyze(
+  file: &str,
+  options: &SceneOptions,
+  properties: &[StreamPropertyMap],
+  keyframes: &[f64],
+  run: impl FnOnce(&[String], f64) -> Result<String>,
+) -> Result<SceneChapters> {
+  let get = |name: &str| analysis::general_f64(properties, name);
+  let duration = get("Duration") / 1000.0;
+  if get("VideoCount") <= 0.0 {
+    return Err(anyhow::anyhow!("SCENES_NO_VIDEO:{}", file));
+  }
+  let stderr = run(&args(file, options), duration)?;
+  let cuts = parse(&stderr);
+  let keyframes = if options.snap_to_keyframes { keyframes } else { &[] };
+  Ok(SceneChapters {
+    duration,
+    cuts: cuts.len() as u32,
+    keyframes: keyframes.len() as u32,
+    chapters: propose(&cuts, keyframes, duration, options.min_spacing),
+  })
+}
+
+/// The ffmpeg arguments (after the global options) that pass only the frames
+/// scoring above the threshold and log their time and score.
+pub fn args(file: &str, options: &SceneOptions) -> Vec<String> {
+  vec![
+    "-i".to_string(),
+    file.to_string(),
+    "-map".to_string(),
+    "0:v:0".to_string(),
+    "-vf".to_string(),
+    format!("select='gt(scene,{})',metadata=print", options.threshold),
+    "-f".to_string(),
+    "null".to_string(),
+    "-".to_string(),
+  ]
+}
+
+/// The keyframe closest to `time` within `MAX_SNAP`, else `time` itself.
+/// `keyframes` is sorted.
+fn nearest_keyframe(keyframes: &[f64], time: f64) -> f64 {
+  let index = keyframes.partition_point(|keyframe| *keyframe < time);
+  [index.checked_sub(1), Some(index)]
+    .into_iter()
+    .flatten()
+    .filter_map(|index| keyframes.get(index).copied())
+    .min_by(|a, b| (a - time).abs().total_cmp(&(b - time).abs()))
+    .filter(|keyframe| (keyframe - time).abs() <= MAX_SNAP)
+    .unwrap_or(time)
+}
+
+/// The cuts `metadata=print` logged, as (seconds, score). Each passed frame
+/// logs its time on one line and its `lavfi.scene_score` on the next.
+fn parse(stderr: &str) -> Vec<(f64, f64)> {
+  let mut cuts = Vec::new();
+  let mut time: Option<f64> = None;
+  for line in stderr.lines().filter(|line| line.contains("[Parsed_metadata")) {
+    if let Some(rest) = line.split("pts_time:").nth(1) {
+      time = rest.split_whitespace().next().and_then(|value| value.parse().ok());
+    } else if let Some(rest) = line.split("lavfi.scene_score=").nth(1)
+      && let (Some(time), Ok(score)) = (time.take(), rest.trim().parse::<f64>())
+    {
+      cuts.push((time, score));
+    }
+  }
+  cuts
+}
+
+/// Chapters from `cuts`: each cut moves onto a nearby keyframe, then the
+/// strongest cuts win wherever two fall within `min_spacing` of each other or
+/// of either end of the file. The first chapter always starts at 0.
+fn propose(cuts: &[(f64, f64)], keyframes: &[f64], duration: f64, min_spacing: f64) -> Vec<SceneChapter> {
+  // Never two chapters at the same time, even without a spacing.
+  let spacing = min_spacing.max(0.001);
+  let mut candidates: Vec<SceneChapter> = cuts
+    .iter()
+    .map(|&(time, score)| {
+      let start = nearest_keyframe(keyframes, time);
+      SceneChapter {
+        start,
+        score,
+        snapped: (start - time).abs() > 1e-6,
+      }
+    })
+    .collect();
+  candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+  let mut chapters = vec![SceneChapter {
+    start: 0.0,
+    score: 0.0,
+    snapped: false,
+  }];
+  for candidate in candidates {
+    let near_end = duration > 0.0 && duration - candidate.start < spacing;
+    let crowded = chapters
+      .iter()
+      .any(|chapter| (chapter.start - candidate.start).abs() < spacing);
+    if !near_end && !crowded {
+      chapters.push(candidate);
+    }
+  }
+  chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+  chapters
+}
+
+/// The MediaInfo properties `analyze` needs.
+pub fn properties() -> Vec<StreamProperty> {
+  analysis::properties(MediaInfoStreamKind::General, PROPERTIES)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const LOG: &str = "\
+[Parsed_metadata_1 @ 0x55d0] frame:0    pts:90090   pts_time:90.09
+[Parsed_metadata_1 @ 0x55d0] lavfi.scene_score=0.512000
+[Parsed_metadata_1 @ 0x55d0] frame:1    pts:120120  pts_time:120.12
+[Parsed_metadata_1 @ 0x55d0] lavfi.scene_score=0.874000
+[Parsed_metadata_1 @ 0x55d0] frame:2    pts:300300  pts_time:300.3
+[Parsed_metadata_1 @ 0x55d0] lavfi.scene_score=0.433000
+[Parsed_metadata_1 @ 0x55d0] frame:3    pts:570570  pts_time:570.57
+[Parsed_metadata_1 @ 0x55d0] lavfi.scene_score=0.950000
+";
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(
+      parse(LOG),
+      vec![(90.09, 0.512), (120.12, 0.874), (300.3, 0.433), (570.57, 0.95)]
+    );
+    assert_eq!(
+      args("in.mkv", &SceneOptions::default())[5],
+      "select='gt(scene,0.4)',metadata=print"
+    );
+  }
+
+  #[test]
+  fn test_propose() {
+    let cuts = parse(LOG);
+    // 90.09 loses to the stronger cut 30 s later; 570.57 is too close to the end.
+    let starts: Vec<f64> = propose(&cuts, &[], 600.0, 60.0)
+      .iter()
+      .map(|chapter| chapter.start)
+      .collect();
+    assert_eq!(starts, vec![0.0, 120.12, 300.3]);
+
+    let keyframes = [0.0, 88.0, 118.0, 122.0, 298.0, 302.0];
+    let chapters = propose(&cuts, &keyframes, 600.0, 0.0);
+    let snapped: Vec<(f64, bool)> = chapters
+      .iter()
+      .map(|chapter| (chapter.start, chapter.snapped))
+      .collect();
+    assert_eq!(
+      snapped,
+      vec![
+        (0.0, false),
+        (88.0, true),
+        (122.0, true),
+        (302.0, true),
+        (570.57, false)
+      ]
+    );
+  }
+}
@@ -0,0 +1,543 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::config::{self, ConfigServer};
+use crate::controller::{self, CancelMap, ChildMap};
+use crate::protocol::{FfmpegCaptureProgressEvent, StreamProperty, TrimOptions};
+
+/// How long a finished job stays queryable when no event stream delivered its end.
+const JOB_RETENTION: Duration = Duration::from_secs(600);
+const MAX_BODY_SIZE: u64 = 1 << 20;
+const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+
+static SERVER: Lazy<Mutex<Option<ApiServer>>> = Lazy::new(|| Mutex::new(None));
+static JOBS: Lazy<Mutex<HashMap<u64, Arc<Job>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+static CHILDREN: Lazy<ChildMap> = Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+static CANCELS: Lazy<CancelMap> = Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+/// A capture job started over the API. `changed` is signalled whenever `state`
+/// moves on, which is what the event streams wait for.
+struct Job {
+  state: Mutex<JobState>,
+  changed: Condvar,
+  /// Event streams currently open on the job.
+  streams: AtomicUsize,
+}
+
+#[derive(Clone)]
+struct JobState {
+  progress: FfmpegCaptureProgressEvent,
+  frame: Option<String>,
+  version: u64,
+  /// When the job's thread returned; see `prune_jobs`.
+  finished_at: Option<Instant>,
+}
+
+impl Job {
+  fn update(&self, f: impl FnOnce(&mut JobState)) {
+    let mut state = self.state.lock().unwrap();
+    f(&mut state);
+    state.version += 1;
+    self.changed.notify_all();
+  }
+}
+
+/// The listening server and its accept thread. Dropping it unblocks the accept
+/// loop and waits for it so the port is free again for a restart.
+struct ApiServer {
+  config: ConfigServer,
+  server: Arc<tiny_http::Server>,
+  accept: Option<JoinHandle<()>>,
+}
+
+impl ApiServer {
+  fn start(config: ConfigServer) -> Result<Self> {
+    let server = tiny_http::Server::http(("127.0.0.1", config.port))
+      .map_err(|err| anyhow::anyhow!("SERVER_START_FAILED: Couldn't listen on port {}: {}", config.port, err))?;
+    let server = Arc::new(server);
+    let accept_server = server.clone();
+    let token = config.token.clone();
+    let port = config.port;
+    let accept = std::thread::spawn(move || {
+      for request in accept_server.incoming_requests() {
+        let token = token.clone();
+        std::thread::spawn(move || handle(request, &token, port));
+      }
+    });
+    log::info!("API server listening on 127.0.0.1:{}.", config.port);
+    Ok(Self {
+      config,
+      server,
+      accept: Some(accept),
+    })
+  }
+}
+
+impl Drop for ApiServer {
+  fn drop(&mut self) {
+    self.server.unblock();
+    if let Some(accept) = self.accept.take() {
+      let _ = accept.join();
+    }
+  }
+}
+
+#[derive(Deserialize)]
+struct CaptureParams {
+  args: Vec<String>,
+  #[serde(rename = "outputPattern")]
+  output_pattern: String,
+  #[serde(rename = "durationSeconds", default)]
+  duration_seconds: f64,
+  #[serde(default)]
+  trim: Option<TrimOptions>,
+}
+
+#[derive(Deserialize)]
+struct FileParams {
+  file: String,
+}
+
+#[derive(Deserialize)]
+struct JobParams {
+  #[serde(rename = "jobId")]
+  job_id: u64,
+}
+
+#[derive(Deserialize)]
+struct PropertiesParams {
+  file: String,
+  #[serde(default)]
+  properties: Option<Vec<StreamProperty>>,
+}
+
+/// Dispatch one JSON-RPC method. Errors from the controller keep their
+/// `CODE: message` text so callers can match on the code as the UI does.
+fn call(method: &str, params: Value) -> Result<Value, (i64, String)> {
+  fn parse<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(params).map_err(|err| (-32602, format!("Invalid params: {}", err)))
+  }
+  fn app(result: Result<impl serde::Serialize>) -> Result<Value, (i64, String)> {
+    result
+      .and_then(|value| Ok(serde_json::to_value(value)?))
+      .map_err(|err| (-32000, err.to_string()))
+  }
+  match method {
+    "cancel_capture" => {
+      let params: JobParams = parse(params)?;
+      cancel_job(params.job_id).map_err(|err| (-32000, err.to_string()))?;
+      Ok(Value::Null)
+    }
+    "get_capture" => {
+      let params: JobParams = parse(params)?;
+      let job = find_job(params.job_id).map_err(|err| (-32000, err.to_string()))?;
+      let state = job.state.lock().unwrap().clone();
+      Ok(json!({ "progress": state.progress, "frame": state.frame }))
+    }
+    "get_mkv_tracks" => {
+      let params: FileParams = parse(params)?;
+      app(tauri::async_runtime::block_on(controller::get_mkv_tracks(params.file)))
+    }
+    "get_parameters" => app(tauri::async_runtime::block_on(controller::get_parameters())),
+    "get_properties" => {
+      let params: PropertiesParams = parse(params)?;
+      app(tauri::async_runtime::block_on(controller::get_properties(params.file, params.properties)))
+    }
+    "get_stream_count" => {
+      let params: FileParams = parse(params)?;
+      app(tauri::async_runtime::block_on(controller::get_stream_count(params.file)))
+    }
+    "start_capture" => {
+      let params: CaptureParams = parse(params)?;
+      Ok(json!({ "jobId": start_job(params) }))
+    }
+    _ => Err((-32601, format!("Method not found: {}", method))),
+  }
+}
+
+/// Stop a capture job the same way `cancel_ffmpeg_capture` stops the window's:
+/// flip the trim pass's flag and kill the ffmpeg child.
+fn cancel_job(id: u64) -> Result<()> {
+  find_job(id)?;
+  let key = job_key(id);
+  if let Some(flag) = CANCELS.lock().unwrap().get(&key) {
+    flag.store(true, Ordering::SeqCst);
+  }
+  let child = CHILDREN.lock().unwrap().remove(&key);
+  if let Some(mut child) = child {
+    let _ = child.kill();
+    let _ = child.wait();
+  }
+  Ok(())
+}
+
+fn find_job(id: u64) -> Result<Arc<Job>> {
+  JOBS
+    .lock()
+    .unwrap()
+    .get(&id)
+    .cloned()
+    .ok_or_else(|| anyhow::anyhow!("SERVER_JOB_NOT_FOUND: No capture job {}.", id))
+}
+
+/// 128 bits from the OS random source, as hex.
+fn generate_token() -> Result<String> {
+  let mut bytes = [0u8; 16];
+  getrandom::fill(&mut bytes).map_err(|err| anyhow::anyhow!("SERVER_TOKEN: {}", err))?;
+  Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// The opt-in localhost API server. Controller operations are exposed as
+/// JSON-RPC 2.0 methods on `POST /rpc`, and capture jobs report their progress
+/// as server-sent events on `GET /jobs/<id>/events`. Every request must carry
+/// the configured token, either as `Authorization: Bearer <token>` or, for
+/// `EventSource` clients that can't set headers, as `?token=<token>`.
+fn handle(mut request: Request, token: &str, port: u16) {
+  let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+  let path = path.to_owned();
+  let query_token = query
+    .split('&')
+    .find_map(|pair| pair.strip_prefix("token="))
+    .unwrap_or_default()
+    .to_owned();
+  let header = |name: &'static str| {
+    request
+      .headers()
+      .iter()
+      .find(|header| header.field.equiv(name))
+      .map(|header| header.value.as_str().to_owned())
+  };
+  // Rejecting foreign Host headers keeps a web page that rebinds its own domain
+  // to 127.0.0.1 from talking to the server.
+  if !is_local_host(header("Host").as_deref().unwrap_or_default(), port) {
+    let _ = request.respond(text_response(403, "Forbidden"));
+    return;
+  }
+  let bearer = header("Authorization")
+    .and_then(|value| value.strip_prefix("Bearer ").map(str::to_owned))
+    .unwrap_or(query_token);
+  if !token_matches(&bearer, token) {
+    let _ = request.respond(text_response(401, "Unauthorized"));
+    return;
+  }
+  let method = request.method().clone();
+  match (&method, path.as_str()) {
+    (Method::Post, "/rpc") => {
+      let mut body = String::new();
+      let read = request.as_reader().take(MAX_BODY_SIZE).read_to_string(&mut body);
+      let response = match read {
+        Ok(_) => handle_rpc(&body),
+        Err(err) => rpc_error(Value::Null, -32700, &format!("Parse error: {}", err)),
+      };
+      let _ = request.respond(json_response(&response));
+    }
+    (Method::Get, path) => match parse_events_path(path) {
+      Some(id) => match find_job(id) {
+        Ok(job) => stream_events(request, id, job),
+        Err(err) => {
+          let _ = request.respond(text_response(404, &err.to_string()));
+        }
+      },
+      None => {
+        let _ = request.respond(text_response(404, "Not Found"));
+      }
+    },
+    _ => {
+      let _ = request.respond(text_response(404, "Not Found"));
+    }
+  }
+}
+
+fn handle_rpc(body: &str) -> Value {
+  let request: Value = match serde_json::from_str(body) {
+    Ok(request) => request,
+    Err(err) => return rpc_error(Value::Null, -32700, &format!("Parse error: {}", err)),
+  };
+  let id = request.get("id").cloned().unwrap_or(Value::Null);
+  let Some(method) = request.get("method").and_then(Value::as_str) else {
+    return rpc_error(id, -32600, "Invalid request: missing method");
+  };
+  let params = match request.get("params") {
+    Some(Value::Null) | None => json!({}),
+    Some(params) => params.clone(),
+  };
+  log::debug!("API server: {}", method);
+  match call(method, params) {
+    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+    Err((code, message)) => rpc_error(id, code, &message),
+  }
+}
+
+/// Start the API server if it's enabled. Called from the window `setup` hook.
+pub fn init() {
+  reload(&config::get_config().server);
+}
+
+fn is_local_host(host: &str, port: u16) -> bool {
+  ["127.0.0.1", "localhost"]
+    .iter()
+    .any(|name| host == format!("{}:{}", name, port) || (port == 80 && host == *name))
+}
+
+fn job_key(id: u64) -> String {
+  format!("api-job-{}", id)
+}
+
+fn json_response(value: &Value) -> Response<std::io::Cursor<Vec<u8>>> {
+  Response::from_data(serde_json::to_vec(value).unwrap_or_default())
+    .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+}
+
+fn parse_events_path(path: &str) -> Option<u64> {
+  path.strip_prefix("/jobs/")?.strip_suffix("/events")?.parse().ok()
+}
+
+/// Start, stop or restart the server to match `server`. A missing token is
+/// generated and saved before the server starts. Called on startup and after
+/// every config change.
+pub fn reload(server: &ConfigServer) {
+  let mut server = server.clone();
+  if server.enabled && server.token.trim().is_empty() {
+    server.token = match generate_token() {
+      Ok(token) => token,
+      Err(err) => {
+        log::error!("Couldn't generate the API server token because {}", err);
+        return;
+      }
+    };
+    let mut cfg = config::get_config();
+    cfg.server = server.clone();
+    if let Err(err) = config::set_config(cfg) {
+      log::error!("Couldn't save the API server token because {}", err);
+    }
+  }
+  let mut running = SERVER.lock().unwrap();
+  if running.as_ref().map(|api| &api.config) == Some(&server) || (running.is_none() && !server.enabled) {
+    return;
+  }
+  *running = None;
+  if server.enabled {
+    match ApiServer::start(server) {
+      Ok(api) => *running = Some(api),
+      Err(err) => log::error!("{}", err),
+    }
+  }
+}
+
+/// Drop the jobs that finished more than `JOB_RETENTION` ago and have no
+/// event stream open. Jobs whose end an event stream delivered are dropped
+/// right away by `stream_events`.
+fn prune_jobs() {
+  JOBS.lock().unwrap().retain(|_, job| {
+    job.streams.load(Ordering::SeqCst) > 0
+      || job.state.lock().unwrap().finished_at.is_none_or(|at| at.elapsed() < JOB_RETENTION)
+  });
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+  json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn start_job(params: CaptureParams) -> u64 {
+  prune_jobs();
+  let id = NEXT_JOB_ID.fetch_add(1, Ordering::SeqCst);
+  let job = Arc::new(Job {
+    state: Mutex::new(JobState {
+      progress: FfmpegCaptureProgressEvent {
+        percent: 0,
+        done: false,
+        cancelled: false,
+        error: None,
+        phase: "capture".to_string(),
+        current: 0,
+        total: 0,
+      },
+      frame: None,
+      version: 0,
+      finished_at: None,
+    }),
+    changed: Condvar::new(),
+    streams: AtomicUsize::new(0),
+  });
+  JOBS.lock().unwrap().insert(id, job.clone());
+  std::thread::spawn(move || {
+    let result = controller::run_ffmpeg_capture_job(
+      params.args,
+      params.output_pattern,
+      params.duration_seconds,
+      params.trim,
+      job_key(id),
+      CHILDREN.clone(),
+      CANCELS.clone(),
+      |event| job.update(|state| state.progress = event),
+      |path| job.update(|state| state.frame = Some(path.to_string_lossy().to_string())),
+    );
+    if let Err(err) = result {
+      job.update(|state| {
+        state.progress.done = true;
+        state.progress.error = Some(err.to_string());
+      });
+    }
+    // Not a change the event streams need to see, so the version stays.
+    job.state.lock().unwrap().finished_at = Some(Instant::now());
+  });
+  id
+}
+
+/// Stream a job's progress as server-sent events: a `progress` event with the
+/// `FfmpegCaptureProgressEvent` JSON whenever it changes, a `frame` event with
+/// the path of each newly captured image, and a comment every few seconds to
+/// keep the connection alive. The stream ends after the final progress event,
+/// and the job is dropped once no other stream is still open on it.
+fn stream_events(request: Request, id: u64, job: Arc<Job>) {
+  job.streams.fetch_add(1, Ordering::SeqCst);
+  let delivered = write_events(request, &job);
+  let last = job.streams.fetch_sub(1, Ordering::SeqCst) == 1;
+  if delivered && last {
+    JOBS.lock().unwrap().remove(&id);
+  }
+}
+
+/// The event loop of `stream_events`; true when the final event went out.
+fn write_events(request: Request, job: &Job) -> bool {
+  let mut writer = request.into_writer();
+  let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+  if writer.write_all(head.as_bytes()).and_then(|_| writer.flush()).is_err() {
+    return false;
+  }
+  let mut seen: Option<JobState> = None;
+  loop {
+    let state = {
+      let guard = job.state.lock().unwrap();
+      let version = seen.as_ref().map(|state| state.version);
+      let (guard, _) = job
+        .changed
+        .wait_timeout_while(guard, SSE_KEEPALIVE, |state| Some(state.version) == version)
+        .unwrap();
+      guard.clone()
+    };
+    let mut chunk = String::new();
+    if seen.as_ref().map(|s| s.version) == Some(state.version) {
+      chunk.push_str(": keepalive\n\n");
+    } else {
+      if state.frame.is_some() && seen.as_ref().map(|s| &s.frame) != Some(&state.frame) {
+        chunk.push_str(&format!("event: frame\ndata: {}\n\n", json!({ "path": state.frame })));
+      }
+      chunk.push_str(&format!("event: progress\ndata: {}\n\n", json!(state.progress)));
+    }
+    if writer.write_all(chunk.as_bytes()).and_then(|_| writer.flush()).is_err() {
+      return false;
+    }
+    if state.progress.done {
+      return true;
+    }
+    seen = Some(state);
+  }
+}
+
+fn text_response(status: u16, text: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+  Response::from_string(text).with_status_code(status)
+}
+
+/// Compare in constant time so the token can't be guessed byte by byte.
+fn token_matches(given: &str, token: &str) -> bool {
+  !token.is_empty()
+    && given.len() == token.len()
+    && given.bytes().zip(token.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn handle_rpc_reports_protocol_errors() {
+    assert_eq!(handle_rpc("not json")["error"]["code"], -32700);
+    assert_eq!(handle_rpc(r#"{"jsonrpc":"2.0","id":1}"#)["error"]["code"], -32600);
+    let response = handle_rpc(r#"{"jsonrpc":"2.0","id":7,"method":"nope"}"#);
+    assert_eq!(response["id"], 7);
+    assert_eq!(response["error"]["code"], -32601);
+    let response = handle_rpc(r#"{"jsonrpc":"2.0","id":8,"method":"get_stream_count","params":{}}"#);
+    assert_eq!(response["error"]["code"], -32602);
+  }
+
+  #[test]
+  fn request_guards() {
+    assert_eq!(parse_events_path("/jobs/12/events"), Some(12));
+    assert_eq!(parse_events_path("/jobs/x/events"), None);
+    assert!(is_local_host("127.0.0.1:8484", 8484));
+    assert!(is_local_host("localhost:8484", 8484));
+    assert!(!is_local_host("evil.example:8484", 8484));
+    assert!(token_matches("abc", "abc"));
+    assert!(!token_matches("abd", "abc"));
+    assert!(!token_matches("", ""));
+    let token = generate_token().unwrap();
+    assert_eq!(token.len(), 32);
+    assert_ne!(token, generate_token().unwrap());
+  }
+
+  #[test]
+  fn prune_jobs_drops_only_stale_finished_jobs() {
+    let job = |finished_at: Option<Instant>, streams: usize| {
+      Arc::new(Job {
+        state: Mutex::new(JobState {
+          progress: FfmpegCaptureProgressEvent {
+            percent: 100,
+            done: finished_at.is_some(),
+            cancelled: false,
+            error: None,
+            phase: "capture".to_string(),
+            current: 0,
+            total: 0,
+          },
+          frame: None,
+          version: 0,
+          finished_at,
+        }),
+        changed: Condvar::new(),
+        streams: AtomicUsize::new(streams),
+      })
+    };
+    let stale = Instant::now().checked_sub(JOB_RETENTION * 2);
+    let ids = [u64::MAX - 3, u64::MAX - 2, u64::MAX - 1, u64::MAX];
+    {
+      let mut jobs = JOBS.lock().unwrap();
+      jobs.insert(ids[0], job(None, 0));
+      jobs.insert(ids[1], job(Some(Instant::now()), 0));
+      jobs.insert(ids[2], job(stale, 0));
+      jobs.insert(ids[3], job(stale, 1));
+    }
+    prune_jobs();
+    let jobs = JOBS.lock().unwrap();
+    let kept: Vec<bool> = ids.iter().map(|id| jobs.contains_key(id)).collect();
+    // Without a stale instant (an uptime under the retention) nothing is old enough.
+    assert_eq!(kept, [true, true, stale.is_none(), true]);
+  }
+}
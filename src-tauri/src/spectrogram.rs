@@ -0,0 +1,590 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use image::{Rgb, RgbImage};
+use realfft::{RealFftPlanner, RealToComplex};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::chart::{self, Panel};
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{SpectrogramOptions, SpectrogramReport, StreamProperty, StreamPropertyMap};
+
+/// A cutoff is a drop of `DROP_DB` within `CLIFF_HZ` that doesn't recover above it.
+const CLIFF_HZ: f64 = 1000.0;
+const CLIPPED_COLOR: Rgb<u8> = Rgb([0xEF, 0x44, 0x44]);
+/// Samples this close to full scale count as clipped.
+const CLIPPED_LEVEL: f32 = 0.9999;
+const CUTOFF_COLOR: Rgb<u8> = Rgb([0x22, 0xD3, 0xEE]);
+const DROP_DB: f64 = 30.0;
+const FFT_SIZE: usize = 4096;
+/// Level shown as black; the scale runs from here up to a full-scale sine at 0 dB.
+const FLOOR_DB: f64 = -120.0;
+/// Frequency at the bottom of the log axis.
+const MIN_FREQUENCY: f64 = 20.0;
+/// Cutoffs above this fraction of Nyquist are the anti-alias filter of the
+/// sample rate itself, not the lowpass of an encoder.
+const MAX_CUTOFF: f64 = 0.9;
+const MIN_CUTOFF: f64 = 1000.0;
+const MIN_HOP: usize = 256;
+/// Margins around each panel: room for the y labels, the title and the x labels.
+const PANEL_LEFT: u32 = 64;
+const PANEL_RIGHT: u32 = 16;
+const PANEL_TOP: u32 = 20;
+const PANEL_BOTTOM: u32 = 24;
+const PROPERTIES: &[&str] = &["Duration", "SamplingRate", "Channels"];
+/// Share of the plot height that goes to the spectrogram, the rest to the waveform.
+const SPECTROGRAM_SHARE: f64 = 0.7;
+/// Seconds the columns first span when MediaInfo has no duration.
+const UNKNOWN_DURATION: f64 = 60.0;
+const WAVEFORM_COLOR: Rgb<u8> = Rgb([0xF5, 0x9E, 0x0B]);
+
+/// Running state of the decode: the spectrum and the waveform per pixel column
+/// of the chart, plus the long-term spectrum the cutoff is read from. Peaks
+/// and clipping are taken per channel; only the spectrum uses the channels'
+/// mean, which would hide clipping in one of them.
+struct Analysis {
+  fft: Arc<dyn RealToComplex<f32>>,
+  window: Vec<f32>,
+  frame: Vec<f32>,
+  hop: usize,
+  /// Sample frames the columns span; doubled as needed when the duration is unknown.
+  expected: u64,
+  grow: bool,
+  position: u64,
+  /// The channel the next interleaved sample belongs to, and the sum of the
+  /// current sample frame so far.
+  channel: usize,
+  sum: f32,
+  /// Power per bin, summed per column and over the whole stream.
+  columns: Vec<Vec<f64>>,
+  frames: Vec<u32>,
+  total: Vec<f64>,
+  /// Lowest and highest sample of any channel per column, and whether any of
+  /// them clipped.
+  waveform: Vec<(f32, f32, bool)>,
+  peaks: Vec<f32>,
+  clipped: Vec<u64>,
+}
+
+impl Analysis {
+  /// `expected` sample frames of `channels` interleaved channels spread over
+  /// `columns` pixel columns. An `expected` of 0 (unknown duration) starts
+  /// from `fallback` and keeps doubling the span as the decode runs past it.
+  fn new(columns: usize, channels: usize, expected: u64, fallback: u64) -> Self {
+    let fft = RealFftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+    let bins = FFT_SIZE / 2 + 1;
+    let window = (0..FFT_SIZE)
+      .map(|i| (0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / FFT_SIZE as f64).cos()) as f32)
+      .collect();
+    let grow = expected == 0;
+    let expected = if grow { fallback } else { expected }.max(1);
+    // Overlap the frames of short streams so every column gets at least one.
+    let hop = ((expected / columns.max(1) as u64) as usize).clamp(MIN_HOP, FFT_SIZE);
+    let channels = channels.max(1);
+    Self {
+      fft,
+      window,
+      frame: Vec::with_capacity(FFT_SIZE),
+      hop,
+      expected,
+      grow,
+      position: 0,
+      channel: 0,
+      sum: 0.0,
+      columns: vec![vec![0.0; bins]; columns],
+      frames: vec![0; columns],
+      total: vec![0.0; bins],
+      waveform: vec![(0.0, 0.0, false); columns],
+      peaks: vec![0.0; channels],
+      clipped: vec![0; channels],
+    }
+  }
+
+  /// Double the span of the columns, merging each pair into one.
+  fn double(&mut self) {
+    fn merge<T: Clone>(values: &mut [T], empty: T, mut join: impl FnMut(&T, &T) -> T) {
+      for column in 0..values.len() {
+        values[column] = if 2 * column < values.len() {
+          let pair = values.get(2 * column + 1).cloned().unwrap_or_else(|| empty.clone());
+          join(&values[2 * column], &pair)
+        } else {
+          empty.clone()
+        };
+      }
+    }
+    let bins = self.total.len();
+    merge(&mut self.columns, vec![0.0; bins], |a, b| {
+      a.iter().zip(b).map(|(a, b)| a + b).collect()
+    });
+    merge(&mut self.frames, 0, |a, b| a + b);
+    merge(&mut self.waveform, (0.0, 0.0, false), |a, b| {
+      (a.0.min(b.0), a.1.max(b.1), a.2 || b.2)
+    });
+    self.expected *= 2;
+  }
+
+  fn column(&self, sample: u64) -> usize {
+    ((sample as u128 * self.columns.len() as u128 / self.expected as u128) as usize).min(self.columns.len() - 1)
+  }
+
+  /// Seconds the time axis covers: what was decoded, or with an unknown
+  /// duration the span of the columns, which the decode may fill only half of.
+  fn span(&self, sample_rate: u32) -> f64 {
+    let samples = if self.grow { self.expected } else { self.position };
+    samples as f64 / sample_rate as f64
+  }
+
+  /// Interleaved samples; a sample frame may be split across two calls.
+  fn push(&mut self, samples: &[f32]) {
+    for &sample in samples {
+      if self.grow && self.position >= self.expected {
+        self.double();
+      }
+      let column = self.column(self.position);
+      let (low, high, clipped) = &mut self.waveform[column];
+      *low = low.min(sample);
+      *high = high.max(sample);
+      if sample.abs() >= CLIPPED_LEVEL {
+        *clipped = true;
+        self.clipped[self.channel] += 1;
+      }
+      self.peaks[self.channel] = self.peaks[self.channel].max(sample.abs());
+      self.sum += sample;
+      self.channel += 1;
+      if self.channel < self.peaks.len() {
+        continue;
+      }
+      self.frame.push(self.sum / self.peaks.len() as f32);
+      self.channel = 0;
+      self.sum = 0.0;
+      self.position += 1;
+      if self.frame.len() == FFT_SIZE {
+        self.transform();
+        self.frame.drain(..self.hop);
+      }
+    }
+  }
+
+  /// Add the power spectrum of the current frame to the column it starts in,
+  /// scaled so a full-scale sine peaks at 1 (0 dB).
+  fn transform(&mut self) {
+    let mut input: Vec<f32> = self.frame.iter().zip(&self.window).map(|(s, w)| s * w).collect();
+    let mut output = self.fft.make_output_vec();
+    if self.fft.process(&mut input, &mut output).is_err() {
+      return;
+    }
+    let column = self.column(self.position - FFT_SIZE as u64);
+    let scale = (FFT_SIZE as f64 / 4.0).powi(2);
+    for (bin, value) in output.iter().enumerate() {
+      let power = value.norm_sqr() as f64 / scale;
+      self.columns[column][bin] += power;
+      self.total[bin] += power;
+    }
+    self.frames[column] += 1;
+  }
+
+  /// The mean power per bin of each column; columns no frame started in take
+  /// the one before.
+  fn spectra(&self) -> Vec<Vec<f64>> {
+    let mut spectra: Vec<Vec<f64>> = Vec::with_capacity(self.columns.len());
+    for (column, sums) in self.columns.iter().enumerate() {
+      let frames = self.frames[column];
+      let spectrum = if frames > 0 {
+        sums.iter().map(|sum| sum / frames as f64).collect()
+      } else {
+        spectra.last().cloned().unwrap_or_else(|| vec![0.0; sums.len()])
+      };
+      spectra.push(spectrum);
+    }
+    spectra
+  }
+}
+
+/// Decode audio stream `options.stream` of `file` to PCM, render its
+/// log-frequency spectrogram above its waveform to `options.output`, and
+/// report the peak and clipped samples of each channel and a likely lowpass
+/// cutoff.
+/// `properties` are the `properties()` of `file`. `run` decodes `args`,
+/// passing the samples on as they arrive (see `ffmpeg::run_pcm_decode`).
+pub fn analyze(
+  file: &str,
+  options: &SpectrogramOptions,
+  properties: &[StreamPropertyMap],
+  run: impl FnOnce(&[String], &mut dyn FnMut(&[f32])) -> Result<()>,
+  mut on_percent: impl FnMut(u32),
+) -> Result<SpectrogramReport> {
+  let audio = properties
+    .iter()
+    .find(|map| map.stream == MediaInfoStreamKind::Audio && map.num == options.stream as i32)
+    .map(|map| &map.property_map)
+    .ok_or_else(|| anyhow::anyhow!("SPECTROGRAM_NO_AUDIO:{}", file))?;
+  let get = |name: &str| -> f64 {
+    audio
+      .get(name)
+      .and_then(|value| value.parse::<f64>().ok())
+      .unwrap_or_default()
+  };
+  let sample_rate = get("SamplingRate").round() as u32;
+  let duration = get("Duration") / 1000.0;
+  if sample_rate == 0 {
+    return Err(anyhow::anyhow!("SPECTROGRAM_NO_AUDIO:{}", file));
+  }
+  // "2 / 1" and the like list the layouts of a stream that changes; the first is decoded.
+  let channels = audio
+    .get("Channels")
+    .and_then(|value| value.split_whitespace().next())
+    .and_then(|value| value.parse::<usize>().ok())
+    .unwrap_or_default();
+  let (spectrogram, waveform) = panels(options.width, options.height)?;
+  let expected = (duration * sample_rate as f64).round() as u64;
+  let mut analysis = Analysis::new(
+    spectrogram.width as usize - 2,
+    channels,
+    expected,
+    (UNKNOWN_DURATION * sample_rate as f64) as u64,
+  );
+  let mut last_percent = u32::MAX;
+  run(&args(file, options.stream, channels), &mut |samples| {
+    analysis.push(samples);
+    // Without a duration there's nothing to measure progress against.
+    let percent = if analysis.grow {
+      0
+    } else {
+      (analysis.position * 100 / analysis.expected).min(100) as u32
+    };
+    if percent != last_percent {
+      last_percent = percent;
+      on_percent(percent);
+    }
+  })?;
+  if analysis.position == 0 {
+    return Err(anyhow::anyhow!("SPECTROGRAM_NO_AUDIO:{}", file));
+  }
+  let bin_hz = sample_rate as f64 / FFT_SIZE as f64;
+  let cutoff = cutoff(&analysis.total, bin_hz, sample_rate as f64 / 2.0);
+  let peak = analysis.peaks.iter().copied().fold(0.0, f32::max);
+  let report = SpectrogramReport {
+    output: options.output.clone(),
+    sample_rate,
+    duration: analysis.position as f64 / sample_rate as f64,
+    peak: to_db(peak as f64 * peak as f64),
+    clipped_samples: analysis.clipped.iter().sum(),
+    channel_peaks: analysis
+      .peaks
+      .iter()
+      .map(|peak| to_db(*peak as f64 * *peak as f64))
+      .collect(),
+    channel_clipped: analysis.clipped.clone(),
+    cutoff,
+  };
+  let mut image = RgbImage::from_pixel(options.width, options.height, chart::BACKGROUND);
+  draw_spectrogram(&mut image, &spectrogram, &analysis, &report, options.stream);
+  draw_waveform(&mut image, &waveform, &analysis, &report);
+  chart::write_png(&image, Path::new(&options.output))?;
+  on_percent(100);
+  Ok(report)
+}
+
+/// Decode one audio stream at its own rate as interleaved raw floats. The
+/// channel count is pinned to MediaInfo's so the interleaving is known; with
+/// none, the stream is downmixed to mono.
+pub fn args(file: &str, stream: u32, channels: usize) -> Vec<String> {
+  [
+    "-loglevel",
+    "error",
+    "-i",
+    file,
+    "-map",
+    &format!("0:a:{}", stream),
+    "-ac",
+    &channels.max(1).to_string(),
+    "-f",
+    "f32le",
+    "-acodec",
+    "pcm_f32le",
+    "pipe:1",
+  ]
+  .iter()
+  .map(|arg| arg.to_string())
+  .collect()
+}
+
+/// The magma-like color of a level between `FLOOR_DB` and 0 dB.
+fn color(db: f64) -> Rgb<u8> {
+  const STOPS: &[(f64, [u8; 3])] = &[
+    (0.0, [0x00, 0x00, 0x04]),
+    (0.25, [0x3B, 0x0F, 0x70]),
+    (0.5, [0x8C, 0x29, 0x81]),
+    (0.7, [0xDE, 0x49, 0x68]),
+    (0.85, [0xFE, 0x9F, 0x6D]),
+    (1.0, [0xFC, 0xFD, 0xBF]),
+  ];
+  let t = ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+  let upper = STOPS
+    .iter()
+    .position(|(stop, _)| *stop >= t)
+    .unwrap_or(STOPS.len() - 1)
+    .max(1);
+  let ((t0, c0), (t1, c1)) = (STOPS[upper - 1], STOPS[upper]);
+  let f = (t - t0) / (t1 - t0);
+  Rgb(std::array::from_fn(|i| {
+    (c0[i] as f64 + (c1[i] as f64 - c0[i] as f64) * f).round() as u8
+  }))
+}
+
+/// The highest frequency the long-term spectrum `total` (power per bin,
+/// `bin_hz` apart) drops at least `DROP_DB` from within `CLIFF_HZ` and never
+/// climbs back above, i.e. where an encoder's lowpass cut everything off.
+fn cutoff(total: &[f64], bin_hz: f64, nyquist: f64) -> Option<f64> {
+  let levels: Vec<f64> = (0..total.len())
+    .map(|bin| {
+      // Smooth over a few bins so a single quiet bin isn't a cliff.
+      let range = bin.saturating_sub(2)..(bin + 3).min(total.len());
+      let count = range.len() as f64;
+      to_db(total[range].iter().sum::<f64>() / count)
+    })
+    .collect();
+  let cliff = (CLIFF_HZ / bin_hz).ceil() as usize;
+  let mut above = vec![f64::NEG_INFINITY; levels.len() + 1];
+  for bin in (0..levels.len()).rev() {
+    above[bin] = above[bin + 1].max(levels[bin]);
+  }
+  let lowest = (MIN_CUTOFF / bin_hz) as usize;
+  let highest = ((nyquist * MAX_CUTOFF / bin_hz) as usize).min(levels.len().saturating_sub(cliff + 1));
+  (lowest..=highest)
+    .rev()
+    .find(|bin| levels[*bin] - above[bin + cliff] >= DROP_DB)
+    .map(|bin| {
+      // The cliff starts somewhere in the next `cliff` bins: take the last one
+      // still within half the drop of where it started.
+      let edge = (bin..bin + cliff)
+        .rev()
+        .find(|edge| levels[*edge] >= levels[bin] - DROP_DB / 2.0)
+        .unwrap_or(bin);
+      edge as f64 * bin_hz
+    })
+}
+
+fn draw_spectrogram(image: &mut RgbImage, panel: &Panel, analysis: &Analysis, report: &SpectrogramReport, stream: u32) {
+  let nyquist = report.sample_rate as f64 / 2.0;
+  let bin_hz = report.sample_rate as f64 / FFT_SIZE as f64;
+  let span = (nyquist / MIN_FREQUENCY).ln();
+  let fraction = |hz: f64| (hz / MIN_FREQUENCY).ln() / span;
+  let y_ticks: Vec<(f64, String)> = [50.0, 100.0, 200.0, 500.0, 1e3, 2e3, 5e3, 1e4, 2e4]
+    .into_iter()
+    .filter(|hz| *hz < nyquist)
+    .map(|hz| (fraction(hz), format_frequency(hz)))
+    .collect();
+  let mut title = format!(
+    "Audio #{}  {} Hz  peak {:.1} dBFS",
+    stream + 1,
+    report.sample_rate,
+    report.peak
+  );
+  if let Some(cutoff) = report.cutoff {
+    title.push_str(&format!("  lowpass ~{:.1} kHz", cutoff / 1000.0));
+  }
+  let x_ticks = time_ticks(analysis.span(report.sample_rate));
+  chart::draw_axes(image, panel, &title, &x_ticks, &y_ticks);
+  // Each row spans a slice of the log axis; it shows the loudest bin in it.
+  let rows = panel.height - 2;
+  let bounds: Vec<(usize, usize)> = (0..rows)
+    .map(|row| {
+      let hz = |row: f64| MIN_FREQUENCY * (span * row / rows as f64).exp();
+      let low = (hz(row as f64) / bin_hz).round() as usize;
+      let high = (hz(row as f64 + 1.0) / bin_hz).round() as usize;
+      (low.min(FFT_SIZE / 2), high.max(low + 1).min(FFT_SIZE / 2 + 1))
+    })
+    .collect();
+  for (column, spectrum) in analysis.spectra().iter().enumerate() {
+    for (row, (low, high)) in bounds.iter().enumerate() {
+      let power = spectrum[*low..(*high).max(*low + 1)]
+        .iter()
+        .copied()
+        .fold(0.0, f64::max);
+      let y = panel.top + panel.height - 2 - row as u32;
+      image.put_pixel(panel.left + 1 + column as u32, y, color(to_db(power)));
+    }
+  }
+  if let Some(cutoff) = report.cutoff {
+    let y = panel.y(fraction(cutoff));
+    chart::draw_hline(image, panel.left + 1, panel.left + panel.width - 2, y, CUTOFF_COLOR);
+  }
+}
+
+fn draw_waveform(image: &mut RgbImage, panel: &Panel, analysis: &Analysis, report: &SpectrogramReport) {
+  let y_ticks = vec![(0.0, "-1".to_string()), (0.5, "0".to_string()), (1.0, "1".to_string())];
+  let title = format!("Waveform  {} clipped samples", report.clipped_samples);
+  let x_ticks = time_ticks(analysis.span(report.sample_rate));
+  chart::draw_axes(image, panel, &title, &x_ticks, &y_ticks);
+  let filled = analysis.column(analysis.position.saturating_sub(1)) + 1;
+  for (column, (low, high, clipped)) in analysis.waveform.iter().take(filled).enumerate() {
+    let color = if *clipped { CLIPPED_COLOR } else { WAVEFORM_COLOR };
+    chart::draw_vline(
+      image,
+      panel.left + 1 + column as u32,
+      panel.y((*high as f64 + 1.0) / 2.0),
+      panel.y((*low as f64 + 1.0) / 2.0),
+      color,
+    );
+  }
+}
+
+/// `1k`, `2.5k` or `500` for a frequency in Hz.
+fn format_frequency(hz: f64) -> String {
+  if hz >= 1000.0 {
+    format!("{}k", hz / 1000.0)
+  } else {
+    format!("{}", hz)
+  }
+}
+
+/// The spectrogram and waveform panels of a `width` x `height` image.
+fn panels(width: u32, height: u32) -> Result<(Panel, Panel)> {
+  let plot_width = width.saturating_sub(PANEL_LEFT + PANEL_RIGHT);
+  let plot_height = height.saturating_sub(2 * (PANEL_TOP + PANEL_BOTTOM));
+  let spectrogram_height = (plot_height as f64 * SPECTROGRAM_SHARE).round() as u32;
+  let waveform_height = plot_height - spectrogram_height;
+  if plot_width < 16 || waveform_height < 16 {
+    return Err(anyhow::anyhow!("SPECTROGRAM_TOO_SMALL:{}x{}", width, height));
+  }
+  let spectrogram = Panel {
+    left: PANEL_LEFT,
+    top: PANEL_TOP,
+    width: plot_width,
+    height: spectrogram_height,
+  };
+  let waveform = Panel {
+    left: PANEL_LEFT,
+    top: PANEL_TOP + spectrogram_height + PANEL_BOTTOM + PANEL_TOP,
+    width: plot_width,
+    height: waveform_height,
+  };
+  Ok((spectrogram, waveform))
+}
+
+pub fn properties() -> Vec<StreamProperty> {
+  PROPERTIES
+    .iter()
+    .map(|property| StreamProperty {
+      stream: MediaInfoStreamKind::Audio,
+      property: property.to_string(),
+    })
+    .collect()
+}
+
+fn time_ticks(duration: f64) -> Vec<(f64, String)> {
+  chart::nice_ticks(duration, 8)
+    .into_iter()
+    .map(|seconds| (seconds / duration.max(1e-9), chart::format_time(seconds)))
+    .collect()
+}
+
+fn to_db(power: f64) -> f64 {
+  if power > 0.0 {
+    (10.0 * power.log10()).max(FLOOR_DB * 2.0)
+  } else {
+    FLOOR_DB * 2.0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sine(hz: f64, amplitude: f64, rate: f64, seconds: f64) -> Vec<f32> {
+    (0..(rate * seconds) as usize)
+      .map(|i| (amplitude * (2.0 * std::f64::consts::PI * hz * i as f64 / rate).sin()) as f32)
+      .collect()
+  }
+
+  #[test]
+  fn test_analysis() {
+    let samples = sine(1000.0, 0.5, 48000.0, 2.0);
+    let mut analysis = Analysis::new(50, 1, samples.len() as u64, 0);
+    analysis.push(&samples[..1001]);
+    analysis.push(&samples[1001..]);
+    assert_eq!(analysis.clipped, vec![0]);
+    assert!((to_db((analysis.peaks[0] as f64).powi(2)) + 6.02).abs() < 0.1);
+    let spectra = analysis.spectra();
+    assert_eq!(spectra.len(), 50);
+    let loudest = spectra[25]
+      .iter()
+      .enumerate()
+      .max_by(|a, b| a.1.total_cmp(b.1))
+      .map(|(bin, power)| (bin as f64 * 48000.0 / FFT_SIZE as f64, to_db(*power)))
+      .unwrap();
+    assert!((loudest.0 - 1000.0).abs() < 12.0);
+    // A half-scale sine sits about 6 dB under full scale.
+    assert!((loudest.1 + 6.0).abs() < 2.0);
+
+    let mut clipping = Analysis::new(10, 1, 100, 0);
+    clipping.push(&[0.2, 1.0, -1.0, 0.5]);
+    assert_eq!(clipping.clipped, vec![2]);
+    assert!(clipping.waveform[0].2);
+  }
+
+  #[test]
+  fn test_analysis_channels() {
+    // The left channel clips while the right is silent; their mean wouldn't.
+    let mut analysis = Analysis::new(10, 2, 100, 0);
+    analysis.push(&[1.0, 0.0, -1.0]);
+    analysis.push(&[0.0, 0.25, 0.0]);
+    assert_eq!(analysis.position, 3);
+    assert_eq!(analysis.clipped, vec![2, 0]);
+    assert_eq!(analysis.peaks, vec![1.0, 0.0]);
+    assert_eq!(analysis.frame, vec![0.5, -0.5, 0.125]);
+    assert!(analysis.waveform[0].2);
+    assert_eq!(args("in.mkv", 1, 6)[7], "6");
+  }
+
+  #[test]
+  fn test_analysis_unknown_duration() {
+    let mut analysis = Analysis::new(4, 1, 0, 4);
+    analysis.push(&[0.1; 4]);
+    assert_eq!(analysis.expected, 4);
+    analysis.push(&[-0.2; 4]);
+    // The span doubled: the first four samples now share the first two columns.
+    assert_eq!(analysis.expected, 8);
+    assert_eq!(
+      analysis.waveform[..3],
+      [(0.0, 0.1, false), (0.0, 0.1, false), (-0.2, 0.0, false)]
+    );
+    analysis.push(&[0.3; 8]);
+    assert_eq!(analysis.expected, 16);
+    assert_eq!(analysis.column(analysis.position - 1), 3);
+  }
+
+  #[test]
+  fn test_cutoff() {
+    let bin_hz = 44100.0 / FFT_SIZE as f64;
+    let bins = FFT_SIZE / 2 + 1;
+    let lowpassed: Vec<f64> = (0..bins)
+      .map(|bin| if (bin as f64) * bin_hz < 16000.0 { 1e-6 } else { 1e-15 })
+      .collect();
+    let found = cutoff(&lowpassed, bin_hz, 22050.0).unwrap();
+    assert!((found - 16000.0).abs() < 100.0, "{}", found);
+    // A gentle roll-off all the way to Nyquist has no cliff.
+    let natural: Vec<f64> = (0..bins)
+      .map(|bin| 10f64.powf(-(4.0 + 4.0 * bin as f64 / bins as f64)))
+      .collect();
+    assert_eq!(cutoff(&natural, bin_hz, 22050.0), None);
+    assert_eq!(format_frequency(2500.0), "2.5k");
+  }
+}
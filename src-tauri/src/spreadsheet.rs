@@ -0,0 +1,337 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::{ConfigTemplateGroup, ConfigTemplates};
+use crate::media_info::MediaInfoStreamKind;
+use crate::protocol::{SpreadsheetExportOptions, SpreadsheetFormat, StreamPropertyMap};
+
+/// The properties MediaInfo reported for one file of the batch.
+pub struct FileAnalysis {
+  pub file: String,
+  pub streams: Vec<StreamPropertyMap>,
+}
+
+/// One table of the export: a worksheet in `.xlsx`, a file of its own in CSV.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+  pub name: String,
+  pub header: Vec<String>,
+  pub rows: Vec<Vec<String>>,
+}
+
+/// Shape the batch into tables. Without `per_stream` there is a single `Files`
+/// table holding one row per file (the General stream); with it there is one
+/// table per stream kind holding one row per stream. Columns follow the
+/// matching `ConfigTemplates` group, the same way the details view orders its
+/// properties; an empty group falls back to every property seen in the batch,
+/// sorted by name.
+pub fn build_sheets(analyses: &[FileAnalysis], templates: &ConfigTemplates, per_stream: bool) -> Vec<Sheet> {
+  if !per_stream {
+    let maps: Vec<(&str, &StreamPropertyMap)> = analyses
+      .iter()
+      .filter_map(|analysis| {
+        analysis
+          .streams
+          .iter()
+          .find(|map| map.stream == MediaInfoStreamKind::General && map.num == 0)
+          .map(|map| (analysis.file.as_str(), map))
+      })
+      .collect();
+    let columns = columns_for(&templates.general, &maps);
+    let mut header = vec!["File".to_owned()];
+    header.extend(columns.iter().cloned());
+    let rows = analyses
+      .iter()
+      .map(|analysis| {
+        let general = maps.iter().find(|(file, _)| *file == analysis.file).map(|(_, map)| *map);
+        let mut row = vec![analysis.file.clone()];
+        row.extend(columns.iter().map(|column| {
+          general
+            .and_then(|map| map.property_map.get(column))
+            .cloned()
+            .unwrap_or_default()
+        }));
+        row
+      })
+      .collect();
+    return vec![Sheet {
+      name: "Files".to_owned(),
+      header,
+      rows,
+    }];
+  }
+  let mut sheets = Vec::new();
+  for stream_kind in MediaInfoStreamKind::values() {
+    let Some(group) = template_group(templates, *stream_kind) else {
+      continue;
+    };
+    let maps: Vec<(&str, &StreamPropertyMap)> = analyses
+      .iter()
+      .flat_map(|analysis| {
+        analysis
+          .streams
+          .iter()
+          .filter(|map| map.stream == *stream_kind)
+          .map(|map| (analysis.file.as_str(), map))
+      })
+      .collect();
+    if maps.is_empty() {
+      continue;
+    }
+    let columns = columns_for(group, &maps);
+    let mut header = vec!["File".to_owned(), "Stream".to_owned()];
+    header.extend(columns.iter().cloned());
+    let rows = maps
+      .iter()
+      .map(|(file, map)| {
+        let mut row = vec![file.to_string(), map.num.to_string()];
+        row.extend(
+          columns
+            .iter()
+            .map(|column| map.property_map.get(column).cloned().unwrap_or_default()),
+        );
+        row
+      })
+      .collect();
+    sheets.push(Sheet {
+      name: stream_kind.get_name().to_owned(),
+      header,
+      rows,
+    });
+  }
+  sheets
+}
+
+fn columns_for(group: &ConfigTemplateGroup, maps: &[(&str, &StreamPropertyMap)]) -> Vec<String> {
+  if !group.properties.is_empty() {
+    return group.properties.clone();
+  }
+  let columns: BTreeSet<&String> = maps.iter().flat_map(|(_, map)| map.property_map.keys()).collect();
+  columns.into_iter().cloned().collect()
+}
+
+/// Quote a field per RFC 4180: fields containing a comma, a double quote or a
+/// line break are wrapped in double quotes, with embedded quotes doubled.
+fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\r', '\n']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_owned()
+  }
+}
+
+/// Write the tables to `options.output`. A workbook holds every table as its own
+/// worksheet; CSV has no sheets, so a multi-table export writes one file per
+/// table next to the chosen path (`report.csv` → `report_Video.csv`, ...).
+/// Returns the paths written.
+pub fn export(sheets: &[Sheet], options: &SpreadsheetExportOptions) -> Result<Vec<String>> {
+  let output = PathBuf::from(options.output.trim());
+  if output.as_os_str().is_empty() {
+    return Err(anyhow::anyhow!("No output file is specified."));
+  }
+  match options.format {
+    SpreadsheetFormat::Csv => {
+      let mut written = Vec::new();
+      for sheet in sheets {
+        let path = if sheets.len() == 1 {
+          output.clone()
+        } else {
+          sheet_path(&output, &sheet.name)
+        };
+        write_csv(sheet, &path)?;
+        written.push(path.to_string_lossy().to_string());
+      }
+      Ok(written)
+    }
+    SpreadsheetFormat::Xlsx => {
+      write_xlsx(sheets, &output)?;
+      Ok(vec![output.to_string_lossy().to_string()])
+    }
+  }
+}
+
+/// Interpret a MediaInfo value as a number cell. Only plain decimal notation is
+/// accepted; values with leading zeros (track IDs, timecodes) or more digits
+/// than a spreadsheet double can hold exactly (UUID-like unique IDs) stay text.
+fn numeric_value(value: &str) -> Option<f64> {
+  if value.is_empty() || value.trim() != value || value.starts_with('+') {
+    return None;
+  }
+  if !value.bytes().all(|b| b.is_ascii_digit() || b == b'.' || b == b'-') {
+    return None;
+  }
+  let unsigned = value.strip_prefix('-').unwrap_or(value);
+  let integer_part = unsigned.split('.').next().unwrap_or_default();
+  if integer_part.is_empty() || (integer_part.len() > 1 && integer_part.starts_with('0')) {
+    return None;
+  }
+  if unsigned.bytes().filter(|b| b.is_ascii_digit()).count() > 15 {
+    return None;
+  }
+  value.parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+fn sheet_path(output: &Path, sheet_name: &str) -> PathBuf {
+  let stem = output.file_stem().and_then(|s| s.to_str()).unwrap_or("report");
+  let name = match output.extension().and_then(|e| e.to_str()) {
+    Some(ext) => format!("{}_{}.{}", stem, sheet_name, ext),
+    None => format!("{}_{}", stem, sheet_name),
+  };
+  output.with_file_name(name)
+}
+
+pub fn template_group(templates: &ConfigTemplates, stream_kind: MediaInfoStreamKind) -> Option<&ConfigTemplateGroup> {
+  match stream_kind {
+    MediaInfoStreamKind::General => Some(&templates.general),
+    MediaInfoStreamKind::Video => Some(&templates.video),
+    MediaInfoStreamKind::Audio => Some(&templates.audio),
+    MediaInfoStreamKind::Text => Some(&templates.text),
+    MediaInfoStreamKind::Other => Some(&templates.other),
+    MediaInfoStreamKind::Image => Some(&templates.image),
+    MediaInfoStreamKind::Menu => Some(&templates.menu),
+    MediaInfoStreamKind::Max => None,
+  }
+}
+
+fn write_csv(sheet: &Sheet, path: &Path) -> Result<()> {
+  let mut writer = BufWriter::new(File::create(path)?);
+  for record in std::iter::once(&sheet.header).chain(sheet.rows.iter()) {
+    let line: Vec<String> = record.iter().map(|field| csv_field(field)).collect();
+    writer.write_all(line.join(",").as_bytes())?;
+    writer.write_all(b"\r\n")?;
+  }
+  writer.flush()?;
+  Ok(())
+}
+
+fn write_xlsx(sheets: &[Sheet], path: &Path) -> Result<()> {
+  let mut workbook = rust_xlsxwriter::Workbook::new();
+  let bold = rust_xlsxwriter::Format::new().set_bold();
+  for sheet in sheets {
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(sheet.name.as_str())?;
+    for (col, title) in sheet.header.iter().enumerate() {
+      worksheet.write_string_with_format(0, col as u16, title.as_str(), &bold)?;
+    }
+    for (row, record) in sheet.rows.iter().enumerate() {
+      let row = row as u32 + 1;
+      for (col, value) in record.iter().enumerate() {
+        if value.is_empty() {
+          continue;
+        }
+        match numeric_value(value) {
+          Some(number) => worksheet.write_number(row, col as u16, number)?,
+          None => worksheet.write_string(row, col as u16, value.as_str())?,
+        };
+      }
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.autofit();
+  }
+  workbook.save(path)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashMap;
+
+  fn stream(stream: MediaInfoStreamKind, num: i32, pairs: &[(&str, &str)]) -> StreamPropertyMap {
+    StreamPropertyMap {
+      stream,
+      num,
+      property_map: pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<_, _>>(),
+      source: Default::default(),
+    }
+  }
+
+  fn analyses() -> Vec<FileAnalysis> {
+    vec![
+      FileAnalysis {
+        file: "a.mkv".to_owned(),
+        streams: vec![
+          stream(MediaInfoStreamKind::General, 0, &[("Format", "Matroska"), ("Duration", "60000")]),
+          stream(MediaInfoStreamKind::Audio, 0, &[("Format", "AAC")]),
+          stream(MediaInfoStreamKind::Audio, 1, &[("Format", "FLAC"), ("Title", "Commentary")]),
+        ],
+      },
+      FileAnalysis {
+        file: "b.mp4".to_owned(),
+        streams: vec![stream(MediaInfoStreamKind::General, 0, &[("Format", "MPEG-4")])],
+      },
+    ]
+  }
+
+  #[test]
+  fn build_sheets_one_row_per_file_uses_general_template() {
+    let mut templates = ConfigTemplates::default();
+    templates.general.properties = vec!["Duration".to_owned(), "Format".to_owned()];
+    let sheets = build_sheets(&analyses(), &templates, false);
+    assert_eq!(sheets.len(), 1);
+    assert_eq!(sheets[0].header, vec!["File", "Duration", "Format"]);
+    assert_eq!(sheets[0].rows[0], vec!["a.mkv", "60000", "Matroska"]);
+    assert_eq!(sheets[0].rows[1], vec!["b.mp4", "", "MPEG-4"]);
+  }
+
+  #[test]
+  fn build_sheets_per_stream_falls_back_to_sorted_union() {
+    let sheets = build_sheets(&analyses(), &ConfigTemplates::default(), true);
+    let names: Vec<&str> = sheets.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["General", "Audio"]);
+    assert_eq!(sheets[1].header, vec!["File", "Stream", "Format", "Title"]);
+    assert_eq!(sheets[1].rows[1], vec!["a.mkv", "1", "FLAC", "Commentary"]);
+  }
+
+  #[test]
+  fn csv_field_quotes_per_rfc_4180() {
+    assert_eq!(csv_field("plain"), "plain");
+    assert_eq!(csv_field("a,b"), "\"a,b\"");
+    assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+  }
+
+  #[test]
+  fn numeric_value_keeps_identifiers_as_text() {
+    assert_eq!(numeric_value("1920"), Some(1920.0));
+    assert_eq!(numeric_value("23.976"), Some(23.976));
+    assert_eq!(numeric_value("-3.5"), Some(-3.5));
+    assert_eq!(numeric_value("0.5"), Some(0.5));
+    assert_eq!(numeric_value("007"), None);
+    assert_eq!(numeric_value("1 920 pixels"), None);
+    assert_eq!(numeric_value("229382342097835729384"), None);
+    assert_eq!(numeric_value("1.2.3"), None);
+    assert_eq!(numeric_value(""), None);
+  }
+
+  #[test]
+  fn sheet_path_appends_sheet_name() {
+    assert_eq!(
+      sheet_path(Path::new("/tmp/report.csv"), "Video"),
+      PathBuf::from("/tmp/report_Video.csv")
+    );
+  }
+}
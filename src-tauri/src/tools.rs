@@ -0,0 +1,618 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::{self, Config};
+use crate::protocol::{ToolCandidate, ToolCandidateSource, VersionNumber};
+
+/// An external program BetterMediaInfo locates and launches. Implementations
+/// only declare facts about the tool; locating, persisting, version probing
+/// and launching are shared by the functions in this module.
+pub trait Tool: Sync {
+  /// Display name used in messages.
+  fn name(&self) -> &'static str;
+
+  /// Binaries (without `.exe`) that must all be present in the tool's directory.
+  /// The first one is the tool's main binary.
+  fn binaries(&self) -> &'static [&'static str];
+
+  /// The directory stored in `Config` for this tool.
+  fn config_path(&self, config: &Config) -> String;
+
+  fn set_config_path(&self, config: &mut Config, path: String);
+
+  /// Process (without `.exe`) whose running instance reveals the install
+  /// directory, for tools the user typically has open.
+  fn process_name(&self) -> Option<&'static str> {
+    None
+  }
+
+  /// Whether the directories on `PATH` are searched when the configured one
+  /// doesn't hold the binaries. Meant for command-line tools.
+  fn search_path(&self) -> bool {
+    false
+  }
+
+  /// Arguments that make the main binary print its version and exit.
+  fn version_args(&self) -> Option<&'static [&'static str]> {
+    None
+  }
+
+  /// Whether a binary inside a macOS `.app` bundle is launched through
+  /// `open -a`. Spawning the bundle's Mach-O binary directly causes a brief
+  /// window flash because the child never goes through app activation.
+  fn open_macos_bundle(&self) -> bool {
+    false
+  }
+
+  /// Further directories to try, in order, when the configured one doesn't
+  /// hold `binaries`, e.g. newer versioned installs of the same app.
+  fn fallback_dirs(&self, _configured: &Path, _binaries: &[&str]) -> Vec<PathBuf> {
+    Vec::new()
+  }
+
+  /// Flatpak app IDs that ship the tool. Their binaries only run inside the
+  /// sandbox, so they are reached through generated wrapper scripts.
+  fn flatpak_app_ids(&self) -> &'static [&'static str] {
+    &[]
+  }
+}
+
+/// Where a tool was found. `auto_detected` marks a directory other than the
+/// configured one, which is saved back to `Config`.
+pub struct Resolution {
+  pub dir: PathBuf,
+  pub found: bool,
+  pub auto_detected: bool,
+}
+
+/// The status every `get_*_status` command reports, before it's mapped onto
+/// the tool's protocol type.
+pub struct ToolStatus {
+  pub found: bool,
+  pub path: String,
+  /// Everything the version command printed, for the tool to parse.
+  pub version_output: Option<String>,
+}
+
+/// `dir/name`, or `dir/name.exe` on Windows, if it's an existing file.
+pub fn binary_path(dir: &Path, name: &str) -> Option<PathBuf> {
+  let direct = dir.join(name);
+  if direct.is_file() {
+    return Some(direct);
+  }
+  #[cfg(target_os = "windows")]
+  {
+    let exe = dir.join(format!("{}.exe", name));
+    if exe.is_file() {
+      return Some(exe);
+    }
+  }
+  None
+}
+
+/// A command for `program` that doesn't flash a console window on Windows.
+pub fn command(program: &Path) -> Command {
+  #[allow(unused_mut)]
+  let mut cmd = Command::new(program);
+  #[cfg(target_os = "windows")]
+  {
+    use std::os::windows::process::CommandExt;
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+  }
+  cmd
+}
+
+/// Every installation holding all of the tool's binaries, with its version, in
+/// the order `resolve` tries them. An install reachable from several
+/// directories (e.g. `/bin` and `/usr/bin`) is listed once. This is the only
+/// place the Flatpak wrappers are written, so resolving never touches disk.
+pub fn discover(tool: &dyn Tool) -> Vec<ToolCandidate> {
+  let binaries = tool.binaries();
+  let configured = PathBuf::from(tool.config_path(&config::get_config()).trim());
+  let mut dirs = Vec::new();
+  if !configured.as_os_str().is_empty() {
+    dirs.push((ToolCandidateSource::Configured, configured.clone()));
+  }
+  if let Some(dir) = tool.process_name().and_then(find_running_process_dir) {
+    dirs.push((ToolCandidateSource::Running, dir));
+  }
+  write_flatpak_wrappers(tool);
+  dirs.extend(search_dirs(tool, &configured, binaries));
+  let mut seen = HashSet::new();
+  dirs
+    .into_iter()
+    .filter(|(_, dir)| has_binaries(dir, binaries))
+    .filter(|(_, dir)| {
+      let main = dir.join(binaries[0]);
+      let key = binary_path(dir, binaries[0])
+        .and_then(|bin| bin.canonicalize().ok())
+        .unwrap_or(main);
+      seen.insert(key)
+    })
+    .map(|(source, dir)| ToolCandidate {
+      version: probe_version(tool, &dir),
+      path: dir.to_string_lossy().to_string(),
+      source,
+    })
+    .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn find_macos_app_bundle(bin: &Path) -> Option<PathBuf> {
+  // The Mach-O binary lives at `<bundle>.app/Contents/MacOS/<name>`, so the
+  // `.app` ancestor is at most three levels up. Walk a bounded number of
+  // parents instead of looping unbounded.
+  let mut current = bin.parent()?;
+  for _ in 0..4 {
+    if current.extension().and_then(|s| s.to_str()) == Some("app") {
+      return Some(current.to_path_buf());
+    }
+    current = current.parent()?;
+  }
+  None
+}
+
+/// The directory of a running process called `name` (`name.exe` on Windows).
+pub fn find_running_process_dir(name: &str) -> Option<PathBuf> {
+  // Windows binaries carry the .exe suffix in sysinfo's process name; Linux
+  // and macOS report the bare executable name, also for a bundle binary at
+  // `<App>.app/Contents/MacOS/<name>`.
+  let exe_name = if cfg!(target_os = "windows") {
+    format!("{}.exe", name)
+  } else {
+    name.to_owned()
+  };
+  let sys = sysinfo::System::new_all();
+  for process in sys.processes().values() {
+    // Process names are case-sensitive on Linux/macOS but conventionally
+    // case-insensitive on Windows; eq_ignore_ascii_case is safe across all
+    // three because the names we look for are unambiguous.
+    if !process.name().to_string_lossy().eq_ignore_ascii_case(&exe_name) {
+      continue;
+    }
+    if let Some(parent) = process.exe().and_then(|exe| exe.parent()) {
+      return Some(parent.to_path_buf());
+    }
+  }
+  None
+}
+
+/// The tool's Flatpak apps installed system-wide or per-user.
+#[cfg(target_os = "linux")]
+fn flatpak_apps(tool: &dyn Tool) -> Vec<&'static str> {
+  let mut installations = Vec::new();
+  if let Some(home) = std::env::var_os("HOME") {
+    installations.push(PathBuf::from(home).join(".local/share/flatpak"));
+  }
+  installations.push(PathBuf::from("/var/lib/flatpak"));
+  tool
+    .flatpak_app_ids()
+    .iter()
+    .copied()
+    .filter(|app_id| {
+      installations
+        .iter()
+        .any(|installation| installation.join("exports/bin").join(app_id).is_file())
+    })
+    .collect()
+}
+
+/// Wrapper directories for the tool's installed Flatpak apps. They only hold
+/// binaries once `discover` has written the wrappers.
+#[cfg(target_os = "linux")]
+fn flatpak_dirs(tool: &dyn Tool) -> Vec<PathBuf> {
+  flatpak_apps(tool).into_iter().map(flatpak_wrapper_dir).collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn flatpak_dirs(_tool: &dyn Tool) -> Vec<PathBuf> {
+  Vec::new()
+}
+
+#[cfg(target_os = "linux")]
+fn flatpak_wrapper_dir(app_id: &str) -> PathBuf {
+  config::get_config_dir().join("flatpak").join(app_id)
+}
+
+pub fn has_binaries(dir: &Path, binaries: &[&str]) -> bool {
+  binaries.iter().all(|name| binary_path(dir, name).is_some())
+}
+
+/// Open `file` with the tool's main binary as a detached GUI app.
+pub fn launch(tool: &dyn Tool, file: &str) -> Result<()> {
+  if !Path::new(file).exists() {
+    return Err(anyhow::anyhow!("File {} does not exist.", file));
+  }
+  let main = tool.binaries()[0];
+  let bin = locate(tool, main)
+    .map_err(|dir| anyhow::anyhow!("{} executable not found under {}.", tool.name(), dir.display()))?;
+
+  #[cfg(target_os = "macos")]
+  if tool.open_macos_bundle() {
+    if let Some(app_bundle) = find_macos_app_bundle(&bin) {
+      Command::new("/usr/bin/open")
+        .arg("-a")
+        .arg(&app_bundle)
+        .arg("--args")
+        .arg(file)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to launch {} via open: {}", tool.name(), e))?;
+      return Ok(());
+    }
+  }
+
+  command(&bin)
+    .arg(file)
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(|e| anyhow::anyhow!("Failed to launch {}: {}", tool.name(), e))?;
+  Ok(())
+}
+
+/// The full path of one of the tool's binaries, resolved from the configured
+/// directory (and saved back when it was auto-detected). On failure returns
+/// the directory that was searched, for the caller's error message.
+pub fn locate(tool: &dyn Tool, binary: &str) -> std::result::Result<PathBuf, PathBuf> {
+  let resolution = resolve(tool, &tool.config_path(&config::get_config()), &[binary]);
+  if !resolution.found {
+    return Err(resolution.dir);
+  }
+  if resolution.auto_detected
+    && let Err(err) = persist_path(tool, &resolution.dir)
+  {
+    log::warn!("Couldn't save the {} path because {}", tool.name(), err);
+  }
+  binary_path(&resolution.dir, binary).ok_or(resolution.dir)
+}
+
+/// Binary directories of common package managers. Apps started from a desktop
+/// launcher often get a minimal `PATH` that leaves these out.
+fn package_manager_dirs() -> Vec<PathBuf> {
+  let mut dirs = Vec::new();
+  #[cfg(target_os = "linux")]
+  {
+    dirs.extend(
+      [
+        "/usr/local/bin",
+        "/usr/bin",
+        "/snap/bin",
+        "/home/linuxbrew/.linuxbrew/bin",
+        "/run/current-system/sw/bin",
+        "/nix/var/nix/profiles/default/bin",
+      ]
+      .map(PathBuf::from),
+    );
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+      dirs.push(home.join(".linuxbrew/bin"));
+      dirs.push(home.join(".nix-profile/bin"));
+      dirs.push(home.join(".local/bin"));
+    }
+  }
+  #[cfg(target_os = "macos")]
+  {
+    dirs.extend(
+      [
+        "/opt/homebrew/bin",
+        "/usr/local/bin",
+        "/opt/local/bin",
+        "/run/current-system/sw/bin",
+        "/nix/var/nix/profiles/default/bin",
+      ]
+      .map(PathBuf::from),
+    );
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+      dirs.push(home.join(".nix-profile/bin"));
+    }
+  }
+  #[cfg(target_os = "windows")]
+  {
+    let env_dir = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    if let Some(scoop) = env_dir("SCOOP").or_else(|| env_dir("USERPROFILE").map(|home| home.join("scoop"))) {
+      dirs.push(scoop.join("shims"));
+    }
+    if let Some(program_data) = env_dir("ProgramData") {
+      dirs.push(program_data.join("chocolatey").join("bin"));
+    }
+    if let Some(local_app_data) = env_dir("LOCALAPPDATA") {
+      dirs.push(local_app_data.join("Microsoft").join("WinGet").join("Links"));
+    }
+  }
+  dirs
+}
+
+fn path_dirs() -> Vec<PathBuf> {
+  std::env::var_os("PATH")
+    .map(|paths| std::env::split_paths(&paths).collect())
+    .unwrap_or_default()
+}
+
+pub fn persist_path(tool: &dyn Tool, dir: &Path) -> Result<()> {
+  let new_path = dir.to_string_lossy().to_string();
+  let mut cfg = config::get_config();
+  if tool.config_path(&cfg) == new_path {
+    return Ok(());
+  }
+  tool.set_config_path(&mut cfg, new_path);
+  config::set_config(cfg)?;
+  Ok(())
+}
+
+/// The first dotted number in `text`, e.g. `7.1` from `ffmpeg version n7.1`
+/// or `82.0` from `mkvmerge v82.0 (...)`. A bare number like `2024` is not
+/// taken as a version.
+pub fn parse_version_number(text: &str) -> Option<VersionNumber> {
+  text.split_whitespace().find_map(|token| {
+    let token = token.trim_start_matches(['v', 'n']);
+    let end = token
+      .find(|c: char| !c.is_ascii_digit() && c != '.')
+      .unwrap_or(token.len());
+    let mut parts = token[..end].split('.').map(|part| part.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some(VersionNumber::new(major, minor, patch))
+  })
+}
+
+/// Run the main binary with `version_args` and return the first line it
+/// prints, e.g. `ffmpeg version 7.1 Copyright (c) ...`.
+pub fn probe_version(tool: &dyn Tool, dir: &Path) -> Option<String> {
+  version_output(tool, dir)?
+    .lines()
+    .map(str::trim)
+    .find(|line| !line.is_empty())
+    .map(str::to_owned)
+}
+
+/// Fail with `<code>:<feature>:<required>:<found>` when `found` is older than
+/// `required`, so the frontend can say e.g. "-fps_mode requires FFmpeg ≥ 5.1".
+/// An unknown version is let through; the tool reports the problem itself.
+pub fn require_version(code: &str, feature: &str, found: Option<VersionNumber>, required: VersionNumber) -> Result<()> {
+  match found {
+    Some(found) if found < required => Err(anyhow::anyhow!("{}:{}:{}:{}", code, feature, required, found)),
+    _ => Ok(()),
+  }
+}
+
+/// Find the directory holding `binaries`: the configured one, then the
+/// directories from `search_dirs`.
+pub fn resolve(tool: &dyn Tool, configured: &str, binaries: &[&str]) -> Resolution {
+  let configured = PathBuf::from(configured.trim());
+  if !configured.as_os_str().is_empty() && has_binaries(&configured, binaries) {
+    return Resolution {
+      dir: configured,
+      found: true,
+      auto_detected: false,
+    };
+  }
+  let found = search_dirs(tool, &configured, binaries)
+    .into_iter()
+    .map(|(_, dir)| dir)
+    .find(|dir| has_binaries(dir, binaries));
+  match found {
+    Some(dir) => Resolution {
+      dir,
+      found: true,
+      auto_detected: true,
+    },
+    None => Resolution {
+      dir: configured,
+      found: false,
+      auto_detected: false,
+    },
+  }
+}
+
+/// Where to look when the configured directory doesn't hold `binaries`: the
+/// tool's fallbacks, then `PATH` and package-manager prefixes for tools that
+/// search them, then Flatpak apps.
+fn search_dirs(tool: &dyn Tool, configured: &Path, binaries: &[&str]) -> Vec<(ToolCandidateSource, PathBuf)> {
+  let mut dirs: Vec<_> = tool
+    .fallback_dirs(configured, binaries)
+    .into_iter()
+    .map(|dir| (ToolCandidateSource::Fallback, dir))
+    .collect();
+  if tool.search_path() {
+    dirs.extend(path_dirs().into_iter().map(|dir| (ToolCandidateSource::Path, dir)));
+    dirs.extend(
+      package_manager_dirs()
+        .into_iter()
+        .map(|dir| (ToolCandidateSource::PackageManager, dir)),
+    );
+  }
+  dirs.extend(
+    flatpak_dirs(tool)
+      .into_iter()
+      .map(|dir| (ToolCandidateSource::Flatpak, dir)),
+  );
+  dirs
+}
+
+/// Locate the tool for a `get_*_status` command: a running instance when
+/// `check_running` is set, otherwise `path` (falling back as in `resolve`).
+/// A found directory is saved to `Config`.
+pub fn status(tool: &dyn Tool, path: &str, check_running: bool) -> Result<ToolStatus> {
+  let binaries = tool.binaries();
+  // A running instance's directory is `<App>.app/Contents/MacOS` on macOS,
+  // which is what we want to store. On Windows/Linux it's the install directory.
+  if check_running
+    && let Some(dir) = tool.process_name().and_then(find_running_process_dir)
+    && has_binaries(&dir, binaries)
+  {
+    persist_path(tool, &dir)?;
+    return Ok(ToolStatus {
+      found: true,
+      path: dir.to_string_lossy().to_string(),
+      version_output: version_output(tool, &dir),
+    });
+  }
+  if path.trim().is_empty() && !tool.search_path() {
+    return Ok(ToolStatus {
+      found: false,
+      path: String::new(),
+      version_output: None,
+    });
+  }
+  let resolution = resolve(tool, path, binaries);
+  if resolution.found {
+    persist_path(tool, &resolution.dir)?;
+  }
+  Ok(ToolStatus {
+    found: resolution.found,
+    path: resolution.dir.to_string_lossy().to_string(),
+    version_output: if resolution.found {
+      version_output(tool, &resolution.dir)
+    } else {
+      None
+    },
+  })
+}
+
+/// Run the main binary with `version_args` and return what it printed.
+pub fn version_output(tool: &dyn Tool, dir: &Path) -> Option<String> {
+  let args = tool.version_args()?;
+  let bin = binary_path(dir, tool.binaries()[0])?;
+  let output = command(&bin).args(args).stdin(Stdio::null()).output().ok()?;
+  if !output.status.success() {
+    return None;
+  }
+  Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Write one `flatpak run --command=<binary>` script per binary into
+/// `<config dir>/flatpak/<app_id>`. Inside our own Flatpak sandbox the host's
+/// `flatpak` is reached through `flatpak-spawn`.
+#[cfg(target_os = "linux")]
+fn write_flatpak_app_wrappers(tool: &dyn Tool, app_id: &str) -> Result<()> {
+  use std::os::unix::fs::PermissionsExt;
+  let dir = flatpak_wrapper_dir(app_id);
+  std::fs::create_dir_all(&dir)?;
+  let flatpak = if std::env::var_os("FLATPAK_ID").is_some() {
+    "flatpak-spawn --host flatpak"
+  } else {
+    "flatpak"
+  };
+  for name in tool.binaries().iter().copied().chain(tool.process_name()) {
+    let path = dir.join(name);
+    let script = format!("#!/bin/sh\nexec {} run --command={} {} \"$@\"\n", flatpak, name, app_id);
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(script.as_str()) {
+      std::fs::write(&path, script)?;
+      std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+  }
+  Ok(())
+}
+
+/// Write the wrappers of every installed Flatpak app of the tool. Failures are
+/// logged and leave that app out of the candidates.
+#[cfg(target_os = "linux")]
+fn write_flatpak_wrappers(tool: &dyn Tool) {
+  for app_id in flatpak_apps(tool) {
+    if let Err(err) = write_flatpak_app_wrappers(tool, app_id) {
+      log::warn!("Couldn't write the Flatpak wrappers for {} because {}", app_id, err);
+    }
+  }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn write_flatpak_wrappers(_tool: &dyn Tool) {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  struct Fake;
+
+  impl Tool for Fake {
+    fn name(&self) -> &'static str {
+      "Fake"
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+      &["fake-a", "fake-b"]
+    }
+
+    fn config_path(&self, config: &Config) -> String {
+      config.ffmpeg.path.clone()
+    }
+
+    fn set_config_path(&self, config: &mut Config, path: String) {
+      config.ffmpeg.path = path;
+    }
+
+    fn fallback_dirs(&self, configured: &Path, _binaries: &[&str]) -> Vec<PathBuf> {
+      vec![configured.join("fallback")]
+    }
+  }
+
+  #[test]
+  fn resolve_prefers_configured_then_fallbacks() {
+    let dir = std::env::temp_dir().join(format!("bmi_tools_{}", std::process::id()));
+    let fallback = dir.join("fallback");
+    std::fs::create_dir_all(&fallback).unwrap();
+    std::fs::write(dir.join("fake-a"), b"").unwrap();
+    std::fs::write(fallback.join("fake-a"), b"").unwrap();
+    std::fs::write(fallback.join("fake-b"), b"").unwrap();
+    let configured = dir.to_string_lossy().to_string();
+
+    let resolution = resolve(&Fake, &configured, &["fake-a"]);
+    assert!(resolution.found && !resolution.auto_detected);
+    assert_eq!(resolution.dir, dir);
+
+    let resolution = resolve(&Fake, &configured, Fake.binaries());
+    assert!(resolution.found && resolution.auto_detected);
+    assert_eq!(resolution.dir, fallback);
+
+    let resolution = resolve(&Fake, &configured, &["fake-c"]);
+    assert!(!resolution.found);
+    assert_eq!(resolution.dir, dir);
+    let _ = std::fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn version_numbers_are_parsed_and_compared() {
+    assert_eq!(
+      parse_version_number("ffmpeg version n7.1 Copyright (c) 2000-2024"),
+      Some(VersionNumber::new(7, 1, 0))
+    );
+    assert_eq!(
+      parse_version_number("ffmpeg version 4.4.2-0ubuntu0.22.04.1 Copyright"),
+      Some(VersionNumber::new(4, 4, 2))
+    );
+    assert_eq!(
+      parse_version_number("mkvmerge v82.0 ('I'm The President') 64-bit"),
+      Some(VersionNumber::new(82, 0, 0))
+    );
+    assert_eq!(parse_version_number("ffmpeg version N-118000-gabc Copyright 2024"), None);
+
+    let required = VersionNumber::new(5, 1, 0);
+    assert!(require_version("X", "f", Some(VersionNumber::new(7, 0, 0)), required).is_ok());
+    assert!(require_version("X", "f", None, required).is_ok());
+    let err = require_version("X", "f", Some(VersionNumber::new(4, 4, 2)), required).unwrap_err();
+    assert_eq!(err.to_string(), "X:f:5.1:4.4.2");
+  }
+}
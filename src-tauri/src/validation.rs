@@ -0,0 +1,137 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use crate::config::{ConfigValidationProfile, ConfigValidationRule, ValidationOperator};
+use crate::protocol::{StreamPropertyMap, ValidationResult};
+
+/// Check one rule against a single stream, returning a failure message when
+/// the stream doesn't satisfy it.
+fn check_rule(rule: &ConfigValidationRule, map: &StreamPropertyMap) -> Option<String> {
+  let name = format!("{} #{} {}", map.stream.get_name(), map.num, rule.property);
+  let Some(actual) = map.property_map.get(&rule.property) else {
+    return Some(format!("{} is missing", name));
+  };
+  let passed = match rule.operator {
+    ValidationOperator::Exists => true,
+    ValidationOperator::Equals => actual == &rule.value,
+    ValidationOperator::NotEquals => actual != &rule.value,
+    ValidationOperator::Contains => actual.contains(rule.value.as_str()),
+    ValidationOperator::Min | ValidationOperator::Max => {
+      match (actual.trim().parse::<f64>(), rule.value.trim().parse::<f64>()) {
+        (Ok(actual), Ok(limit)) if rule.operator == ValidationOperator::Min => actual >= limit,
+        (Ok(actual), Ok(limit)) => actual <= limit,
+        _ => false,
+      }
+    }
+  };
+  if passed {
+    return None;
+  }
+  let expected = match rule.operator {
+    ValidationOperator::Exists => String::new(),
+    ValidationOperator::Equals => format!("= {}", rule.value),
+    ValidationOperator::NotEquals => format!("!= {}", rule.value),
+    ValidationOperator::Contains => format!("containing {}", rule.value),
+    ValidationOperator::Min => format!(">= {}", rule.value),
+    ValidationOperator::Max => format!("<= {}", rule.value),
+  };
+  Some(format!("{} is {}, expected {}", name, actual, expected))
+}
+
+/// Run a validation profile against the properties of one file. Each rule is
+/// applied to every stream of its kind, and a rule whose stream kind is absent
+/// from the file fails, so a profile can require e.g. an audio track.
+pub fn validate(profile: &ConfigValidationProfile, streams: &[StreamPropertyMap]) -> ValidationResult {
+  let mut failures = Vec::new();
+  for rule in &profile.rules {
+    let matching: Vec<&StreamPropertyMap> = streams.iter().filter(|map| map.stream == rule.stream).collect();
+    if matching.is_empty() {
+      failures.push(format!("No {} stream", rule.stream.get_name()));
+      continue;
+    }
+    failures.extend(matching.into_iter().filter_map(|map| check_rule(rule, map)));
+  }
+  ValidationResult {
+    profile: profile.name.clone(),
+    passed: failures.is_empty(),
+    failures,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::media_info::MediaInfoStreamKind;
+  use std::collections::HashMap;
+
+  fn rule(stream: MediaInfoStreamKind, property: &str, operator: ValidationOperator, value: &str) -> ConfigValidationRule {
+    ConfigValidationRule {
+      stream,
+      property: property.to_owned(),
+      operator,
+      value: value.to_owned(),
+    }
+  }
+
+  fn video(width: &str) -> StreamPropertyMap {
+    StreamPropertyMap {
+      stream: MediaInfoStreamKind::Video,
+      num: 0,
+      property_map: HashMap::from([
+        ("Width".to_owned(), width.to_owned()),
+        ("Format".to_owned(), "HEVC".to_owned()),
+      ]),
+      source: Default::default(),
+    }
+  }
+
+  #[test]
+  fn validate_passes_when_every_rule_holds() {
+    let profile = ConfigValidationProfile {
+      name: "UHD".to_owned(),
+      rules: vec![
+        rule(MediaInfoStreamKind::Video, "Width", ValidationOperator::Min, "3840"),
+        rule(MediaInfoStreamKind::Video, "Format", ValidationOperator::Equals, "HEVC"),
+      ],
+    };
+    let result = validate(&profile, &[video("3840")]);
+    assert!(result.passed);
+    assert!(result.failures.is_empty());
+  }
+
+  #[test]
+  fn validate_reports_each_failure() {
+    let profile = ConfigValidationProfile {
+      name: "UHD".to_owned(),
+      rules: vec![
+        rule(MediaInfoStreamKind::Video, "Width", ValidationOperator::Min, "3840"),
+        rule(MediaInfoStreamKind::Video, "HDR_Format", ValidationOperator::Exists, ""),
+        rule(MediaInfoStreamKind::Audio, "Format", ValidationOperator::Exists, ""),
+      ],
+    };
+    let result = validate(&profile, &[video("1920")]);
+    assert!(!result.passed);
+    assert_eq!(
+      result.failures,
+      vec![
+        "Video #0 Width is 1920, expected >= 3840",
+        "Video #0 HDR_Format is missing",
+        "No Audio stream",
+      ]
+    );
+  }
+}
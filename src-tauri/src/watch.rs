@@ -0,0 +1,345 @@
+/*
+* Copyright (c) 2024-2026. caoccao.com Sam Cao
+* All rights reserved.
+
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+
+* http://www.apache.org/licenses/LICENSE-2.0
+
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use anyhow::Result;
+use notify::Watcher;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::config::{self, ConfigWatch, ConfigWatchFolder, WatchReportTarget};
+use crate::constants::APP_NAME;
+use crate::controller;
+use crate::protocol::{ValidationResult, WatchFolderReportEvent, WatchReport};
+use crate::validation;
+
+const REPORT_SUFFIX: &str = ".mediainfo.json";
+
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+static SERVICE: Lazy<Mutex<Option<WatchService>>> = Lazy::new(|| Mutex::new(None));
+
+type PendingMap = Arc<Mutex<HashMap<PathBuf, Pending>>>;
+
+/// A file seen by a watcher that hasn't settled yet.
+struct Pending {
+  folder: usize,
+  size: u64,
+  changed_at: Instant,
+}
+
+/// The running watchers for the enabled watch folders plus the settle thread
+/// that analyzes files once they stop growing. Dropping it stops both.
+struct WatchService {
+  folders: Vec<ConfigWatchFolder>,
+  _watchers: Vec<Box<dyn Watcher + Send>>,
+  stop: Arc<AtomicBool>,
+}
+
+impl WatchService {
+  fn start(folders: Vec<ConfigWatchFolder>) -> Self {
+    let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut watchers = Vec::new();
+    for (index, folder) in folders.iter().enumerate() {
+      match watch_folder(index, folder, pending.clone()) {
+        Ok(watcher) => {
+          log::info!("Watching {}.", folder.path);
+          watchers.push(watcher);
+        }
+        Err(err) => log::warn!("Couldn't watch {} because {}", folder.path, err),
+      }
+    }
+    let settle_folders = folders.clone();
+    let settle_stop = stop.clone();
+    std::thread::spawn(move || run_settle_loop(settle_folders, pending, settle_stop));
+    Self {
+      folders,
+      _watchers: watchers,
+      stop,
+    }
+  }
+}
+
+impl Drop for WatchService {
+  fn drop(&mut self) {
+    self.stop.store(true, Ordering::SeqCst);
+  }
+}
+
+fn analyze(folder: &ConfigWatchFolder, path: &Path) -> Result<(String, bool)> {
+  let file = path.to_string_lossy().to_string();
+  let size = std::fs::metadata(path)?.len();
+  let streams = tauri::async_runtime::block_on(controller::get_properties(file.clone(), None))?;
+  let cfg = config::get_config();
+  let validation: Vec<ValidationResult> = folder
+    .profiles
+    .iter()
+    .filter_map(|name| cfg.validation.profiles.iter().find(|profile| &profile.name == name))
+    .map(|profile| validation::validate(profile, &streams))
+    .collect();
+  let sha256 = if folder.hash { Some(sha256_file(path)?) } else { None };
+  let passed = validation.iter().all(|result| result.passed);
+  let report = WatchReport {
+    file,
+    size,
+    analyzed_at: std::time::SystemTime::now()
+      .duration_since(std::time::UNIX_EPOCH)
+      .map(|d| d.as_secs())
+      .unwrap_or(0),
+    sha256,
+    streams,
+    validation,
+  };
+  let report_path = match folder.report {
+    WatchReportTarget::Folder => {
+      let report_path = report_path_for(path);
+      std::fs::write(&report_path, serde_json::to_vec_pretty(&report)?)?;
+      report_path
+    }
+    WatchReportTarget::Log => {
+      let log_path = log_path_for(folder);
+      let mut log_file = std::fs::OpenOptions::new().create(true).append(true).open(&log_path)?;
+      writeln!(log_file, "{}", serde_json::to_string(&report)?)?;
+      log_path
+    }
+  };
+  Ok((report_path.to_string_lossy().to_string(), passed))
+}
+
+/// Lower-cased extensions (without the dot) a watch folder picks up.
+fn folder_extensions(folder: &ConfigWatchFolder) -> Vec<String> {
+  let extensions = if folder.extensions.is_empty() {
+    let file_extensions = config::get_config().file_extensions;
+    [file_extensions.audio, file_extensions.image, file_extensions.video].concat()
+  } else {
+    folder.extensions.clone()
+  };
+  extensions
+    .iter()
+    .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+    .filter(|ext| !ext.is_empty())
+    .collect()
+}
+
+/// Remember the app handle used for report events and start the configured
+/// watch folders. Wired up from the window `setup` hook.
+pub fn init(app_handle: AppHandle) {
+  let _ = APP_HANDLE.set(app_handle);
+  reload(&config::get_config().watch);
+}
+
+fn is_watched_file(path: &Path, extensions: &[String]) -> bool {
+  let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+    return false;
+  };
+  if name.ends_with(REPORT_SUFFIX) || name.starts_with('.') {
+    return false;
+  }
+  path
+    .extension()
+    .and_then(|e| e.to_str())
+    .map(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+    .unwrap_or(false)
+}
+
+fn log_path_for(folder: &ConfigWatchFolder) -> PathBuf {
+  let log_file = folder.log_file.trim();
+  if log_file.is_empty() {
+    Path::new(folder.path.trim()).join(format!("{}.log", APP_NAME))
+  } else {
+    PathBuf::from(log_file)
+  }
+}
+
+/// Restart the watchers if the enabled watch folders differ from the running
+/// ones. Called on startup and after every config change.
+pub fn reload(watch: &ConfigWatch) {
+  if APP_HANDLE.get().is_none() {
+    return;
+  }
+  let folders: Vec<ConfigWatchFolder> = watch
+    .folders
+    .iter()
+    .filter(|folder| folder.enabled && !folder.path.trim().is_empty())
+    .cloned()
+    .collect();
+  let mut service = SERVICE.lock().unwrap();
+  let unchanged = match service.as_ref() {
+    Some(running) => running.folders == folders,
+    None => folders.is_empty(),
+  };
+  if unchanged {
+    return;
+  }
+  *service = None;
+  if !folders.is_empty() {
+    *service = Some(WatchService::start(folders));
+  }
+}
+
+fn report_path_for(path: &Path) -> PathBuf {
+  let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+  path.with_file_name(format!("{}{}", name, REPORT_SUFFIX))
+}
+
+/// Poll the pending files once a second. A file is analyzed when its size has
+/// not changed for the folder's settle time and it can be opened for reading
+/// (on Windows a file still being copied is locked by the writer).
+fn run_settle_loop(folders: Vec<ConfigWatchFolder>, pending: PendingMap, stop: Arc<AtomicBool>) {
+  while !stop.load(Ordering::SeqCst) {
+    std::thread::sleep(Duration::from_secs(1));
+    let mut ready: Vec<(PathBuf, usize)> = Vec::new();
+    pending.lock().unwrap().retain(|path, entry| {
+      let Ok(metadata) = std::fs::metadata(path) else {
+        // Deleted or moved away before it settled.
+        return false;
+      };
+      if metadata.len() != entry.size {
+        entry.size = metadata.len();
+        entry.changed_at = Instant::now();
+        return true;
+      }
+      let settle = Duration::from_secs(folders[entry.folder].settle_seconds);
+      if entry.changed_at.elapsed() < settle || std::fs::File::open(path).is_err() {
+        return true;
+      }
+      ready.push((path.clone(), entry.folder));
+      false
+    });
+    for (path, index) in ready {
+      if stop.load(Ordering::SeqCst) {
+        break;
+      }
+      let folder = &folders[index];
+      let file = path.to_string_lossy().to_string();
+      let event = match analyze(folder, &path) {
+        Ok((report, passed)) => WatchFolderReportEvent {
+          folder: folder.path.clone(),
+          file,
+          report,
+          passed,
+          error: None,
+        },
+        Err(err) => {
+          log::warn!("Couldn't analyze {} because {}", file, err);
+          WatchFolderReportEvent {
+            folder: folder.path.clone(),
+            file,
+            report: String::new(),
+            passed: false,
+            error: Some(err.to_string()),
+          }
+        }
+      };
+      if let Some(app_handle) = APP_HANDLE.get() {
+        let _ = app_handle.emit("watch-folder-report", event);
+      }
+    }
+  }
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+  use sha2::{Digest, Sha256};
+  let mut file = std::fs::File::open(path)?;
+  let mut hasher = Sha256::new();
+  let mut buf = vec![0u8; 1 << 20];
+  loop {
+    let read = file.read(&mut buf)?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buf[..read]);
+  }
+  Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Watch a folder with the platform's native notifications, falling back to
+/// polling when `polling` is set or the native watcher refuses the path (as it
+/// does for many network filesystems).
+fn watch_folder(index: usize, folder: &ConfigWatchFolder, pending: PendingMap) -> Result<Box<dyn Watcher + Send>> {
+  let path = Path::new(folder.path.trim());
+  let mode = if folder.recursive {
+    notify::RecursiveMode::Recursive
+  } else {
+    notify::RecursiveMode::NonRecursive
+  };
+  let extensions = folder_extensions(folder);
+  let handler = move |event: notify::Result<notify::Event>| {
+    let Ok(event) = event else {
+      return;
+    };
+    if !(event.kind.is_create() || event.kind.is_modify()) {
+      return;
+    }
+    for path in event.paths {
+      if !is_watched_file(&path, &extensions) {
+        continue;
+      }
+      let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+      pending.lock().unwrap().insert(
+        path,
+        Pending {
+          folder: index,
+          size,
+          changed_at: Instant::now(),
+        },
+      );
+    }
+  };
+  if !folder.polling {
+    match notify::recommended_watcher(handler.clone()) {
+      Ok(mut watcher) => match watcher.watch(path, mode) {
+        Ok(()) => return Ok(Box::new(watcher)),
+        Err(err) => log::warn!("Falling back to polling {} because {}", folder.path, err),
+      },
+      Err(err) => log::warn!("Falling back to polling {} because {}", folder.path, err),
+    }
+  }
+  let poll_config =
+    notify::Config::default().with_poll_interval(Duration::from_secs(folder.poll_interval_seconds.max(1)));
+  let mut watcher = notify::PollWatcher::new(handler, poll_config)?;
+  watcher.watch(path, mode)?;
+  Ok(Box::new(watcher))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn is_watched_file_skips_reports_and_other_extensions() {
+    let extensions = vec!["mkv".to_owned(), "flac".to_owned()];
+    assert!(is_watched_file(Path::new("/ingest/a.mkv"), &extensions));
+    assert!(is_watched_file(Path::new("/ingest/B.MKV"), &extensions));
+    assert!(!is_watched_file(Path::new("/ingest/a.mkv.mediainfo.json"), &extensions));
+    assert!(!is_watched_file(Path::new("/ingest/.a.mkv"), &extensions));
+    assert!(!is_watched_file(Path::new("/ingest/a.txt"), &extensions));
+  }
+
+  #[test]
+  fn report_path_for_appends_suffix() {
+    assert_eq!(
+      report_path_for(Path::new("/ingest/a.mkv")),
+      PathBuf::from("/ingest/a.mkv.mediainfo.json")
+    );
+  }
+}
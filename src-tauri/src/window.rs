@@ -16,15 +16,45 @@
 */
 
 use std::sync::atomic::{AtomicBool, Ordering};
-use tauri::Manager;
+use tauri::{Emitter, EventTarget, Manager};
 
 use crate::config;
 use crate::constants;
 use crate::controller;
-use crate::protocol::{UpdateCheckResult, UpdateCheckState};
+use crate::protocol::{FilesOpenedEvent, UpdateCheckResult, UpdateCheckState};
+use crate::server;
+use crate::watch;
 
 pub static WINDOW_READY: AtomicBool = AtomicBool::new(false);
 
+/// Forward the paths of a second launch (e.g. another Explorer context-menu
+/// click) to the running main window as a `files-opened` event, and bring the
+/// window to the front. The frontend treats them like dropped files, so they
+/// are appended or replace the list per `append_on_file_drop`. Wired up as the
+/// single-instance plugin's callback; the second process exits afterwards.
+pub fn on_second_instance(app: &tauri::AppHandle, args: Vec<String>, cwd: String) {
+  let files: Vec<String> = args
+    .into_iter()
+    .skip(1)
+    .map(|arg| {
+      let path = std::path::Path::new(&arg);
+      if path.is_absolute() {
+        arg
+      } else {
+        std::path::Path::new(&cwd).join(path).to_string_lossy().to_string()
+      }
+    })
+    .collect();
+  if let Some(window) = app.get_webview_window("main") {
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+  if !files.is_empty() {
+    let _ = app.emit_to(EventTarget::webview_window("main"), "files-opened", FilesOpenedEvent { files });
+  }
+}
+
 /// Persist the main window's size/position on move/resize and tear down any
 /// secondary windows when the main window is destroyed. Wired up as the Tauri
 /// builder's `on_window_event` hook.
@@ -69,8 +99,9 @@ pub fn on_window_event(window: &tauri::Window, event: &tauri::WindowEvent) {
   }
 }
 
-/// Initialize the main window: title, restored size/position, visibility, and
-/// the background update check. Wired up as the Tauri builder's `setup` hook.
+/// Initialize the main window: title, restored size/position, visibility, the
+/// watch folders, the API server, and the background update check. Wired up as
+/// the Tauri builder's `setup` hook.
 pub fn setup(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
   let window = app.get_webview_window("main").unwrap();
   let _ = window.set_title(&format!("{} v{}", constants::APP_NAME, controller::get_app_version()));
@@ -90,6 +121,9 @@ pub fn setup(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
   let _ = window.set_focus();
   WINDOW_READY.store(true, Ordering::SeqCst);
 
+  watch::init(app.handle().clone());
+  server::init();
+
   // Check for updates in background
   let update_state = app.state::<UpdateCheckState>();
   let result_arc = update_state.result.clone();
@@ -0,0 +1,230 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useState } from 'react';
+import { Box, Button, CircularProgress, Dialog, TextField, Typography } from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+
+import * as Protocol from '../lib/protocol';
+import { downsampleMax, formatBitrate, seriesLabel } from '../lib/bitrate';
+import { STREAM_KIND_COLORS } from '../lib/cardTables';
+import { formatChapterTime } from '../lib/chapters';
+import { openSaveImageFileDialog } from '../lib/dialog';
+import { basename } from '../lib/format';
+import { analyzeBitrate, renderBitrateGraph } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface BitrateDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+}
+
+const CHART_WIDTH = 1000;
+const CHART_HEIGHT = 160;
+const PNG_WIDTH = 1600;
+const PNG_PANEL_HEIGHT = 300;
+
+function describeBitrateError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/BITRATE_NO_PACKETS:/.test(message)) {
+    return t('bitrate.noPackets');
+  }
+  const ffprobeMissing = message.match(/FFPROBE_NOT_AVAILABLE:(.*)$/);
+  if (ffprobeMissing) {
+    return t('ffprobe.ffprobeNotAvailable', { path: ffprobeMissing[1] });
+  }
+  return message;
+}
+
+function BitrateChart({ graph, series }: { graph: Protocol.BitrateGraph; series: Protocol.BitrateSeries }) {
+  const points = downsampleMax(series.points, CHART_WIDTH);
+  const max = Math.max(series.max, ...points) || 1;
+  const step = CHART_WIDTH / Math.max(1, points.length);
+  const path = points
+    .map((value, index) => `L${(index * step).toFixed(1)},${(CHART_HEIGHT * (1 - value / max)).toFixed(1)}`)
+    .join(' ');
+  const area = `M0,${CHART_HEIGHT} ${path} L${(points.length * step).toFixed(1)},${CHART_HEIGHT} Z`;
+  const averageY = CHART_HEIGHT * (1 - series.average / max);
+  const color = STREAM_KIND_COLORS[series.stream];
+
+  return (
+    <Box sx={{ mb: 2 }}>
+      <Box sx={{ display: 'flex', gap: 2, alignItems: 'baseline', mb: 0.5 }}>
+        <Typography variant="subtitle2" sx={{ fontWeight: 'bold' }}>
+          {seriesLabel(series)}
+        </Typography>
+        <Typography variant="caption" sx={{ fontFamily: 'monospace' }}>
+          avg {formatBitrate(series.average)} / min {formatBitrate(series.min)} / max {formatBitrate(series.max)}
+        </Typography>
+      </Box>
+      <Box sx={{ border: 1, borderColor: 'divider' }}>
+        <svg
+          viewBox={`0 0 ${CHART_WIDTH} ${CHART_HEIGHT}`}
+          preserveAspectRatio="none"
+          style={{ display: 'block', width: '100%', height: CHART_HEIGHT }}
+        >
+          <path d={area} fill={color} fillOpacity={0.8} />
+          <line x1={0} x2={CHART_WIDTH} y1={averageY} y2={averageY} stroke="currentColor" strokeDasharray="6 4" />
+        </svg>
+      </Box>
+      <Box sx={{ display: 'flex', justifyContent: 'space-between' }}>
+        <Typography variant="caption">{formatChapterTime(0, 0)}</Typography>
+        <Typography variant="caption">{formatChapterTime(graph.duration, 0)}</Typography>
+      </Box>
+    </Box>
+  );
+}
+
+export default function BitrateDialog({ open, onClose, file }: BitrateDialogProps) {
+  const { t } = useTranslation();
+  const [windowSeconds, setWindowSeconds] = useState(1);
+  const [running, setRunning] = useState(false);
+  const [graph, setGraph] = useState<Protocol.BitrateGraph | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  useEffect(() => {
+    setGraph(null);
+  }, [file]);
+
+  const handleRun = async () => {
+    setRunning(true);
+    setGraph(null);
+    try {
+      setGraph(await analyzeBitrate(file, windowSeconds));
+    } catch (error) {
+      setDialogNotification({
+        title: t('bitrate.failed', { error: describeBitrateError(t, error) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const handleSave = async () => {
+    if (!graph) return;
+    const output = (await openSaveImageFileDialog('png', `${basename(file)}.bitrate.png`)) as string | null;
+    if (!output) return;
+    try {
+      await renderBitrateGraph(graph, output, PNG_WIDTH, PNG_PANEL_HEIGHT * graph.series.length);
+      setDialogNotification({
+        title: t('bitrate.saved', { filePath: output }),
+        type: Protocol.DialogNotificationType.Info,
+      });
+    } catch (error) {
+      setDialogNotification({
+        title: t('bitrate.failed', { error: String(error) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    }
+  };
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '70vw',
+            height: '80vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('bitrate.title')}
+        </Typography>
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        <TextField
+          label={t('bitrate.window')}
+          type="number"
+          size="small"
+          value={windowSeconds}
+          onChange={(e) => {
+            const parsed = parseFloat(e.target.value);
+            if (!Number.isNaN(parsed)) setWindowSeconds(Math.max(0.04, parsed));
+          }}
+          disabled={running}
+          slotProps={{ htmlInput: { step: 0.5, min: 0.04 } }}
+          sx={{ width: 160 }}
+        />
+        <Button
+          variant="contained"
+          size="small"
+          onClick={handleRun}
+          disabled={running}
+          startIcon={running ? <CircularProgress size={14} /> : undefined}
+          sx={{ textTransform: 'none' }}
+        >
+          {running ? t('bitrate.running') : t('bitrate.run')}
+        </Button>
+        <Button
+          variant="contained"
+          size="small"
+          onClick={handleSave}
+          disabled={running || graph === null}
+          sx={{ textTransform: 'none' }}
+        >
+          {t('bitrate.savePng')}
+        </Button>
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {graph ? (
+        <Box sx={{ flex: 1, minHeight: 0, overflow: 'auto', p: 2 }}>
+          <Typography variant="caption" color="text.secondary" sx={{ display: 'block', mb: 1 }}>
+            {t('bitrate.windowUsed', { window: graph.window.toFixed(2) })}
+          </Typography>
+          {graph.series.map((series) => (
+            <BitrateChart key={`${series.stream}-${series.num}`} graph={graph} series={series} />
+          ))}
+        </Box>
+      ) : (
+        !running && (
+          <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+            <Typography variant="body2" color="text.secondary">
+              {t('bitrate.hint')}
+            </Typography>
+          </Box>
+        )
+      )}
+    </Dialog>
+  );
+}
@@ -31,6 +31,8 @@ import {
   FormControlLabel,
   IconButton,
   InputAdornment,
+  ListItemText,
+  Menu,
   MenuItem,
   Paper,
   Select,
@@ -74,6 +76,7 @@ import {
   Update as UpdateIcon,
   VideoFile as VideoIcon,
   ViewAgenda as CardViewIcon,
+  Visibility as WatchIcon,
 } from '@mui/icons-material';
 import { open } from '@tauri-apps/plugin-dialog';
 import {
@@ -107,9 +110,11 @@ import {
   areExtensionsContextMenuRegistered,
   getBatchMkvExtractStatus,
   getBDMasterStatus,
+  getFfmpegCandidates,
   getFfmpegStatus,
   getMpcHcStatus,
   isFolderContextMenuRegistered,
+  getMkvtoolnixCandidates,
   getMkvtoolnixStatus,
   registerExtensionsContextMenu,
   registerFolderContextMenu,
@@ -127,6 +132,7 @@ enum ConfigTab {
   Integration = 'Integration',
   Templates = 'Templates',
   Update = 'Update',
+  Watch = 'Watch',
 }
 
 interface StreamFormatState {
@@ -1717,6 +1723,7 @@ export default function Config() {
   );
   const [mkvLanguages, setMkvLanguages] = useState<readonly MkvLanguage[]>([]);
   const [mkvtoolnixFound, setMkvtoolnixFound] = useState(false);
+  const [mkvtoolnixVersion, setMkvtoolnixVersion] = useState<string | null>(null);
   const [mkvOptionsDialogOpen, setMkvOptionsDialogOpen] = useState(false);
   const [mkvOptionsSaving, setMkvOptionsSaving] = useState(false);
   const [mkvOptionsTab, setMkvOptionsTab] = useState(0);
@@ -1728,6 +1735,12 @@ export default function Config() {
   const [mpcHcFound, setMpcHcFound] = useState(false);
   const [ffmpegPath, setFfmpegPath] = useState('');
   const [ffmpegFound, setFfmpegFound] = useState(false);
+  const [ffmpegVersion, setFfmpegVersion] = useState<string | null>(null);
+  const [toolCandidates, setToolCandidates] = useState<Protocol.ToolCandidate[]>([]);
+  const [toolCandidatesAnchor, setToolCandidatesAnchor] = useState<{
+    element: HTMLElement;
+    tool: 'ffmpeg' | 'mkvtoolnix';
+  } | null>(null);
   const [cardViewShowGeneral, setCardViewShowGeneral] = useState(true);
   const [cardViewShowVideo, setCardViewShowVideo] = useState(true);
   const [cardViewShowAudio, setCardViewShowAudio] = useState(true);
@@ -1745,7 +1758,10 @@ export default function Config() {
   const [imageContextMenuRegistered, setImageContextMenuRegistered] = useState(false);
   const [folderContextMenuRegistered, setFolderContextMenuRegistered] = useState(false);
   const isWindows = useMemo(() => typeof navigator !== 'undefined' && /windows/i.test(navigator.userAgent), []);
+  const isLinux = useMemo(() => typeof navigator !== 'undefined' && /linux/i.test(navigator.userAgent) && !/android/i.test(navigator.userAgent), []);
+  const isContextMenuSupported = isWindows || isLinux;
   const [updateCheckInterval, setUpdateCheckInterval] = useState<Protocol.UpdateCheckInterval>(Protocol.UpdateCheckInterval.Weekly);
+  const [watchFolders, setWatchFolders] = useState<Protocol.ConfigWatchFolder[]>([]);
   const [mainTab, setMainTab] = useState<ConfigTab>(ConfigTab.Appearance);
   const [formatTab, setFormatTab] = useState(0);
   const [fileExtensionsTab, setFileExtensionsTab] = useState(0);
@@ -1805,6 +1821,7 @@ export default function Config() {
       setDetailViewShowImage(config.view?.detail?.showImage ?? true);
       setDetailViewShowMenu(config.view?.detail?.showMenu ?? true);
       setUpdateCheckInterval(config.update?.checkInterval ?? Protocol.UpdateCheckInterval.Weekly);
+      setWatchFolders(config.watch?.folders ?? []);
       setTemplates({
         general: { properties: config.templates?.general?.properties ?? [] },
         video: { properties: config.templates?.video?.properties ?? [] },
@@ -1859,7 +1876,7 @@ export default function Config() {
     batchMkvExtract: { path: batchMkvExtractPath },
     bdMaster: { path: bdMasterPath },
     mpcHc: { path: mpcHcPath },
-    ffmpeg: { path: ffmpegPath },
+    ffmpeg: { path: ffmpegPath, audioPresets: config?.ffmpeg?.audioPresets ?? [] },
     view: {
       card: {
         showGeneral: cardViewShowGeneral,
@@ -1881,6 +1898,9 @@ export default function Config() {
     update: { checkInterval: updateCheckInterval, lastChecked: config?.update?.lastChecked ?? 0, lastVersion: config?.update?.lastVersion ?? '', ignoreVersion: config?.update?.ignoreVersion ?? '' },
     window: config?.window ?? { position: { x: -1, y: -1 }, size: { width: 1200, height: 900 } },
     templates,
+    validation: config?.validation ?? { profiles: [] },
+    watch: { folders: watchFolders },
+    server: config?.server ?? { enabled: false, port: 8484, token: '' },
   });
 
   const handleBrowseMkvToolNixPath = async () => {
@@ -1893,9 +1913,9 @@ export default function Config() {
     }
   };
 
-  // Load initial context menu registration state on Windows
+  // Load initial context menu registration state on Windows and Linux
   useEffect(() => {
-    if (!isWindows) return;
+    if (!isContextMenuSupported) return;
     const videoExts = convertFileExtensions(fileExtensionsVideo);
     const audioExts = convertFileExtensions(fileExtensionsAudio);
     const imageExts = convertFileExtensions(fileExtensionsImage);
@@ -1904,7 +1924,7 @@ export default function Config() {
     areExtensionsContextMenuRegistered(imageExts).then(setImageContextMenuRegistered).catch(() => {});
     isFolderContextMenuRegistered().then(setFolderContextMenuRegistered).catch(() => {});
     // eslint-disable-next-line react-hooks/exhaustive-deps
-  }, [isWindows, fileExtensionsVideo, fileExtensionsAudio, fileExtensionsImage]);
+  }, [isContextMenuSupported, fileExtensionsVideo, fileExtensionsAudio, fileExtensionsImage]);
 
   const handleRegisterExtensionsContextMenu = async (
     extensions: string[],
@@ -2053,30 +2073,25 @@ export default function Config() {
     }
   };
 
-  const handleDetectMkvToolNix = async () => {
+  // List every installation the backend can find and let the user pick one.
+  // The path effects below then validate and save the choice.
+  const handleDetectTool = async (event: React.MouseEvent<HTMLElement>, tool: 'ffmpeg' | 'mkvtoolnix') => {
+    const element = event.currentTarget;
     try {
-      const status = await getMkvtoolnixStatus(mkvToolNixPath.trim(), true);
-      setMkvtoolnixFound(status.found);
-      if (status.found && status.mkvToolNixPath && status.mkvToolNixPath !== mkvToolNixPath) {
-        setMkvToolNixPath(status.mkvToolNixPath);
-        if (config && config.mkv?.mkvToolNixPath !== status.mkvToolNixPath) {
-          setStoreConfig({
-            ...config,
-            mkv: {
-              ...(config.mkv ?? {
-                mkvToolNixPath: status.mkvToolNixPath,
-                additionalParameters: { priority: mkvPriority },
-                languages: { preferred: mkvPreferredLanguages },
-                titleAutocompletion: { titles: mkvTitleAutocompletionTitles },
-              }),
-              mkvToolNixPath: status.mkvToolNixPath,
-            },
-          });
-        }
-      }
+      setToolCandidates(tool === 'ffmpeg' ? await getFfmpegCandidates() : await getMkvtoolnixCandidates());
     } catch {
-      setMkvtoolnixFound(false);
+      setToolCandidates([]);
+    }
+    setToolCandidatesAnchor({ element, tool });
+  };
+
+  const handleSelectToolCandidate = (candidate: Protocol.ToolCandidate) => {
+    if (toolCandidatesAnchor?.tool === 'ffmpeg') {
+      setFfmpegPath(candidate.path);
+    } else {
+      setMkvToolNixPath(candidate.path);
     }
+    setToolCandidatesAnchor(null);
   };
 
   const handleOpenMkvOptionsDialog = () => {
@@ -2178,6 +2193,7 @@ export default function Config() {
         const status = await getMkvtoolnixStatus(mkvToolNixPath.trim());
         if (!isCancelled) {
           setMkvtoolnixFound(status.found);
+          setMkvtoolnixVersion(status.version?.text ?? null);
           if (status.found && status.mkvToolNixPath && status.mkvToolNixPath !== mkvToolNixPath) {
             setMkvToolNixPath(status.mkvToolNixPath);
             if (config && config.mkv?.mkvToolNixPath !== status.mkvToolNixPath) {
@@ -2199,6 +2215,7 @@ export default function Config() {
       } catch {
         if (!isCancelled) {
           setMkvtoolnixFound(false);
+          setMkvtoolnixVersion(null);
         }
       }
     }, 250);
@@ -2305,10 +2322,17 @@ export default function Config() {
         const status = await getFfmpegStatus(ffmpegPath.trim());
         if (!isCancelled) {
           setFfmpegFound(status.found);
+          setFfmpegVersion(status.version?.text ?? null);
+          // An empty or stale path is resolved from PATH and package-manager
+          // prefixes on the backend; reflect the directory it found.
+          if (status.found && status.path && status.path !== ffmpegPath) {
+            setFfmpegPath(status.path);
+          }
         }
       } catch {
         if (!isCancelled) {
           setFfmpegFound(false);
+          setFfmpegVersion(null);
         }
       }
     }, 250);
@@ -2415,6 +2439,7 @@ export default function Config() {
     detailViewShowMenu,
     updateCheckInterval,
     templates,
+    watchFolders,
   ]);
 
   const getThemeDisplayLabel = (themeOption: Protocol.Theme): string => t(`config.theme${themeOption}`);
@@ -2666,7 +2691,7 @@ export default function Config() {
           label={t('config.image')}
           sx={{ minHeight: 36, textTransform: 'none' }}
         />
-        {isWindows && (
+        {isContextMenuSupported && (
           <Tab
             icon={<FolderIcon sx={{ fontSize: 16 }} />}
             iconPosition="start"
@@ -2684,7 +2709,7 @@ export default function Config() {
             fullWidth
             placeholder="mp4, mkv, avi, mov..."
           />
-          {isWindows && (
+          {isContextMenuSupported && (
             <Box sx={{ display: 'flex', gap: 1, flexWrap: 'wrap' }}>
               <Button
                 variant="outlined"
@@ -2723,7 +2748,7 @@ export default function Config() {
             fullWidth
             placeholder="mp3, flac, wav, aac..."
           />
-          {isWindows && (
+          {isContextMenuSupported && (
             <Box sx={{ display: 'flex', gap: 1, flexWrap: 'wrap' }}>
               <Button
                 variant="outlined"
@@ -2762,7 +2787,7 @@ export default function Config() {
             fullWidth
             placeholder="jpg, png, gif, webp..."
           />
-          {isWindows && (
+          {isContextMenuSupported && (
             <Box sx={{ display: 'flex', gap: 1, flexWrap: 'wrap' }}>
               <Button
                 variant="outlined"
@@ -2792,7 +2817,7 @@ export default function Config() {
           )}
         </Stack>
       )}
-      {fileExtensionsTab === 3 && isWindows && (
+      {fileExtensionsTab === 3 && isContextMenuSupported && (
         <Box sx={{ display: 'flex', gap: 1, flexWrap: 'wrap' }}>
           <Button
             variant="outlined"
@@ -2985,7 +3010,7 @@ export default function Config() {
             <Button
               variant="outlined"
               size="small"
-              onClick={handleDetectMkvToolNix}
+              onClick={(e) => handleDetectTool(e, 'mkvtoolnix')}
               sx={{ minWidth: 90, height: 36, textTransform: 'none' }}
             >
               {t('config.detect')}
@@ -3000,6 +3025,7 @@ export default function Config() {
             }}
           >
             {mkvtoolnixFound ? t('config.mkvtoolnixFound') : t('config.mkvtoolnixNotFound')}
+            {mkvtoolnixFound && mkvtoolnixVersion ? ` ${mkvtoolnixVersion}` : ''}
           </Typography>
         </Box>
       </Paper>
@@ -3140,6 +3166,14 @@ export default function Config() {
             >
               {t('config.browse')}
             </Button>
+            <Button
+              variant="outlined"
+              size="small"
+              onClick={(e) => handleDetectTool(e, 'ffmpeg')}
+              sx={{ minWidth: 90, height: 36, textTransform: 'none' }}
+            >
+              {t('config.detect')}
+            </Button>
           </Box>
           <Typography
             variant="caption"
@@ -3150,10 +3184,30 @@ export default function Config() {
             }}
           >
             {ffmpegFound ? t('config.ffmpegFound') : t('config.ffmpegNotFound')}
+            {ffmpegFound && ffmpegVersion ? ` ${ffmpegVersion}` : ''}
           </Typography>
         </Box>
       </Paper>
 
+      <Menu
+        anchorEl={toolCandidatesAnchor?.element}
+        open={toolCandidatesAnchor !== null}
+        onClose={() => setToolCandidatesAnchor(null)}
+      >
+        {toolCandidates.length === 0 ? (
+          <MenuItem disabled>{t('config.noInstallationsFound')}</MenuItem>
+        ) : (
+          toolCandidates.map((candidate) => (
+            <MenuItem key={candidate.path} onClick={() => handleSelectToolCandidate(candidate)}>
+              <ListItemText
+                primary={candidate.path}
+                secondary={`${t(`config.toolSource${candidate.source}`)} · ${candidate.version ?? t('config.versionUnknown')}`}
+              />
+            </MenuItem>
+          ))
+        )}
+      </Menu>
+
       <Paper variant="outlined" sx={{ p: 2, borderRadius: 2 }}>
         <SectionHeader
           icon={
@@ -3362,6 +3416,134 @@ export default function Config() {
     </Box>
   );
 
+  const handleAddWatchFolder = async () => {
+    const directory = await open({ directory: true });
+    if (typeof directory !== 'string' || directory.length === 0) return;
+    if (watchFolders.some((folder) => folder.path === directory)) return;
+    setWatchFolders([
+      ...watchFolders,
+      {
+        path: directory,
+        enabled: true,
+        recursive: false,
+        polling: false,
+        pollIntervalSeconds: 10,
+        settleSeconds: 5,
+        extensions: [],
+        profiles: [],
+        hash: false,
+        report: Protocol.WatchReportTarget.Folder,
+        logFile: '',
+      },
+    ]);
+  };
+
+  const updateWatchFolder = (index: number, changes: Partial<Protocol.ConfigWatchFolder>) => {
+    setWatchFolders(watchFolders.map((folder, i) => (i === index ? { ...folder, ...changes } : folder)));
+  };
+
+  const watchPanel = (
+    <Box>
+      <SectionHeader
+        icon={<WatchIcon fontSize="small" />}
+        title={t('config.watch')}
+        action={
+          <Button
+            variant="outlined"
+            size="small"
+            startIcon={<AddIcon />}
+            onClick={handleAddWatchFolder}
+            sx={{ textTransform: 'none' }}
+          >
+            {t('config.add')}
+          </Button>
+        }
+      />
+      <TableContainer sx={{ border: 1, borderColor: 'divider', borderRadius: 1 }}>
+        <Table size="small">
+          <TableHead>
+            <TableRow>
+              <TableCell sx={{ fontWeight: 600 }}>{t('config.watchFolder')}</TableCell>
+              <TableCell align="center" sx={{ fontWeight: 600 }}>{t('config.watchEnabled')}</TableCell>
+              <TableCell align="center" sx={{ fontWeight: 600 }}>{t('config.watchRecursive')}</TableCell>
+              <TableCell align="center" sx={{ fontWeight: 600 }}>{t('config.watchPolling')}</TableCell>
+              <TableCell align="center" sx={{ fontWeight: 600 }}>{t('config.watchHash')}</TableCell>
+              <TableCell sx={{ fontWeight: 600 }}>{t('config.watchReport')}</TableCell>
+              <TableCell align="right" sx={{ fontWeight: 600 }}>{t('config.action')}</TableCell>
+            </TableRow>
+          </TableHead>
+          <TableBody>
+            {watchFolders.length === 0 ? (
+              <TableRow>
+                <TableCell colSpan={7}>
+                  <Typography variant="body2" color="text.secondary">
+                    {t('config.watchNoFolders')}
+                  </Typography>
+                </TableCell>
+              </TableRow>
+            ) : (
+              watchFolders.map((folder, index) => (
+                <TableRow key={folder.path}>
+                  <TableCell sx={{ wordBreak: 'break-all' }}>{folder.path}</TableCell>
+                  <TableCell align="center">
+                    <Checkbox
+                      size="small"
+                      checked={folder.enabled}
+                      onChange={(e) => updateWatchFolder(index, { enabled: e.target.checked })}
+                    />
+                  </TableCell>
+                  <TableCell align="center">
+                    <Checkbox
+                      size="small"
+                      checked={folder.recursive}
+                      onChange={(e) => updateWatchFolder(index, { recursive: e.target.checked })}
+                    />
+                  </TableCell>
+                  <TableCell align="center">
+                    <Checkbox
+                      size="small"
+                      checked={folder.polling}
+                      onChange={(e) => updateWatchFolder(index, { polling: e.target.checked })}
+                    />
+                  </TableCell>
+                  <TableCell align="center">
+                    <Checkbox
+                      size="small"
+                      checked={folder.hash}
+                      onChange={(e) => updateWatchFolder(index, { hash: e.target.checked })}
+                    />
+                  </TableCell>
+                  <TableCell>
+                    <Select
+                      size="small"
+                      value={folder.report}
+                      onChange={(e) => updateWatchFolder(index, { report: e.target.value as Protocol.WatchReportTarget })}
+                    >
+                      <MenuItem value={Protocol.WatchReportTarget.Folder}>{t('config.watchReportFolder')}</MenuItem>
+                      <MenuItem value={Protocol.WatchReportTarget.Log}>{t('config.watchReportLog')}</MenuItem>
+                    </Select>
+                  </TableCell>
+                  <TableCell align="right">
+                    <IconButton
+                      size="small"
+                      aria-label={t('config.delete')}
+                      onClick={() => setWatchFolders(watchFolders.filter((_, i) => i !== index))}
+                    >
+                      <DeleteIcon fontSize="small" />
+                    </IconButton>
+                  </TableCell>
+                </TableRow>
+              ))
+            )}
+          </TableBody>
+        </Table>
+      </TableContainer>
+      <Typography variant="body2" color="text.secondary" sx={{ mt: 1 }}>
+        {t('config.watchInstruction')}
+      </Typography>
+    </Box>
+  );
+
   return (
     <Box sx={{ width: '100%', maxWidth: 960, mx: 'auto', py: 2, px: 1, display: 'flex', gap: 2, height: '100%', minHeight: 0 }}>
       <Tabs
@@ -3417,6 +3599,12 @@ export default function Config() {
           iconPosition="start"
           label={t('config.update')}
         />
+        <Tab
+          value={ConfigTab.Watch}
+          icon={<WatchIcon sx={{ fontSize: 18 }} />}
+          iconPosition="start"
+          label={t('config.watch')}
+        />
       </Tabs>
       <Box sx={{ flex: 1, minWidth: 0, minHeight: 0, display: 'flex', flexDirection: 'column', overflow: 'auto' }}>
         {mainTab === ConfigTab.Appearance && appearancePanel}
@@ -3425,6 +3613,7 @@ export default function Config() {
         {mainTab === ConfigTab.Integration && integrationPanel}
         {mainTab === ConfigTab.Templates && templatesPanel}
         {mainTab === ConfigTab.Update && updatePanel}
+        {mainTab === ConfigTab.Watch && watchPanel}
       </Box>
     </Box>
   );
@@ -0,0 +1,206 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useState } from 'react';
+import {
+  Box,
+  Button,
+  Checkbox,
+  CircularProgress,
+  Dialog,
+  DialogActions,
+  DialogContent,
+  DialogTitle,
+  FormControlLabel,
+  Stack,
+  TextField,
+  ToggleButton,
+  ToggleButtonGroup,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+
+import * as Protocol from '../lib/protocol';
+import { openSaveImageFileDialog } from '../lib/dialog';
+import { describeFfmpegError } from '../lib/ffmpegErrors';
+import { basename } from '../lib/format';
+import { createContactSheet } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface ContactSheetDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+}
+
+const FORMAT_EXTENSIONS: Record<Protocol.ContactSheetFormat, string> = {
+  [Protocol.ContactSheetFormat.Png]: 'png',
+  [Protocol.ContactSheetFormat.Jpeg]: 'jpg',
+};
+
+function describeContactSheetError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/CONTACT_SHEET_NO_CHAPTERS:/.test(message)) {
+    return t('contactSheet.noChapters');
+  }
+  if (/CONTACT_SHEET_NO_DURATION:/.test(message)) {
+    return t('contactSheet.noDuration');
+  }
+  if (/CONTACT_SHEET_TOO_NARROW:/.test(message)) {
+    return t('contactSheet.tooNarrow');
+  }
+  return describeFfmpegError(t, error);
+}
+
+export default function ContactSheetDialog({ open, onClose, file }: ContactSheetDialogProps) {
+  const { t } = useTranslation();
+  const [positions, setPositions] = useState<Protocol.ContactSheetPositions>(Protocol.ContactSheetPositions.Even);
+  const [format, setFormat] = useState<Protocol.ContactSheetFormat>(Protocol.ContactSheetFormat.Png);
+  const [count, setCount] = useState(16);
+  const [columns, setColumns] = useState(4);
+  const [width, setWidth] = useState(1600);
+  const [header, setHeader] = useState(true);
+  const [timestamps, setTimestamps] = useState(true);
+  const [running, setRunning] = useState(false);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  const numberField = (label: string, value: number, onChange: (n: number) => void, min: number, max: number) => (
+    <TextField
+      label={label}
+      type="number"
+      size="small"
+      fullWidth
+      value={value}
+      disabled={running}
+      onChange={(e) => {
+        const parsed = parseInt(e.target.value);
+        onChange(Number.isNaN(parsed) ? min : Math.min(max, Math.max(min, parsed)));
+      }}
+      slotProps={{ htmlInput: { min, max, step: 1 } }}
+    />
+  );
+
+  const handleCreate = async () => {
+    const extension = FORMAT_EXTENSIONS[format];
+    const output = (await openSaveImageFileDialog(extension, `${basename(file)}.sheet.${extension}`)) as string | null;
+    if (!output) return;
+    setRunning(true);
+    try {
+      const result = await createContactSheet(file, {
+        output,
+        format,
+        positions,
+        count,
+        columns,
+        width,
+        header,
+        timestamps,
+      });
+      setDialogNotification({
+        title: t('contactSheet.saved', { filePath: result.output, frames: result.frames }),
+        type: Protocol.DialogNotificationType.Info,
+      });
+      onClose();
+    } catch (error) {
+      setDialogNotification({
+        title: t('contactSheet.failed', { error: describeContactSheetError(t, error) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  return (
+    <Dialog open={open} onClose={running ? undefined : onClose} maxWidth="xs" fullWidth>
+      <DialogTitle>{t('contactSheet.title')}</DialogTitle>
+      <DialogContent>
+        <Stack spacing={2} sx={{ pt: 1 }}>
+          <ToggleButtonGroup
+            value={positions}
+            exclusive
+            fullWidth
+            size="small"
+            disabled={running}
+            onChange={(_e, value: Protocol.ContactSheetPositions | null) => value !== null && setPositions(value)}
+          >
+            <ToggleButton value={Protocol.ContactSheetPositions.Even} sx={{ textTransform: 'none' }}>
+              {t('contactSheet.positionsEven')}
+            </ToggleButton>
+            <ToggleButton value={Protocol.ContactSheetPositions.Chapters} sx={{ textTransform: 'none' }}>
+              {t('contactSheet.positionsChapters')}
+            </ToggleButton>
+          </ToggleButtonGroup>
+          <Box sx={{ display: 'flex', gap: 1 }}>
+            {positions === Protocol.ContactSheetPositions.Even &&
+              numberField(t('contactSheet.count'), count, setCount, 1, 400)}
+            {numberField(t('contactSheet.columns'), columns, setColumns, 1, 16)}
+            {numberField(t('contactSheet.width'), width, setWidth, 320, 8192)}
+          </Box>
+          <ToggleButtonGroup
+            value={format}
+            exclusive
+            fullWidth
+            size="small"
+            disabled={running}
+            onChange={(_e, value: Protocol.ContactSheetFormat | null) => value !== null && setFormat(value)}
+          >
+            <ToggleButton value={Protocol.ContactSheetFormat.Png} sx={{ textTransform: 'none' }}>
+              PNG
+            </ToggleButton>
+            <ToggleButton value={Protocol.ContactSheetFormat.Jpeg} sx={{ textTransform: 'none' }}>
+              JPEG
+            </ToggleButton>
+          </ToggleButtonGroup>
+          <Box>
+            <FormControlLabel
+              control={
+                <Checkbox size="small" checked={header} disabled={running} onChange={(e) => setHeader(e.target.checked)} />
+              }
+              label={t('contactSheet.header')}
+            />
+            <FormControlLabel
+              control={
+                <Checkbox
+                  size="small"
+                  checked={timestamps}
+                  disabled={running}
+                  onChange={(e) => setTimestamps(e.target.checked)}
+                />
+              }
+              label={t('contactSheet.timestamps')}
+            />
+          </Box>
+        </Stack>
+      </DialogContent>
+      <DialogActions sx={{ justifyContent: 'center', gap: 1, pb: 2 }}>
+        <Button
+          variant="contained"
+          disabled={running}
+          onClick={handleCreate}
+          startIcon={running ? <CircularProgress size={14} color="inherit" /> : undefined}
+          sx={{ minWidth: 90, textTransform: 'none' }}
+        >
+          {t('contactSheet.create')}
+        </Button>
+        <Button variant="outlined" disabled={running} onClick={onClose} sx={{ minWidth: 90, textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </DialogActions>
+    </Dialog>
+  );
+}
@@ -41,12 +41,37 @@ import JavascriptIcon from '@mui/icons-material/Javascript';
 import FileDownloadIcon from '@mui/icons-material/FileDownload';
 import DoneAllIcon from '@mui/icons-material/DoneAll';
 import RemoveDoneIcon from '@mui/icons-material/RemoveDone';
+import BookmarksIcon from '@mui/icons-material/Bookmarks';
+import ShowChartIcon from '@mui/icons-material/ShowChart';
 import ClearIcon from '@mui/icons-material/Clear';
+import CompareArrowsIcon from '@mui/icons-material/CompareArrows';
+import FactCheckIcon from '@mui/icons-material/FactCheck';
+import FlipIcon from '@mui/icons-material/Flip';
+import GraphicEqIcon from '@mui/icons-material/GraphicEq';
+import GridViewIcon from '@mui/icons-material/GridView';
+import HdrOnIcon from '@mui/icons-material/HdrOn';
+import HighQualityIcon from '@mui/icons-material/HighQuality';
+import KeyIcon from '@mui/icons-material/Key';
+import TuneIcon from '@mui/icons-material/Tune';
+import WavesIcon from '@mui/icons-material/Waves';
 import { useTranslation } from 'react-i18next';
 import * as Protocol from '../lib/protocol';
 import type { ExportStream } from '../lib/export';
+import { loudnessEntries } from '../lib/loudness';
 import { useAppStore } from '../lib/store';
+import BitrateDialog from './BitrateDialog';
+import ContactSheetDialog from './ContactSheetDialog';
+import EncoderDialog from './EncoderDialog';
 import ExportDialog from './ExportDialog';
+import FfprobeDialog from './FfprobeDialog';
+import FrameCompareDialog from './FrameCompareDialog';
+import GopDialog from './GopDialog';
+import HdrDialog from './HdrDialog';
+import LoudnessDialog from './LoudnessDialog';
+import QcDialog from './QcDialog';
+import QualityDialog from './QualityDialog';
+import SceneChaptersDialog from './SceneChaptersDialog';
+import SpectrogramDialog from './SpectrogramDialog';
 
 interface DetailsProps {
   file: string;
@@ -80,6 +105,23 @@ export default function Details({ file }: DetailsProps) {
   const [debouncedQuery, setDebouncedQuery] = useState('');
   const [streamGroup, setStreamGroup] = useState<Protocol.StreamKind[]>([]);
   const [exportDialogOpen, setExportDialogOpen] = useState(false);
+  const [ffprobeDialogOpen, setFfprobeDialogOpen] = useState(false);
+  const [frameCompareDialogOpen, setFrameCompareDialogOpen] = useState(false);
+  const [contactSheetDialogOpen, setContactSheetDialogOpen] = useState(false);
+  const [qcDialogOpen, setQcDialogOpen] = useState(false);
+  const [bitrateDialogOpen, setBitrateDialogOpen] = useState(false);
+  const [encoderDialogOpen, setEncoderDialogOpen] = useState(false);
+  const [gopDialogOpen, setGopDialogOpen] = useState(false);
+  const [hdrDialogOpen, setHdrDialogOpen] = useState(false);
+  const [qualityDialogOpen, setQualityDialogOpen] = useState(false);
+  const [loudnessDialogOpen, setLoudnessDialogOpen] = useState(false);
+  const [sceneChaptersDialogOpen, setSceneChaptersDialogOpen] = useState(false);
+  const [spectrogramDialogOpen, setSpectrogramDialogOpen] = useState(false);
+  const [attachedLoudness, setAttachedLoudness] = useState<{
+    file: string;
+    report: Protocol.LoudnessReport;
+    target: Protocol.LoudnessTarget;
+  } | null>(null);
   const debounceRef = useRef<ReturnType<typeof setTimeout> | undefined>(undefined);
 
   const config = useAppStore((state) => state.config);
@@ -158,18 +200,28 @@ export default function Details({ file }: DetailsProps) {
     return Object.entries(properties.propertyMap).sort(([a], [b]) => a.localeCompare(b));
   };
 
+  // Loudness attached from the loudness dialog goes into the exported report
+  // of the audio stream it was measured on.
+  const attachedLoudnessEntries = (properties: Protocol.StreamPropertyMap): Array<[string, string]> => {
+    if (!attachedLoudness || attachedLoudness.file !== file || properties.stream !== Protocol.StreamKind.Audio) {
+      return [];
+    }
+    const measurement = attachedLoudness.report.streams.find((m) => m.num === properties.num);
+    return measurement ? loudnessEntries(measurement, attachedLoudness.target) : [];
+  };
+
   const exportStreams = useMemo<ExportStream[]>(() => {
     return allProperties
       .filter((p) => streamGroup.includes(p.stream))
       .map((p) => ({
         stream: p.stream,
         num: p.num,
-        entries: orderedPropertyEntries(p),
+        entries: [...orderedPropertyEntries(p), ...attachedLoudnessEntries(p)],
       }))
       .filter((s) => s.entries.length > 0);
     // orderedPropertyEntries closes over config.templates; depend on it directly.
     // eslint-disable-next-line react-hooks/exhaustive-deps
-  }, [allProperties, streamGroup, config?.templates]);
+  }, [allProperties, streamGroup, config?.templates, attachedLoudness]);
 
   const filteredAllProperties = useMemo(() => {
     return allProperties
@@ -239,6 +291,90 @@ export default function Details({ file }: DetailsProps) {
                   </IconButton>
                 </span>
               </Tooltip>
+              <Tooltip title={t('details.bitrate')}>
+                <span>
+                  <IconButton size="small" onClick={() => setBitrateDialogOpen(true)}>
+                    <ShowChartIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.contactSheet')}>
+                <span>
+                  <IconButton size="small" onClick={() => setContactSheetDialogOpen(true)}>
+                    <GridViewIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.encoder')}>
+                <span>
+                  <IconButton size="small" onClick={() => setEncoderDialogOpen(true)}>
+                    <TuneIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.compareWithFfprobe')}>
+                <span>
+                  <IconButton size="small" onClick={() => setFfprobeDialogOpen(true)}>
+                    <CompareArrowsIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.gop')}>
+                <span>
+                  <IconButton size="small" onClick={() => setGopDialogOpen(true)}>
+                    <KeyIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.hdr')}>
+                <span>
+                  <IconButton size="small" onClick={() => setHdrDialogOpen(true)}>
+                    <HdrOnIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.loudness')}>
+                <span>
+                  <IconButton size="small" onClick={() => setLoudnessDialogOpen(true)}>
+                    <GraphicEqIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.qc')}>
+                <span>
+                  <IconButton size="small" onClick={() => setQcDialogOpen(true)}>
+                    <FactCheckIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.quality')}>
+                <span>
+                  <IconButton size="small" onClick={() => setQualityDialogOpen(true)}>
+                    <HighQualityIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.frameCompare')}>
+                <span>
+                  <IconButton size="small" onClick={() => setFrameCompareDialogOpen(true)}>
+                    <FlipIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.sceneChapters')}>
+                <span>
+                  <IconButton size="small" onClick={() => setSceneChaptersDialogOpen(true)}>
+                    <BookmarksIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
+              <Tooltip title={t('details.spectrogram')}>
+                <span>
+                  <IconButton size="small" onClick={() => setSpectrogramDialogOpen(true)}>
+                    <WavesIcon fontSize="small" />
+                  </IconButton>
+                </span>
+              </Tooltip>
               <Tooltip title={t('details.json')}>
                 <span>
                   <IconButton size="small" onClick={openDialogJsonCode} disabled={allProperties.length === 0}>
@@ -401,6 +537,68 @@ export default function Details({ file }: DetailsProps) {
         file={file}
         streams={exportStreams}
       />
+      <BitrateDialog
+        open={bitrateDialogOpen}
+        onClose={() => setBitrateDialogOpen(false)}
+        file={file}
+      />
+      <ContactSheetDialog
+        open={contactSheetDialogOpen}
+        onClose={() => setContactSheetDialogOpen(false)}
+        file={file}
+      />
+      <EncoderDialog
+        open={encoderDialogOpen}
+        onClose={() => setEncoderDialogOpen(false)}
+        file={file}
+      />
+      <FfprobeDialog
+        open={ffprobeDialogOpen}
+        onClose={() => setFfprobeDialogOpen(false)}
+        file={file}
+      />
+      <FrameCompareDialog
+        open={frameCompareDialogOpen}
+        onClose={() => setFrameCompareDialogOpen(false)}
+        file={file}
+      />
+      <GopDialog
+        open={gopDialogOpen}
+        onClose={() => setGopDialogOpen(false)}
+        file={file}
+      />
+      <HdrDialog
+        open={hdrDialogOpen}
+        onClose={() => setHdrDialogOpen(false)}
+        file={file}
+      />
+      <LoudnessDialog
+        open={loudnessDialogOpen}
+        onClose={() => setLoudnessDialogOpen(false)}
+        file={file}
+        onAttach={(report, target) => setAttachedLoudness({ file, report, target })}
+      />
+      <QcDialog
+        open={qcDialogOpen}
+        onClose={() => setQcDialogOpen(false)}
+        file={file}
+      />
+      <QualityDialog
+        open={qualityDialogOpen}
+        onClose={() => setQualityDialogOpen(false)}
+        file={file}
+      />
+      <SceneChaptersDialog
+        open={sceneChaptersDialogOpen}
+        onClose={() => setSceneChaptersDialogOpen(false)}
+        file={file}
+      />
+      <SpectrogramDialog
+        open={spectrogramDialogOpen}
+        onClose={() => setSpectrogramDialogOpen(false)}
+        file={file}
+        audioStreams={streamCountMap.get(Protocol.StreamKind.Audio)?.count ?? 0}
+      />
     </Box>
   );
 }
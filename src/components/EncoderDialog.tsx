@@ -0,0 +1,247 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useState } from 'react';
+import {
+  Box,
+  Button,
+  Checkbox,
+  Chip,
+  CircularProgress,
+  Dialog,
+  FormControlLabel,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableHead,
+  TableRow,
+  TextField,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+
+import * as Protocol from '../lib/protocol';
+import { openVideoFileDialog } from '../lib/dialog';
+import { ENCODER_KIND_LABELS, rateControlLabel } from '../lib/encoder';
+import { compareEncoderSettings } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface EncoderDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+}
+
+function describeEncoderError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  const noSettings = message.match(/ENCODER_NO_SETTINGS:(.*)$/);
+  if (noSettings) {
+    return t('encoder.noSettings', { file: noSettings[1] });
+  }
+  return message;
+}
+
+function SettingsSummary({ t, settings }: { t: TFunction; settings: Protocol.EncoderSettings }) {
+  const rateControl = rateControlLabel(settings);
+  const preset = settings.preset;
+  return (
+    <Box sx={{ display: 'flex', flexWrap: 'wrap', alignItems: 'center', gap: 1 }}>
+      <Chip size="small" color="primary" label={ENCODER_KIND_LABELS[settings.encoder]} />
+      {rateControl && <Chip size="small" label={rateControl} />}
+      {preset && (
+        <Chip
+          size="small"
+          label={
+            preset.compared === 0
+              ? t('encoder.preset', { preset: preset.name })
+              : t('encoder.presetGuess', { preset: preset.name, matched: preset.matched, compared: preset.compared })
+          }
+        />
+      )}
+      {settings.library && (
+        <Typography variant="caption" color="text.secondary" sx={{ fontFamily: 'monospace' }}>
+          {settings.library}
+        </Typography>
+      )}
+    </Box>
+  );
+}
+
+export default function EncoderDialog({ open, onClose, file }: EncoderDialogProps) {
+  const { t } = useTranslation();
+  const [other, setOther] = useState('');
+  const [onlyDifferences, setOnlyDifferences] = useState(false);
+  const [running, setRunning] = useState(false);
+  const [comparison, setComparison] = useState<Protocol.EncoderComparison | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  useEffect(() => {
+    if (!open) return;
+    setComparison(null);
+    setError(null);
+    compareEncoderSettings(file)
+      .then(setComparison)
+      .catch((e) => setError(describeEncoderError(t, e)));
+  }, [open, file, t]);
+
+  const handleBrowse = async () => {
+    const selected = (await openVideoFileDialog(other || undefined)) as string | null;
+    if (selected) setOther(selected);
+  };
+
+  const handleCompare = async () => {
+    setRunning(true);
+    try {
+      setComparison(await compareEncoderSettings(file, other));
+    } catch (e) {
+      setDialogNotification({
+        title: t('encoder.failed', { error: describeEncoderError(t, e) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const headerSx = { fontWeight: 'bold', bgcolor: 'background.default' };
+  const compared = comparison?.other ?? null;
+  const groups = Object.values(Protocol.EncoderSettingGroup);
+  const rows: Protocol.EncoderField[] =
+    comparison === null
+      ? []
+      : compared
+        ? comparison.fields.filter((field) => !onlyDifferences || field.mismatch)
+        : [...comparison.settings.settings]
+            .sort((a, b) => groups.indexOf(a.group) - groups.indexOf(b.group))
+            .map((setting) => ({ key: setting.key, group: setting.group, left: setting.value, right: null, mismatch: false }));
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '60vw',
+            height: '80vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('encoder.title')}
+        </Typography>
+      </Box>
+      <Box sx={{ px: 2, py: 1, display: 'flex', alignItems: 'center', gap: 1, flexShrink: 0 }}>
+        <TextField
+          label={t('encoder.other')}
+          size="small"
+          fullWidth
+          value={other}
+          disabled={running}
+          onChange={(e) => setOther(e.target.value)}
+        />
+        <Button variant="outlined" size="small" onClick={handleBrowse} disabled={running} sx={{ textTransform: 'none', flexShrink: 0 }}>
+          {t('encoder.browse')}
+        </Button>
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        <Button
+          variant="contained"
+          size="small"
+          onClick={handleCompare}
+          disabled={running || other.length === 0 || comparison === null}
+          startIcon={running ? <CircularProgress size={14} /> : undefined}
+          sx={{ textTransform: 'none' }}
+        >
+          {t('encoder.compare')}
+        </Button>
+        <FormControlLabel
+          control={
+            <Checkbox
+              size="small"
+              checked={onlyDifferences}
+              disabled={compared === null}
+              onChange={(e) => setOnlyDifferences(e.target.checked)}
+            />
+          }
+          label={t('encoder.onlyDifferences')}
+        />
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {comparison ? (
+        <Box sx={{ flex: 1, minHeight: 0, display: 'flex', flexDirection: 'column', gap: 1, p: 2 }}>
+          <SettingsSummary t={t} settings={comparison.settings} />
+          {compared && <SettingsSummary t={t} settings={compared} />}
+          <TableContainer sx={{ flex: 1, minHeight: 0, overflow: 'auto' }}>
+            <Table size="small" stickyHeader>
+              <TableHead>
+                <TableRow>
+                  <TableCell sx={headerSx}>{t('encoder.group')}</TableCell>
+                  <TableCell sx={headerSx}>{t('encoder.parameter')}</TableCell>
+                  <TableCell sx={headerSx}>{compared ? t('encoder.thisFile') : t('encoder.value')}</TableCell>
+                  {compared && <TableCell sx={headerSx}>{t('encoder.otherFile')}</TableCell>}
+                </TableRow>
+              </TableHead>
+              <TableBody>
+                {rows.map((row) => (
+                  <TableRow key={row.key} sx={row.mismatch ? { bgcolor: 'action.selected' } : undefined}>
+                    <TableCell sx={{ whiteSpace: 'nowrap' }}>{t(`encoder.group${row.group}`)}</TableCell>
+                    <TableCell sx={{ fontFamily: 'monospace', fontWeight: row.mismatch ? 'bold' : undefined }}>{row.key}</TableCell>
+                    <TableCell sx={{ fontFamily: 'monospace', wordBreak: 'break-all' }}>{row.left ?? '-'}</TableCell>
+                    {compared && (
+                      <TableCell sx={{ fontFamily: 'monospace', wordBreak: 'break-all' }}>{row.right ?? '-'}</TableCell>
+                    )}
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </TableContainer>
+        </Box>
+      ) : (
+        <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+          <Typography variant="body2" color="text.secondary">
+            {error ?? t('encoder.loading')}
+          </Typography>
+        </Box>
+      )}
+    </Dialog>
+  );
+}
@@ -35,6 +35,7 @@ import { renderHtml } from '../lib/exportHtml';
 import { renderMarkdown } from '../lib/exportMarkdown';
 import { canvasToBlob, renderPng } from '../lib/exportPng';
 import { renderText } from '../lib/exportText';
+import { basename } from '../lib/format';
 import { writeBinaryFile, writeTextFile } from '../lib/service';
 import { useAppStore } from '../lib/store';
 
@@ -45,11 +46,6 @@ interface ExportDialogProps {
   streams: ExportStream[];
 }
 
-function basename(path: string): string {
-  const parts = path.split(/[/\\]/);
-  return parts[parts.length - 1] || path;
-}
-
 export default function ExportDialog({ open, onClose, file, streams }: ExportDialogProps) {
   const { t } = useTranslation();
   const [format, setFormat] = useState<ExportFormat>(ExportFormat.Text);
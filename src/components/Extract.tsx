@@ -420,9 +420,12 @@ function Extract({ file, mkvToolNixPath }: ExtractProps) {
       const msg = String(err);
       setDialogOpen(false);
       setExtracting(false);
+      const tooOld = msg.match(/MKVTOOLNIX_TOO_OLD:([^:]+):([^:]+):(.+)$/);
       if (msg.includes('MKVEXTRACT_NOT_AVAILABLE:')) {
         const detail = msg.split('MKVEXTRACT_NOT_AVAILABLE:')[1];
         setError(t('extract.error.mkvextractNotAvailable', { detail }));
+      } else if (tooOld) {
+        setError(t('extract.error.mkvtoolnixTooOld', { feature: tooOld[1], required: tooOld[2], found: tooOld[3] }));
       } else {
         setError(msg);
       }
@@ -45,19 +45,36 @@ import {
   Tooltip,
   Typography,
 } from '@mui/material';
+import AudiotrackIcon from '@mui/icons-material/Audiotrack';
 import ClearIcon from '@mui/icons-material/Clear';
+import CropIcon from '@mui/icons-material/Crop';
 import ExpandMoreIcon from '@mui/icons-material/ExpandMore';
 import FolderOpenIcon from '@mui/icons-material/FolderOpen';
+import GifBoxIcon from '@mui/icons-material/GifBox';
 import PhotoCameraIcon from '@mui/icons-material/PhotoCamera';
 import StopIcon from '@mui/icons-material/Stop';
 import { useTranslation } from 'react-i18next';
+import type { TFunction } from 'i18next';
 import { basename, dirname, extname, join } from '@tauri-apps/api/path';
 import { getCurrentWindow, LogicalSize } from '@tauri-apps/api/window';
 import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 import { open } from '@tauri-apps/plugin-dialog';
+import { openSaveImageFileDialog, openSaveTextFileDialog } from '../lib/dialog';
+import { describeFfmpegError, isFfmpegAnalysisCancelled } from '../lib/ffmpegErrors';
+import { LOUDNESS_TARGET_LABELS } from '../lib/loudness';
 import * as Protocol from '../lib/protocol';
 import { useAppStore } from '../lib/store';
-import { captureFfmpegFrame, cancelFfmpegCapture, getPropertiesMap, runFfmpegCapture } from '../lib/service';
+import {
+  captureFfmpegFrame,
+  cancelFfmpegCapture,
+  createPreviewClip,
+  detectCrop,
+  exportAudio,
+  getCapturePoints,
+  getConfig,
+  getPropertiesMap,
+  runFfmpegCapture,
+} from '../lib/service';
 
 // Fixed width (px) of the tab control drawn beside the preview panel.
 const TAB_PANEL_WIDTH = 400;
@@ -90,6 +107,7 @@ type CaptureMode =
   | 'everyXSeconds'
   | 'everySecond'
   | 'keyframes'
+  | 'chapters'
   | 'sceneChanges'
   | 'thumbnail'
   | 'tile'
@@ -102,6 +120,7 @@ const CAPTURE_MODES: CaptureMode[] = [
   'everyXSeconds',
   'everySecond',
   'keyframes',
+  'chapters',
   'sceneChanges',
   'thumbnail',
   'tile',
@@ -142,6 +161,26 @@ interface OutputOptions {
 
 const HWACCELS = ['auto', 'cuda', 'd3d11va', 'dxva2', 'videotoolbox', 'vaapi', 'qsv'];
 
+// File extension of each audio export codec.
+const AUDIO_EXTENSIONS: Record<Protocol.AudioCodec, string> = {
+  [Protocol.AudioCodec.Flac]: 'flac',
+  [Protocol.AudioCodec.Opus]: 'opus',
+  [Protocol.AudioCodec.Aac]: 'm4a',
+  [Protocol.AudioCodec.Wav]: 'wav',
+};
+
+// File extension and preview MIME type of each animated preview clip format.
+const CLIP_EXTENSIONS: Record<Protocol.ClipFormat, string> = {
+  [Protocol.ClipFormat.Gif]: 'gif',
+  [Protocol.ClipFormat.Webp]: 'webp',
+  [Protocol.ClipFormat.Apng]: 'apng',
+};
+const CLIP_MIME_TYPES: Record<Protocol.ClipFormat, string> = {
+  [Protocol.ClipFormat.Gif]: 'image/gif',
+  [Protocol.ClipFormat.Webp]: 'image/webp',
+  [Protocol.ClipFormat.Apng]: 'image/apng',
+};
+
 // Default output filename pattern. `{name}` = source base name; `%04d` is
 // ffmpeg's image2 frame-number token. The extension is appended from the format.
 const DEFAULT_NAME_PATTERN = '{name}_shot_%04d';
@@ -152,15 +191,14 @@ function applyName(pattern: string, stem: string): string {
   return pattern.replace(/\{name\}/g, stem);
 }
 
-// Replace the ffmpeg frame-number token with a concrete zero-padded index. Used
-// by the timestamp mode, where each frame is its own single-image ffmpeg run, so
-// ffmpeg's own %d would be 1 every time and the files would collide. Falls back
-// to appending the index when the pattern contains no token.
-function fillFrameToken(name: string, index: number): string {
-  const m = name.match(/%(\d*)d/);
-  if (!m) return `${name}_${String(index).padStart(4, '0')}`;
-  const width = m[1] ? parseInt(m[1], 10) : 1;
-  return name.replace(/%(\d*)d/, String(index).padStart(width, '0'));
+// Replace the ffmpeg frame-number token with a capture point's label (its
+// chapter number and title, or its timestamp). Used by the point modes, where
+// each frame is its own single-image ffmpeg run, so ffmpeg's own %d would be 1
+// every time and the files would collide. Falls back to appending the label
+// when the pattern contains no token.
+function fillFrameToken(name: string, label: string): string {
+  if (!/%(\d*)d/.test(name)) return `${name}_${label}`;
+  return name.replace(/%(\d*)d/, () => label);
 }
 
 function formatTime(seconds: number): string {
@@ -175,6 +213,34 @@ function bytesToBlobUrl(bytes: number[], mime: string): string {
   return URL.createObjectURL(new Blob([new Uint8Array(bytes)], { type: mime }));
 }
 
+// Turn the crop, clip and audio export errors into readable messages; the
+// rest go through `describeFfmpegError`.
+function describeCaptureError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/CROP_DETECT_TOO_DARK:/.test(message)) {
+    return t('crop.tooDark');
+  }
+  if (/CROP_DETECT_NO_VIDEO:/.test(message)) {
+    return t('crop.noVideo');
+  }
+  if (/CLIP_NO_VIDEO:/.test(message)) {
+    return t('crop.noVideo');
+  }
+  if (/CLIP_NO_DURATION:/.test(message)) {
+    return t('ffmpegTools.clip.noDuration');
+  }
+  if (/CLIP_EMPTY_RANGE:/.test(message)) {
+    return t('ffmpegTools.clip.emptyRange');
+  }
+  if (/AUDIO_NO_STREAM:/.test(message)) {
+    return t('ffmpegTools.audio.noAudio');
+  }
+  if (/AUDIO_EMPTY_RANGE:/.test(message)) {
+    return t('ffmpegTools.audio.emptyRange');
+  }
+  return describeFfmpegError(t, error);
+}
+
 async function getStem(file: string): Promise<string> {
   let ext = '';
   try {
@@ -266,23 +332,22 @@ async function buildCaptureArgs(
   return { args, output: outPattern };
 }
 
-// Timestamp mode writes one explicit filename per invocation (no `%d` token); the
+// Point modes write one explicit filename per invocation (no `%d` token); the
 // backend matches that literal name. Returns the argv plus that output filename.
-async function buildTimestampArgs(
+async function buildPointArgs(
   file: string,
   opts: OutputOptions,
-  timeSec: number,
-  index: number
+  point: Protocol.CapturePoint
 ): Promise<{ args: string[]; output: string }> {
   const ext = opts.format === 'jpg' ? 'jpg' : 'png';
   const stem = await getStem(file);
   const pat = opts.pattern.trim() || DEFAULT_NAME_PATTERN;
-  const out = await join(opts.outputDir, `${fillFrameToken(applyName(pat, stem), index + 1)}.${ext}`);
+  const out = await join(opts.outputDir, `${fillFrameToken(applyName(pat, stem), point.label)}.${ext}`);
   const args: string[] = [];
   if (opts.hwaccelEnabled && opts.hwaccel) {
     args.push('-hwaccel', opts.hwaccel);
   }
-  args.push('-ss', String(timeSec), '-i', file, '-frames:v', '1');
+  args.push('-ss', String(point.seconds), '-i', file, '-frames:v', '1');
   const filters: string[] = [];
   if (opts.cropEnabled) {
     filters.push(`crop=${opts.cropW}:${opts.cropH}:${opts.cropX}:${opts.cropY}`);
@@ -296,7 +361,9 @@ async function buildTimestampArgs(
   if (opts.format === 'jpg') {
     args.push('-q:v', String(opts.jpgQuality));
   }
-  args.push(out);
+  // The name comes from a chapter title or timestamp, so write it as a single
+  // image: without `-update`, image2 reads a `%` in it as a frame-number token.
+  args.push('-update', '1', out);
   return { args, output: out };
 }
 
@@ -325,6 +392,7 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
   const [frameCount, setFrameCount] = useState(0);
   const [width, setWidth] = useState(0);
   const [height, setHeight] = useState(0);
+  const [audioCount, setAudioCount] = useState(0);
 
   // Preview frame + the panel size derived from the video dimensions.
   const [frameUrl, setFrameUrl] = useState<string | null>(null);
@@ -343,7 +411,8 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
   const [progress, setProgress] = useState(0);
   // Which pass is running and the trim pass's per-image counts. While trimming,
   // the slider doubles as the trim progress bar and the label reflects the count.
-  const [phase, setPhase] = useState<'capture' | 'trim'>('capture');
+  // A preview clip reports its encode attempt as the current count.
+  const [phase, setPhase] = useState<'capture' | 'trim' | 'clip' | 'audio'>('capture');
   const [trimCurrent, setTrimCurrent] = useState(0);
   const [trimTotal, setTrimTotal] = useState(0);
   // Shown when the user tries to close (Esc) while a capture/trim is running.
@@ -368,6 +437,28 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
   const [tileRows, setTileRows] = useState(4);
   const [dedupeFps, setDedupeFps] = useState(0);
   const [timestamps, setTimestamps] = useState('');
+  const [keyframeNames, setKeyframeNames] = useState(false);
+
+  // Preview clip parameters.
+  const [clipFormat, setClipFormat] = useState<Protocol.ClipFormat>(Protocol.ClipFormat.Gif);
+  const [clipSegments, setClipSegments] = useState<Protocol.ClipSegments>(Protocol.ClipSegments.Range);
+  const [clipStart, setClipStart] = useState(0);
+  const [clipEnd, setClipEnd] = useState(5);
+  const [clipCount, setClipCount] = useState(6);
+  const [clipLength, setClipLength] = useState(1);
+  const [clipFps, setClipFps] = useState(12);
+  const [clipMaxWidth, setClipMaxWidth] = useState(480);
+  const [clipMaxHeight, setClipMaxHeight] = useState(0);
+  const [clipTargetKib, setClipTargetKib] = useState(0);
+
+  // Audio export (the Audio tab). Presets come from the FFmpeg settings.
+  const [audioPresets, setAudioPresets] = useState<Protocol.ConfigAudioPreset[]>([]);
+  const [audioPresetIndex, setAudioPresetIndex] = useState(0);
+  const [audioStream, setAudioStream] = useState(0);
+  const [audioDownmix, setAudioDownmix] = useState<Protocol.AudioDownmix>(Protocol.AudioDownmix.Keep);
+  const [audioNormalize, setAudioNormalize] = useState<Protocol.LoudnessTarget | ''>('');
+  const [audioStart, setAudioStart] = useState(0);
+  const [audioEnd, setAudioEnd] = useState(0);
 
   // Output options.
   const [outputDir, setOutputDir] = useState('');
@@ -381,6 +472,7 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
   const [cropH, setCropH] = useState(720);
   const [cropX, setCropX] = useState(0);
   const [cropY, setCropY] = useState(0);
+  const [cropDetecting, setCropDetecting] = useState(false);
   const [hwaccelEnabled, setHwaccelEnabled] = useState(false);
   const [hwaccel, setHwaccel] = useState('auto');
 
@@ -388,6 +480,7 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
   const [trimEnabled, setTrimEnabled] = useState(false);
   const [trimColor, setTrimColor] = useState('#000000');
   const [trimTolerance, setTrimTolerance] = useState(10);
+  const [trimMode, setTrimMode] = useState<Protocol.TrimMode>(Protocol.TrimMode.Each);
   const [trimThreads, setTrimThreads] = useState(CPU_COUNT);
 
   const collectParams = useCallback(
@@ -443,9 +536,10 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
       enabled: trimEnabled,
       color: trimColor,
       tolerance: trimTolerance,
+      mode: trimMode,
       threads: trimThreads,
     }),
-    [trimEnabled, trimColor, trimTolerance, trimThreads]
+    [trimEnabled, trimColor, trimTolerance, trimMode, trimThreads]
   );
 
   // Swap the preview image only once the new frame decodes, so partially
@@ -517,6 +611,7 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
     setMetaError(null);
     getPropertiesMap(file, [
       { stream: Protocol.StreamKind.General, property: 'Duration' },
+      { stream: Protocol.StreamKind.General, property: 'AudioCount' },
       { stream: Protocol.StreamKind.Video, property: 'Duration' },
       { stream: Protocol.StreamKind.Video, property: 'FrameRate' },
       { stream: Protocol.StreamKind.Video, property: 'FrameCount' },
@@ -535,6 +630,7 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
         setFrameCount(parseInt(video?.propertyMap['FrameCount'] ?? '') || 0);
         setWidth(vw);
         setHeight(vh);
+        setAudioCount(parseInt(general?.propertyMap['AudioCount'] ?? '') || 0);
         setMetaLoading(false);
         void applyLayout(vw, vh);
       })
@@ -549,6 +645,14 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
     };
   }, [file, applyLayout]);
 
+  // This window doesn't share the main window's store, so read the audio
+  // presets from the saved configuration.
+  useEffect(() => {
+    getConfig()
+      .then((config) => setAudioPresets(config.ffmpeg.audioPresets ?? []))
+      .catch(() => undefined);
+  }, []);
+
   // Initialize output directory from the source file's folder.
   useEffect(() => {
     dirname(file).then(setOutputDir).catch(() => undefined);
@@ -565,18 +669,18 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
     const w = getCurrentWebviewWindow();
     const unlistenProgress = w.listen<Protocol.FfmpegCaptureProgress>('ffmpeg-capture-progress', (event) => {
       const { percent, done, cancelled, error, phase: evPhase, current, total } = event.payload;
-      // In timestamp mode the frontend loop owns the progress UI (one ffmpeg run
-      // per timestamp), so don't let the per-run backend events fight it.
+      // In the point modes the frontend loop owns the progress UI (one ffmpeg run
+      // per point), so don't let the per-run backend events fight it.
       if (!manualModeRef.current) {
         setProgress(percent);
-        setPhase(evPhase === 'trim' ? 'trim' : 'capture');
+        setPhase(evPhase === 'trim' || evPhase === 'clip' || evPhase === 'audio' ? evPhase : 'capture');
         setTrimCurrent(current);
         setTrimTotal(total);
       }
       if (done && !manualModeRef.current) {
         setCapturing(false);
         if (error) {
-          setNotification({ title: t('ffmpegTools.captureFailed', { detail: error }), type: Protocol.DialogNotificationType.Error });
+          setNotification({ title: t('ffmpegTools.captureFailed', { detail: describeCaptureError(t, error) }), type: Protocol.DialogNotificationType.Error });
         } else if (!cancelled) {
           setNotification({ title: t('ffmpegTools.captureComplete'), type: Protocol.DialogNotificationType.Info });
         }
@@ -617,9 +721,19 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
     setTrimCurrent(0);
     setTrimTotal(0);
 
-    if (mode === 'timestampList') {
+    // Chapters, timestamps and (when named by time) keyframes are captured one
+    // point at a time, each file named after its point's label.
+    const pointSource =
+      mode === 'chapters'
+        ? Protocol.CapturePointSource.Chapters
+        : mode === 'timestampList'
+          ? Protocol.CapturePointSource.Timestamps
+          : mode === 'keyframes' && keyframeNames
+            ? Protocol.CapturePointSource.Keyframes
+            : null;
+    if (pointSource !== null) {
       const times = parseTimestamps(timestamps);
-      if (times.length === 0) {
+      if (pointSource === Protocol.CapturePointSource.Timestamps && times.length === 0) {
         setNotification({ title: t('ffmpegTools.noTimestamps'), type: Protocol.DialogNotificationType.Error });
         return;
       }
@@ -628,17 +742,25 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
       setCapturing(true);
       setProgress(0);
       try {
-        for (let i = 0; i < times.length; i++) {
+        const points = await getCapturePoints(file, pointSource, times);
+        if (points.length === 0) {
+          setNotification({
+            title: pointSource === Protocol.CapturePointSource.Chapters ? t('ffmpegTools.noChapters') : t('ffmpegTools.noKeyframes'),
+            type: Protocol.DialogNotificationType.Error,
+          });
+          return;
+        }
+        for (let i = 0; i < points.length; i++) {
           if (cancelRef.current) break;
-          const { args, output } = await buildTimestampArgs(file, collectOpts(), times[i], i);
+          const { args, output } = await buildPointArgs(file, collectOpts(), points[i]);
           await runFfmpegCapture(args, output, durationSeconds, collectTrim(), previewWidthRef.current);
-          setProgress(Math.round(((i + 1) / times.length) * 100));
+          setProgress(Math.round(((i + 1) / points.length) * 100));
         }
         if (!cancelRef.current) {
           setNotification({ title: t('ffmpegTools.captureComplete'), type: Protocol.DialogNotificationType.Info });
         }
       } catch (e) {
-        setNotification({ title: t('ffmpegTools.captureFailed', { detail: String(e) }), type: Protocol.DialogNotificationType.Error });
+        setNotification({ title: t('ffmpegTools.captureFailed', { detail: describeCaptureError(t, e) }), type: Protocol.DialogNotificationType.Error });
       } finally {
         setCapturing(false);
         manualModeRef.current = false;
@@ -654,8 +776,129 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
       await runFfmpegCapture(args, output, durationSeconds, collectTrim(), previewWidthRef.current);
     } catch (e) {
       setCapturing(false);
-      setNotification({ title: t('ffmpegTools.captureFailed', { detail: String(e) }), type: Protocol.DialogNotificationType.Error });
+      setNotification({ title: t('ffmpegTools.captureFailed', { detail: describeCaptureError(t, e) }), type: Protocol.DialogNotificationType.Error });
+      restorePosition();
+    }
+  };
+
+  // Encode an animated preview clip. The backend retries smaller encodes until
+  // the clip fits the target size, then pushes the clip itself to the preview
+  // panel, where it stays until the next seek.
+  const handleCreateClip = async () => {
+    if (capturing) return;
+    const extension = CLIP_EXTENSIONS[clipFormat];
+    const name = `${await getStem(file)}.clip.${extension}`;
+    const defaultPath = outputDir ? await join(outputDir, name) : name;
+    const output = (await openSaveImageFileDialog(extension, defaultPath)) as string | null;
+    if (!output) return;
+    captureMimeRef.current = CLIP_MIME_TYPES[clipFormat];
+    savedPositionRef.current = position;
+    setPhase('clip');
+    setTrimCurrent(0);
+    setTrimTotal(0);
+    setCapturing(true);
+    setProgress(0);
+    try {
+      const result = await createPreviewClip(file, {
+        output,
+        format: clipFormat,
+        segments: clipSegments,
+        start: clipStart,
+        end: clipEnd,
+        count: clipCount,
+        length: clipLength,
+        fps: clipFps,
+        maxWidth: clipMaxWidth,
+        maxHeight: clipMaxHeight,
+        targetSize: clipTargetKib * 1024,
+      });
+      const params = {
+        filePath: result.output,
+        size: Math.round(result.size / 1024),
+        width: result.width,
+        height: result.height,
+        fps: result.fps,
+        attempts: result.attempts,
+      };
+      setNotification({
+        title: result.targetMet ? t('ffmpegTools.clip.saved', params) : t('ffmpegTools.clip.savedOverTarget', params),
+        type: Protocol.DialogNotificationType.Info,
+      });
+    } catch (e) {
+      if (!isFfmpegAnalysisCancelled(e)) {
+        setNotification({ title: t('ffmpegTools.clip.failed', { detail: describeCaptureError(t, e) }), type: Protocol.DialogNotificationType.Error });
+      }
       restorePosition();
+    } finally {
+      setCapturing(false);
+    }
+  };
+
+  // Extract or transcode one audio stream with the chosen preset. Progress
+  // arrives on the capture events; the preview stays on the current frame.
+  const handleExportAudio = async () => {
+    if (capturing) return;
+    const preset = audioPresets[audioPresetIndex];
+    if (!preset) return;
+    const extension = AUDIO_EXTENSIONS[preset.codec];
+    const name = `${await getStem(file)}.audio${audioStream + 1}.${extension}`;
+    const defaultPath = outputDir ? await join(outputDir, name) : name;
+    const output = (await openSaveTextFileDialog('audio', extension, defaultPath)) as string | null;
+    if (!output) return;
+    setPhase('audio');
+    setTrimCurrent(0);
+    setTrimTotal(0);
+    setCapturing(true);
+    setProgress(0);
+    try {
+      const result = await exportAudio(file, {
+        output,
+        stream: audioStream,
+        preset,
+        downmix: audioDownmix,
+        normalize: audioNormalize === '' ? null : audioNormalize,
+        start: audioStart,
+        end: audioEnd,
+      });
+      setNotification({
+        title: t('ffmpegTools.audio.saved', {
+          filePath: result.output,
+          size: Math.round(result.size / 1024),
+          duration: formatTime(result.duration),
+        }),
+        type: Protocol.DialogNotificationType.Info,
+      });
+    } catch (e) {
+      if (!isFfmpegAnalysisCancelled(e)) {
+        setNotification({ title: t('ffmpegTools.audio.failed', { detail: describeCaptureError(t, e) }), type: Protocol.DialogNotificationType.Error });
+      }
+    } finally {
+      setCapturing(false);
+    }
+  };
+
+  // Sample the whole file with cropdetect and fill the crop fields with the result.
+  const handleDetectCrop = async () => {
+    setCropDetecting(true);
+    try {
+      const detection = await detectCrop(file);
+      setCropW(detection.width - detection.left - detection.right);
+      setCropH(detection.height - detection.top - detection.bottom);
+      setCropX(detection.left);
+      setCropY(detection.top);
+      setCropEnabled(true);
+      setNotification({
+        title: t('crop.detected', {
+          crop: detection.ffmpegCrop,
+          aspectRatio: detection.aspectRatio.toFixed(3),
+          agreement: Math.round(detection.agreement * 100),
+        }),
+        type: Protocol.DialogNotificationType.Info,
+      });
+    } catch (e) {
+      setNotification({ title: t('crop.failed', { error: describeCaptureError(t, e) }), type: Protocol.DialogNotificationType.Error });
+    } finally {
+      setCropDetecting(false);
     }
   };
 
@@ -741,9 +984,28 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
           </Typography>
         );
       case 'keyframes':
+        return (
+          <Stack spacing={1}>
+            <Typography variant="body2" color="text.secondary">
+              {t('ffmpegTools.params.keyframesHint')}
+            </Typography>
+            <FormControlLabel
+              control={
+                <Checkbox checked={keyframeNames} disabled={capturing} onChange={(e) => setKeyframeNames(e.target.checked)} />
+              }
+              label={<Typography variant="body2">{t('ffmpegTools.params.keyframeNames')}</Typography>}
+            />
+            {keyframeNames && (
+              <Typography variant="caption" color="text.secondary">
+                {t('ffmpegTools.params.keyframeNamesHint')}
+              </Typography>
+            )}
+          </Stack>
+        );
+      case 'chapters':
         return (
           <Typography variant="body2" color="text.secondary">
-            {t('ffmpegTools.params.keyframesHint')}
+            {t('ffmpegTools.params.chaptersHint')}
           </Typography>
         );
       case 'sceneChanges':
@@ -800,7 +1062,7 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
             value={timestamps}
             disabled={capturing}
             placeholder={t('ffmpegTools.params.timestampsPlaceholder')}
-            helperText={t('ffmpegTools.params.timestampsHint')}
+            helperText={`${t('ffmpegTools.params.timestampsHint')} ${t('ffmpegTools.params.timestampNamesHint')}`}
             onChange={(e) => setTimestamps(e.target.value)}
           />
         );
@@ -813,10 +1075,15 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
   // per-image trim count. Otherwise it shows the video metadata.
   let sliderInfo: string;
   if (capturing) {
-    sliderInfo =
-      phase === 'trim'
-        ? t('ffmpegTools.trimmingStatus', { current: trimCurrent, total: trimTotal, percent: progress })
-        : `${progress}% · ${formatTime((progress / 100) * durationSeconds)} / ${formatTime(durationSeconds)}`;
+    if (phase === 'trim') {
+      sliderInfo = t('ffmpegTools.trimmingStatus', { current: trimCurrent, total: trimTotal, percent: progress });
+    } else if (phase === 'clip') {
+      sliderInfo = t('ffmpegTools.clip.encodingStatus', { attempt: trimCurrent, percent: progress });
+    } else if (phase === 'audio') {
+      sliderInfo = t('ffmpegTools.audio.exportingStatus', { percent: progress });
+    } else {
+      sliderInfo = `${progress}% · ${formatTime((progress / 100) * durationSeconds)} / ${formatTime(durationSeconds)}`;
+    }
   } else {
     const infoSegments: string[] = [];
     if (width > 0 && height > 0) infoSegments.push(`${width}x${height}`);
@@ -908,6 +1175,8 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
           }}
         >
           <Tab label={t('ffmpegTools.tabs.screenshots')} style={{ minHeight: '24px' }} />
+          <Tab label={t('ffmpegTools.tabs.clip')} style={{ minHeight: '24px' }} />
+          <Tab label={t('ffmpegTools.tabs.audio')} style={{ minHeight: '24px' }} />
         </Tabs>
 
         <Box sx={{ flex: 1, overflow: 'auto', p: 2 }}>
@@ -989,6 +1258,22 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
                         {t('ffmpegTools.trimToleranceHint')}
                       </Typography>
                     </Box>
+                    <TextField
+                      select
+                      label={t('ffmpegTools.trimMode')}
+                      size="small"
+                      fullWidth
+                      value={trimMode}
+                      disabled={!trimEnabled || capturing}
+                      onChange={(e) => setTrimMode(e.target.value as Protocol.TrimMode)}
+                      helperText={t(`ffmpegTools.trimModeHints.${trimMode}`)}
+                    >
+                      {Object.values(Protocol.TrimMode).map((m) => (
+                        <MenuItem key={m} value={m}>
+                          {t(`ffmpegTools.trimModes.${m}`)}
+                        </MenuItem>
+                      ))}
+                    </TextField>
                     <Box>
                       <Typography variant="body2" gutterBottom>
                         {t('ffmpegTools.trimThreads')}: {trimThreads}
@@ -1113,6 +1398,13 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
                         }
                         label={<Typography variant="body2">{t('ffmpegTools.crop')}</Typography>}
                       />
+                      <Tooltip title={t('crop.detect')}>
+                        <span>
+                          <IconButton size="small" disabled={capturing || cropDetecting} onClick={handleDetectCrop}>
+                            {cropDetecting ? <CircularProgress size={16} /> : <CropIcon fontSize="small" />}
+                          </IconButton>
+                        </span>
+                      </Tooltip>
                       {cropEnabled && (
                         <Box sx={{ display: 'grid', gridTemplateColumns: '1fr 1fr', gap: 1, mt: 0.5 }}>
                           {numberField(t('ffmpegTools.cropW'), cropW, setCropW, { min: 1, integer: true })}
@@ -1156,6 +1448,191 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
               </Accordion>
             </Stack>
           )}
+          {tab === 1 && (
+            <Stack spacing={2}>
+              <Box>
+                <Typography variant="caption" color="text.secondary">
+                  {t('ffmpegTools.format')}
+                </Typography>
+                <ToggleButtonGroup
+                  size="small"
+                  exclusive
+                  value={clipFormat}
+                  disabled={capturing}
+                  onChange={(_, v) => v && setClipFormat(v)}
+                  sx={{ display: 'block', mt: 0.5, '& .MuiToggleButton-root': { textTransform: 'none' } }}
+                >
+                  <ToggleButton value={Protocol.ClipFormat.Gif}>GIF</ToggleButton>
+                  <ToggleButton value={Protocol.ClipFormat.Webp}>WebP</ToggleButton>
+                  <ToggleButton value={Protocol.ClipFormat.Apng}>APNG</ToggleButton>
+                </ToggleButtonGroup>
+              </Box>
+              <ToggleButtonGroup
+                size="small"
+                exclusive
+                fullWidth
+                value={clipSegments}
+                disabled={capturing}
+                onChange={(_, v) => v && setClipSegments(v)}
+                sx={{ '& .MuiToggleButton-root': { textTransform: 'none' } }}
+              >
+                <ToggleButton value={Protocol.ClipSegments.Range}>{t('ffmpegTools.clip.range')}</ToggleButton>
+                <ToggleButton value={Protocol.ClipSegments.Even}>{t('ffmpegTools.clip.even')}</ToggleButton>
+              </ToggleButtonGroup>
+              {clipSegments === Protocol.ClipSegments.Range ? (
+                <Box sx={{ display: 'flex', alignItems: 'flex-start', gap: 1 }}>
+                  {numberField(t('ffmpegTools.clip.start'), clipStart, setClipStart, {
+                    min: 0,
+                    step: 0.5,
+                    helperText: formatTime(clipStart),
+                  })}
+                  {numberField(t('ffmpegTools.clip.end'), clipEnd, setClipEnd, {
+                    min: 0,
+                    step: 0.5,
+                    helperText: formatTime(clipEnd),
+                  })}
+                  <Tooltip title={t('ffmpegTools.clip.fromPosition')}>
+                    <span>
+                      <Button
+                        size="small"
+                        disabled={capturing}
+                        onClick={() => {
+                          setClipStart(Math.round(position * 10) / 10);
+                          setClipEnd(Math.round((position + Math.max(0.5, clipEnd - clipStart)) * 10) / 10);
+                        }}
+                        sx={{ textTransform: 'none', mt: 0.5, flexShrink: 0 }}
+                      >
+                        {formatTime(position)}
+                      </Button>
+                    </span>
+                  </Tooltip>
+                </Box>
+              ) : (
+                <Box sx={{ display: 'flex', gap: 1 }}>
+                  {numberField(t('ffmpegTools.clip.count'), clipCount, setClipCount, { min: 1, max: 50, integer: true })}
+                  {numberField(t('ffmpegTools.clip.length'), clipLength, setClipLength, { min: 0.1, step: 0.5 })}
+                </Box>
+              )}
+              <Divider />
+              {numberField(t('ffmpegTools.clip.fps'), clipFps, setClipFps, {
+                min: 1,
+                step: 1,
+                helperText: fps > 0 ? t('ffmpegTools.clip.fpsHint', { fps: fps.toFixed(3) }) : undefined,
+              })}
+              <Box sx={{ display: 'flex', gap: 1 }}>
+                {numberField(t('ffmpegTools.clip.maxWidth'), clipMaxWidth, setClipMaxWidth, { min: 0, integer: true })}
+                {numberField(t('ffmpegTools.clip.maxHeight'), clipMaxHeight, setClipMaxHeight, { min: 0, integer: true })}
+              </Box>
+              {numberField(t('ffmpegTools.clip.targetSize'), clipTargetKib, setClipTargetKib, {
+                min: 0,
+                integer: true,
+                helperText: t('ffmpegTools.clip.targetSizeHint'),
+              })}
+              <Typography variant="caption" color="text.secondary">
+                {t('ffmpegTools.clip.hint')}
+              </Typography>
+            </Stack>
+          )}
+          {tab === 2 &&
+            (audioCount === 0 ? (
+              <Typography variant="body2" color="text.secondary">
+                {t('ffmpegTools.audio.noAudio')}
+              </Typography>
+            ) : (
+              <Stack spacing={2}>
+                <TextField
+                  select
+                  label={t('ffmpegTools.audio.stream')}
+                  size="small"
+                  fullWidth
+                  value={Math.min(audioStream, audioCount - 1)}
+                  disabled={capturing}
+                  onChange={(e) => setAudioStream(Number(e.target.value))}
+                >
+                  {Array.from({ length: audioCount }, (_, num) => (
+                    <MenuItem key={num} value={num}>
+                      {t('ffmpegTools.audio.streamLabel', { num: num + 1 })}
+                    </MenuItem>
+                  ))}
+                </TextField>
+                <TextField
+                  select
+                  label={t('ffmpegTools.audio.preset')}
+                  size="small"
+                  fullWidth
+                  value={audioPresets.length > 0 ? Math.min(audioPresetIndex, audioPresets.length - 1) : ''}
+                  disabled={capturing || audioPresets.length === 0}
+                  onChange={(e) => setAudioPresetIndex(Number(e.target.value))}
+                  helperText={audioPresets.length === 0 ? t('ffmpegTools.audio.noPresets') : undefined}
+                >
+                  {audioPresets.map((preset, index) => (
+                    <MenuItem key={index} value={index}>
+                      {preset.name}
+                    </MenuItem>
+                  ))}
+                </TextField>
+                <TextField
+                  select
+                  label={t('ffmpegTools.audio.downmix')}
+                  size="small"
+                  fullWidth
+                  value={audioDownmix}
+                  disabled={capturing}
+                  onChange={(e) => setAudioDownmix(e.target.value as Protocol.AudioDownmix)}
+                >
+                  {Object.values(Protocol.AudioDownmix).map((d) => (
+                    <MenuItem key={d} value={d}>
+                      {t(`ffmpegTools.audio.downmixes.${d}`)}
+                    </MenuItem>
+                  ))}
+                </TextField>
+                <TextField
+                  select
+                  label={t('ffmpegTools.audio.normalize')}
+                  size="small"
+                  fullWidth
+                  value={audioNormalize}
+                  disabled={capturing}
+                  onChange={(e) => setAudioNormalize(e.target.value as Protocol.LoudnessTarget | '')}
+                  helperText={t('ffmpegTools.audio.normalizeHint')}
+                >
+                  <MenuItem value="">{t('ffmpegTools.audio.noNormalize')}</MenuItem>
+                  {Object.values(Protocol.LoudnessTarget).map((target) => (
+                    <MenuItem key={target} value={target}>
+                      {LOUDNESS_TARGET_LABELS[target]}
+                    </MenuItem>
+                  ))}
+                </TextField>
+                <Divider />
+                <Box sx={{ display: 'flex', alignItems: 'flex-start', gap: 1 }}>
+                  {numberField(t('ffmpegTools.clip.start'), audioStart, setAudioStart, {
+                    min: 0,
+                    step: 0.5,
+                    helperText: formatTime(audioStart),
+                  })}
+                  {numberField(t('ffmpegTools.clip.end'), audioEnd, setAudioEnd, {
+                    min: 0,
+                    step: 0.5,
+                    helperText: audioEnd > 0 ? formatTime(audioEnd) : t('ffmpegTools.audio.toEnd'),
+                  })}
+                  <Tooltip title={t('ffmpegTools.audio.fromPosition')}>
+                    <span>
+                      <Button
+                        size="small"
+                        disabled={capturing}
+                        onClick={() => setAudioStart(Math.round(position * 10) / 10)}
+                        sx={{ textTransform: 'none', mt: 0.5, flexShrink: 0 }}
+                      >
+                        {formatTime(position)}
+                      </Button>
+                    </span>
+                  </Tooltip>
+                </Box>
+                <Typography variant="caption" color="text.secondary">
+                  {t('ffmpegTools.audio.hint')}
+                </Typography>
+              </Stack>
+            ))}
         </Box>
 
         {/* Bottom: capture/cancel */}
@@ -1175,12 +1652,12 @@ function FfmpegTools({ file }: FfmpegToolsProps) {
             <Button
               variant="contained"
               fullWidth
-              startIcon={<PhotoCameraIcon />}
-              disabled={metaLoading || !!metaError}
-              onClick={handleCapture}
+              startIcon={tab === 2 ? <AudiotrackIcon /> : tab === 1 ? <GifBoxIcon /> : <PhotoCameraIcon />}
+              disabled={metaLoading || !!metaError || (tab === 2 && (audioCount === 0 || audioPresets.length === 0))}
+              onClick={tab === 2 ? handleExportAudio : tab === 1 ? handleCreateClip : handleCapture}
               sx={{ textTransform: 'none' }}
             >
-              {t('ffmpegTools.capture')}
+              {tab === 2 ? t('ffmpegTools.audio.export') : tab === 1 ? t('ffmpegTools.clip.create') : t('ffmpegTools.capture')}
             </Button>
           )}
         </Box>
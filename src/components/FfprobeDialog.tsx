@@ -0,0 +1,234 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useState } from 'react';
+import {
+  Box,
+  Button,
+  CircularProgress,
+  Dialog,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableHead,
+  TableRow,
+  ToggleButton,
+  ToggleButtonGroup,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+
+import * as Protocol from '../lib/protocol';
+import { compareWithFfprobe } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface FfprobeDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+}
+
+function describeProbeError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  const ffmpegMissing = message.match(/FFMPEG_NOT_AVAILABLE:(.*)$/);
+  if (ffmpegMissing) {
+    return t('ffprobe.ffmpegNotAvailable');
+  }
+  const ffprobeMissing = message.match(/FFPROBE_NOT_AVAILABLE:(.*)$/);
+  if (ffprobeMissing) {
+    return t('ffprobe.ffprobeNotAvailable', { path: ffprobeMissing[1] });
+  }
+  return message;
+}
+
+export default function FfprobeDialog({ open, onClose, file }: FfprobeDialogProps) {
+  const { t } = useTranslation();
+  const [frames, setFrames] = useState<Protocol.FfprobeFrames>(Protocol.FfprobeFrames.Sampled);
+  const [running, setRunning] = useState(false);
+  const [comparison, setComparison] = useState<Protocol.ProbeComparison | null>(null);
+  const setDialogJsonCode = useAppStore((state) => state.setDialogJsonCode);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  const mismatchCount = comparison?.fields.filter((field) => field.mismatch).length ?? 0;
+
+  const handleFramesChange = (_e: React.MouseEvent<HTMLElement>, value: Protocol.FfprobeFrames | null) => {
+    if (value !== null) {
+      setFrames(value);
+    }
+  };
+
+  const handleRun = async () => {
+    setRunning(true);
+    setComparison(null);
+    try {
+      setComparison(await compareWithFfprobe(file, frames));
+    } catch (error) {
+      setDialogNotification({
+        title: t('ffprobe.failed', { error: describeProbeError(t, error) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const handleJson = () => {
+    if (comparison) {
+      setDialogJsonCode({
+        title: `${file} (ffprobe)`,
+        jsonCode: comparison.ffprobe,
+      });
+    }
+  };
+
+  const headerSx = { fontWeight: 'bold', bgcolor: 'background.default' };
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '80vw',
+            height: '80vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box
+        sx={{
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        <Typography
+          variant="h6"
+          sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}
+        >
+          {file}
+        </Typography>
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        <ToggleButtonGroup
+          value={frames}
+          exclusive
+          onChange={handleFramesChange}
+          size="small"
+          disabled={running}
+        >
+          <ToggleButton value={Protocol.FfprobeFrames.None} sx={{ textTransform: 'none' }}>
+            {t('ffprobe.framesNone')}
+          </ToggleButton>
+          <ToggleButton value={Protocol.FfprobeFrames.Sampled} sx={{ textTransform: 'none' }}>
+            {t('ffprobe.framesSampled')}
+          </ToggleButton>
+          <ToggleButton value={Protocol.FfprobeFrames.All} sx={{ textTransform: 'none' }}>
+            {t('ffprobe.framesAll')}
+          </ToggleButton>
+        </ToggleButtonGroup>
+        <Button
+          variant="contained"
+          size="small"
+          onClick={handleRun}
+          disabled={running}
+          startIcon={running ? <CircularProgress size={14} color="inherit" /> : undefined}
+          sx={{ textTransform: 'none' }}
+        >
+          {t('ffprobe.run')}
+        </Button>
+        <Button
+          variant="contained"
+          size="small"
+          onClick={handleJson}
+          disabled={comparison === null}
+          sx={{ textTransform: 'none' }}
+        >
+          {t('details.json')}
+        </Button>
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {comparison ? (
+        <>
+          <Typography
+            variant="body2"
+            sx={{ px: 2, py: 1, flexShrink: 0, color: mismatchCount > 0 ? 'error.main' : 'success.main' }}
+          >
+            {mismatchCount > 0
+              ? t('ffprobe.mismatches', { count: mismatchCount })
+              : t('ffprobe.noMismatches')}
+          </Typography>
+          <TableContainer sx={{ flex: 1, minHeight: 0, overflow: 'auto' }}>
+            <Table size="small" stickyHeader>
+              <TableHead>
+                <TableRow>
+                  <TableCell sx={headerSx}>{t('ffprobe.stream')}</TableCell>
+                  <TableCell sx={headerSx}>{t('details.property')}</TableCell>
+                  <TableCell sx={headerSx}>MediaInfo</TableCell>
+                  <TableCell sx={headerSx}>ffprobe</TableCell>
+                </TableRow>
+              </TableHead>
+              <TableBody>
+                {comparison.fields.map((field) => (
+                  <TableRow
+                    key={`${field.stream}-${field.num}-${field.property}`}
+                    sx={field.mismatch ? { bgcolor: 'error.light', '& td': { color: 'error.contrastText' } } : undefined}
+                  >
+                    <TableCell>{`${field.stream} #${field.num + 1}`}</TableCell>
+                    <TableCell>{field.property}</TableCell>
+                    <TableCell>{field.mediaInfo ?? '-'}</TableCell>
+                    <TableCell>{field.ffprobe ?? '-'}</TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </TableContainer>
+        </>
+      ) : (
+        <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+          <Typography variant="body2" color="text.secondary">
+            {running ? t('ffprobe.running') : t('ffprobe.hint')}
+          </Typography>
+        </Box>
+      )}
+    </Dialog>
+  );
+}
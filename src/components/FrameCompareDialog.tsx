@@ -0,0 +1,317 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useState } from 'react';
+import {
+  Box,
+  Button,
+  Chip,
+  Dialog,
+  LinearProgress,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableHead,
+  TableRow,
+  TextField,
+  ToggleButton,
+  ToggleButtonGroup,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import { open as openDialog } from '@tauri-apps/plugin-dialog';
+
+import * as Protocol from '../lib/protocol';
+import { formatChapterTime } from '../lib/chapters';
+import { openVideoFileDialog } from '../lib/dialog';
+import { describeFfmpegError, isFfmpegAnalysisCancelled } from '../lib/ffmpegErrors';
+import { cancelFfmpegCapture, compareFrames } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface FrameCompareDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+}
+
+function describeFrameCompareError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/COMPARE_NO_VIDEO:/.test(message)) {
+    return t('frameCompare.noVideo');
+  }
+  if (/COMPARE_NO_FRAME_RATE/.test(message)) {
+    return t('frameCompare.noFrameRate');
+  }
+  if (/COMPARE_NO_POSITIONS/.test(message)) {
+    return t('frameCompare.noPositions');
+  }
+  return describeFfmpegError(t, error);
+}
+
+function parsePositions(input: string): number[] {
+  return input
+    .split(/[\s,]+/)
+    .filter((s) => s.length > 0)
+    .map((s) => parseFloat(s))
+    .filter((n) => isFinite(n) && n >= 0);
+}
+
+export default function FrameCompareDialog({ open, onClose, file }: FrameCompareDialogProps) {
+  const { t } = useTranslation();
+  const [reference, setReference] = useState('');
+  const [alignment, setAlignment] = useState<Protocol.FrameAlignment>(Protocol.FrameAlignment.Timestamp);
+  const [positions, setPositions] = useState('');
+  const [gain, setGain] = useState(10);
+  const [outputDir, setOutputDir] = useState('');
+  const [running, setRunning] = useState(false);
+  const [percent, setPercent] = useState(0);
+  const [comparison, setComparison] = useState<Protocol.FrameComparison | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  useEffect(() => {
+    setComparison(null);
+  }, [file, reference]);
+
+  useEffect(() => {
+    if (!running) return;
+    const unlisten = getCurrentWebviewWindow().listen<Protocol.FfmpegCaptureProgress>(
+      'ffmpeg-capture-progress',
+      (event) => {
+        if (!event.payload.done) {
+          setPercent(event.payload.percent);
+        }
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [running]);
+
+  const handleBrowseReference = async () => {
+    const selected = (await openVideoFileDialog(reference || undefined)) as string | null;
+    if (selected) setReference(selected);
+  };
+
+  const handleBrowseOutputDir = async () => {
+    const selected = (await openDialog({ directory: true, defaultPath: outputDir || undefined })) as string | null;
+    if (selected) setOutputDir(selected);
+  };
+
+  const handleRun = async () => {
+    setRunning(true);
+    setPercent(0);
+    setComparison(null);
+    try {
+      setComparison(
+        await compareFrames(reference, file, { alignment, positions: parsePositions(positions), outputDir, gain })
+      );
+    } catch (error) {
+      if (!isFfmpegAnalysisCancelled(error)) {
+        setDialogNotification({
+          title: t('frameCompare.failed', { error: describeFrameCompareError(t, error) }),
+          type: Protocol.DialogNotificationType.Error,
+        });
+      }
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const headerSx = { fontWeight: 'bold', bgcolor: 'background.default' };
+  const byFrame = alignment === Protocol.FrameAlignment.Frame;
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '70vw',
+            height: '85vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('frameCompare.title')}
+        </Typography>
+      </Box>
+      <Box sx={{ px: 2, py: 1, display: 'flex', flexDirection: 'column', gap: 1, flexShrink: 0 }}>
+        <Box sx={{ display: 'flex', alignItems: 'center', gap: 1 }}>
+          <TextField
+            label={t('quality.reference')}
+            size="small"
+            fullWidth
+            value={reference}
+            disabled={running}
+            onChange={(e) => setReference(e.target.value)}
+          />
+          <Button variant="outlined" size="small" onClick={handleBrowseReference} disabled={running} sx={{ textTransform: 'none', flexShrink: 0 }}>
+            {t('quality.browse')}
+          </Button>
+        </Box>
+        <TextField label={t('quality.distorted')} size="small" fullWidth value={file} disabled />
+        <Box sx={{ display: 'flex', alignItems: 'center', gap: 1 }}>
+          <TextField
+            label={t('frameCompare.outputDir')}
+            size="small"
+            fullWidth
+            value={outputDir}
+            disabled={running}
+            onChange={(e) => setOutputDir(e.target.value)}
+          />
+          <Button variant="outlined" size="small" onClick={handleBrowseOutputDir} disabled={running} sx={{ textTransform: 'none', flexShrink: 0 }}>
+            {t('quality.browse')}
+          </Button>
+        </Box>
+        <Box sx={{ display: 'flex', alignItems: 'flex-start', gap: 1 }}>
+          <ToggleButtonGroup
+            size="small"
+            exclusive
+            value={alignment}
+            disabled={running}
+            onChange={(_, value) => value && setAlignment(value)}
+            sx={{ flexShrink: 0, '& .MuiToggleButton-root': { textTransform: 'none' } }}
+          >
+            <ToggleButton value={Protocol.FrameAlignment.Timestamp}>{t('frameCompare.byTimestamp')}</ToggleButton>
+            <ToggleButton value={Protocol.FrameAlignment.Frame}>{t('frameCompare.byFrame')}</ToggleButton>
+          </ToggleButtonGroup>
+          <TextField
+            label={byFrame ? t('frameCompare.frames') : t('frameCompare.timestamps')}
+            size="small"
+            fullWidth
+            value={positions}
+            disabled={running}
+            placeholder={byFrame ? '240, 1200, 3600' : '10, 35, 62.5'}
+            helperText={t('frameCompare.positionsHint')}
+            onChange={(e) => setPositions(e.target.value)}
+          />
+          <TextField
+            label={t('frameCompare.gain')}
+            type="number"
+            size="small"
+            value={gain}
+            disabled={running}
+            onChange={(e) => {
+              const parsed = parseInt(e.target.value);
+              setGain(Number.isNaN(parsed) ? 1 : Math.min(100, Math.max(1, parsed)));
+            }}
+            slotProps={{ htmlInput: { min: 1, max: 100, step: 1 } }}
+            sx={{ width: 140, flexShrink: 0 }}
+          />
+        </Box>
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        {running ? (
+          <Button variant="contained" size="small" color="error" onClick={() => cancelFfmpegCapture()} sx={{ textTransform: 'none' }}>
+            {t('quality.cancel')}
+          </Button>
+        ) : (
+          <Button
+            variant="contained"
+            size="small"
+            onClick={handleRun}
+            disabled={reference === '' || outputDir === '' || parsePositions(positions).length === 0}
+            sx={{ textTransform: 'none' }}
+          >
+            {t('frameCompare.run')}
+          </Button>
+        )}
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {running && (
+        <Box sx={{ px: 2, py: 1, flexShrink: 0 }}>
+          <LinearProgress variant="determinate" value={percent} />
+          <Typography variant="caption" color="text.secondary">
+            {t('frameCompare.running', { percent })}
+          </Typography>
+        </Box>
+      )}
+      {comparison ? (
+        <Box sx={{ flex: 1, minHeight: 0, display: 'flex', flexDirection: 'column', gap: 1, p: 2 }}>
+          <Box sx={{ display: 'flex', flexWrap: 'wrap', gap: 1 }}>
+            <Chip
+              size="small"
+              label={t('frameCompare.comparedAt', { width: comparison.width, height: comparison.height })}
+            />
+            {comparison.scaled && <Chip size="small" color="warning" label={t('quality.scaled')} />}
+          </Box>
+          <TableContainer sx={{ flex: 1, minHeight: 0, overflow: 'auto' }}>
+            <Table size="small" stickyHeader>
+              <TableHead>
+                <TableRow>
+                  <TableCell sx={headerSx}>#</TableCell>
+                  <TableCell sx={headerSx}>{t('frameCompare.position')}</TableCell>
+                  <TableCell sx={headerSx}>{t('frameCompare.referenceTime')}</TableCell>
+                  <TableCell sx={headerSx}>{t('frameCompare.distortedTime')}</TableCell>
+                  <TableCell sx={headerSx}>PSNR</TableCell>
+                  <TableCell sx={headerSx}>{t('quality.images')}</TableCell>
+                </TableRow>
+              </TableHead>
+              <TableBody>
+                {comparison.pairs.map((pair, index) => (
+                  <TableRow key={index}>
+                    <TableCell>{index + 1}</TableCell>
+                    <TableCell>{pair.position}</TableCell>
+                    <TableCell>{formatChapterTime(pair.referenceTime, 3)}</TableCell>
+                    <TableCell>{formatChapterTime(pair.distortedTime, 3)}</TableCell>
+                    <TableCell>{`${pair.psnr.toFixed(2)} dB`}</TableCell>
+                    <TableCell sx={{ fontFamily: 'monospace', fontSize: 12, wordBreak: 'break-all', whiteSpace: 'pre-line' }}>
+                      {[pair.reference, pair.distorted, pair.sideBySide, pair.difference].join('\n')}
+                    </TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </TableContainer>
+        </Box>
+      ) : (
+        !running && (
+          <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+            <Typography variant="body2" color="text.secondary">
+              {t('frameCompare.hint')}
+            </Typography>
+          </Box>
+        )
+      )}
+    </Dialog>
+  );
+}
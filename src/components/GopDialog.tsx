@@ -0,0 +1,302 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useMemo, useState } from 'react';
+import {
+  Box,
+  Button,
+  CircularProgress,
+  Dialog,
+  Menu,
+  MenuItem,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableHead,
+  TableRow,
+  TextField,
+  ToggleButton,
+  ToggleButtonGroup,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+import { writeText } from '@tauri-apps/plugin-clipboard-manager';
+
+import * as Protocol from '../lib/protocol';
+import { formatChapterTime } from '../lib/chapters';
+import { openSaveTextFileDialog } from '../lib/dialog';
+import { describeFfmpegError } from '../lib/ffmpegErrors';
+import { basename } from '../lib/format';
+import {
+  KEYFRAME_EXPORT_FILES,
+  KeyframeExportFormat,
+  renderKeyframes,
+  splitKeyframes,
+  toMkvmergeSplit,
+} from '../lib/gop';
+import { analyzeGop, writeTextFile } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface GopDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+}
+
+function describeGopError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/GOP_NO_VIDEO:/.test(message)) {
+    return t('gop.noVideo');
+  }
+  return describeFfmpegError(t, error);
+}
+
+function formatStats(stats: Protocol.GopStats, digits: number, unit: string): string {
+  return `${stats.min.toFixed(digits)} / ${stats.max.toFixed(digits)} / ${stats.mean.toFixed(2)} ${unit}`;
+}
+
+export default function GopDialog({ open, onClose, file }: GopDialogProps) {
+  const { t } = useTranslation();
+  const [frames, setFrames] = useState<Protocol.FfprobeFrames>(Protocol.FfprobeFrames.Sampled);
+  const [running, setRunning] = useState(false);
+  const [report, setReport] = useState<Protocol.GopReport | null>(null);
+  const [splitSpacing, setSplitSpacing] = useState(0);
+  const [exportAnchor, setExportAnchor] = useState<HTMLElement | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  const splits = useMemo(() => (report ? splitKeyframes(report.keyframes, splitSpacing) : []), [report, splitSpacing]);
+
+  useEffect(() => {
+    setReport(null);
+  }, [file]);
+
+  const handleFramesChange = (_e: React.MouseEvent<HTMLElement>, value: Protocol.FfprobeFrames | null) => {
+    if (value !== null) {
+      setFrames(value);
+    }
+  };
+
+  const handleRun = async () => {
+    setRunning(true);
+    setReport(null);
+    try {
+      setReport(await analyzeGop(file, frames));
+    } catch (error) {
+      setDialogNotification({
+        title: t('gop.failed', { error: describeGopError(t, error) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const handleExport = async (format: KeyframeExportFormat) => {
+    setExportAnchor(null);
+    if (!report) return;
+    const { extension, filter } = KEYFRAME_EXPORT_FILES[format];
+    const output = (await openSaveTextFileDialog(
+      filter,
+      extension,
+      `${basename(file)}.keyframes.${extension}`
+    )) as string | null;
+    if (!output) return;
+    try {
+      await writeTextFile(output, renderKeyframes(report.keyframes, format));
+      setDialogNotification({
+        title: t('gop.exported', { filePath: output }),
+        type: Protocol.DialogNotificationType.Info,
+      });
+    } catch (error) {
+      setDialogNotification({
+        title: t('gop.failed', { error: String(error) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    }
+  };
+
+  const handleCopySplit = async () => {
+    await writeText(`--split ${toMkvmergeSplit(splits)}`);
+    setDialogNotification({
+      title: t('gop.splitCopied', { count: splits.length }),
+      type: Protocol.DialogNotificationType.Info,
+    });
+  };
+
+  const summaryRows: Array<[string, string]> = report
+    ? [
+        [t('gop.keyframes'), String(report.keyframes.length)],
+        [t('gop.lengthFrames'), formatStats(report.lengthFrames, 0, t('gop.frames'))],
+        [t('gop.lengthSeconds'), formatStats(report.lengthSeconds, 3, 's')],
+        [t('gop.openClosed'), `${report.openGops} / ${report.closedGops}`],
+        ...(report.frameTypes
+          ? ([
+              [t('gop.frameTypes'), `I ${report.frameTypes.iFrames} / P ${report.frameTypes.pFrames} / B ${report.frameTypes.bFrames}`],
+              [t('gop.idr'), `${report.frameTypes.idrFrames} / ${report.frameTypes.nonIdrIFrames}`],
+              [t('gop.maxConsecutiveB'), String(report.frameTypes.maxConsecutiveB)],
+              [t('gop.pattern'), report.frameTypes.pattern ?? '-'],
+            ] as Array<[string, string]>)
+          : []),
+      ]
+    : [];
+
+  const headerSx = { fontWeight: 'bold', bgcolor: 'background.default' };
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '60vw',
+            height: '80vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('gop.title')}
+        </Typography>
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        <ToggleButtonGroup size="small" exclusive value={frames} onChange={handleFramesChange} disabled={running}>
+          <ToggleButton value={Protocol.FfprobeFrames.None} sx={{ textTransform: 'none' }}>
+            {t('gop.framesNone')}
+          </ToggleButton>
+          <ToggleButton value={Protocol.FfprobeFrames.Sampled} sx={{ textTransform: 'none' }}>
+            {t('ffprobe.framesSampled')}
+          </ToggleButton>
+          <ToggleButton value={Protocol.FfprobeFrames.All} sx={{ textTransform: 'none' }}>
+            {t('ffprobe.framesAll')}
+          </ToggleButton>
+        </ToggleButtonGroup>
+        <Button
+          variant="contained"
+          size="small"
+          onClick={handleRun}
+          disabled={running}
+          startIcon={running ? <CircularProgress size={14} /> : undefined}
+          sx={{ textTransform: 'none' }}
+        >
+          {running ? t('gop.running') : t('gop.run')}
+        </Button>
+        <Button
+          variant="contained"
+          size="small"
+          onClick={(e) => setExportAnchor(e.currentTarget)}
+          disabled={running || report === null}
+          sx={{ textTransform: 'none' }}
+        >
+          {t('gop.export')}
+        </Button>
+        <Menu anchorEl={exportAnchor} open={exportAnchor !== null} onClose={() => setExportAnchor(null)}>
+          <MenuItem onClick={() => handleExport(KeyframeExportFormat.Csv)}>CSV</MenuItem>
+          <MenuItem onClick={() => handleExport(KeyframeExportFormat.Text)}>{t('gop.textList')}</MenuItem>
+        </Menu>
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {report ? (
+        <Box sx={{ flex: 1, minHeight: 0, display: 'flex', gap: 2, p: 2 }}>
+          <TableContainer sx={{ flex: 3, overflow: 'auto' }}>
+            <Table size="small">
+              <TableBody>
+                {summaryRows.map(([label, value]) => (
+                  <TableRow key={label}>
+                    <TableCell sx={{ fontWeight: 'bold', whiteSpace: 'nowrap' }}>{label}</TableCell>
+                    <TableCell sx={{ fontFamily: 'monospace', wordBreak: 'break-all' }}>{value}</TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+            <Box sx={{ display: 'flex', alignItems: 'center', gap: 1, mt: 2 }}>
+              <TextField
+                label={t('gop.splitSpacing')}
+                type="number"
+                size="small"
+                value={splitSpacing}
+                onChange={(e) => {
+                  const parsed = parseFloat(e.target.value);
+                  if (!Number.isNaN(parsed)) setSplitSpacing(Math.max(0, parsed));
+                }}
+                slotProps={{ htmlInput: { step: 10, min: 0 } }}
+                sx={{ width: 200 }}
+              />
+              <Button
+                variant="outlined"
+                size="small"
+                onClick={handleCopySplit}
+                disabled={splits.length === 0}
+                sx={{ textTransform: 'none' }}
+              >
+                {t('gop.copySplit', { count: splits.length })}
+              </Button>
+            </Box>
+          </TableContainer>
+          <TableContainer sx={{ flex: 2, overflow: 'auto' }}>
+            <Table size="small" stickyHeader>
+              <TableHead>
+                <TableRow>
+                  <TableCell sx={headerSx}>#</TableCell>
+                  <TableCell sx={headerSx}>{t('gop.keyframe')}</TableCell>
+                </TableRow>
+              </TableHead>
+              <TableBody>
+                {report.keyframes.map((keyframe, index) => (
+                  <TableRow key={keyframe}>
+                    <TableCell>{index + 1}</TableCell>
+                    <TableCell>{formatChapterTime(keyframe, 3)}</TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </TableContainer>
+        </Box>
+      ) : (
+        !running && (
+          <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+            <Typography variant="body2" color="text.secondary">
+              {t('gop.hint')}
+            </Typography>
+          </Box>
+        )
+      )}
+    </Dialog>
+  );
+}
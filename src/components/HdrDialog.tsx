@@ -0,0 +1,350 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useState } from 'react';
+import {
+  Box,
+  Button,
+  Chip,
+  CircularProgress,
+  Dialog,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableHead,
+  TableRow,
+  ToggleButton,
+  ToggleButtonGroup,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+
+import * as Protocol from '../lib/protocol';
+import { formatChapterTime } from '../lib/chapters';
+import { HDR_TRANSFER_LABELS, dolbyVisionProfile, formatChromaticity, formatNits, nitsToPq } from '../lib/hdr';
+import { analyzeHdr } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface HdrDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+}
+
+const CHART_WIDTH = 1000;
+const CHART_HEIGHT = 140;
+const CHART_TICKS = [1, 10, 100, 1000, 4000, 10000];
+
+function describeHdrError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/HDR_NO_VIDEO:/.test(message)) {
+    return t('hdr.noVideo');
+  }
+  const ffprobeMissing = message.match(/FFPROBE_NOT_AVAILABLE:(.*)$/);
+  if (ffprobeMissing) {
+    return t('ffprobe.ffprobeNotAvailable', { path: ffprobeMissing[1] });
+  }
+  return message;
+}
+
+function LuminanceChart({ luminance }: { luminance: Protocol.HdrLuminance }) {
+  const first = luminance.scenes[0]?.start ?? 0;
+  const last = luminance.scenes[luminance.scenes.length - 1]?.end ?? 0;
+  const span = Math.max(1e-9, last - first);
+  const x = (time: number) => ((time - first) / span) * CHART_WIDTH;
+  const y = (nits: number) => CHART_HEIGHT * (1 - nitsToPq(nits));
+  const steps = (value: (scene: Protocol.HdrScene) => number) =>
+    luminance.scenes
+      .map(
+        (scene, index) =>
+          `${index === 0 ? 'M' : 'L'}${x(scene.start).toFixed(1)},${y(value(scene)).toFixed(1)} L${x(scene.end).toFixed(1)},${y(value(scene)).toFixed(1)}`
+      )
+      .join(' ');
+
+  return (
+    <Box sx={{ display: 'flex', gap: 1, mb: 1 }}>
+      <Box sx={{ position: 'relative', width: 40, height: CHART_HEIGHT, flexShrink: 0 }}>
+        {CHART_TICKS.map((tick) => (
+          <Typography
+            key={tick}
+            variant="caption"
+            sx={{ position: 'absolute', right: 0, top: y(tick) - 8, fontFamily: 'monospace', fontSize: 10 }}
+          >
+            {tick}
+          </Typography>
+        ))}
+      </Box>
+      <Box sx={{ flex: 1, border: 1, borderColor: 'divider' }}>
+        <svg
+          viewBox={`0 0 ${CHART_WIDTH} ${CHART_HEIGHT}`}
+          preserveAspectRatio="none"
+          style={{ display: 'block', width: '100%', height: CHART_HEIGHT }}
+        >
+          {CHART_TICKS.map((tick) => (
+            <line key={tick} x1={0} x2={CHART_WIDTH} y1={y(tick)} y2={y(tick)} stroke="currentColor" strokeOpacity={0.15} vectorEffect="non-scaling-stroke" />
+          ))}
+          <path d={steps((scene) => scene.maxNits)} fill="none" stroke="#f59e0b" strokeWidth={1.5} vectorEffect="non-scaling-stroke" />
+          <path d={steps((scene) => scene.avgNits)} fill="none" stroke="#0ea5e9" strokeWidth={1.5} vectorEffect="non-scaling-stroke" />
+        </svg>
+      </Box>
+    </Box>
+  );
+}
+
+function HdrStreamReport({ t, stream }: { t: TFunction; stream: Protocol.HdrStream }) {
+  const display = stream.masteringDisplay;
+  const dolbyVision = stream.dolbyVision;
+  const luminance = stream.luminance;
+  const headerSx = { fontWeight: 'bold', bgcolor: 'background.default' };
+  const rows: Array<[string, string]> = [
+    [t('hdr.hdrFormat'), stream.hdrFormat ?? '-'],
+    [t('hdr.compatibility'), stream.compatibility ?? '-'],
+    [t('hdr.transfer'), stream.transferName ?? '-'],
+    [t('hdr.colorPrimaries'), stream.colorPrimaries ?? '-'],
+    [t('hdr.matrixCoefficients'), stream.matrixCoefficients ?? '-'],
+    [t('hdr.bitDepth'), stream.bitDepth === null ? '-' : String(stream.bitDepth)],
+    ...(display
+      ? ([
+          [t('hdr.masteringPrimaries'), display.primaries ?? '-'],
+          [
+            t('hdr.masteringCoordinates'),
+            `R ${formatChromaticity(display.red)}\nG ${formatChromaticity(display.green)}\nB ${formatChromaticity(display.blue)}\nW ${formatChromaticity(display.whitePoint)}`,
+          ],
+          [
+            t('hdr.masteringLuminance'),
+            `${formatNits(display.minLuminance)} - ${formatNits(display.maxLuminance)} ${t('hdr.nits')}`,
+          ],
+        ] as Array<[string, string]>)
+      : []),
+    [t('hdr.maxCll'), stream.maxCll === null ? '-' : `${formatNits(stream.maxCll)} ${t('hdr.nits')}`],
+    [t('hdr.maxFall'), stream.maxFall === null ? '-' : `${formatNits(stream.maxFall)} ${t('hdr.nits')}`],
+    ...(dolbyVision
+      ? ([
+          [
+            t('hdr.dolbyVision'),
+            t('hdr.dolbyVisionValue', {
+              profile: dolbyVisionProfile(dolbyVision),
+              level: dolbyVision.level ?? '?',
+            }),
+          ],
+          [
+            t('hdr.layers'),
+            `BL${dolbyVision.enhancementLayer ? '+EL' : ''}${dolbyVision.rpu ? '+RPU' : ''}`,
+          ],
+          [t('hdr.rpuFrames'), String(dolbyVision.rpuFrames)],
+        ] as Array<[string, string]>)
+      : []),
+    [t('hdr.hdr10PlusFrames'), String(stream.hdr10PlusFrames)],
+    [t('hdr.decodedFrames'), String(stream.decodedFrames)],
+  ];
+
+  return (
+    <Box sx={{ mb: 3 }}>
+      <Box sx={{ display: 'flex', alignItems: 'center', gap: 1, mb: 1 }}>
+        <Typography variant="subtitle1" sx={{ fontWeight: 'bold' }}>
+          {`Video #${stream.num + 1}`}
+        </Typography>
+        <Chip
+          size="small"
+          label={HDR_TRANSFER_LABELS[stream.transfer]}
+          color={stream.transfer === Protocol.HdrTransfer.Sdr ? 'default' : 'primary'}
+        />
+        {dolbyVision && <Chip size="small" color="secondary" label={`Dolby Vision ${dolbyVisionProfile(dolbyVision)}`} />}
+        {stream.hdr10Plus && <Chip size="small" color="secondary" label="HDR10+" />}
+        {stream.transfer === Protocol.HdrTransfer.Pq && display && <Chip size="small" label="HDR10" />}
+      </Box>
+      <TableContainer>
+        <Table size="small">
+          <TableBody>
+            {rows.map(([label, value]) => (
+              <TableRow key={label}>
+                <TableCell sx={{ fontWeight: 'bold', whiteSpace: 'nowrap', width: 240 }}>{label}</TableCell>
+                <TableCell sx={{ fontFamily: 'monospace', wordBreak: 'break-all', whiteSpace: 'pre-line' }}>{value}</TableCell>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+      </TableContainer>
+      {luminance ? (
+        <Box sx={{ mt: 2 }}>
+          <Typography variant="subtitle2" sx={{ fontWeight: 'bold' }}>
+            {t('hdr.luminance', {
+              source: luminance.source,
+              max: formatNits(luminance.maxNits),
+              avg: formatNits(luminance.avgNits),
+            })}
+          </Typography>
+          <Typography variant="caption" color="text.secondary" sx={{ display: 'block', mb: 1 }}>
+            {t('hdr.legend')}
+          </Typography>
+          <LuminanceChart luminance={luminance} />
+          {luminance.truncated && (
+            <Typography variant="caption" color="warning.main" sx={{ display: 'block', mb: 1 }}>
+              {t('hdr.truncated', { count: luminance.scenes.length })}
+            </Typography>
+          )}
+          <TableContainer sx={{ maxHeight: 320 }}>
+            <Table size="small" stickyHeader>
+              <TableHead>
+                <TableRow>
+                  <TableCell sx={headerSx}>#</TableCell>
+                  <TableCell sx={headerSx}>{t('hdr.start')}</TableCell>
+                  <TableCell sx={headerSx}>{t('hdr.end')}</TableCell>
+                  <TableCell sx={headerSx}>{t('hdr.frames')}</TableCell>
+                  <TableCell sx={headerSx}>{t('hdr.maxNits')}</TableCell>
+                  <TableCell sx={headerSx}>{t('hdr.avgNits')}</TableCell>
+                </TableRow>
+              </TableHead>
+              <TableBody>
+                {luminance.scenes.map((scene, index) => (
+                  <TableRow key={`${scene.start}-${index}`}>
+                    <TableCell>{index + 1}</TableCell>
+                    <TableCell>{formatChapterTime(scene.start, 3)}</TableCell>
+                    <TableCell>{formatChapterTime(scene.end, 3)}</TableCell>
+                    <TableCell>{scene.frames}</TableCell>
+                    <TableCell>{formatNits(scene.maxNits)}</TableCell>
+                    <TableCell>{formatNits(scene.avgNits)}</TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </TableContainer>
+        </Box>
+      ) : (
+        stream.decodedFrames > 0 && (
+          <Typography variant="caption" color="text.secondary" sx={{ display: 'block', mt: 1 }}>
+            {t('hdr.noLuminance')}
+          </Typography>
+        )
+      )}
+    </Box>
+  );
+}
+
+export default function HdrDialog({ open, onClose, file }: HdrDialogProps) {
+  const { t } = useTranslation();
+  const [frames, setFrames] = useState<Protocol.FfprobeFrames>(Protocol.FfprobeFrames.Sampled);
+  const [running, setRunning] = useState(false);
+  const [report, setReport] = useState<Protocol.HdrReport | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  useEffect(() => {
+    setReport(null);
+  }, [file]);
+
+  const handleFramesChange = (_e: React.MouseEvent<HTMLElement>, value: Protocol.FfprobeFrames | null) => {
+    if (value !== null) {
+      setFrames(value);
+    }
+  };
+
+  const handleRun = async () => {
+    setRunning(true);
+    setReport(null);
+    try {
+      setReport(await analyzeHdr(file, frames));
+    } catch (error) {
+      setDialogNotification({
+        title: t('hdr.failed', { error: describeHdrError(t, error) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '60vw',
+            height: '85vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('hdr.title')}
+        </Typography>
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        <ToggleButtonGroup size="small" exclusive value={frames} onChange={handleFramesChange} disabled={running}>
+          <ToggleButton value={Protocol.FfprobeFrames.None} sx={{ textTransform: 'none' }}>
+            {t('ffprobe.framesNone')}
+          </ToggleButton>
+          <ToggleButton value={Protocol.FfprobeFrames.Sampled} sx={{ textTransform: 'none' }}>
+            {t('ffprobe.framesSampled')}
+          </ToggleButton>
+          <ToggleButton value={Protocol.FfprobeFrames.All} sx={{ textTransform: 'none' }}>
+            {t('ffprobe.framesAll')}
+          </ToggleButton>
+        </ToggleButtonGroup>
+        <Button
+          variant="contained"
+          size="small"
+          onClick={handleRun}
+          disabled={running}
+          startIcon={running ? <CircularProgress size={14} /> : undefined}
+          sx={{ textTransform: 'none' }}
+        >
+          {running ? t('hdr.running') : t('hdr.run')}
+        </Button>
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {report ? (
+        <Box sx={{ flex: 1, minHeight: 0, overflow: 'auto', p: 2 }}>
+          {report.streams.map((stream) => (
+            <HdrStreamReport key={stream.num} t={t} stream={stream} />
+          ))}
+        </Box>
+      ) : (
+        !running && (
+          <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+            <Typography variant="body2" color="text.secondary">
+              {t('hdr.hint')}
+            </Typography>
+          </Box>
+        )
+      )}
+    </Dialog>
+  );
+}
@@ -0,0 +1,252 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useState } from 'react';
+import {
+  Box,
+  Button,
+  Chip,
+  Dialog,
+  LinearProgress,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableHead,
+  TableRow,
+  ToggleButton,
+  ToggleButtonGroup,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+
+import * as Protocol from '../lib/protocol';
+import { describeFfmpegError, isFfmpegAnalysisCancelled } from '../lib/ffmpegErrors';
+import { LOUDNESS_TARGET_LABELS, formatLoudness } from '../lib/loudness';
+import { cancelFfmpegAnalysis, runLoudnessAnalysis } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface LoudnessDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+  onAttach: (report: Protocol.LoudnessReport, target: Protocol.LoudnessTarget) => void;
+}
+
+function describeLoudnessError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/LOUDNESS_NO_AUDIO:/.test(message)) {
+    return t('loudness.noAudio');
+  }
+  return describeFfmpegError(t, error);
+}
+
+export default function LoudnessDialog({ open, onClose, file, onAttach }: LoudnessDialogProps) {
+  const { t } = useTranslation();
+  const [target, setTarget] = useState<Protocol.LoudnessTarget>(Protocol.LoudnessTarget.EbuR128);
+  const [running, setRunning] = useState(false);
+  const [percent, setPercent] = useState(0);
+  const [report, setReport] = useState<Protocol.LoudnessReport | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  useEffect(() => {
+    setReport(null);
+  }, [file]);
+
+  useEffect(() => {
+    if (!running) return;
+    const unlisten = getCurrentWebviewWindow().listen<Protocol.FfmpegAnalysisProgress>(
+      'ffmpeg-analysis-progress',
+      (event) => {
+        if (event.payload.analysis === 'loudness') {
+          setPercent(event.payload.percent);
+        }
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [running]);
+
+  const handleRun = async () => {
+    setRunning(true);
+    setPercent(0);
+    setReport(null);
+    try {
+      setReport(await runLoudnessAnalysis(file));
+    } catch (error) {
+      if (!isFfmpegAnalysisCancelled(error)) {
+        setDialogNotification({
+          title: t('loudness.failed', { error: describeLoudnessError(t, error) }),
+          type: Protocol.DialogNotificationType.Error,
+        });
+      }
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const handleAttach = () => {
+    if (!report) return;
+    onAttach(report, target);
+    setDialogNotification({
+      title: t('loudness.attached'),
+      type: Protocol.DialogNotificationType.Info,
+    });
+  };
+
+  const headerSx = { fontWeight: 'bold', bgcolor: 'background.default' };
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '70vw',
+            height: '60vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('loudness.title')}
+        </Typography>
+      </Box>
+      <Box sx={{ px: 2, py: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', gap: 1, flexShrink: 0 }}>
+        <Typography variant="body2">{t('loudness.target')}</Typography>
+        <ToggleButtonGroup
+          size="small"
+          exclusive
+          value={target}
+          onChange={(_, value) => value && setTarget(value)}
+        >
+          {Object.values(Protocol.LoudnessTarget).map((value) => (
+            <ToggleButton key={value} value={value} sx={{ textTransform: 'none' }}>
+              {LOUDNESS_TARGET_LABELS[value]}
+            </ToggleButton>
+          ))}
+        </ToggleButtonGroup>
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        {running ? (
+          <Button variant="contained" size="small" color="error" onClick={() => cancelFfmpegAnalysis()} sx={{ textTransform: 'none' }}>
+            {t('loudness.cancel')}
+          </Button>
+        ) : (
+          <Button variant="contained" size="small" onClick={handleRun} sx={{ textTransform: 'none' }}>
+            {t('loudness.run')}
+          </Button>
+        )}
+        <Button
+          variant="contained"
+          size="small"
+          onClick={handleAttach}
+          disabled={running || report === null}
+          sx={{ textTransform: 'none' }}
+        >
+          {t('loudness.attach')}
+        </Button>
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {running && (
+        <Box sx={{ px: 2, py: 1, flexShrink: 0 }}>
+          <LinearProgress variant="determinate" value={percent} />
+          <Typography variant="caption" color="text.secondary">
+            {t('loudness.running', { percent })}
+          </Typography>
+        </Box>
+      )}
+      {report ? (
+        <TableContainer sx={{ flex: 1, minHeight: 0, overflow: 'auto' }}>
+          <Table size="small" stickyHeader>
+            <TableHead>
+              <TableRow>
+                <TableCell sx={headerSx}>{t('loudness.stream')}</TableCell>
+                <TableCell sx={headerSx}>{t('loudness.integrated')}</TableCell>
+                <TableCell sx={headerSx}>{t('loudness.range')}</TableCell>
+                <TableCell sx={headerSx}>{t('loudness.truePeak')}</TableCell>
+                <TableCell sx={headerSx}>{t('loudness.momentaryMax')}</TableCell>
+                <TableCell sx={headerSx}>{t('loudness.shortTermMax')}</TableCell>
+                <TableCell sx={headerSx}>{t('loudness.result')}</TableCell>
+              </TableRow>
+            </TableHead>
+            <TableBody>
+              {report.streams.map((measurement) => {
+                const check = measurement.checks.find((c) => c.target === target);
+                return (
+                  <TableRow key={measurement.num}>
+                    <TableCell>{`Audio #${measurement.num + 1}`}</TableCell>
+                    <TableCell sx={{ color: check && !check.integratedOk ? 'error.main' : undefined }}>
+                      {formatLoudness(measurement.integrated, 'LUFS')}
+                    </TableCell>
+                    <TableCell>{formatLoudness(measurement.lra, 'LU')}</TableCell>
+                    <TableCell sx={{ color: check && !check.truePeakOk ? 'error.main' : undefined }}>
+                      {formatLoudness(measurement.truePeak, 'dBTP')}
+                    </TableCell>
+                    <TableCell>{formatLoudness(measurement.momentaryMax, 'LUFS')}</TableCell>
+                    <TableCell>{formatLoudness(measurement.shortTermMax, 'LUFS')}</TableCell>
+                    <TableCell>
+                      {check && (
+                        <Chip
+                          size="small"
+                          label={check.passed ? t('loudness.pass') : t('loudness.fail')}
+                          color={check.passed ? 'success' : 'error'}
+                        />
+                      )}
+                    </TableCell>
+                  </TableRow>
+                );
+              })}
+            </TableBody>
+          </Table>
+        </TableContainer>
+      ) : (
+        !running && (
+          <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+            <Typography variant="body2" color="text.secondary">
+              {t('loudness.hint')}
+            </Typography>
+          </Box>
+        )
+      )}
+    </Dialog>
+  );
+}
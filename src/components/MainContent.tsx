@@ -35,6 +35,7 @@ import {
 import CloseIcon from '@mui/icons-material/Close';
 import { useTranslation } from 'react-i18next';
 import { getCurrentWindow, type DragDropEvent } from '@tauri-apps/api/window';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 import type { Event, UnlistenFn } from '@tauri-apps/api/event';
 import { writeText } from '@tauri-apps/plugin-clipboard-manager';
 import { openUrl } from '@tauri-apps/plugin-opener';
@@ -250,9 +251,35 @@ export default function MainContent() {
     };
   }, []);
 
-  // File drop handling
+  // Report each file a watch folder analyzed
+  useEffect(() => {
+    const unlisten = getCurrentWebviewWindow().listen<Protocol.WatchFolderReportEvent>(
+      'watch-folder-report',
+      (event) => {
+        const { file, report, passed, error } = event.payload;
+        if (error) {
+          setDialogNotification({
+            title: t('watch.failed', { file, error }),
+            type: Protocol.DialogNotificationType.Error,
+          });
+        } else {
+          setDialogNotification({
+            title: passed ? t('watch.analyzed', { file, report }) : t('watch.validationFailed', { file, report }),
+            type: passed ? Protocol.DialogNotificationType.Info : Protocol.DialogNotificationType.Error,
+          });
+        }
+      },
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [t, setDialogNotification]);
+
+  // File drop handling, also used for the files a second launch of the app
+  // forwards to this instance
   useEffect(() => {
     let cancelFileDrop: UnlistenFn | null = null;
+    let cancelFilesOpened: UnlistenFn | null = null;
 
     const handleDrop = async (paths: string[]) => {
       // Split out Blu-ray folders so we can prompt to open them in BDMaster
@@ -309,10 +336,21 @@ export default function MainContent() {
         cancelFileDrop = value;
       });
 
+    getCurrentWebviewWindow()
+      .listen<Protocol.FilesOpened>('files-opened', (event) => {
+        handleDrop(event.payload.files);
+      })
+      .then((value) => {
+        cancelFilesOpened = value;
+      });
+
     return () => {
       if (cancelFileDrop) {
         cancelFileDrop();
       }
+      if (cancelFilesOpened) {
+        cancelFilesOpened();
+      }
     };
   }, [appendOnFileDrop]);
 
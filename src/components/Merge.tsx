@@ -33,6 +33,8 @@ import {
   Box,
   Button,
   Checkbox,
+  Chip,
+  CircularProgress,
   Dialog,
   DialogActions,
   DialogContent,
@@ -52,9 +54,11 @@ import {
   Tooltip,
   Typography,
 } from '@mui/material';
+import BookmarksIcon from '@mui/icons-material/Bookmarks';
 import ClearIcon from '@mui/icons-material/Clear';
 import CloseIcon from '@mui/icons-material/Close';
 import ContentCopyIcon from '@mui/icons-material/ContentCopy';
+import CropIcon from '@mui/icons-material/Crop';
 import FlagIcon from '@mui/icons-material/Flag';
 import RestartAltIcon from '@mui/icons-material/RestartAlt';
 import TransformIcon from '@mui/icons-material/Transform';
@@ -97,11 +101,14 @@ import {
 } from '../lib/merge';
 import {
   cancelMkvmerge,
+  detectCrop,
   getConfig,
   getPropertiesMap,
   runMkvmerge,
   suggestMergeOutputPath,
 } from '../lib/service';
+import { useAppStore } from '../lib/store';
+import SceneChaptersDialog from './SceneChaptersDialog';
 
 const IS_WINDOWS = typeof navigator !== 'undefined' && /windows/i.test(navigator.userAgent);
 const LANGUAGE_OPTION_ROW_HEIGHT = 34;
@@ -706,6 +713,7 @@ interface TrackArgInput {
   language: string;
   isDefault: boolean;
   isForced: boolean;
+  cropping?: string;
 }
 
 /**
@@ -756,6 +764,9 @@ function buildMergeArgs(
   // Global options
   args.push('-o', mergeData.destinationFile);
   args.push('--title', mergeData.general.title);
+  if (mergeData.general.chaptersFile) {
+    args.push('--chapters', mergeData.general.chaptersFile);
+  }
   if (priority) {
     args.push('--priority', priority.toLowerCase());
   }
@@ -783,6 +794,9 @@ function buildMergeArgs(
       }
       args.push('--default-track-flag', `${tid}:${track.isDefault ? 1 : 0}`);
       args.push('--forced-display-flag', `${tid}:${track.isForced ? 1 : 0}`);
+      if (track.cropping) {
+        args.push('--cropping', `${tid}:${track.cropping}`);
+      }
     }
     return enabled.map((e) => e.tid);
   };
@@ -791,8 +805,9 @@ function buildMergeArgs(
   const audioTids = emitTrackType(Protocol.StreamKind.Audio, mergeData.audios, '-a', '--no-audio');
   const textTids = emitTrackType(Protocol.StreamKind.Text, mergeData.texts, '-s', '--no-subtitles');
 
-  // Chapters (menus): all-or-nothing. Always emit --no-chapters when none enabled.
-  if (!mergeData.menus.some((m) => m.isEnabled)) {
+  // Chapters (menus): all-or-nothing. Always emit --no-chapters when none enabled,
+  // or when a chapter file replaces them.
+  if (mergeData.general.chaptersFile || !mergeData.menus.some((m) => m.isEnabled)) {
     args.push('--no-chapters');
   }
 
@@ -1093,6 +1108,9 @@ function Merge({ file, mkvToolNixPath }: MergeProps) {
   const [closeWhenDone, setCloseWhenDone] = useState(false);
   const closeWhenDoneRef = useRef(false);
   const [completion, setCompletion] = useState<{ type: 'success' | 'error'; message: string } | null>(null);
+  const [cropDetecting, setCropDetecting] = useState(false);
+  const [sceneChaptersOpen, setSceneChaptersOpen] = useState(false);
+  const setNotification = useAppStore((state) => state.setDialogNotification);
   const startTimeRef = useRef(0);
   const timerRef = useRef<ReturnType<typeof setInterval> | undefined>(undefined);
 
@@ -1218,6 +1236,33 @@ function Merge({ file, mkvToolNixPath }: MergeProps) {
     if (!canResetForcedStreams) { return; }
     setMergeData((prev) => resetForcedTracks(prev));
   }, [canResetForcedStreams]);
+  // Crop detection samples the first video stream, so its result goes to that track.
+  const firstVideo = mergeData.videos.find((video) => video.num === 0);
+  const handleDetectCrop = useCallback(async () => {
+    setCropDetecting(true);
+    try {
+      const detection = await detectCrop(file);
+      setMergeData((prev) => prev.withVideoCropping(0, detection.mkvmergeCropping));
+      setNotification({
+        title: t('crop.detected', {
+          crop: detection.mkvmergeCropping,
+          aspectRatio: detection.aspectRatio.toFixed(3),
+          agreement: Math.round(detection.agreement * 100),
+        }),
+        type: Protocol.DialogNotificationType.Info,
+      });
+    } catch (error) {
+      const message = String(error);
+      const detail = /CROP_DETECT_TOO_DARK:/.test(message)
+        ? t('crop.tooDark')
+        : /CROP_DETECT_NO_VIDEO:/.test(message)
+          ? t('crop.noVideo')
+          : message;
+      setNotification({ title: t('crop.failed', { error: detail }), type: Protocol.DialogNotificationType.Error });
+    } finally {
+      setCropDetecting(false);
+    }
+  }, [file, setNotification, t]);
 
   // Keep the destination file extension in sync with what mkvmerge can
   // actually produce: .mkv (has video), .mka (audio-only), .mks (text-only).
@@ -1530,6 +1575,52 @@ function Merge({ file, mkvToolNixPath }: MergeProps) {
               </IconButton>
             </span>
           </Tooltip>
+          <Tooltip title={t('crop.detect')}>
+            <span>
+              <IconButton
+                size="small"
+                disabled={!firstVideo || merging || cropDetecting}
+                aria-label={t('crop.detect')}
+                onClick={handleDetectCrop}
+                sx={{ p: 0.5 }}
+              >
+                {cropDetecting ? <CircularProgress size={16} /> : <CropIcon fontSize="small" />}
+              </IconButton>
+            </span>
+          </Tooltip>
+          {firstVideo?.cropping && (
+            <Chip
+              size="small"
+              label={t('merge.cropping', { cropping: firstVideo.cropping })}
+              disabled={merging}
+              onDelete={() => setMergeData((prev) => prev.withVideoCropping(0, ''))}
+              sx={{ ml: 0.5 }}
+            />
+          )}
+          <Tooltip title={t('scenes.detect')}>
+            <span>
+              <IconButton
+                size="small"
+                disabled={!firstVideo || merging}
+                aria-label={t('scenes.detect')}
+                onClick={() => setSceneChaptersOpen(true)}
+                sx={{ p: 0.5 }}
+              >
+                <BookmarksIcon fontSize="small" />
+              </IconButton>
+            </span>
+          </Tooltip>
+          {mergeData.general.chaptersFile && (
+            <Tooltip title={mergeData.general.chaptersFile}>
+              <Chip
+                size="small"
+                label={t('merge.chaptersFile')}
+                disabled={merging}
+                onDelete={() => setMergeData((prev) => prev.withGeneralChaptersFile(''))}
+                sx={{ ml: 0.5 }}
+              />
+            </Tooltip>
+          )}
         </Toolbar>
       </AppBar>
       <Box sx={{ flex: 1, overflow: 'auto', pt: 0.5, pb: 2, pl: 2, pr: 2 }}>
@@ -1775,6 +1866,12 @@ function Merge({ file, mkvToolNixPath }: MergeProps) {
           )}
         </DialogActions>
       </Dialog>
+      <SceneChaptersDialog
+        open={sceneChaptersOpen}
+        onClose={() => setSceneChaptersOpen(false)}
+        file={file}
+        onSaved={(chaptersFile) => setMergeData((prev) => prev.withGeneralChaptersFile(chaptersFile))}
+      />
     </Box>
   );
 }
@@ -0,0 +1,325 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useState } from 'react';
+import {
+  Box,
+  Button,
+  Checkbox,
+  Chip,
+  Dialog,
+  FormControlLabel,
+  LinearProgress,
+  Menu,
+  MenuItem,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableHead,
+  TableRow,
+  TextField,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+
+import * as Protocol from '../lib/protocol';
+import { formatChapterTime } from '../lib/chapters';
+import { openSaveTextFileDialog } from '../lib/dialog';
+import { describeFfmpegError, isFfmpegAnalysisCancelled } from '../lib/ffmpegErrors';
+import { basename } from '../lib/format';
+import { QC_EXPORT_FILES, QcExportFormat, renderQcTimeline } from '../lib/qc';
+import { cancelFfmpegAnalysis, runQcAnalysis, writeTextFile } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface QcDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+}
+
+const DEFAULT_OPTIONS: Protocol.QcOptions = {
+  black: true,
+  freeze: true,
+  silence: true,
+  blackMinDuration: 2,
+  blackPixelThreshold: 0.1,
+  freezeNoise: -60,
+  freezeMinDuration: 2,
+  silenceNoise: -50,
+  silenceMinDuration: 2,
+};
+
+const KIND_COLORS: Record<Protocol.QcEventKind, 'default' | 'info' | 'warning'> = {
+  [Protocol.QcEventKind.Black]: 'default',
+  [Protocol.QcEventKind.Freeze]: 'info',
+  [Protocol.QcEventKind.Silence]: 'warning',
+};
+
+function describeQcError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/QC_NOTHING_TO_ANALYZE:/.test(message)) {
+    return t('qc.nothingToAnalyze');
+  }
+  return describeFfmpegError(t, error);
+}
+
+export default function QcDialog({ open, onClose, file }: QcDialogProps) {
+  const { t } = useTranslation();
+  const [options, setOptions] = useState<Protocol.QcOptions>(DEFAULT_OPTIONS);
+  const [running, setRunning] = useState(false);
+  const [percent, setPercent] = useState(0);
+  const [timeline, setTimeline] = useState<Protocol.QcTimeline | null>(null);
+  const [exportAnchor, setExportAnchor] = useState<HTMLElement | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  const kindLabel = (kind: Protocol.QcEventKind) => t(`qc.kind${kind}`);
+
+  useEffect(() => {
+    if (!running) return;
+    const unlisten = getCurrentWebviewWindow().listen<Protocol.FfmpegAnalysisProgress>(
+      'ffmpeg-analysis-progress',
+      (event) => {
+        if (event.payload.analysis === 'qc') {
+          setPercent(event.payload.percent);
+        }
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [running]);
+
+  const setOption = <K extends keyof Protocol.QcOptions>(key: K, value: Protocol.QcOptions[K]) =>
+    setOptions((prev) => ({ ...prev, [key]: value }));
+
+  const numberField = (label: string, key: keyof Protocol.QcOptions, disabled: boolean, step: number) => (
+    <TextField
+      label={label}
+      type="number"
+      size="small"
+      value={options[key] as number}
+      disabled={running || disabled}
+      onChange={(e) => {
+        const parsed = parseFloat(e.target.value);
+        if (!Number.isNaN(parsed)) setOption(key, parsed);
+      }}
+      slotProps={{ htmlInput: { step } }}
+      sx={{ width: 150 }}
+    />
+  );
+
+  const detectorRow = (
+    enabledKey: 'black' | 'freeze' | 'silence',
+    kind: Protocol.QcEventKind,
+    durationKey: keyof Protocol.QcOptions,
+    thresholdKey: keyof Protocol.QcOptions,
+    thresholdLabel: string,
+    thresholdStep: number
+  ) => (
+    <Box sx={{ display: 'flex', alignItems: 'center', gap: 1 }}>
+      <FormControlLabel
+        control={
+          <Checkbox
+            size="small"
+            checked={options[enabledKey]}
+            disabled={running}
+            onChange={(e) => setOption(enabledKey, e.target.checked)}
+          />
+        }
+        label={kindLabel(kind)}
+        sx={{ width: 140 }}
+      />
+      {numberField(t('qc.minDuration'), durationKey, !options[enabledKey], 0.1)}
+      {numberField(thresholdLabel, thresholdKey, !options[enabledKey], thresholdStep)}
+    </Box>
+  );
+
+  const handleRun = async () => {
+    setRunning(true);
+    setPercent(0);
+    setTimeline(null);
+    try {
+      setTimeline(await runQcAnalysis(file, options));
+    } catch (error) {
+      if (!isFfmpegAnalysisCancelled(error)) {
+        setDialogNotification({
+          title: t('qc.failed', { error: describeQcError(t, error) }),
+          type: Protocol.DialogNotificationType.Error,
+        });
+      }
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const handleExport = async (format: QcExportFormat) => {
+    setExportAnchor(null);
+    if (!timeline) return;
+    const { extension, filter } = QC_EXPORT_FILES[format];
+    const suffix = format === QcExportFormat.MatroskaChapters || format === QcExportFormat.OgmChapters ? 'chapters' : 'qc';
+    const output = (await openSaveTextFileDialog(
+      filter,
+      extension,
+      `${basename(file)}.${suffix}.${extension}`
+    )) as string | null;
+    if (!output) return;
+    try {
+      await writeTextFile(output, renderQcTimeline(timeline, format, kindLabel));
+      setDialogNotification({
+        title: t('qc.exported', { filePath: output }),
+        type: Protocol.DialogNotificationType.Info,
+      });
+    } catch (error) {
+      setDialogNotification({
+        title: t('qc.failed', { error: String(error) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    }
+  };
+
+  const headerSx = { fontWeight: 'bold', bgcolor: 'background.default' };
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '70vw',
+            height: '80vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('qc.title')}
+        </Typography>
+      </Box>
+      <Box sx={{ px: 2, py: 1, display: 'flex', flexDirection: 'column', gap: 1, flexShrink: 0 }}>
+        {detectorRow('black', Protocol.QcEventKind.Black, 'blackMinDuration', 'blackPixelThreshold', t('qc.pixelThreshold'), 0.01)}
+        {detectorRow('freeze', Protocol.QcEventKind.Freeze, 'freezeMinDuration', 'freezeNoise', t('qc.noiseDb'), 1)}
+        {detectorRow('silence', Protocol.QcEventKind.Silence, 'silenceMinDuration', 'silenceNoise', t('qc.noiseDb'), 1)}
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        {running ? (
+          <Button variant="contained" size="small" color="error" onClick={() => cancelFfmpegAnalysis()} sx={{ textTransform: 'none' }}>
+            {t('qc.cancel')}
+          </Button>
+        ) : (
+          <Button
+            variant="contained"
+            size="small"
+            onClick={handleRun}
+            disabled={!options.black && !options.freeze && !options.silence}
+            sx={{ textTransform: 'none' }}
+          >
+            {t('qc.run')}
+          </Button>
+        )}
+        <Button
+          variant="contained"
+          size="small"
+          onClick={(e) => setExportAnchor(e.currentTarget)}
+          disabled={running || timeline === null}
+          sx={{ textTransform: 'none' }}
+        >
+          {t('qc.export')}
+        </Button>
+        <Menu anchorEl={exportAnchor} open={exportAnchor !== null} onClose={() => setExportAnchor(null)}>
+          <MenuItem onClick={() => handleExport(QcExportFormat.Csv)}>CSV</MenuItem>
+          <MenuItem onClick={() => handleExport(QcExportFormat.Json)}>JSON</MenuItem>
+          <MenuItem onClick={() => handleExport(QcExportFormat.MatroskaChapters)}>{t('qc.matroskaChapters')}</MenuItem>
+          <MenuItem onClick={() => handleExport(QcExportFormat.OgmChapters)}>{t('qc.ogmChapters')}</MenuItem>
+        </Menu>
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {running && (
+        <Box sx={{ px: 2, py: 1, flexShrink: 0 }}>
+          <LinearProgress variant="determinate" value={percent} />
+          <Typography variant="caption" color="text.secondary">
+            {t('qc.running', { percent })}
+          </Typography>
+        </Box>
+      )}
+      {timeline ? (
+        <>
+          <Typography variant="body2" sx={{ px: 2, py: 1, flexShrink: 0 }}>
+            {timeline.intervals.length > 0 ? t('qc.found', { count: timeline.intervals.length }) : t('qc.clean')}
+            {timeline.skipped.length > 0 &&
+              ` ${t('qc.skipped', { kinds: timeline.skipped.map(kindLabel).join(', ') })}`}
+          </Typography>
+          <TableContainer sx={{ flex: 1, minHeight: 0, overflow: 'auto' }}>
+            <Table size="small" stickyHeader>
+              <TableHead>
+                <TableRow>
+                  <TableCell sx={headerSx}>{t('qc.kind')}</TableCell>
+                  <TableCell sx={headerSx}>{t('qc.start')}</TableCell>
+                  <TableCell sx={headerSx}>{t('qc.end')}</TableCell>
+                  <TableCell sx={headerSx}>{t('qc.duration')}</TableCell>
+                </TableRow>
+              </TableHead>
+              <TableBody>
+                {timeline.intervals.map((interval) => (
+                  <TableRow key={`${interval.kind}-${interval.start}`}>
+                    <TableCell>
+                      <Chip size="small" label={kindLabel(interval.kind)} color={KIND_COLORS[interval.kind]} />
+                    </TableCell>
+                    <TableCell>{formatChapterTime(interval.start, 3)}</TableCell>
+                    <TableCell>{formatChapterTime(interval.end, 3)}</TableCell>
+                    <TableCell>{formatChapterTime(interval.duration, 3)}</TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </TableContainer>
+        </>
+      ) : (
+        !running && (
+          <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+            <Typography variant="body2" color="text.secondary">
+              {t('qc.hint')}
+            </Typography>
+          </Box>
+        )
+      )}
+    </Dialog>
+  );
+}
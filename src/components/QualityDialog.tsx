@@ -0,0 +1,388 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useState } from 'react';
+import {
+  Box,
+  Button,
+  Checkbox,
+  Chip,
+  Dialog,
+  FormControlLabel,
+  LinearProgress,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableHead,
+  TableRow,
+  TextField,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import { open as openDialog } from '@tauri-apps/plugin-dialog';
+
+import * as Protocol from '../lib/protocol';
+import { formatChapterTime } from '../lib/chapters';
+import { openVideoFileDialog } from '../lib/dialog';
+import { describeFfmpegError, isFfmpegAnalysisCancelled } from '../lib/ffmpegErrors';
+import { QUALITY_METRIC_LABELS, QUALITY_METRIC_MAX, downsampleMin, formatQualityScore } from '../lib/quality';
+import { cancelFfmpegCapture, runQualityComparison } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface QualityDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+}
+
+const CHART_WIDTH = 1000;
+const CHART_HEIGHT = 120;
+
+function describeQualityError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/QUALITY_NO_VIDEO:/.test(message)) {
+    return t('quality.noVideo');
+  }
+  if (/QUALITY_NO_FRAMES:/.test(message)) {
+    return t('quality.noFrames');
+  }
+  if (/QUALITY_NO_METRICS/.test(message)) {
+    return t('quality.noMetrics');
+  }
+  return describeFfmpegError(t, error);
+}
+
+function QualityChart({ score }: { score: Protocol.QualityScore }) {
+  const points = downsampleMin(score.series, CHART_WIDTH);
+  const max = QUALITY_METRIC_MAX[score.metric];
+  const floor = Math.min(score.min, max * 0.9);
+  const y = (value: number) => CHART_HEIGHT * (1 - (value - floor) / Math.max(1e-9, max - floor));
+  const step = CHART_WIDTH / Math.max(1, points.length);
+  const path = points.map((value, index) => `${index === 0 ? 'M' : 'L'}${(index * step).toFixed(1)},${y(value).toFixed(1)}`).join(' ');
+
+  return (
+    <Box sx={{ mb: 1 }}>
+      <Typography variant="caption" sx={{ fontWeight: 'bold' }}>
+        {`${QUALITY_METRIC_LABELS[score.metric]} (${formatQualityScore(score.metric, floor)} - ${formatQualityScore(score.metric, max)})`}
+      </Typography>
+      <Box sx={{ border: 1, borderColor: 'divider' }}>
+        <svg
+          viewBox={`0 0 ${CHART_WIDTH} ${CHART_HEIGHT}`}
+          preserveAspectRatio="none"
+          style={{ display: 'block', width: '100%', height: CHART_HEIGHT }}
+        >
+          <path d={path} fill="none" stroke="#0ea5e9" strokeWidth={1.5} vectorEffect="non-scaling-stroke" />
+          <line
+            x1={0}
+            x2={CHART_WIDTH}
+            y1={y(score.mean)}
+            y2={y(score.mean)}
+            stroke="currentColor"
+            strokeDasharray="6 4"
+            vectorEffect="non-scaling-stroke"
+          />
+        </svg>
+      </Box>
+    </Box>
+  );
+}
+
+export default function QualityDialog({ open, onClose, file }: QualityDialogProps) {
+  const { t } = useTranslation();
+  const [reference, setReference] = useState('');
+  const [metrics, setMetrics] = useState<Protocol.QualityMetric[]>(Object.values(Protocol.QualityMetric));
+  const [worstFrames, setWorstFrames] = useState(5);
+  const [outputDir, setOutputDir] = useState('');
+  const [running, setRunning] = useState(false);
+  const [phase, setPhase] = useState('quality');
+  const [percent, setPercent] = useState(0);
+  const [report, setReport] = useState<Protocol.QualityReport | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  useEffect(() => {
+    setReport(null);
+  }, [file, reference]);
+
+  useEffect(() => {
+    if (!running) return;
+    const unlisten = getCurrentWebviewWindow().listen<Protocol.FfmpegCaptureProgress>(
+      'ffmpeg-capture-progress',
+      (event) => {
+        if (!event.payload.done) {
+          setPhase(event.payload.phase);
+          setPercent(event.payload.percent);
+        }
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [running]);
+
+  const toggleMetric = (metric: Protocol.QualityMetric) => {
+    setMetrics((current) =>
+      current.includes(metric) ? current.filter((m) => m !== metric) : [...current, metric]
+    );
+  };
+
+  const handleBrowseReference = async () => {
+    const selected = (await openVideoFileDialog(reference || undefined)) as string | null;
+    if (selected) setReference(selected);
+  };
+
+  const handleBrowseOutputDir = async () => {
+    const selected = (await openDialog({ directory: true, defaultPath: outputDir || undefined })) as string | null;
+    if (selected) setOutputDir(selected);
+  };
+
+  const handleRun = async () => {
+    setRunning(true);
+    setPhase('quality');
+    setPercent(0);
+    setReport(null);
+    try {
+      setReport(await runQualityComparison(reference, file, { metrics, worstFrames, outputDir }));
+    } catch (error) {
+      if (!isFfmpegAnalysisCancelled(error)) {
+        setDialogNotification({
+          title: t('quality.failed', { error: describeQualityError(t, error) }),
+          type: Protocol.DialogNotificationType.Error,
+        });
+      }
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const headerSx = { fontWeight: 'bold', bgcolor: 'background.default' };
+  const worstMetric = report?.worstMetric ?? null;
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '70vw',
+            height: '85vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('quality.title')}
+        </Typography>
+      </Box>
+      <Box sx={{ px: 2, py: 1, display: 'flex', flexDirection: 'column', gap: 1, flexShrink: 0 }}>
+        <Box sx={{ display: 'flex', alignItems: 'center', gap: 1 }}>
+          <TextField
+            label={t('quality.reference')}
+            size="small"
+            fullWidth
+            value={reference}
+            disabled={running}
+            onChange={(e) => setReference(e.target.value)}
+          />
+          <Button variant="outlined" size="small" onClick={handleBrowseReference} disabled={running} sx={{ textTransform: 'none', flexShrink: 0 }}>
+            {t('quality.browse')}
+          </Button>
+        </Box>
+        <TextField label={t('quality.distorted')} size="small" fullWidth value={file} disabled />
+        <Box sx={{ display: 'flex', alignItems: 'center', gap: 1 }}>
+          <TextField
+            label={t('quality.outputDir')}
+            size="small"
+            fullWidth
+            value={outputDir}
+            disabled={running}
+            placeholder={t('quality.outputDirNone')}
+            onChange={(e) => setOutputDir(e.target.value)}
+          />
+          <Button variant="outlined" size="small" onClick={handleBrowseOutputDir} disabled={running} sx={{ textTransform: 'none', flexShrink: 0 }}>
+            {t('quality.browse')}
+          </Button>
+        </Box>
+        <Box sx={{ display: 'flex', alignItems: 'center', justifyContent: 'center', gap: 1 }}>
+          {Object.values(Protocol.QualityMetric).map((metric) => (
+            <FormControlLabel
+              key={metric}
+              control={
+                <Checkbox
+                  size="small"
+                  checked={metrics.includes(metric)}
+                  onChange={() => toggleMetric(metric)}
+                  disabled={running}
+                />
+              }
+              label={QUALITY_METRIC_LABELS[metric]}
+            />
+          ))}
+          <TextField
+            label={t('quality.worstFrames')}
+            type="number"
+            size="small"
+            value={worstFrames}
+            disabled={running}
+            onChange={(e) => {
+              const parsed = parseInt(e.target.value);
+              setWorstFrames(Number.isNaN(parsed) ? 0 : Math.min(50, Math.max(0, parsed)));
+            }}
+            slotProps={{ htmlInput: { min: 0, max: 50, step: 1 } }}
+            sx={{ width: 140 }}
+          />
+        </Box>
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        {running ? (
+          <Button variant="contained" size="small" color="error" onClick={() => cancelFfmpegCapture()} sx={{ textTransform: 'none' }}>
+            {t('quality.cancel')}
+          </Button>
+        ) : (
+          <Button
+            variant="contained"
+            size="small"
+            onClick={handleRun}
+            disabled={reference === '' || metrics.length === 0}
+            sx={{ textTransform: 'none' }}
+          >
+            {t('quality.run')}
+          </Button>
+        )}
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {running && (
+        <Box sx={{ px: 2, py: 1, flexShrink: 0 }}>
+          <LinearProgress variant="determinate" value={percent} />
+          <Typography variant="caption" color="text.secondary">
+            {phase === 'capture' ? t('quality.capturing', { percent }) : t('quality.running', { percent })}
+          </Typography>
+        </Box>
+      )}
+      {report ? (
+        <Box sx={{ flex: 1, minHeight: 0, overflow: 'auto', p: 2 }}>
+          <Box sx={{ display: 'flex', flexWrap: 'wrap', gap: 1, mb: 1 }}>
+            <Chip
+              size="small"
+              label={t('quality.comparedAt', {
+                width: report.width,
+                height: report.height,
+                frameRate: report.frameRate.toFixed(3),
+                frames: report.frames,
+              })}
+            />
+            {report.scaled && <Chip size="small" color="warning" label={t('quality.scaled')} />}
+            {report.resampled && <Chip size="small" color="warning" label={t('quality.resampled')} />}
+            {report.skipped.map((metric) => (
+              <Chip
+                key={metric}
+                size="small"
+                color="default"
+                label={t('quality.skipped', { metric: QUALITY_METRIC_LABELS[metric] })}
+              />
+            ))}
+          </Box>
+          <Table size="small" sx={{ mb: 2 }}>
+            <TableHead>
+              <TableRow>
+                <TableCell sx={headerSx}>{t('quality.metric')}</TableCell>
+                <TableCell sx={headerSx}>{t('quality.mean')}</TableCell>
+                <TableCell sx={headerSx}>{t('quality.min')}</TableCell>
+                <TableCell sx={headerSx}>{t('quality.max')}</TableCell>
+              </TableRow>
+            </TableHead>
+            <TableBody>
+              {report.scores.map((score) => (
+                <TableRow key={score.metric}>
+                  <TableCell>{QUALITY_METRIC_LABELS[score.metric]}</TableCell>
+                  <TableCell>{formatQualityScore(score.metric, score.mean)}</TableCell>
+                  <TableCell>{formatQualityScore(score.metric, score.min)}</TableCell>
+                  <TableCell>{formatQualityScore(score.metric, score.max)}</TableCell>
+                </TableRow>
+              ))}
+            </TableBody>
+          </Table>
+          {report.scores.map((score) => (
+            <QualityChart key={score.metric} score={score} />
+          ))}
+          {worstMetric && report.worstFrames.length > 0 && (
+            <TableContainer sx={{ mt: 2 }}>
+              <Typography variant="subtitle2" sx={{ fontWeight: 'bold' }}>
+                {t('quality.worstTitle', { metric: QUALITY_METRIC_LABELS[worstMetric] })}
+              </Typography>
+              <Table size="small">
+                <TableHead>
+                  <TableRow>
+                    <TableCell sx={headerSx}>#</TableCell>
+                    <TableCell sx={headerSx}>{t('quality.frame')}</TableCell>
+                    <TableCell sx={headerSx}>{t('quality.time')}</TableCell>
+                    <TableCell sx={headerSx}>{t('quality.score')}</TableCell>
+                    <TableCell sx={headerSx}>{t('quality.images')}</TableCell>
+                  </TableRow>
+                </TableHead>
+                <TableBody>
+                  {report.worstFrames.map((frame, index) => (
+                    <TableRow key={frame.frame}>
+                      <TableCell>{index + 1}</TableCell>
+                      <TableCell>{frame.frame}</TableCell>
+                      <TableCell>{formatChapterTime(frame.time, 3)}</TableCell>
+                      <TableCell>{formatQualityScore(worstMetric, frame.score)}</TableCell>
+                      <TableCell sx={{ fontFamily: 'monospace', fontSize: 12, wordBreak: 'break-all', whiteSpace: 'pre-line' }}>
+                        {frame.distorted ? `${frame.reference}\n${frame.distorted}` : '-'}
+                      </TableCell>
+                    </TableRow>
+                  ))}
+                </TableBody>
+              </Table>
+            </TableContainer>
+          )}
+        </Box>
+      ) : (
+        !running && (
+          <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+            <Typography variant="body2" color="text.secondary">
+              {t('quality.hint')}
+            </Typography>
+          </Box>
+        )
+      )}
+    </Dialog>
+  );
+}
@@ -0,0 +1,298 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useMemo, useState } from 'react';
+import {
+  Box,
+  Button,
+  Checkbox,
+  Chip,
+  Dialog,
+  FormControlLabel,
+  LinearProgress,
+  Menu,
+  MenuItem,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableHead,
+  TableRow,
+  TextField,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+
+import * as Protocol from '../lib/protocol';
+import { formatChapterTime } from '../lib/chapters';
+import { openSaveTextFileDialog } from '../lib/dialog';
+import { describeFfmpegError, isFfmpegAnalysisCancelled } from '../lib/ffmpegErrors';
+import { basename } from '../lib/format';
+import {
+  SCENE_CHAPTERS_FILES,
+  SceneChaptersFormat,
+  renderSceneChapters,
+  sceneChapterMarks,
+} from '../lib/scenes';
+import { cancelFfmpegAnalysis, runSceneDetection, writeTextFile } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface SceneChaptersDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+  // Called with the saved chapter file, e.g. so the merge window can pass it to mkvmerge.
+  onSaved?: (chaptersFile: string) => void;
+}
+
+const DEFAULT_OPTIONS: Protocol.SceneOptions = {
+  threshold: 0.4,
+  minSpacing: 60,
+  snapToKeyframes: true,
+};
+
+function describeSceneError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/SCENES_NO_VIDEO:/.test(message)) {
+    return t('scenes.noVideo');
+  }
+  return describeFfmpegError(t, error);
+}
+
+export default function SceneChaptersDialog({ open, onClose, file, onSaved }: SceneChaptersDialogProps) {
+  const { t } = useTranslation();
+  const [options, setOptions] = useState<Protocol.SceneOptions>(DEFAULT_OPTIONS);
+  const [running, setRunning] = useState(false);
+  const [percent, setPercent] = useState(0);
+  const [result, setResult] = useState<Protocol.SceneChapters | null>(null);
+  const [saveAnchor, setSaveAnchor] = useState<HTMLElement | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  const marks = useMemo(
+    () => (result ? sceneChapterMarks(result.chapters, (number) => t('scenes.chapterTitle', { number })) : []),
+    [result, t]
+  );
+
+  useEffect(() => {
+    setResult(null);
+  }, [file]);
+
+  useEffect(() => {
+    if (!running) return;
+    const unlisten = getCurrentWebviewWindow().listen<Protocol.FfmpegAnalysisProgress>(
+      'ffmpeg-analysis-progress',
+      (event) => {
+        if (event.payload.analysis === 'scenes') {
+          setPercent(event.payload.percent);
+        }
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [running]);
+
+  const numberField = (label: string, key: 'threshold' | 'minSpacing', step: number) => (
+    <TextField
+      label={label}
+      type="number"
+      size="small"
+      value={options[key]}
+      disabled={running}
+      onChange={(e) => {
+        const parsed = parseFloat(e.target.value);
+        if (!Number.isNaN(parsed)) setOptions((prev) => ({ ...prev, [key]: parsed }));
+      }}
+      slotProps={{ htmlInput: { step, min: 0 } }}
+      sx={{ width: 170 }}
+    />
+  );
+
+  const handleRun = async () => {
+    setRunning(true);
+    setPercent(0);
+    setResult(null);
+    try {
+      setResult(await runSceneDetection(file, options));
+    } catch (error) {
+      if (!isFfmpegAnalysisCancelled(error)) {
+        setDialogNotification({
+          title: t('scenes.failed', { error: describeSceneError(t, error) }),
+          type: Protocol.DialogNotificationType.Error,
+        });
+      }
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const handleSave = async (format: SceneChaptersFormat) => {
+    setSaveAnchor(null);
+    if (!result) return;
+    const { extension, filter } = SCENE_CHAPTERS_FILES[format];
+    const output = (await openSaveTextFileDialog(
+      filter,
+      extension,
+      `${basename(file)}.chapters.${extension}`
+    )) as string | null;
+    if (!output) return;
+    try {
+      await writeTextFile(output, renderSceneChapters(marks, format));
+      onSaved?.(output);
+      setDialogNotification({
+        title: onSaved ? t('scenes.savedForMerge', { filePath: output }) : t('scenes.saved', { filePath: output }),
+        type: Protocol.DialogNotificationType.Info,
+      });
+    } catch (error) {
+      setDialogNotification({
+        title: t('scenes.failed', { error: String(error) }),
+        type: Protocol.DialogNotificationType.Error,
+      });
+    }
+  };
+
+  const headerSx = { fontWeight: 'bold', bgcolor: 'background.default' };
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '60vw',
+            height: '80vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('scenes.title')}
+        </Typography>
+      </Box>
+      <Box sx={{ px: 2, py: 1.5, display: 'flex', alignItems: 'center', justifyContent: 'center', gap: 1, flexShrink: 0 }}>
+        {numberField(t('scenes.threshold'), 'threshold', 0.05)}
+        {numberField(t('scenes.minSpacing'), 'minSpacing', 5)}
+        <FormControlLabel
+          control={
+            <Checkbox
+              size="small"
+              checked={options.snapToKeyframes}
+              disabled={running}
+              onChange={(e) => setOptions((prev) => ({ ...prev, snapToKeyframes: e.target.checked }))}
+            />
+          }
+          label={t('scenes.snapToKeyframes')}
+        />
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        {running ? (
+          <Button variant="contained" size="small" color="error" onClick={() => cancelFfmpegAnalysis()} sx={{ textTransform: 'none' }}>
+            {t('scenes.cancel')}
+          </Button>
+        ) : (
+          <Button variant="contained" size="small" onClick={handleRun} sx={{ textTransform: 'none' }}>
+            {t('scenes.run')}
+          </Button>
+        )}
+        <Button
+          variant="contained"
+          size="small"
+          onClick={(e) => setSaveAnchor(e.currentTarget)}
+          disabled={running || result === null}
+          sx={{ textTransform: 'none' }}
+        >
+          {onSaved ? t('scenes.saveForMerge') : t('scenes.save')}
+        </Button>
+        <Menu anchorEl={saveAnchor} open={saveAnchor !== null} onClose={() => setSaveAnchor(null)}>
+          <MenuItem onClick={() => handleSave(SceneChaptersFormat.Matroska)}>{t('qc.matroskaChapters')}</MenuItem>
+          <MenuItem onClick={() => handleSave(SceneChaptersFormat.Ogm)}>{t('qc.ogmChapters')}</MenuItem>
+        </Menu>
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {running && (
+        <Box sx={{ px: 2, py: 1, flexShrink: 0 }}>
+          <LinearProgress variant="determinate" value={percent} />
+          <Typography variant="caption" color="text.secondary">
+            {t('scenes.running', { percent })}
+          </Typography>
+        </Box>
+      )}
+      {result ? (
+        <>
+          <Typography variant="body2" sx={{ px: 2, py: 1, flexShrink: 0 }}>
+            {t('scenes.found', { cuts: result.cuts, chapters: result.chapters.length })}
+          </Typography>
+          <TableContainer sx={{ flex: 1, minHeight: 0, overflow: 'auto' }}>
+            <Table size="small" stickyHeader>
+              <TableHead>
+                <TableRow>
+                  <TableCell sx={headerSx}>{t('scenes.chapter')}</TableCell>
+                  <TableCell sx={headerSx}>{t('scenes.start')}</TableCell>
+                  <TableCell sx={headerSx}>{t('scenes.score')}</TableCell>
+                  <TableCell sx={headerSx} />
+                </TableRow>
+              </TableHead>
+              <TableBody>
+                {result.chapters.map((chapter, index) => (
+                  <TableRow key={chapter.start}>
+                    <TableCell>{marks[index]?.title}</TableCell>
+                    <TableCell>{formatChapterTime(chapter.start, 3)}</TableCell>
+                    <TableCell>{index === 0 ? '-' : chapter.score.toFixed(3)}</TableCell>
+                    <TableCell>
+                      {chapter.snapped && <Chip size="small" label={t('scenes.snapped')} />}
+                    </TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </TableContainer>
+        </>
+      ) : (
+        !running && (
+          <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+            <Typography variant="body2" color="text.secondary">
+              {t('scenes.hint')}
+            </Typography>
+          </Box>
+        )
+      )}
+    </Dialog>
+  );
+}
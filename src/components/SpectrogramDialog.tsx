@@ -0,0 +1,280 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { useEffect, useState } from 'react';
+import {
+  Box,
+  Button,
+  Chip,
+  Dialog,
+  LinearProgress,
+  MenuItem,
+  Table,
+  TableBody,
+  TableCell,
+  TableContainer,
+  TableRow,
+  TextField,
+  Typography,
+} from '@mui/material';
+import type { TFunction } from 'i18next';
+import { useTranslation } from 'react-i18next';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+
+import * as Protocol from '../lib/protocol';
+import { formatChapterTime } from '../lib/chapters';
+import { openSaveImageFileDialog } from '../lib/dialog';
+import { describeFfmpegError, isFfmpegAnalysisCancelled } from '../lib/ffmpegErrors';
+import { basename } from '../lib/format';
+import { cancelFfmpegAnalysis, renderSpectrogram } from '../lib/service';
+import { useAppStore } from '../lib/store';
+
+interface SpectrogramDialogProps {
+  open: boolean;
+  onClose: () => void;
+  file: string;
+  audioStreams: number;
+}
+
+function describeSpectrogramError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/SPECTROGRAM_NO_AUDIO:/.test(message)) {
+    return t('spectrogram.noAudio');
+  }
+  if (/SPECTROGRAM_TOO_SMALL:/.test(message)) {
+    return t('spectrogram.tooSmall');
+  }
+  return describeFfmpegError(t, error);
+}
+
+export default function SpectrogramDialog({ open, onClose, file, audioStreams }: SpectrogramDialogProps) {
+  const { t } = useTranslation();
+  const [stream, setStream] = useState(0);
+  const [width, setWidth] = useState(1600);
+  const [height, setHeight] = useState(900);
+  const [running, setRunning] = useState(false);
+  const [percent, setPercent] = useState(0);
+  const [report, setReport] = useState<Protocol.SpectrogramReport | null>(null);
+  const setDialogNotification = useAppStore((state) => state.setDialogNotification);
+
+  useEffect(() => {
+    setReport(null);
+    setStream(0);
+  }, [file]);
+
+  useEffect(() => {
+    if (!running) return;
+    const unlisten = getCurrentWebviewWindow().listen<Protocol.FfmpegAnalysisProgress>(
+      'ffmpeg-analysis-progress',
+      (event) => {
+        if (event.payload.analysis === 'spectrogram') {
+          setPercent(event.payload.percent);
+        }
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [running]);
+
+  const numberField = (label: string, value: number, onChange: (n: number) => void, min: number, max: number) => (
+    <TextField
+      label={label}
+      type="number"
+      size="small"
+      value={value}
+      disabled={running}
+      onChange={(e) => {
+        const parsed = parseInt(e.target.value);
+        onChange(Number.isNaN(parsed) ? min : Math.min(max, Math.max(min, parsed)));
+      }}
+      slotProps={{ htmlInput: { min, max, step: 100 } }}
+      sx={{ width: 120 }}
+    />
+  );
+
+  const handleRender = async () => {
+    const output = (await openSaveImageFileDialog(
+      'png',
+      `${basename(file)}.audio${stream + 1}.spectrogram.png`
+    )) as string | null;
+    if (!output) return;
+    setRunning(true);
+    setPercent(0);
+    setReport(null);
+    try {
+      const result = await renderSpectrogram(file, { stream, output, width, height });
+      setReport(result);
+      setDialogNotification({
+        title: t('spectrogram.saved', { filePath: result.output }),
+        type: Protocol.DialogNotificationType.Info,
+      });
+    } catch (error) {
+      if (!isFfmpegAnalysisCancelled(error)) {
+        setDialogNotification({
+          title: t('spectrogram.failed', { error: describeSpectrogramError(t, error) }),
+          type: Protocol.DialogNotificationType.Error,
+        });
+      }
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const rows: Array<[string, React.ReactNode]> = report
+    ? [
+        [t('spectrogram.output'), report.output],
+        [t('spectrogram.sampleRate'), `${report.sampleRate} Hz`],
+        [t('spectrogram.duration'), formatChapterTime(report.duration, 3)],
+        [
+          t('spectrogram.peak'),
+          report.channelPeaks.length > 1
+            ? `${report.peak.toFixed(1)} dBFS (${report.channelPeaks.map((peak) => peak.toFixed(1)).join(' / ')})`
+            : `${report.peak.toFixed(1)} dBFS`,
+        ],
+        [
+          t('spectrogram.clippedSamples'),
+          <Chip
+            size="small"
+            label={
+              report.channelClipped.length > 1
+                ? `${report.clippedSamples} (${report.channelClipped.join(' / ')})`
+                : report.clippedSamples
+            }
+            color={report.clippedSamples > 0 ? 'error' : 'success'}
+          />,
+        ],
+        [
+          t('spectrogram.cutoff'),
+          report.cutoff === null ? (
+            <Chip size="small" label={t('spectrogram.noCutoff')} color="success" />
+          ) : (
+            <Chip
+              size="small"
+              label={t('spectrogram.cutoffAt', { frequency: (report.cutoff / 1000).toFixed(1) })}
+              color="warning"
+            />
+          ),
+        ],
+      ]
+    : [];
+
+  return (
+    <Dialog
+      open={open}
+      onClose={running ? undefined : onClose}
+      maxWidth={false}
+      slotProps={{
+        paper: {
+          sx: {
+            width: '60vw',
+            height: '60vh',
+            maxWidth: 'none',
+            maxHeight: 'none',
+            display: 'flex',
+            flexDirection: 'column',
+          },
+        },
+      }}
+    >
+      <Box sx={{ px: 2, py: 1, borderBottom: 1, borderColor: 'divider', flexShrink: 0 }}>
+        <Typography variant="h6" sx={{ fontWeight: 600, wordBreak: 'break-all', textAlign: 'center' }}>
+          {t('spectrogram.title')}
+        </Typography>
+      </Box>
+      <Box sx={{ px: 2, py: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', gap: 1, flexShrink: 0 }}>
+        <TextField
+          select
+          label={t('spectrogram.stream')}
+          size="small"
+          value={stream}
+          disabled={running || audioStreams === 0}
+          onChange={(e) => setStream(Number(e.target.value))}
+          sx={{ width: 140 }}
+        >
+          {Array.from({ length: Math.max(1, audioStreams) }, (_, num) => (
+            <MenuItem key={num} value={num}>{`Audio #${num + 1}`}</MenuItem>
+          ))}
+        </TextField>
+        {numberField(t('spectrogram.width'), width, setWidth, 400, 8192)}
+        {numberField(t('spectrogram.height'), height, setHeight, 300, 8192)}
+      </Box>
+      <Box
+        sx={{
+          display: 'flex',
+          alignItems: 'center',
+          justifyContent: 'center',
+          gap: 1,
+          px: 2,
+          py: 1,
+          borderBottom: 1,
+          borderColor: 'divider',
+          flexShrink: 0,
+        }}
+      >
+        {running ? (
+          <Button variant="contained" size="small" color="error" onClick={() => cancelFfmpegAnalysis()} sx={{ textTransform: 'none' }}>
+            {t('spectrogram.cancel')}
+          </Button>
+        ) : (
+          <Button
+            variant="contained"
+            size="small"
+            onClick={handleRender}
+            disabled={audioStreams === 0}
+            sx={{ textTransform: 'none' }}
+          >
+            {t('spectrogram.render')}
+          </Button>
+        )}
+        <Button variant="outlined" size="small" onClick={onClose} disabled={running} sx={{ textTransform: 'none' }}>
+          {t('dialog.close')}
+        </Button>
+      </Box>
+      {running && (
+        <Box sx={{ px: 2, py: 1, flexShrink: 0 }}>
+          <LinearProgress variant="determinate" value={percent} />
+          <Typography variant="caption" color="text.secondary">
+            {t('spectrogram.running', { percent })}
+          </Typography>
+        </Box>
+      )}
+      {report ? (
+        <TableContainer sx={{ flex: 1, minHeight: 0, overflow: 'auto' }}>
+          <Table size="small">
+            <TableBody>
+              {rows.map(([label, value]) => (
+                <TableRow key={label}>
+                  <TableCell sx={{ fontWeight: 'bold', whiteSpace: 'nowrap' }}>{label}</TableCell>
+                  <TableCell sx={{ wordBreak: 'break-all' }}>{value}</TableCell>
+                </TableRow>
+              ))}
+            </TableBody>
+          </Table>
+        </TableContainer>
+      ) : (
+        !running && (
+          <Box sx={{ flex: 1, display: 'flex', alignItems: 'center', justifyContent: 'center', p: 2 }}>
+            <Typography variant="body2" color="text.secondary">
+              {audioStreams === 0 ? t('spectrogram.noAudio') : t('spectrogram.hint')}
+            </Typography>
+          </Box>
+        )
+      )}
+    </Dialog>
+  );
+}
@@ -0,0 +1,48 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import * as Protocol from "./protocol";
+
+/** A bitrate in kbit/s, switching to Mbit/s from 1000 kbit/s on. */
+export function formatBitrate(kbps: number): string {
+  return kbps >= 1000 ? `${(kbps / 1000).toFixed(2)} Mb/s` : `${kbps.toFixed(0)} kb/s`;
+}
+
+export function seriesLabel(series: Protocol.BitrateSeries): string {
+  return `${series.stream} #${series.num + 1} (${series.codec})`;
+}
+
+/**
+ * Reduces a series to at most `columns` points, keeping the maximum of each
+ * column so short peaks stay visible when a long file is drawn narrow.
+ */
+export function downsampleMax(points: number[], columns: number): number[] {
+  if (points.length <= columns) {
+    return points;
+  }
+  const result: number[] = [];
+  for (let column = 0; column < columns; column++) {
+    const start = Math.floor((column * points.length) / columns);
+    const end = Math.max(start + 1, Math.floor(((column + 1) * points.length) / columns));
+    let max = 0;
+    for (let i = start; i < end; i++) {
+      max = Math.max(max, points[i]);
+    }
+    result.push(max);
+  }
+  return result;
+}
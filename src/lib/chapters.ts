@@ -0,0 +1,82 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+/** A chapter to write: where it starts, in seconds, and its title. */
+export interface ChapterMark {
+  start: number;
+  title: string;
+}
+
+function escapeXml(value: string): string {
+  return value
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;");
+}
+
+/** `HH:MM:SS.fff…` with `digits` fractional digits, as both chapter formats expect. */
+export function formatChapterTime(seconds: number, digits: number): string {
+  const scale = 10 ** digits;
+  const total = Math.round(Math.max(0, seconds) * scale);
+  const fraction = total % scale;
+  const whole = Math.floor(total / scale);
+  const h = Math.floor(whole / 3600);
+  const m = Math.floor((whole % 3600) / 60);
+  const s = whole % 60;
+  const clock = [h, m, s].map((n) => String(n).padStart(2, "0")).join(":");
+  return digits > 0 ? `${clock}.${String(fraction).padStart(digits, "0")}` : clock;
+}
+
+/** Chapters as mkvmerge's Matroska XML chapter file (`--chapters file.xml`). */
+export function toMatroskaChapters(marks: ChapterMark[], language = "und"): string {
+  const atoms = [...marks]
+    .sort((a, b) => a.start - b.start)
+    .map((mark) =>
+      [
+        "    <ChapterAtom>",
+        `      <ChapterTimeStart>${formatChapterTime(mark.start, 9)}</ChapterTimeStart>`,
+        "      <ChapterDisplay>",
+        `        <ChapterString>${escapeXml(mark.title)}</ChapterString>`,
+        `        <ChapterLanguage>${language}</ChapterLanguage>`,
+        "      </ChapterDisplay>",
+        "    </ChapterAtom>",
+      ].join("\n")
+    );
+  return [
+    '<?xml version="1.0" encoding="UTF-8"?>',
+    '<!DOCTYPE Chapters SYSTEM "matroskachapters.dtd">',
+    "<Chapters>",
+    "  <EditionEntry>",
+    ...atoms,
+    "  </EditionEntry>",
+    "</Chapters>",
+    "",
+  ].join("\n");
+}
+
+/** Chapters as an OGM (`CHAPTERxx=` / `CHAPTERxxNAME=`) text file, which mkvmerge also reads. */
+export function toOgmChapters(marks: ChapterMark[]): string {
+  const digits = Math.max(2, String(marks.length).length);
+  return [...marks]
+    .sort((a, b) => a.start - b.start)
+    .map((mark, index) => {
+      const id = `CHAPTER${String(index + 1).padStart(digits, "0")}`;
+      return `${id}=${formatChapterTime(mark.start, 3)}\n${id}NAME=${mark.title}\n`;
+    })
+    .join("");
+}
@@ -70,6 +70,15 @@ export async function openFileDialog(append: boolean) {
   }
 }
 
+export async function openVideoFileDialog(defaultPath?: string) {
+  initFilters();
+  return await open({
+    multiple: false,
+    defaultPath,
+    filters: filters.slice(0, 1),
+  });
+}
+
 export async function openSaveJsonCodeFileDialog() {
   return await save({
     filters: [{ name: i18n.t("fileFilter.json"), extensions: ["json"] }],
@@ -90,3 +99,24 @@ export async function openSaveExportFileDialog(
     filters: [{ name: i18n.t(`fileFilter.${format}`), extensions: [ext] }],
   });
 }
+
+export async function openSaveTextFileDialog(
+  filter: string,
+  extension: string,
+  defaultPath: string,
+) {
+  return await save({
+    defaultPath,
+    filters: [{ name: i18n.t(`fileFilter.${filter}`), extensions: [extension] }],
+  });
+}
+
+export async function openSaveImageFileDialog(
+  extension: string,
+  defaultPath: string,
+) {
+  return await save({
+    defaultPath,
+    filters: [{ name: i18n.t(`fileFilter.${extension}`), extensions: [extension] }],
+  });
+}
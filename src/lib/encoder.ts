@@ -0,0 +1,46 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import * as Protocol from "./protocol";
+
+export const ENCODER_KIND_LABELS: Record<Protocol.EncoderKind, string> = {
+  [Protocol.EncoderKind.X264]: "x264",
+  [Protocol.EncoderKind.X265]: "x265",
+  [Protocol.EncoderKind.SvtAv1]: "SVT-AV1",
+  [Protocol.EncoderKind.Unknown]: "?",
+};
+
+const RATE_CONTROL_LABELS: Record<Protocol.EncoderRateControl, string> = {
+  [Protocol.EncoderRateControl.Crf]: "CRF",
+  [Protocol.EncoderRateControl.Cqp]: "CQP",
+  [Protocol.EncoderRateControl.Abr]: "ABR",
+  [Protocol.EncoderRateControl.Cbr]: "CBR",
+  [Protocol.EncoderRateControl.Vbr]: "VBR",
+  [Protocol.EncoderRateControl.TwoPass]: "2-pass",
+};
+
+/** `CRF 18.0`, `ABR 5000 kb/s`, just the mode, or null when it is unknown. */
+export function rateControlLabel(settings: Protocol.EncoderSettings): string | null {
+  if (settings.rateControl === null) {
+    return null;
+  }
+  const mode = RATE_CONTROL_LABELS[settings.rateControl];
+  if (settings.quality !== null) {
+    return `${mode} ${settings.quality.toFixed(1)}`;
+  }
+  return settings.bitrate !== null ? `${mode} ${settings.bitrate} kb/s` : mode;
+}
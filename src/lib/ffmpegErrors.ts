@@ -0,0 +1,46 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import type { TFunction } from 'i18next';
+
+// The errors any ffmpeg-backed command can fail with, as readable messages.
+// Dialogs check their own codes first and fall back to this; other errors are
+// shown as they are.
+export function describeFfmpegError(t: TFunction, error: unknown): string {
+  const message = String(error);
+  if (/FFMPEG_NOT_AVAILABLE:/.test(message)) {
+    return t('ffprobe.ffmpegNotAvailable');
+  }
+  const ffprobeMissing = message.match(/FFPROBE_NOT_AVAILABLE:(.*)$/);
+  if (ffprobeMissing) {
+    return t('ffprobe.ffprobeNotAvailable', { path: ffprobeMissing[1] });
+  }
+  const tooOld = message.match(/FFMPEG_TOO_OLD:([^:]+):([^:]+):(.+)$/);
+  if (tooOld) {
+    return t('ffmpegTools.ffmpegTooOld', { feature: tooOld[1], required: tooOld[2], found: tooOld[3] });
+  }
+  const filterMissing = message.match(/FFMPEG_FILTER_MISSING:(.+)$/);
+  if (filterMissing) {
+    return t('ffmpegTools.ffmpegFilterMissing', { filter: filterMissing[1] });
+  }
+  return message;
+}
+
+// A cancelled analysis rejects with this code; it isn't reported as a failure.
+export function isFfmpegAnalysisCancelled(error: unknown): boolean {
+  return /FFMPEG_ANALYSIS_CANCELLED/.test(String(error));
+}
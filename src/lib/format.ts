@@ -17,6 +17,11 @@
 
 import * as Protocol from "../lib/protocol";
 
+export function basename(path: string): string {
+  const parts = path.split(/[/\\]/);
+  return parts[parts.length - 1] || path;
+}
+
 export function formatStreamCount(
   streamCountMap: Map<string, Protocol.StreamCount> | undefined
 ): string {
@@ -0,0 +1,65 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import { formatChapterTime } from "./chapters";
+
+export enum KeyframeExportFormat {
+  Csv = "csv",
+  Text = "text",
+}
+
+/** File extension and `fileFilter` key of each export format. */
+export const KEYFRAME_EXPORT_FILES: Record<KeyframeExportFormat, { extension: string; filter: string }> = {
+  [KeyframeExportFormat.Csv]: { extension: "csv", filter: "csv" },
+  [KeyframeExportFormat.Text]: { extension: "txt", filter: "text" },
+};
+
+/**
+ * The keyframes to split at: each at least `minSpacing` seconds after the
+ * previous split, or after the start for the first. A split at 0 is none.
+ */
+export function splitKeyframes(keyframes: number[], minSpacing: number): number[] {
+  const kept: number[] = [];
+  let last = 0;
+  for (const keyframe of keyframes) {
+    if (keyframe > 0 && keyframe - last >= minSpacing) {
+      kept.push(keyframe);
+      last = keyframe;
+    }
+  }
+  return kept;
+}
+
+/**
+ * The mkvmerge `--split` value that cuts at `keyframes`. mkvmerge splits at
+ * the first keyframe at or after each timestamp, so cutting exactly on one
+ * keeps every part starting where expected.
+ */
+export function toMkvmergeSplit(keyframes: number[]): string {
+  return `timestamps:${keyframes.map((keyframe) => formatChapterTime(keyframe, 3)).join(",")}`;
+}
+
+export function renderKeyframes(keyframes: number[], format: KeyframeExportFormat): string {
+  if (format === KeyframeExportFormat.Csv) {
+    return [
+      "index,seconds,timestamp",
+      ...keyframes.map((keyframe, index) => `${index + 1},${keyframe.toFixed(6)},${formatChapterTime(keyframe, 3)}`),
+      "",
+    ].join("\n");
+  }
+  return keyframes.map((keyframe) => `${formatChapterTime(keyframe, 3)}\n`).join("");
+}
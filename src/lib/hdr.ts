@@ -0,0 +1,58 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import * as Protocol from "./protocol";
+
+export const HDR_TRANSFER_LABELS: Record<Protocol.HdrTransfer, string> = {
+  [Protocol.HdrTransfer.Sdr]: "SDR",
+  [Protocol.HdrTransfer.Pq]: "PQ",
+  [Protocol.HdrTransfer.Hlg]: "HLG",
+};
+
+/** `8.1` for profile 8 with an HDR10 compatible base layer, otherwise just the profile. */
+export function dolbyVisionProfile(dolbyVision: Protocol.HdrDolbyVision): string {
+  if (dolbyVision.profile === null) {
+    return "?";
+  }
+  return dolbyVision.profile === 8 && dolbyVision.compatibilityId !== null
+    ? `${dolbyVision.profile}.${dolbyVision.compatibilityId}`
+    : String(dolbyVision.profile);
+}
+
+/** CIE 1931 `x, y` with four decimals, or `-`. */
+export function formatChromaticity(point: [number, number] | null): string {
+  return point ? `${point[0].toFixed(4)}, ${point[1].toFixed(4)}` : "-";
+}
+
+/** Nits with more decimals the darker they are: `1000`, `0.0050`. */
+export function formatNits(nits: number | null): string {
+  if (nits === null) {
+    return "-";
+  }
+  return nits >= 100 ? nits.toFixed(0) : nits >= 1 ? nits.toFixed(1) : nits.toFixed(4);
+}
+
+/** The normalized PQ (SMPTE ST 2084) signal value of `nits`, the scale HDR charts use. */
+export function nitsToPq(nits: number): number {
+  const m1 = 2610 / 16384;
+  const m2 = (2523 / 4096) * 128;
+  const c1 = 3424 / 4096;
+  const c2 = (2413 / 4096) * 32;
+  const c3 = (2392 / 4096) * 32;
+  const power = Math.pow(Math.min(1, Math.max(0, nits / 10000)), m1);
+  return Math.pow((c1 + c2 * power) / (1 + c3 * power), m2);
+}
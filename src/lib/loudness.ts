@@ -0,0 +1,52 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import * as Protocol from "./protocol";
+
+/** Short label of each loudness target, as used in reports. */
+export const LOUDNESS_TARGET_LABELS: Record<Protocol.LoudnessTarget, string> = {
+  [Protocol.LoudnessTarget.EbuR128]: "EBU R128 (-23 LUFS)",
+  [Protocol.LoudnessTarget.AtscA85]: "ATSC A/85 (-24 LKFS)",
+  [Protocol.LoudnessTarget.Streaming14]: "Streaming (-14 LUFS)",
+  [Protocol.LoudnessTarget.Streaming16]: "Streaming (-16 LUFS)",
+};
+
+export function formatLoudness(value: number | null, unit: string): string {
+  return value === null ? "-" : `${value.toFixed(1)} ${unit}`;
+}
+
+/**
+ * The report entries for one measured audio stream, appended to that
+ * stream's properties when a loudness measurement is attached to an export.
+ */
+export function loudnessEntries(
+  measurement: Protocol.LoudnessMeasurement,
+  target: Protocol.LoudnessTarget
+): Array<[string, string]> {
+  const check = measurement.checks.find((c) => c.target === target);
+  const entries: Array<[string, string]> = [
+    ["IntegratedLoudness", formatLoudness(measurement.integrated, "LUFS")],
+    ["LoudnessRange", formatLoudness(measurement.lra, "LU")],
+    ["TruePeak", formatLoudness(measurement.truePeak, "dBTP")],
+    ["MomentaryLoudnessMax", formatLoudness(measurement.momentaryMax, "LUFS")],
+    ["ShortTermLoudnessMax", formatLoudness(measurement.shortTermMax, "LUFS")],
+  ];
+  if (check) {
+    entries.push(["LoudnessTarget", `${LOUDNESS_TARGET_LABELS[target]}: ${check.passed ? "Pass" : "Fail"}`]);
+  }
+  return entries;
+}
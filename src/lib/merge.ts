@@ -64,22 +64,27 @@ export function openMergeWindow(file: string) {
 
 /**
  * Editable fields for the General stream. Exactly one General per file.
+ * `chaptersFile` is a chapter file for mkvmerge `--chapters` that replaces
+ * the source chapters, empty for none.
  */
 export class MergeGeneralData {
   title: string;
+  chaptersFile: string;
 
-  constructor(title = '') {
+  constructor(title = '', chaptersFile = '') {
     this.title = title;
+    this.chaptersFile = chaptersFile;
   }
 
   clone(): MergeGeneralData {
-    return new MergeGeneralData(this.title);
+    return new MergeGeneralData(this.title, this.chaptersFile);
   }
 }
 
 /**
  * Editable fields for a single video track. `isEnabled` controls whether
- * the track is muxed into the merge output.
+ * the track is muxed into the merge output. `cropping` is the mkvmerge
+ * `--cropping` value (`left,top,right,bottom`), empty for none.
  */
 export class MergeVideoData {
   num: number;
@@ -88,18 +93,36 @@ export class MergeVideoData {
   language: string;
   isDefault: boolean;
   isForced: boolean;
+  cropping: string;
 
-  constructor(num: number, isEnabled = true, title = '', language = '', isDefault = false, isForced = false) {
+  constructor(
+    num: number,
+    isEnabled = true,
+    title = '',
+    language = '',
+    isDefault = false,
+    isForced = false,
+    cropping = '',
+  ) {
     this.num = num;
     this.isEnabled = isEnabled;
     this.title = title;
     this.language = language;
     this.isDefault = isDefault;
     this.isForced = isForced;
+    this.cropping = cropping;
   }
 
   clone(): MergeVideoData {
-    return new MergeVideoData(this.num, this.isEnabled, this.title, this.language, this.isDefault, this.isForced);
+    return new MergeVideoData(
+      this.num,
+      this.isEnabled,
+      this.title,
+      this.language,
+      this.isDefault,
+      this.isForced,
+      this.cropping,
+    );
   }
 }
 
@@ -269,6 +292,12 @@ export class MergeData {
     return next;
   }
 
+  withGeneralChaptersFile(value: string): MergeData {
+    const next = this.clone();
+    next.general.chaptersFile = value;
+    return next;
+  }
+
   withVideoEnabled(num: number, value: boolean): MergeData {
     const next = this.clone();
     const track = next.findVideo(num);
@@ -304,6 +333,13 @@ export class MergeData {
     return next;
   }
 
+  withVideoCropping(num: number, value: string): MergeData {
+    const next = this.clone();
+    const track = next.findVideo(num);
+    if (track) { track.cropping = value; }
+    return next;
+  }
+
   withAudioEnabled(num: number, value: boolean): MergeData {
     const next = this.clone();
     const track = next.findAudio(num);
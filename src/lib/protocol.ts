@@ -102,8 +102,22 @@ export interface ConfigMpcHc {
   path: string;
 }
 
+export enum AudioCodec {
+  Flac = "Flac",
+  Opus = "Opus",
+  Aac = "Aac",
+  Wav = "Wav",
+}
+
+export interface ConfigAudioPreset {
+  name: string;
+  codec: AudioCodec;
+  bitrate: number;
+}
+
 export interface ConfigFfmpeg {
   path: string;
+  audioPresets: Array<ConfigAudioPreset>;
 }
 
 export interface ConfigCardView {
@@ -157,6 +171,60 @@ export interface ConfigWindow {
   size: ConfigWindowSize;
 }
 
+export enum ValidationOperator {
+  Exists = "Exists",
+  Equals = "Equals",
+  NotEquals = "NotEquals",
+  Contains = "Contains",
+  Min = "Min",
+  Max = "Max",
+}
+
+export interface ConfigValidationRule {
+  stream: StreamKind;
+  property: string;
+  operator: ValidationOperator;
+  value: string;
+}
+
+export interface ConfigValidationProfile {
+  name: string;
+  rules: ConfigValidationRule[];
+}
+
+export interface ConfigValidation {
+  profiles: ConfigValidationProfile[];
+}
+
+export enum WatchReportTarget {
+  Folder = "Folder",
+  Log = "Log",
+}
+
+export interface ConfigWatchFolder {
+  path: string;
+  enabled: boolean;
+  recursive: boolean;
+  polling: boolean;
+  pollIntervalSeconds: number;
+  settleSeconds: number;
+  extensions: string[];
+  profiles: string[];
+  hash: boolean;
+  report: WatchReportTarget;
+  logFile: string;
+}
+
+export interface ConfigWatch {
+  folders: ConfigWatchFolder[];
+}
+
+export interface ConfigServer {
+  enabled: boolean;
+  port: number;
+  token: string;
+}
+
 export interface ConfigTemplateGroup {
   properties: string[];
 }
@@ -190,6 +258,9 @@ export interface Config {
   update: ConfigUpdate;
   window: ConfigWindow;
   templates: ConfigTemplates;
+  validation: ConfigValidation;
+  watch: ConfigWatch;
+  server: ConfigServer;
 }
 
 export enum DisplayMode {
@@ -294,10 +365,317 @@ export interface StreamProperty {
   property: String;
 }
 
+export enum PropertySource {
+  MediaInfo = "MediaInfo",
+  Ffprobe = "Ffprobe",
+}
+
 export interface StreamPropertyMap {
   stream: StreamKind;
   num: number;
   propertyMap: Record<string, string>;
+  source?: PropertySource;
+}
+
+export enum FfprobeFrames {
+  None = "None",
+  Sampled = "Sampled",
+  All = "All",
+}
+
+export interface ProbeField {
+  stream: StreamKind;
+  num: number;
+  property: string;
+  mediaInfo: string | null;
+  ffprobe: string | null;
+  mismatch: boolean;
+}
+
+export interface ProbeComparison {
+  ffprobe: Array<StreamPropertyMap>;
+  fields: Array<ProbeField>;
+}
+
+export enum AudioDownmix {
+  Keep = "Keep",
+  Stereo = "Stereo",
+  Mono = "Mono",
+}
+
+export enum CapturePointSource {
+  Chapters = "Chapters",
+  Keyframes = "Keyframes",
+  Timestamps = "Timestamps",
+}
+
+export enum ClipFormat {
+  Gif = "Gif",
+  Webp = "Webp",
+  Apng = "Apng",
+}
+
+export enum ClipSegments {
+  Range = "Range",
+  Even = "Even",
+}
+
+export enum ContactSheetFormat {
+  Png = "Png",
+  Jpeg = "Jpeg",
+}
+
+export enum ContactSheetPositions {
+  Even = "Even",
+  Chapters = "Chapters",
+}
+
+export interface AudioExportOptions {
+  output: string;
+  stream: number;
+  preset: ConfigAudioPreset;
+  downmix: AudioDownmix;
+  normalize: LoudnessTarget | null;
+  start: number;
+  end: number;
+}
+
+export interface AudioExportResult {
+  output: string;
+  size: number;
+  duration: number;
+}
+
+export interface BitrateGraph {
+  window: number;
+  duration: number;
+  series: Array<BitrateSeries>;
+}
+
+export interface BitrateSeries {
+  stream: StreamKind;
+  num: number;
+  codec: string;
+  points: Array<number>;
+  average: number;
+  min: number;
+  max: number;
+}
+
+export interface CapturePoint {
+  seconds: number;
+  label: string;
+}
+
+export interface ClipOptions {
+  output: string;
+  format: ClipFormat;
+  segments: ClipSegments;
+  start: number;
+  end: number;
+  count: number;
+  length: number;
+  fps: number;
+  maxWidth: number;
+  maxHeight: number;
+  targetSize: number;
+}
+
+export interface ClipResult {
+  output: string;
+  size: number;
+  width: number;
+  height: number;
+  fps: number;
+  duration: number;
+  attempts: number;
+  targetMet: boolean;
+}
+
+export interface ContactSheetOptions {
+  output: string;
+  format: ContactSheetFormat;
+  positions: ContactSheetPositions;
+  count: number;
+  columns: number;
+  width: number;
+  header: boolean;
+  timestamps: boolean;
+}
+
+export interface ContactSheetResult {
+  output: string;
+  frames: number;
+  width: number;
+  height: number;
+}
+
+export interface CropDetection {
+  width: number;
+  height: number;
+  left: number;
+  top: number;
+  right: number;
+  bottom: number;
+  aspectRatio: number;
+  samples: number;
+  ignored: number;
+  agreement: number;
+  mkvmergeCropping: string;
+  ffmpegCrop: string;
+}
+
+export interface EncoderComparison {
+  settings: EncoderSettings;
+  other: EncoderSettings | null;
+  fields: EncoderField[];
+}
+
+export interface EncoderField {
+  key: string;
+  group: EncoderSettingGroup;
+  left: string | null;
+  right: string | null;
+  mismatch: boolean;
+}
+
+export enum EncoderKind {
+  X264 = "X264",
+  X265 = "X265",
+  SvtAv1 = "SvtAv1",
+  Unknown = "Unknown",
+}
+
+export interface EncoderPreset {
+  name: string;
+  matched: number;
+  compared: number;
+}
+
+export enum EncoderRateControl {
+  Crf = "Crf",
+  Cqp = "Cqp",
+  Abr = "Abr",
+  Cbr = "Cbr",
+  Vbr = "Vbr",
+  TwoPass = "TwoPass",
+}
+
+export interface EncoderSetting {
+  key: string;
+  value: string;
+  group: EncoderSettingGroup;
+}
+
+export enum EncoderSettingGroup {
+  RateControl = "RateControl",
+  FrameStructure = "FrameStructure",
+  AdaptiveQuantization = "AdaptiveQuantization",
+  MotionEstimation = "MotionEstimation",
+  Other = "Other",
+}
+
+export interface EncoderSettings {
+  encoder: EncoderKind;
+  library: string | null;
+  rateControl: EncoderRateControl | null;
+  quality: number | null;
+  bitrate: number | null;
+  preset: EncoderPreset | null;
+  settings: EncoderSetting[];
+}
+
+export interface GopFrameTypes {
+  frames: FfprobeFrames;
+  iFrames: number;
+  pFrames: number;
+  bFrames: number;
+  idrFrames: number;
+  nonIdrIFrames: number;
+  maxConsecutiveB: number;
+  pattern: string | null;
+}
+
+export interface GopReport {
+  keyframes: number[];
+  gops: number;
+  openGops: number;
+  closedGops: number;
+  lengthFrames: GopStats;
+  lengthSeconds: GopStats;
+  // Null when no frames were decoded.
+  frameTypes: GopFrameTypes | null;
+}
+
+export interface GopStats {
+  min: number;
+  max: number;
+  mean: number;
+}
+
+export interface HdrDolbyVision {
+  profile: number | null;
+  level: number | null;
+  compatibilityId: number | null;
+  rpu: boolean;
+  enhancementLayer: boolean;
+  rpuFrames: number;
+}
+
+export interface HdrLuminance {
+  source: string;
+  maxNits: number;
+  avgNits: number;
+  scenes: HdrScene[];
+  truncated: boolean;
+}
+
+export interface HdrMasteringDisplay {
+  primaries: string | null;
+  red: [number, number] | null;
+  green: [number, number] | null;
+  blue: [number, number] | null;
+  whitePoint: [number, number] | null;
+  minLuminance: number | null;
+  maxLuminance: number | null;
+}
+
+export interface HdrReport {
+  frames: FfprobeFrames;
+  streams: HdrStream[];
+}
+
+export interface HdrScene {
+  start: number;
+  end: number;
+  frames: number;
+  maxNits: number;
+  avgNits: number;
+}
+
+export interface HdrStream {
+  num: number;
+  hdrFormat: string | null;
+  compatibility: string | null;
+  transfer: HdrTransfer;
+  transferName: string | null;
+  colorPrimaries: string | null;
+  matrixCoefficients: string | null;
+  bitDepth: number | null;
+  masteringDisplay: HdrMasteringDisplay | null;
+  maxCll: number | null;
+  maxFall: number | null;
+  dolbyVision: HdrDolbyVision | null;
+  hdr10Plus: boolean;
+  hdr10PlusFrames: number;
+  decodedFrames: number;
+  luminance: HdrLuminance | null;
+}
+
+export enum HdrTransfer {
+  Sdr = "Sdr",
+  Pq = "Pq",
+  Hlg = "Hlg",
 }
 
 export interface MkvTrack {
@@ -313,6 +691,18 @@ export interface MkvTrack {
 export interface MkvToolNixStatus {
   found: boolean;
   mkvToolNixPath: string;
+  version: MkvToolNixVersion | null;
+}
+
+export interface VersionNumber {
+  major: number;
+  minor: number;
+  patch: number;
+}
+
+export interface MkvToolNixVersion {
+  text: string;
+  number: VersionNumber | null;
 }
 
 export interface BatchMkvExtractStatus {
@@ -333,6 +723,69 @@ export interface MpcHcStatus {
 export interface FfmpegStatus {
   found: boolean;
   path: string;
+  version: FfmpegVersion | null;
+}
+
+export interface FfmpegLibraryVersion {
+  name: string;
+  version: string;
+}
+
+export interface FfmpegVersion {
+  text: string;
+  number: VersionNumber | null;
+  snapshot: boolean;
+  configuration: string[];
+  enabled: string[];
+  libraries: FfmpegLibraryVersion[];
+  filters: string[];
+}
+
+export enum ToolCandidateSource {
+  Configured = "Configured",
+  Running = "Running",
+  Fallback = "Fallback",
+  Path = "Path",
+  PackageManager = "PackageManager",
+  Flatpak = "Flatpak",
+}
+
+export interface ToolCandidate {
+  path: string;
+  source: ToolCandidateSource;
+  version: string | null;
+}
+
+export enum SpreadsheetFormat {
+  Csv = "Csv",
+  Xlsx = "Xlsx",
+}
+
+export interface SpreadsheetExportOptions {
+  format: SpreadsheetFormat;
+  output: string;
+  // One row per stream (one sheet per stream kind) instead of one row per file.
+  perStream: boolean;
+}
+
+export interface SpreadsheetExportResult {
+  outputs: string[];
+  files: number;
+  failed: string[];
+}
+
+export interface ValidationResult {
+  profile: string;
+  passed: boolean;
+  failures: string[];
+}
+
+export interface WatchFolderReportEvent {
+  folder: string;
+  file: string;
+  report: string;
+  passed: boolean;
+  error: string | null;
 }
 
 export interface BDStatus {
@@ -375,14 +828,207 @@ export interface FfmpegCaptureFrame {
   bytes: number[];
 }
 
+export interface FfmpegAnalysisProgress {
+  // Which analysis the progress refers to, e.g. "qc".
+  analysis: string;
+  percent: number;
+}
+
+export enum FrameAlignment {
+  Timestamp = "Timestamp",
+  Frame = "Frame",
+}
+
+export interface FrameCompareOptions {
+  alignment: FrameAlignment;
+  // Seconds or frame numbers, by alignment.
+  positions: number[];
+  outputDir: string;
+  // Factor the difference image is amplified by.
+  gain: number;
+}
+
+export interface FrameComparison {
+  // Size of the reference, which the distorted frames are scaled to.
+  width: number;
+  height: number;
+  scaled: boolean;
+  pairs: FramePair[];
+}
+
+export interface FramePair {
+  position: number;
+  referenceTime: number;
+  distortedTime: number;
+  // PSNR of the RGB channels in dB.
+  psnr: number;
+  reference: string;
+  distorted: string;
+  sideBySide: string;
+  difference: string;
+}
+
+export enum LoudnessTarget {
+  EbuR128 = "EbuR128",
+  AtscA85 = "AtscA85",
+  Streaming14 = "Streaming14",
+  Streaming16 = "Streaming16",
+}
+
+export interface LoudnessCheck {
+  target: LoudnessTarget;
+  integratedOk: boolean;
+  truePeakOk: boolean;
+  passed: boolean;
+}
+
+export interface LoudnessMeasurement {
+  // Audio stream index, matching StreamPropertyMap.num of the audio stream.
+  num: number;
+  integrated: number | null;
+  lra: number | null;
+  truePeak: number | null;
+  momentaryMax: number | null;
+  shortTermMax: number | null;
+  checks: LoudnessCheck[];
+}
+
+export interface LoudnessReport {
+  duration: number;
+  streams: LoudnessMeasurement[];
+}
+
+export enum QcEventKind {
+  Black = "Black",
+  Freeze = "Freeze",
+  Silence = "Silence",
+}
+
+export interface QcInterval {
+  kind: QcEventKind;
+  start: number;
+  end: number;
+  duration: number;
+}
+
+export interface QcOptions {
+  black: boolean;
+  freeze: boolean;
+  silence: boolean;
+  blackMinDuration: number;
+  blackPixelThreshold: number;
+  freezeNoise: number;
+  freezeMinDuration: number;
+  silenceNoise: number;
+  silenceMinDuration: number;
+}
+
+export interface QcTimeline {
+  duration: number;
+  intervals: QcInterval[];
+  // Requested detectors that were not run because the file has no stream for them.
+  skipped: QcEventKind[];
+}
+
+export interface QualityFrame {
+  frame: number;
+  time: number;
+  score: number;
+  reference: string;
+  distorted: string;
+}
+
+export enum QualityMetric {
+  Psnr = "Psnr",
+  Ssim = "Ssim",
+  Vmaf = "Vmaf",
+}
+
+export interface QualityOptions {
+  metrics: QualityMetric[];
+  worstFrames: number;
+  outputDir: string;
+}
+
+export interface QualityReport {
+  width: number;
+  height: number;
+  frameRate: number;
+  frames: number;
+  scaled: boolean;
+  resampled: boolean;
+  scores: QualityScore[];
+  worstMetric: QualityMetric | null;
+  worstFrames: QualityFrame[];
+  skipped: QualityMetric[];
+}
+
+export interface QualityScore {
+  metric: QualityMetric;
+  mean: number;
+  min: number;
+  max: number;
+  series: number[];
+}
+
+export interface SceneChapter {
+  start: number;
+  score: number;
+  snapped: boolean;
+}
+
+export interface SceneChapters {
+  duration: number;
+  cuts: number;
+  keyframes: number;
+  chapters: SceneChapter[];
+}
+
+export interface SceneOptions {
+  threshold: number;
+  minSpacing: number;
+  snapToKeyframes: boolean;
+}
+
+export interface SpectrogramOptions {
+  stream: number;
+  output: string;
+  width: number;
+  height: number;
+}
+
+export interface SpectrogramReport {
+  output: string;
+  sampleRate: number;
+  duration: number;
+  peak: number;
+  clippedSamples: number;
+  // Peak (dBFS) and clipped samples of each channel.
+  channelPeaks: Array<number>;
+  channelClipped: Array<number>;
+  // Likely lowpass cutoff in Hz; null when the spectrum runs to Nyquist.
+  cutoff: number | null;
+}
+
+export interface FilesOpened {
+  files: string[];
+}
+
 export interface FfmpegTrimOptions {
   enabled: boolean;
   color: string;
   tolerance: number;
+  mode: TrimMode;
   // Number of worker threads for the trim pass (defaults to CPU core count).
   threads: number;
 }
 
+export enum TrimMode {
+  Each = "Each",
+  Union = "Union",
+  Median = "Median",
+}
+
 export enum TabType {
   About,
   Config,
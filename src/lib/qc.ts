@@ -0,0 +1,70 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import * as Protocol from "./protocol";
+import { formatChapterTime, toMatroskaChapters, toOgmChapters } from "./chapters";
+
+export enum QcExportFormat {
+  Csv = "csv",
+  Json = "json",
+  MatroskaChapters = "matroska",
+  OgmChapters = "ogm",
+}
+
+/** File extension and `fileFilter` key of each export format. */
+export const QC_EXPORT_FILES: Record<QcExportFormat, { extension: string; filter: string }> = {
+  [QcExportFormat.Csv]: { extension: "csv", filter: "csv" },
+  [QcExportFormat.Json]: { extension: "json", filter: "json" },
+  [QcExportFormat.MatroskaChapters]: { extension: "xml", filter: "xml" },
+  [QcExportFormat.OgmChapters]: { extension: "txt", filter: "text" },
+};
+
+/**
+ * Render a QC timeline for export. Chapter formats get one chapter at the
+ * start of every interval, titled with its kind and length, so a player can
+ * jump straight to each finding.
+ */
+export function renderQcTimeline(
+  timeline: Protocol.QcTimeline,
+  format: QcExportFormat,
+  kindLabel: (kind: Protocol.QcEventKind) => string
+): string {
+  switch (format) {
+    case QcExportFormat.Csv:
+      return [
+        "kind,start,end,duration",
+        ...timeline.intervals.map((interval) =>
+          [
+            interval.kind,
+            interval.start.toFixed(3),
+            interval.end.toFixed(3),
+            interval.duration.toFixed(3),
+          ].join(",")
+        ),
+        "",
+      ].join("\n");
+    case QcExportFormat.Json:
+      return JSON.stringify(timeline, null, 2);
+    default: {
+      const marks = timeline.intervals.map((interval) => ({
+        start: interval.start,
+        title: `${kindLabel(interval.kind)} (${formatChapterTime(interval.duration, 3)})`,
+      }));
+      return format === QcExportFormat.MatroskaChapters ? toMatroskaChapters(marks) : toOgmChapters(marks);
+    }
+  }
+}
@@ -0,0 +1,63 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import * as Protocol from "./protocol";
+
+export const QUALITY_METRIC_LABELS: Record<Protocol.QualityMetric, string> = {
+  [Protocol.QualityMetric.Psnr]: "PSNR",
+  [Protocol.QualityMetric.Ssim]: "SSIM",
+  [Protocol.QualityMetric.Vmaf]: "VMAF",
+};
+
+/** Upper bound of each metric's scale, for charts. PSNR is capped at 100 dB. */
+export const QUALITY_METRIC_MAX: Record<Protocol.QualityMetric, number> = {
+  [Protocol.QualityMetric.Psnr]: 100,
+  [Protocol.QualityMetric.Ssim]: 1,
+  [Protocol.QualityMetric.Vmaf]: 100,
+};
+
+export function formatQualityScore(metric: Protocol.QualityMetric, value: number): string {
+  switch (metric) {
+    case Protocol.QualityMetric.Psnr:
+      return `${value.toFixed(2)} dB`;
+    case Protocol.QualityMetric.Ssim:
+      return value.toFixed(4);
+    default:
+      return value.toFixed(2);
+  }
+}
+
+/**
+ * Reduces a per-frame series to at most `columns` points, keeping the lowest
+ * value of each column so drops in quality stay visible.
+ */
+export function downsampleMin(points: number[], columns: number): number[] {
+  if (points.length <= columns) {
+    return points;
+  }
+  const result: number[] = [];
+  for (let column = 0; column < columns; column++) {
+    const start = Math.floor((column * points.length) / columns);
+    const end = Math.max(start + 1, Math.floor(((column + 1) * points.length) / columns));
+    let min = Infinity;
+    for (let i = start; i < end; i++) {
+      min = Math.min(min, points[i]);
+    }
+    result.push(min);
+  }
+  return result;
+}
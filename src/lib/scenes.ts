@@ -0,0 +1,46 @@
+/*
+ *   Copyright (c) 2024-2026. caoccao.com Sam Cao
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+import * as Protocol from "./protocol";
+import { type ChapterMark, toMatroskaChapters, toOgmChapters } from "./chapters";
+
+export enum SceneChaptersFormat {
+  Matroska = "matroska",
+  Ogm = "ogm",
+}
+
+/** File extension and `fileFilter` key of each chapter format. */
+export const SCENE_CHAPTERS_FILES: Record<SceneChaptersFormat, { extension: string; filter: string }> = {
+  [SceneChaptersFormat.Matroska]: { extension: "xml", filter: "xml" },
+  [SceneChaptersFormat.Ogm]: { extension: "txt", filter: "text" },
+};
+
+/** The proposed chapters as marks titled by `title(number)`, numbered from 1. */
+export function sceneChapterMarks(
+  chapters: Protocol.SceneChapter[],
+  title: (number: string) => string
+): ChapterMark[] {
+  const digits = Math.max(2, String(chapters.length).length);
+  return chapters.map((chapter, index) => ({
+    start: chapter.start,
+    title: title(String(index + 1).padStart(digits, "0")),
+  }));
+}
+
+export function renderSceneChapters(marks: ChapterMark[], format: SceneChaptersFormat): string {
+  return format === SceneChaptersFormat.Matroska ? toMatroskaChapters(marks) : toOgmChapters(marks);
+}
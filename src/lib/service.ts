@@ -58,6 +58,14 @@ export async function skipVersion(version: string): Promise<void> {
   return await invoke<void>("skip_version", { version });
 }
 
+export async function getCapturePoints(
+  file: string,
+  source: Protocol.CapturePointSource,
+  timestamps: number[] = []
+): Promise<Protocol.CapturePoint[]> {
+  return await invoke<Protocol.CapturePoint[]>("get_capture_points", { file, source, timestamps });
+}
+
 export async function getConfig(): Promise<Protocol.Config> {
   return await invoke<Protocol.Config>("get_config");
 }
@@ -66,6 +74,10 @@ export async function getMkvTracks(file: string): Promise<Array<Protocol.MkvTrac
   return await invoke<Array<Protocol.MkvTrack>>("get_mkv_tracks", { file });
 }
 
+export async function getMkvtoolnixCandidates(): Promise<Array<Protocol.ToolCandidate>> {
+  return await invoke<Array<Protocol.ToolCandidate>>("get_mkvtoolnix_candidates");
+}
+
 export async function getMkvtoolnixStatus(path: string, checkRunning: boolean = false): Promise<Protocol.MkvToolNixStatus> {
   return await invoke<Protocol.MkvToolNixStatus>("get_mkvtoolnix_status", { path, checkRunning });
 }
@@ -82,6 +94,10 @@ export async function getMpcHcStatus(path: string, checkRunning: boolean = false
   return await invoke<Protocol.MpcHcStatus>("get_mpchc_status", { path, checkRunning });
 }
 
+export async function getFfmpegCandidates(): Promise<Array<Protocol.ToolCandidate>> {
+  return await invoke<Array<Protocol.ToolCandidate>>("get_ffmpeg_candidates");
+}
+
 export async function getFfmpegStatus(path: string): Promise<Protocol.FfmpegStatus> {
   return await invoke<Protocol.FfmpegStatus>("get_ffmpeg_status", { path });
 }
@@ -140,10 +156,77 @@ export async function runFfmpegCapture(
   return await invoke<void>("run_ffmpeg_capture", { args, outputPattern, durationSeconds, trim, previewWidth });
 }
 
+export async function analyzeBitrate(file: string, window: number): Promise<Protocol.BitrateGraph> {
+  return await invoke<Protocol.BitrateGraph>("analyze_bitrate", { file, window });
+}
+
+export async function analyzeGop(file: string, frames: Protocol.FfprobeFrames): Promise<Protocol.GopReport> {
+  return await invoke<Protocol.GopReport>("analyze_gop", { file, frames });
+}
+
+export async function analyzeHdr(file: string, frames: Protocol.FfprobeFrames): Promise<Protocol.HdrReport> {
+  return await invoke<Protocol.HdrReport>("analyze_hdr", { file, frames });
+}
+
+export async function cancelFfmpegAnalysis(): Promise<void> {
+  return await invoke<void>("cancel_ffmpeg_analysis");
+}
+
 export async function cancelFfmpegCapture(): Promise<void> {
   return await invoke<void>("cancel_ffmpeg_capture");
 }
 
+export async function compareEncoderSettings(
+  file: string,
+  other: string | null = null
+): Promise<Protocol.EncoderComparison> {
+  return await invoke<Protocol.EncoderComparison>("compare_encoder_settings", { file, other });
+}
+
+export async function compareFrames(
+  reference: string,
+  distorted: string,
+  options: Protocol.FrameCompareOptions
+): Promise<Protocol.FrameComparison> {
+  return await invoke<Protocol.FrameComparison>("compare_frames", { reference, distorted, options });
+}
+
+export async function compareWithFfprobe(
+  file: string,
+  frames: Protocol.FfprobeFrames
+): Promise<Protocol.ProbeComparison> {
+  return await invoke<Protocol.ProbeComparison>("compare_with_ffprobe", { file, frames });
+}
+
+export async function createContactSheet(
+  file: string,
+  options: Protocol.ContactSheetOptions
+): Promise<Protocol.ContactSheetResult> {
+  return await invoke<Protocol.ContactSheetResult>("create_contact_sheet", { file, options });
+}
+
+export async function createPreviewClip(file: string, options: Protocol.ClipOptions): Promise<Protocol.ClipResult> {
+  return await invoke<Protocol.ClipResult>("create_preview_clip", { file, options });
+}
+
+export async function detectCrop(file: string): Promise<Protocol.CropDetection> {
+  return await invoke<Protocol.CropDetection>("detect_crop", { file });
+}
+
+export async function exportAudio(
+  file: string,
+  options: Protocol.AudioExportOptions
+): Promise<Protocol.AudioExportResult> {
+  return await invoke<Protocol.AudioExportResult>("export_audio", { file, options });
+}
+
+export async function exportSpreadsheet(
+  files: string[],
+  options: Protocol.SpreadsheetExportOptions
+): Promise<Protocol.SpreadsheetExportResult> {
+  return await invoke<Protocol.SpreadsheetExportResult>("export_spreadsheet", { files, options });
+}
+
 export async function getFiles(files: string[]): Promise<string[]> {
   return await invoke<string[]>("get_files", { files });
 }
@@ -176,6 +259,42 @@ export async function getStreamCountMap(
   return streamCountMap;
 }
 
+export async function renderBitrateGraph(
+  graph: Protocol.BitrateGraph,
+  output: string,
+  width: number,
+  height: number
+): Promise<void> {
+  return await invoke<void>("render_bitrate_graph", { graph, output, width, height });
+}
+
+export async function renderSpectrogram(
+  file: string,
+  options: Protocol.SpectrogramOptions
+): Promise<Protocol.SpectrogramReport> {
+  return await invoke<Protocol.SpectrogramReport>("render_spectrogram", { file, options });
+}
+
+export async function runLoudnessAnalysis(file: string): Promise<Protocol.LoudnessReport> {
+  return await invoke<Protocol.LoudnessReport>("run_loudness_analysis", { file });
+}
+
+export async function runQcAnalysis(file: string, options: Protocol.QcOptions): Promise<Protocol.QcTimeline> {
+  return await invoke<Protocol.QcTimeline>("run_qc_analysis", { file, options });
+}
+
+export async function runQualityComparison(
+  reference: string,
+  distorted: string,
+  options: Protocol.QualityOptions
+): Promise<Protocol.QualityReport> {
+  return await invoke<Protocol.QualityReport>("run_quality_comparison", { reference, distorted, options });
+}
+
+export async function runSceneDetection(file: string, options: Protocol.SceneOptions): Promise<Protocol.SceneChapters> {
+  return await invoke<Protocol.SceneChapters>("run_scene_detection", { file, options });
+}
+
 export async function setConfig(
   config: Protocol.Config
 ): Promise<Protocol.Config> {